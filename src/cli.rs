@@ -18,12 +18,30 @@ pub enum Command {
     Pic {
         #[command(subcommand)]
         action: PicAction,
+        /// Keep the stored pixel order instead of applying the EXIF Orientation
+        #[arg(long, global = true)]
+        no_auto_orient: bool,
+        /// Tone mapping for HDR/EXR written to 8-bit formats: aces, reinhard, clip
+        #[arg(long, global = true, default_value = "aces")]
+        tone_map: String,
+        /// HDR exposure in EV (default: automatic)
+        #[arg(long, global = true, allow_hyphen_values = true)]
+        exposure: Option<f32>,
+        /// HDR output gamma
+        #[arg(long, global = true, default_value = "2.2")]
+        gamma: f32,
     },
     /// Document conversion & PDF tools
     Doc {
         #[command(subcommand)]
         action: DocAction,
     },
+    /// Video to GIF/WebP, thumbnails and contact sheets
+    #[cfg(feature = "api")]
+    Video {
+        #[command(subcommand)]
+        action: VideoAction,
+    },
     /// MKV tagging (mark watched, edit tags, inject NFO, attach images, reset)
     #[cfg(feature = "api")]
     Tag {
@@ -126,6 +144,9 @@ pub enum PicAction {
         /// JXL mode: lossless, folder, pivot
         #[arg(long, default_value = "lossless")]
         jxl_mode: String,
+        /// Color profile of the output: srgb, display-p3, adobe-rgb, keep, or an .icc/.icm file
+        #[arg(long, default_value = "srgb")]
+        profile: String,
     },
     /// Resize image(s)
     Resize {
@@ -137,9 +158,35 @@ pub enum PicAction {
         /// Target height in pixels
         #[arg(long)]
         height: Option<u32>,
-        /// Max file size in KB
+        /// Max file size in KB (JPEG/AVIF: quality search; PNG/TIFF: resolution only; WebP/JXL not supported)
         #[arg(long)]
         max_kb: Option<u32>,
+        /// Lowest JPEG/AVIF quality tried before reducing resolution (1-95, with --max-kb)
+        #[arg(long, default_value = "30")]
+        min_quality: u8,
+        /// Mode: exact, fit, fill, pad, percent, long-edge, short-edge
+        #[arg(long, default_value = "exact")]
+        mode: String,
+        /// Scale in percent (percent mode)
+        #[arg(long)]
+        percent: Option<f32>,
+        /// Edge length in pixels (long-edge / short-edge modes)
+        #[arg(long)]
+        edge: Option<u32>,
+        /// Padding color, #RRGGBB or #RRGGBBAA (pad mode)
+        #[arg(long, default_value = "#00000000")]
+        background: String,
+        /// Never enlarge images
+        #[arg(long)]
+        only_shrink: bool,
+        /// Resampling filter: nearest, bilinear, bicubic, gaussian, lanczos3
+        #[arg(long, default_value = "lanczos3")]
+        filter: String,
+    },
+    /// Apply the EXIF Orientation to the pixels and reset the tag
+    AutoOrient {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
     },
     /// Rotate image(s)
     Rotate {
@@ -158,15 +205,239 @@ pub enum PicAction {
         #[arg(long, short)] width: u32,
         #[arg(long, short = 'H')] height: u32,
     },
-    /// Read EXIF metadata
-    Exif {
+    /// Adjust brightness, contrast, colors and sharpness
+    Adjust {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        /// Brightness (-100 to 100)
+        #[arg(long, default_value = "0", allow_hyphen_values = true)]
+        brightness: f32,
+        /// Contrast (-100 to 100)
+        #[arg(long, default_value = "0", allow_hyphen_values = true)]
+        contrast: f32,
+        /// Midtone gamma (above 1 brightens)
+        #[arg(long, default_value = "1")]
+        gamma: f32,
+        /// Saturation (-100 = grayscale, 100 = double)
+        #[arg(long, default_value = "0", allow_hyphen_values = true)]
+        saturation: f32,
+        /// Unsharp mask amount (0 = off, 1 = normal)
+        #[arg(long, default_value = "0")]
+        sharpen: f32,
+        /// Unsharp mask radius (sigma, px)
+        #[arg(long, default_value = "1")]
+        sharpen_radius: f32,
+        /// Unsharp mask threshold (0-255)
+        #[arg(long, default_value = "0")]
+        sharpen_threshold: i32,
+        /// Denoise blur radius (sigma, px)
+        #[arg(long, default_value = "0")]
+        denoise: f32,
+        /// Tint: none, grayscale, sepia
+        #[arg(long, default_value = "none")]
+        tint: String,
+        /// Stretch the histogram to full range
+        #[arg(long)]
+        auto_levels: bool,
+        /// Neutralize color casts (gray world)
+        #[arg(long)]
+        auto_wb: bool,
+    },
+    /// Find near-duplicate images across files and folders (perceptual hashes)
+    Duplicates {
+        /// Files or folders (scanned recursively)
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+        /// Hash: ahash, dhash, phash
+        #[arg(long, default_value = "phash")]
+        hash: String,
+        /// Maximum Hamming distance (0-64) between two near-duplicates
+        #[arg(long, default_value_t = 8)]
+        threshold: u32,
+        /// Keep one image per group and remove the others: largest, oldest
+        #[arg(long)]
+        keep: Option<String>,
+        /// Move removed duplicates to this folder
+        #[arg(long, conflicts_with = "delete")]
+        move_to: Option<PathBuf>,
+        /// Permanently delete removed duplicates
+        #[arg(long)]
+        delete: bool,
+        /// Only report what --keep would remove (the default without --move-to or --delete)
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Extract the frames of an animated GIF/APNG/WebP as PNG files
+    Frames {
         #[arg(required = true)]
         files: Vec<PathBuf>,
+        /// Output folder (default: <name>_frames next to each file)
+        #[arg(long)]
+        output_dir: Option<PathBuf>,
     },
-    /// Strip EXIF metadata
+    /// Assemble images (or a folder of images) into an animated GIF, APNG or WebP
+    Animate {
+        /// Images in frame order, or a single folder (sorted by name)
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        /// Output file (.gif, .png/.apng, .webp)
+        #[arg(long, short)]
+        output: String,
+        /// Frame delay in ms (frames named *_<n>ms keep their own delay)
+        #[arg(long, default_value = "100")]
+        delay: u32,
+        /// Number of loops (0 = infinite)
+        #[arg(long, default_value = "0")]
+        loops: u32,
+    },
+    /// Generate a full icon set (ICO, favicons, Apple touch, Android/PWA, ICNS, web manifest) from one image or SVG
+    Icons {
+        #[arg(required = true)]
+        file: PathBuf,
+        /// Output folder (default: <name>_icons next to the source)
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+        /// Padding around the artwork, in % of the icon size (0-40)
+        #[arg(long, default_value_t = 0.0)]
+        padding: f32,
+        /// Background color (#RRGGBB or #RRGGBBAA); transparent by default
+        #[arg(long, default_value = "#00000000")]
+        background: String,
+        /// App name in the web manifest (default: file name)
+        #[arg(long, default_value = "")]
+        name: String,
+        /// Short name shown under the home screen icon
+        #[arg(long, default_value = "")]
+        short_name: String,
+        /// Theme color for the manifest and the theme-color meta tag
+        #[arg(long, default_value = "#FFFFFF")]
+        theme_color: String,
+        /// URL prefix of the icon files in the manifest and HTML snippet
+        #[arg(long, default_value = "/")]
+        url_prefix: String,
+    },
+    /// Lay out images in a grid: contact sheet or collage (PNG, JPEG or multi-page PDF)
+    Montage {
+        /// Images or folders (scanned recursively, sorted by name)
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+        /// Output file (.png, .jpg, .pdf); several pages are numbered <name>_001.png …
+        #[arg(long, short)]
+        output: String,
+        /// Columns (0 = auto)
+        #[arg(long, default_value_t = 0)]
+        cols: u32,
+        /// Rows per page (0 = auto)
+        #[arg(long, default_value_t = 0)]
+        rows: u32,
+        /// Cell size in pixels when no --page is given
+        #[arg(long, default_value_t = 300)]
+        cell_size: u32,
+        /// Space between cells and around the grid (px)
+        #[arg(long, default_value_t = 16)]
+        spacing: u32,
+        /// Background color (#RRGGBB or #RRGGBBAA)
+        #[arg(long, default_value = "#FFFFFF")]
+        background: String,
+        /// Caption under each image: none, filename, date (EXIF)
+        #[arg(long, default_value = "filename")]
+        caption: String,
+        /// Page size: a3, a4, a5, letter, legal or WxH in mm
+        #[arg(long)]
+        page: Option<String>,
+        /// Landscape page
+        #[arg(long)]
+        landscape: bool,
+        /// Page resolution (dots per inch)
+        #[arg(long, default_value_t = 150)]
+        dpi: u32,
+        /// Caption font (TTF/OTF)
+        #[arg(long)]
+        font: Option<PathBuf>,
+    },
+    /// Read EXIF metadata, or edit it with --set / --shift
+    Exif {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        /// Set a field: date, artist, copyright, description, keywords, gps (empty value removes it)
+        #[arg(long = "set", value_name = "KEY=VALUE")]
+        set: Vec<String>,
+        /// Shift all dates (wrong camera clock): +1h30m, -2d, -01:30
+        #[arg(long, allow_hyphen_values = true)]
+        shift: Option<String>,
+    },
+    /// Strip metadata (everything by default, or selectively without re-encoding)
     StripExif {
         #[arg(required = true)]
         files: Vec<PathBuf>,
+        /// Remove GPS location only (EXIF and XMP)
+        #[arg(long)]
+        gps: bool,
+        /// Remove camera/lens serial numbers, owner name and maker notes
+        #[arg(long)]
+        serials: bool,
+        /// Remove the embedded EXIF thumbnail
+        #[arg(long)]
+        thumbnail: bool,
+        /// Remove everything except copyright and orientation (ICC profile kept)
+        #[arg(long)]
+        keep_essential: bool,
+    },
+    /// Add a text watermark (TrueType font, outline, shadow, rotation, tiling)
+    Watermark {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        /// Watermark text
+        #[arg(long)]
+        text: String,
+        /// Text size (px)
+        #[arg(long, default_value = "48")]
+        size: f32,
+        /// Opacity (0.0 - 1.0)
+        #[arg(long, default_value = "0.5")]
+        opacity: f32,
+        /// TTF/OTF font file (default: bundled DejaVu Sans)
+        #[arg(long)]
+        font: Option<PathBuf>,
+        /// Text color (#RRGGBB or #RRGGBBAA)
+        #[arg(long, default_value = "#FFFFFF")]
+        color: String,
+        /// Outline width (px), 0 = no outline
+        #[arg(long, default_value = "0")]
+        outline: f32,
+        /// Outline color
+        #[arg(long, default_value = "#000000")]
+        outline_color: String,
+        /// Drop shadow offset (px)
+        #[arg(long)]
+        shadow: Option<f32>,
+        /// Rotation (degrees, counter-clockwise); default follows the image diagonal
+        #[arg(long, allow_hyphen_values = true)]
+        rotation: Option<f32>,
+        /// Anchor: HautGauche, HautCentre, HautDroite, MilieuGauche, Centre, MilieuDroite, BasGauche, BasCentre, BasDroite
+        #[arg(long, default_value = "Centre")]
+        anchor: String,
+        /// Repeat the text over the whole image
+        #[arg(long)]
+        tile: bool,
+    },
+    /// Add meme text at the top and/or bottom
+    Meme {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        #[arg(long, default_value = "")]
+        top: String,
+        #[arg(long, default_value = "")]
+        bottom: String,
+        /// TTF/OTF font file (default: bundled DejaVu Sans)
+        #[arg(long)]
+        font: Option<PathBuf>,
+        /// Text size (px), 0 = automatic
+        #[arg(long, default_value = "0")]
+        size: f32,
+        /// Draw outlined text over the image instead of adding black bars
+        #[arg(long)]
+        no_bars: bool,
     },
 }
 
@@ -182,10 +453,31 @@ pub enum DocAction {
         #[arg(long, short)]
         to: String,
     },
-    /// Split PDF into individual pages
+    /// Split PDF (one file per page by default)
     PdfSplit {
         #[arg(required = true)]
         file: PathBuf,
+        /// Page ranges, one file per range (e.g. "1-3,4-10,11-")
+        #[arg(long, conflicts_with_all = ["every", "bookmarks", "max_size", "blank"])]
+        ranges: Option<String>,
+        /// One file every N pages
+        #[arg(long, conflicts_with_all = ["bookmarks", "max_size", "blank"])]
+        every: Option<u32>,
+        /// One file per top-level bookmark
+        #[arg(long, conflicts_with_all = ["max_size", "blank"])]
+        bookmarks: bool,
+        /// Maximum size per output file (MB)
+        #[arg(long, conflicts_with = "blank")]
+        max_size: Option<f64>,
+        /// Split on blank separator pages (scanned batches); separators are dropped
+        #[arg(long)]
+        blank: bool,
+        /// Ink threshold (%) under which a scanned page counts as blank
+        #[arg(long, default_value = "1.0")]
+        blank_threshold: f64,
+        /// File name template: {name}, {n}, {start}, {end}, {title}
+        #[arg(long)]
+        name: Option<String>,
     },
     /// Merge multiple PDFs
     PdfMerge {
@@ -202,8 +494,8 @@ pub enum DocAction {
         /// Angle: 90, 180, 270
         #[arg(long, default_value = "90")]
         angle: u16,
-        /// Pages to rotate (e.g. "1,3,5"). Omit for all pages.
-        #[arg(long)]
+        /// Pages to rotate (e.g. "1-5,odd,!3,-1"). Omit for all pages.
+        #[arg(long, allow_hyphen_values = true)]
         pages: Option<String>,
     },
     /// Compress a PDF
@@ -219,23 +511,64 @@ pub enum DocAction {
         #[arg(long)] y: f64,
         #[arg(long, short)] width: f64,
         #[arg(long, short = 'H')] height: f64,
+        /// Page selection (e.g. "1-5,odd,!3,-1")
+        #[arg(long, allow_hyphen_values = true)]
+        pages: Option<String>,
+    },
+    /// Clean scanned PDFs: remove blank pages, deskew image-only pages, crop to content
+    PdfCleanScan {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        /// Do not crop pages to their content
+        #[arg(long)]
+        no_crop: bool,
+        /// Margin kept around the content when cropping (mm)
+        #[arg(long, default_value = "5.0")]
+        margin: f64,
+        /// Do not straighten tilted scans
         #[arg(long)]
+        no_deskew: bool,
+        /// Keep blank pages
+        #[arg(long)]
+        keep_blank: bool,
+        /// Ink threshold (%) under which a scanned page counts as blank
+        #[arg(long, default_value = "1.0")]
+        blank_threshold: f64,
+        /// Page selection (e.g. "1-5,odd,!3,-1")
+        #[arg(long, allow_hyphen_values = true)]
         pages: Option<String>,
     },
+    /// Impose PDF pages on A4 sheets: N-up, booklet or poster tiling
+    PdfImpose {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        /// Pages per sheet (2, 4, 6, 8, 9, 16…)
+        #[arg(long, conflicts_with_all = ["booklet", "tile"])]
+        nup: Option<u32>,
+        /// Saddle-stitch booklet (2 pages per side, print duplex, short-edge flip)
+        #[arg(long, conflicts_with = "tile")]
+        booklet: bool,
+        /// Tile each page across A4 sheets, enlarged by this factor (e.g. 2.0)
+        #[arg(long)]
+        tile: Option<f64>,
+        /// Overlap between tiles (mm)
+        #[arg(long, default_value = "10")]
+        overlap: f64,
+    },
     /// Reorder PDF pages
     PdfOrganize {
         #[arg(required = true)]
         file: PathBuf,
-        /// New page order (e.g. "3,1,2,4")
-        #[arg(long)]
+        /// New page order (e.g. "3,1,2,4", "10-1" or "reverse")
+        #[arg(long, allow_hyphen_values = true)]
         order: String,
     },
     /// Delete pages from a PDF
     PdfDelete {
         #[arg(required = true)]
         file: PathBuf,
-        /// Pages to delete (e.g. "2,5")
-        #[arg(long)]
+        /// Pages to delete (e.g. "2,5", "even" or "-1")
+        #[arg(long, allow_hyphen_values = true)]
         pages: String,
     },
     /// Add page numbers to a PDF
@@ -262,6 +595,39 @@ pub enum DocAction {
         allow_print: bool,
         #[arg(long, default_value = "true")]
         allow_copy: bool,
+        /// Allow modifying the document
+        #[arg(long)]
+        allow_modify: bool,
+        /// Allow adding annotations (and filling forms)
+        #[arg(long)]
+        allow_annotate: bool,
+        /// Allow filling form fields
+        #[arg(long)]
+        allow_forms: bool,
+        /// Allow assembling pages (insert, rotate, delete)
+        #[arg(long)]
+        allow_assemble: bool,
+        /// Use AES-256 (PDF 2.0) instead of AES-128
+        #[arg(long)]
+        aes256: bool,
+    },
+    /// Security report: encryption, permissions, version, pages, fonts, JavaScript, forms
+    PdfInfo {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        /// Open password, if the document requires one
+        #[arg(long, default_value = "")]
+        password: String,
+    },
+    /// Compare two PDFs page by page: text insertions / deletions by page and line
+    PdfCompare {
+        /// Original document
+        old: PathBuf,
+        /// Revised document
+        new: PathBuf,
+        /// Also write a copy of the revised PDF with changes highlighted
+        #[arg(long)]
+        highlight: bool,
     },
     /// Unlock a protected PDF
     PdfUnlock {
@@ -287,9 +653,228 @@ pub enum DocAction {
         /// Opacity (0.0 - 1.0)
         #[arg(long, default_value = "0.15")]
         opacity: f64,
+        /// Page selection (e.g. "1-5,odd,!3,-1")
+        #[arg(long, allow_hyphen_values = true)]
+        pages: Option<String>,
+    },
+    /// Stamp PDF pages: image watermark and/or header/footer templates
+    PdfStamp {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        /// Image to stamp (PNG with transparency or JPEG)
+        #[arg(long)]
+        image: Option<PathBuf>,
+        /// Image anchor: HautGauche, HautCentre, HautDroite, MilieuGauche, Centre, MilieuDroite, BasGauche, BasCentre, BasDroite
+        #[arg(long, default_value = "Centre")]
+        anchor: String,
+        /// Image offset from the anchored edges, in mm ("x,y")
+        #[arg(long, default_value = "10,10", allow_hyphen_values = true)]
+        offset: String,
+        /// Image width (% of page width)
+        #[arg(long, default_value = "30")]
+        scale: f64,
+        /// Image rotation (degrees, counter-clockwise)
+        #[arg(long, default_value = "0", allow_hyphen_values = true)]
+        rotation: f64,
+        /// Image opacity (0.0 - 1.0)
+        #[arg(long, default_value = "1.0")]
+        opacity: f64,
+        /// Header template. Tokens: {page}, {pages}, {date}, {filename}, {title}
+        #[arg(long)]
+        header: Option<String>,
+        /// Footer template (same tokens), e.g. "Page {page} / {pages}"
+        #[arg(long)]
+        footer: Option<String>,
+        /// Header/footer font size
+        #[arg(long, default_value = "9")]
+        size: f64,
+        /// Page selection (e.g. "2-,!-1")
+        #[arg(long, allow_hyphen_values = true)]
+        pages: Option<String>,
+    },
+    /// Add a native annotation (note, text box, highlight, underline, strikeout, link)
+    PdfAnnotate {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        /// Annotation kind: note, text, highlight, underline, strikeout, link
+        #[arg(long, default_value = "text")]
+        kind: String,
+        /// Annotation text (comment / box content)
+        #[arg(long, default_value = "")]
+        text: String,
+        /// Target URL (with --kind link)
         #[arg(long)]
+        url: Option<String>,
+        /// Left edge (% of page width)
+        #[arg(long, default_value = "10")]
+        x: f64,
+        /// Bottom edge (% of page height)
+        #[arg(long, default_value = "80")]
+        y: f64,
+        /// Width (% of page width)
+        #[arg(long, default_value = "30")]
+        width: f64,
+        /// Height (% of page height)
+        #[arg(long, default_value = "10")]
+        height: f64,
+        /// Color as "r,g,b" (0.0 - 1.0). Defaults depend on the kind.
+        #[arg(long)]
+        color: Option<String>,
+        /// Author (markup annotations)
+        #[arg(long, default_value = "")]
+        author: String,
+        /// Page selection (e.g. "1-5,odd,!3,-1")
+        #[arg(long, allow_hyphen_values = true)]
         pages: Option<String>,
     },
+    /// List the annotations of a PDF
+    PdfAnnotations {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+    /// Remove annotations (all kinds except form fields by default)
+    PdfAnnotRemove {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        /// Annotation subtypes to remove, comma-separated (e.g. "Highlight,Text")
+        #[arg(long)]
+        kind: Option<String>,
+        /// Page selection (e.g. "1-5,odd,!3,-1")
+        #[arg(long, allow_hyphen_values = true)]
+        pages: Option<String>,
+    },
+    /// Flatten annotations: burn their appearance into the page content
+    PdfFlatten {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+    /// List the files attached to a PDF
+    PdfAttachments {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+    /// Attach files to a PDF (e.g. source spreadsheet, Factur-X XML)
+    PdfAttach {
+        #[arg(required = true)]
+        file: PathBuf,
+        /// File to attach (repeatable)
+        #[arg(long = "add", required = true)]
+        add: Vec<PathBuf>,
+        /// Description shown in the reader's attachment panel
+        #[arg(long, default_value = "")]
+        description: String,
+    },
+    /// Extract the files attached to a PDF into a <name>_attachments/ folder
+    PdfAttachExtract {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+    /// Remove attached files (all of them, or only the given names)
+    PdfDetach {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        /// Attachment names to remove, comma-separated
+        #[arg(long)]
+        name: Option<String>,
+    },
+    /// Extract the images of a PDF into a <name>_images/ folder
+    PdfImages {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        /// Output format: auto (JPEG kept as-is, PNG otherwise), png, jpg, webp, tiff…
+        #[arg(long, default_value = "auto")]
+        format: String,
+        /// Skip images smaller than this many pixels on either side
+        #[arg(long, default_value = "32")]
+        min_size: u32,
+        /// Page selection (e.g. "1-5,odd,!3,-1")
+        #[arg(long, allow_hyphen_values = true)]
+        pages: Option<String>,
+    },
+    /// Convert document(s) to PDF/A-2b (archival) and print the validation report
+    PdfA {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+    /// Check PDF/A-2b conformance without modifying the file
+    PdfaCheck {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+}
+
+// ─── VIDEO ──────────────────────────────────────────────────────
+
+#[cfg(feature = "api")]
+#[derive(clap::Subcommand)]
+pub enum VideoAction {
+    /// Make a GIF or animated WebP from a time range
+    Animate {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        /// Output format: gif, webp
+        #[arg(long, default_value = "gif")]
+        to: String,
+        /// Start time (SS, MM:SS or HH:MM:SS)
+        #[arg(long, default_value = "0")]
+        start: String,
+        /// Duration (SS, MM:SS or HH:MM:SS); omit to go to the end
+        #[arg(long)]
+        duration: Option<String>,
+        /// Frames per second
+        #[arg(long, default_value = "12")]
+        fps: u32,
+        /// Width in pixels (0 = source width)
+        #[arg(long, default_value = "480")]
+        width: u32,
+        /// Number of loops (0 = infinite)
+        #[arg(long, default_value = "0")]
+        loops: u32,
+        /// WebP quality (0-100)
+        #[arg(long, default_value = "75")]
+        quality: u32,
+    },
+    /// Extract a single frame at a timestamp or at the next scene change
+    Thumbnail {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        /// Timestamp (SS, MM:SS or HH:MM:SS)
+        #[arg(long, default_value = "0")]
+        at: String,
+        /// Take the first scene change after --at whose score exceeds this threshold (0-1)
+        #[arg(long)]
+        scene: Option<f64>,
+        /// Width in pixels (0 = source width)
+        #[arg(long, default_value = "0")]
+        width: u32,
+        /// Output format: jpg, png, webp
+        #[arg(long, default_value = "jpg")]
+        to: String,
+    },
+    /// Build a contact sheet: a grid of timecoded thumbnails
+    Sheet {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        #[arg(long, default_value = "4")]
+        cols: u32,
+        #[arg(long, default_value = "4")]
+        rows: u32,
+        /// Thumbnail width in pixels
+        #[arg(long, default_value = "320")]
+        width: u32,
+        /// Hide the timecodes
+        #[arg(long)]
+        no_timecodes: bool,
+        /// Hide the header (file name, duration, resolution)
+        #[arg(long)]
+        no_header: bool,
+        /// TrueType/OpenType font file (default: embedded font)
+        #[arg(long)]
+        font: Option<PathBuf>,
+        /// Output format: jpg, png, webp
+        #[arg(long, default_value = "jpg")]
+        to: String,
+    },
 }
 
 // ─── TAG ────────────────────────────────────────────────────────
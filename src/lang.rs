@@ -174,7 +174,7 @@ pub const EN: Lang = Lang {
     action_label: "Action:",
     angle_label: "Angle:",
     pages_label: "Pages:",
-    pages_hint: "(e.g. 1,3,5 or empty = all)",
+    pages_hint: "(e.g. 1-5,odd,!3,-1 or empty = all)",
     save_format: "💾 Save format",
     run_all: "🔥 RUN ALL",
     browse: "📂 Browse",
@@ -315,7 +315,7 @@ pub const FR: Lang = Lang {
     action_label: "Action :",
     angle_label: "Angle :",
     pages_label: "Pages :",
-    pages_hint: "(ex: 1,3,5 ou vide = toutes)",
+    pages_hint: "(ex: 1-5,odd,!3,-1 ou vide = toutes)",
     save_format: "💾 Sauvegarder ce format",
     run_all: "🔥 EXÉCUTER TOUT",
     browse: "📂 Parcourir",
@@ -20,6 +20,21 @@ struct ScrapeEntry {
     data: modules::scrap::ScrapeResult,
     texture: Option<egui::TextureHandle>,
 }
+/// Aperçu avant/après des réglages photo (premier fichier du lot, en miniature)
+struct ApercuReglages {
+    source: PathBuf,
+    orientation_auto: bool,
+    options: modules::pic::OptionsReglages,
+    avant: egui::TextureHandle,
+    apres: egui::TextureHandle,
+}
+/// Miniature de l'aperçu, décodée une seule fois par source hors du thread de l'interface
+struct MiniatureApercu {
+    source: PathBuf,
+    orientation_auto: bool,
+    /// `None` pendant le décodage ; `Some(None)` si l'image est illisible
+    image: Option<Option<image::DynamicImage>>,
+}
 #[derive(PartialEq, Clone, Copy, Debug)]
 enum ModuleType {
     Archive,
@@ -85,6 +100,30 @@ struct OxyonApp {
         save_video_format: bool,
         #[cfg(feature = "api")]
         video_speed: u32,
+        #[cfg(feature = "api")]
+        video_action: String,
+        #[cfg(feature = "api")]
+        video_anim_format: String,
+        #[cfg(feature = "api")]
+        video_anim_debut: String,
+        #[cfg(feature = "api")]
+        video_anim_duree: String,
+        #[cfg(feature = "api")]
+        video_anim_fps: u32,
+        #[cfg(feature = "api")]
+        video_anim_largeur: u32,
+        #[cfg(feature = "api")]
+        video_vignette_instant: String,
+        #[cfg(feature = "api")]
+        video_vignette_scene: bool,
+        #[cfg(feature = "api")]
+        video_vignette_seuil: f64,
+        #[cfg(feature = "api")]
+        video_planche_colonnes: u32,
+        #[cfg(feature = "api")]
+        video_planche_lignes: u32,
+        #[cfg(feature = "api")]
+        video_planche_largeur: u32,
         image_action: String,
         jxl_mode: String,
         rotation_angle: u32,
@@ -95,6 +134,12 @@ struct OxyonApp {
         resize_width: String,
         resize_height: String,
         resize_max_kb: String,
+        resize_mode: String,
+        resize_pourcentage: f32,
+        resize_cote: String,
+        resize_fond: [u8; 4],
+        resize_reduire_seulement: bool,
+        resize_filtre: String,
         doc_action: String,
         pdf_rotation_angle: u16,
         pdf_pages_spec: String,
@@ -109,6 +154,11 @@ struct OxyonApp {
         pdf_user_pass: String,
         pdf_allow_print: bool,
         pdf_allow_copy: bool,
+        pdf_allow_modify: bool,
+        pdf_allow_annotate: bool,
+        pdf_allow_forms: bool,
+        pdf_allow_assemble: bool,
+        pdf_aes256: bool,
         pdf_unlock_pass: String,
         pdf_wm_texte: String,
         pdf_wm_taille: f64,
@@ -119,15 +169,84 @@ struct OxyonApp {
         pdf_annot_y: f64,
         pdf_annot_w: f64,
         pdf_annot_h: f64,
+        pdf_annot_type: String,
+        pdf_annot_url: String,
         pdf_sign_nom: String,
         pdf_sign_position: String,
         pdf_sign_taille: f64,
+        pdf_split_mode: String,
+        pdf_split_plages: String,
+        pdf_split_n: u32,
+        pdf_split_taille: f64,
+        pdf_split_seuil: f64,
+        pdf_split_modele: String,
+        pdf_impose_mode: String,
+        pdf_impose_n: u32,
+        pdf_impose_echelle: f64,
+        pdf_impose_recouvrement: f64,
+        pdf_stamp_image: String,
+        pdf_stamp_ancre: String,
+        pdf_stamp_echelle: f64,
+        pdf_stamp_rotation: f64,
+        pdf_stamp_opacite: f64,
+        pdf_stamp_entete: String,
+        pdf_stamp_pied: String,
+        pdf_stamp_taille: f64,
+        pdf_pj_fichiers: Vec<PathBuf>,
+        pdf_pj_description: String,
+        pdf_pj_noms: String,
+        pdf_images_format: String,
+        pdf_images_min: u32,
+        pdf_scan_recadrer: bool,
+        pdf_scan_marge: f64,
+        pdf_scan_redresser: bool,
+        pdf_scan_blanches: bool,
+        pdf_scan_seuil: f64,
         img_wm_texte: String,
         img_wm_taille: f32,
         img_wm_opacite: f32,
+        img_texte_police: String,
+        img_wm_couleur: [u8; 4],
+        img_wm_contour: f32,
+        img_wm_ombre: bool,
+        img_wm_diagonale: bool,
+        img_wm_rotation: f32,
+        img_wm_ancre: String,
+        img_wm_mosaique: bool,
         img_meme_top: String,
         img_meme_bottom: String,
+        img_meme_bandes: bool,
+        img_orientation_auto: bool,
+        img_profil_couleur: String,
+        img_tons: modules::pic::OptionsTonales,
+        img_tons_ev: f32,
+        img_anim_format: String,
+        img_anim_delai: u32,
+        img_anim_boucles: u32,
+        img_meta_gps: bool,
+        img_meta_serie: bool,
+        img_meta_miniature: bool,
+        img_meta_essentiel: bool,
+        img_edit_date: String,
+        img_edit_decalage: String,
+        img_edit_artiste: String,
+        img_edit_copyright: String,
+        img_edit_description: String,
+        img_edit_mots_cles: String,
+        img_edit_gps: String,
         img_upscale_factor: u32,
+        img_reglages: modules::pic::OptionsReglages,
+        img_apercu: Option<ApercuReglages>,
+        img_apercu_miniature: Arc<Mutex<Option<MiniatureApercu>>>,
+        img_doublons_algo: modules::pic::Empreinte,
+        img_doublons_seuil: u32,
+        img_doublons_garder: Option<modules::pic::Conservation>,
+        img_doublons_simulation: bool,
+        img_montage: modules::pic::OptionsMontage,
+        img_icones: modules::pic::OptionsIcones,
+        img_montage_format: String,
+        img_montage_page: String,
+        img_montage_paysage: bool,
         ico_size_16: bool,
         ico_size_32: bool,
         ico_size_64: bool,
@@ -189,6 +308,30 @@ impl Default for OxyonApp {
                 save_video_format: false,
                 #[cfg(feature = "api")]
                 video_speed: 4,
+                #[cfg(feature = "api")]
+                video_action: "Convert".into(),
+                #[cfg(feature = "api")]
+                video_anim_format: "gif".into(),
+                #[cfg(feature = "api")]
+                video_anim_debut: "0".into(),
+                #[cfg(feature = "api")]
+                video_anim_duree: "5".into(),
+                #[cfg(feature = "api")]
+                video_anim_fps: 12,
+                #[cfg(feature = "api")]
+                video_anim_largeur: 480,
+                #[cfg(feature = "api")]
+                video_vignette_instant: "0".into(),
+                #[cfg(feature = "api")]
+                video_vignette_scene: false,
+                #[cfg(feature = "api")]
+                video_vignette_seuil: 0.4,
+                #[cfg(feature = "api")]
+                video_planche_colonnes: 4,
+                #[cfg(feature = "api")]
+                video_planche_lignes: 4,
+                #[cfg(feature = "api")]
+                video_planche_largeur: 320,
                 image_action: "Convert".into(),
                 jxl_mode: "lossless".into(),
                 rotation_angle: 90,
@@ -199,6 +342,12 @@ impl Default for OxyonApp {
                 resize_width: String::new(),
                 resize_height: String::new(),
                 resize_max_kb: String::new(),
+                resize_mode: "exact".into(),
+                resize_pourcentage: 50.0,
+                resize_cote: String::new(),
+                resize_fond: [0, 0, 0, 0],
+                resize_reduire_seulement: false,
+                resize_filtre: "lanczos3".into(),
                 doc_action: "Convert".into(),
                 pdf_rotation_angle: 90,
                 pdf_pages_spec: "1-end".into(),
@@ -213,6 +362,11 @@ impl Default for OxyonApp {
                 pdf_user_pass: String::new(),
                 pdf_allow_print: true,
                 pdf_allow_copy: true,
+                pdf_allow_modify: false,
+                pdf_allow_annotate: false,
+                pdf_allow_forms: false,
+                pdf_allow_assemble: false,
+                pdf_aes256: false,
                 pdf_unlock_pass: String::new(),
                 pdf_wm_texte: "CONFIDENTIEL".into(),
                 pdf_wm_taille: 60.0,
@@ -223,15 +377,84 @@ impl Default for OxyonApp {
                 pdf_annot_y: 80.0,
                 pdf_annot_w: 30.0,
                 pdf_annot_h: 10.0,
+                pdf_annot_type: "TexteLibre".into(),
+                pdf_annot_url: String::new(),
                 pdf_sign_nom: String::new(),
                 pdf_sign_position: "BasDroite".into(),
                 pdf_sign_taille: 10.0,
+                pdf_split_mode: "Pages".into(),
+                pdf_split_plages: String::new(),
+                pdf_split_n: 2,
+                pdf_split_taille: 10.0,
+                pdf_split_seuil: 1.0,
+                pdf_split_modele: String::new(),
+                pdf_impose_mode: "NUp".into(),
+                pdf_impose_n: 2,
+                pdf_impose_echelle: 2.0,
+                pdf_impose_recouvrement: 10.0,
+                pdf_stamp_image: String::new(),
+                pdf_stamp_ancre: "Centre".into(),
+                pdf_stamp_echelle: 30.0,
+                pdf_stamp_rotation: 0.0,
+                pdf_stamp_opacite: 1.0,
+                pdf_stamp_entete: String::new(),
+                pdf_stamp_pied: "Page {page} / {pages}".into(),
+                pdf_stamp_taille: 9.0,
+                pdf_pj_fichiers: Vec::new(),
+                pdf_pj_description: String::new(),
+                pdf_pj_noms: String::new(),
+                pdf_images_format: "auto".into(),
+                pdf_images_min: 32,
+                pdf_scan_recadrer: true,
+                pdf_scan_marge: 5.0,
+                pdf_scan_redresser: true,
+                pdf_scan_blanches: true,
+                pdf_scan_seuil: 1.0,
                 img_wm_texte: "WATERMARK".into(),
                 img_wm_taille: 48.0,
                 img_wm_opacite: 0.3,
+                img_texte_police: String::new(),
+                img_wm_couleur: [255, 255, 255, 255],
+                img_wm_contour: 0.0,
+                img_wm_ombre: false,
+                img_wm_diagonale: true,
+                img_wm_rotation: 0.0,
+                img_wm_ancre: "Centre".into(),
+                img_wm_mosaique: false,
                 img_meme_top: String::new(),
                 img_meme_bottom: String::new(),
+                img_meme_bandes: true,
+                img_orientation_auto: true,
+                img_profil_couleur: "srgb".into(),
+                img_tons: modules::pic::OptionsTonales::DEFAUT,
+                img_tons_ev: 0.0,
+                img_anim_format: "gif".into(),
+                img_anim_delai: modules::pic::DELAI_TRAME_DEFAUT_MS,
+                img_anim_boucles: 0,
+                img_meta_gps: true,
+                img_meta_serie: false,
+                img_meta_miniature: false,
+                img_meta_essentiel: false,
+                img_edit_date: String::new(),
+                img_edit_decalage: String::new(),
+                img_edit_artiste: String::new(),
+                img_edit_copyright: String::new(),
+                img_edit_description: String::new(),
+                img_edit_mots_cles: String::new(),
+                img_edit_gps: String::new(),
                 img_upscale_factor: 2,
+                img_reglages: modules::pic::OptionsReglages::default(),
+                img_apercu: None,
+                img_apercu_miniature: Arc::new(Mutex::new(None)),
+                img_doublons_algo: modules::pic::Empreinte::default(),
+                img_doublons_seuil: 8,
+                img_doublons_garder: None,
+                img_doublons_simulation: true,
+                img_montage: modules::pic::OptionsMontage::default(),
+                img_icones: modules::pic::OptionsIcones::default(),
+                img_montage_format: "png".into(),
+                img_montage_page: "none".into(),
+                img_montage_paysage: false,
                 ico_size_16: false,
                 ico_size_32: false,
                 ico_size_64: false,
@@ -471,6 +694,77 @@ impl OxyonApp {
             _ => ctx.set_visuals(egui::Visuals::default()),
         }
     }
+    /// Met à jour l'aperçu avant/après quand le premier fichier ou les réglages changent
+    fn apercu_reglages(&mut self, ui: &mut egui::Ui) {
+        let Some(source) = self.current_files.first().cloned() else {
+            ui.small("Add files to preview the adjustments on the first one.");
+            return;
+        };
+        let orientation_auto = self.img_orientation_auto;
+        let a_jour = self.img_apercu.as_ref()
+            .is_some_and(|a| a.source == source && a.orientation_auto == orientation_auto && a.options == self.img_reglages);
+        let mut en_cours = false;
+        if !a_jour {
+            let miniature = {
+                let mut partage = self.img_apercu_miniature.lock().unwrap();
+                match partage.as_ref() {
+                    Some(m) if m.source == source && m.orientation_auto == orientation_auto => m.image.clone(),
+                    _ => {
+                        *partage = Some(MiniatureApercu { source: source.clone(), orientation_auto, image: None });
+                        let (partage, chemin, ctx) = (Arc::clone(&self.img_apercu_miniature), source.clone(), ui.ctx().clone());
+                        std::thread::spawn(move || {
+                            let image = modules::pic::ouvrir_image(&chemin, orientation_auto).ok().map(|img| img.thumbnail(320, 320));
+                            // La sélection a pu changer pendant le décodage : résultat ignoré
+                            if let Some(m) = partage.lock().unwrap().as_mut().filter(|m| m.source == chemin && m.orientation_auto == orientation_auto) {
+                                m.image = Some(image);
+                            }
+                            ctx.request_repaint();
+                        });
+                        None
+                    }
+                }
+            };
+            en_cours = miniature.is_none();
+            let options = self.img_reglages;
+            self.img_apercu = miniature.flatten().map(|miniature| {
+                let texture = |nom: &str, img: &image::DynamicImage| {
+                    let rgba = img.to_rgba8();
+                    let ci = egui::ColorImage::from_rgba_unmultiplied(
+                        [rgba.width() as usize, rgba.height() as usize],
+                        rgba.as_flat_samples().as_slice(),
+                    );
+                    ui.ctx().load_texture(nom, ci, Default::default())
+                };
+                let apres = modules::pic::appliquer_reglages(&miniature, &options);
+                ApercuReglages {
+                    avant: texture("apercu_avant", &miniature),
+                    apres: texture("apercu_apres", &apres),
+                    source: source.clone(),
+                    orientation_auto,
+                    options,
+                }
+            });
+        }
+        match &self.img_apercu {
+            Some(apercu) => {
+                ui.horizontal(|ui| {
+                    for (titre, texture) in [("Before", &apercu.avant), ("After", &apercu.apres)] {
+                        ui.vertical(|ui| {
+                            ui.label(titre);
+                            ui.image((texture.id(), texture.size_vec2()));
+                        });
+                    }
+                });
+            }
+            None if en_cours => {
+                ui.small("Loading preview…");
+            }
+            None => {
+                ui.small(format!("No preview: {} could not be opened.", source.display()));
+            }
+        }
+    }
+
     fn verifier_deps(&mut self) {
         self.deps_manquantes = Vec::new();
     }
@@ -517,6 +811,37 @@ impl OxyonApp {
         #[cfg(feature = "api")]
         let video_speed = self.video_speed;
         #[cfg(feature = "api")]
+        let video_action = self.video_action.clone();
+        // Instants saisis : vides = début / jusqu'à la fin
+        #[cfg(feature = "api")]
+        let video_anim_options: Result<modules::video::OptionsAnimationVideo, String> = (|| {
+            let temps = |t: &str| if t.trim().is_empty() { Ok(None) } else { modules::video::parser_temps(t).map(Some) };
+            Ok(modules::video::OptionsAnimationVideo {
+                debut: temps(&self.video_anim_debut)?.unwrap_or(0.0),
+                duree: temps(&self.video_anim_duree)?,
+                fps: self.video_anim_fps,
+                largeur: self.video_anim_largeur,
+                ..Default::default()
+            })
+        })();
+        #[cfg(feature = "api")]
+        let video_anim_format = self.video_anim_format.clone();
+        #[cfg(feature = "api")]
+        let video_vignette: Result<modules::video::PositionVignette, String> = modules::video::parser_temps(&self.video_vignette_instant)
+            .map(|t| if self.video_vignette_scene {
+                modules::video::PositionVignette::Scene { apres: t, seuil: self.video_vignette_seuil }
+            } else {
+                modules::video::PositionVignette::Instant(t)
+            });
+        #[cfg(feature = "api")]
+        let video_planche = modules::video::OptionsPlanche {
+            colonnes: self.video_planche_colonnes,
+            lignes: self.video_planche_lignes,
+            largeur_vignette: self.video_planche_largeur,
+            police: (!self.img_texte_police.trim().is_empty()).then(|| PathBuf::from(self.img_texte_police.trim())),
+            ..Default::default()
+        };
+        #[cfg(feature = "api")]
         let audio_action = self.audio_action.clone();
         #[cfg(feature = "api")]
         let audio_qualite = self.audio_qualite;
@@ -529,9 +854,17 @@ impl OxyonApp {
         let crop_y = self.crop_y;
         let crop_w = self.crop_width;
         let crop_h = self.crop_height;
-        let resize_w = self.resize_width.parse::<u32>().unwrap_or(0);
-        let resize_h = self.resize_height.parse::<u32>().unwrap_or(0);
         let resize_kb = self.resize_max_kb.parse::<u32>().unwrap_or(0);
+        let resize_options = modules::pic::OptionsRedimension {
+            mode: modules::pic::ModeRedimension::depuis_nom(&self.resize_mode).unwrap_or_default(),
+            largeur: self.resize_width.parse::<u32>().unwrap_or(0),
+            hauteur: self.resize_height.parse::<u32>().unwrap_or(0),
+            pourcentage: self.resize_pourcentage,
+            cote: self.resize_cote.parse::<u32>().unwrap_or(0),
+            fond: self.resize_fond,
+            reduire_seulement: self.resize_reduire_seulement,
+            filtre: modules::pic::filtre_depuis_nom(&self.resize_filtre).unwrap_or(image::imageops::FilterType::Lanczos3),
+        };
         let doc_action = self.doc_action.clone();
         let pdf_angle = self.pdf_rotation_angle;
         let pdf_pages = self.pdf_pages_spec.clone();
@@ -545,8 +878,15 @@ impl OxyonApp {
         let pdf_num_taille = self.pdf_num_taille;
         let pdf_owner_pass = self.pdf_owner_pass.clone();
         let pdf_user_pass = self.pdf_user_pass.clone();
-        let pdf_allow_print = self.pdf_allow_print;
-        let pdf_allow_copy = self.pdf_allow_copy;
+        let pdf_permissions = modules::doc::PermissionsPdf {
+            impression: self.pdf_allow_print,
+            copie: self.pdf_allow_copy,
+            modification: self.pdf_allow_modify,
+            annotation: self.pdf_allow_annotate,
+            formulaires: self.pdf_allow_forms,
+            assemblage: self.pdf_allow_assemble,
+        };
+        let pdf_chiffrement = if self.pdf_aes256 { modules::doc::ChiffrementPdf::Aes256 } else { modules::doc::ChiffrementPdf::Aes128 };
         let pdf_unlock_pass = self.pdf_unlock_pass.clone();
         let pdf_wm_texte = self.pdf_wm_texte.clone();
         let pdf_wm_taille = self.pdf_wm_taille;
@@ -557,15 +897,136 @@ impl OxyonApp {
         let pdf_annot_y = self.pdf_annot_y;
         let pdf_annot_w = self.pdf_annot_w;
         let pdf_annot_h = self.pdf_annot_h;
+        let pdf_annot_type = match self.pdf_annot_type.as_str() {
+            "Note" => modules::doc::TypeAnnotation::Note,
+            "Surlignage" => modules::doc::TypeAnnotation::Surlignage,
+            "Soulignement" => modules::doc::TypeAnnotation::Soulignement,
+            "Barre" => modules::doc::TypeAnnotation::Barre,
+            "Lien" => modules::doc::TypeAnnotation::Lien(self.pdf_annot_url.clone()),
+            _ => modules::doc::TypeAnnotation::TexteLibre,
+        };
         let pdf_sign_nom = self.pdf_sign_nom.clone();
         let pdf_sign_position = self.pdf_sign_position.clone();
         let pdf_sign_taille = self.pdf_sign_taille;
+        let pdf_split_mode = match self.pdf_split_mode.as_str() {
+            "Plages" => modules::doc::ModeSplit::Plages(self.pdf_split_plages.clone()),
+            "ToutesLesN" => modules::doc::ModeSplit::ToutesLesN(self.pdf_split_n),
+            "Signets" => modules::doc::ModeSplit::Signets,
+            "TailleMax" => modules::doc::ModeSplit::TailleMax(self.pdf_split_taille),
+            "PagesBlanches" => modules::doc::ModeSplit::PagesBlanches(self.pdf_split_seuil),
+            _ => modules::doc::ModeSplit::Pages,
+        };
+        let pdf_split_modele = self.pdf_split_modele.clone();
+        let pdf_tampons: Vec<modules::doc::Tampon> = {
+            let mut t = Vec::new();
+            if !self.pdf_stamp_image.trim().is_empty() {
+                let placement = modules::doc::PlacementTampon {
+                    ancre: modules::doc::AncreTampon::depuis_nom(&self.pdf_stamp_ancre).unwrap_or(modules::doc::AncreTampon::Centre),
+                    rotation: self.pdf_stamp_rotation,
+                    opacite: self.pdf_stamp_opacite,
+                    ..Default::default()
+                };
+                t.push(modules::doc::Tampon::Image { chemin: PathBuf::from(self.pdf_stamp_image.trim()), echelle: self.pdf_stamp_echelle, placement });
+            }
+            for (modele, ancre) in [(&self.pdf_stamp_entete, modules::doc::AncreTampon::HautCentre), (&self.pdf_stamp_pied, modules::doc::AncreTampon::BasCentre)] {
+                if !modele.trim().is_empty() {
+                    let placement = modules::doc::PlacementTampon { ancre, ..Default::default() };
+                    t.push(modules::doc::Tampon::Texte { modele: modele.clone(), taille_police: self.pdf_stamp_taille, placement });
+                }
+            }
+            t
+        };
+        let pdf_impose_mode = match self.pdf_impose_mode.as_str() {
+            "Livret" => modules::doc::ModeImposition::Livret,
+            "Mosaique" => modules::doc::ModeImposition::Mosaique { echelle: self.pdf_impose_echelle, recouvrement_mm: self.pdf_impose_recouvrement },
+            _ => modules::doc::ModeImposition::NUp(self.pdf_impose_n),
+        };
+        let pdf_pj_fichiers: Vec<(PathBuf, String)> = self.pdf_pj_fichiers.iter()
+            .map(|f| (f.clone(), self.pdf_pj_description.clone()))
+            .collect();
+        let pdf_pj_noms: Vec<String> = self.pdf_pj_noms.split(',')
+            .map(|n| n.trim().to_string())
+            .filter(|n| !n.is_empty())
+            .collect();
+        let pdf_images_format = self.pdf_images_format.clone();
+        let pdf_images_min = self.pdf_images_min;
+        let pdf_scan_options = modules::doc::OptionsScan {
+            recadrer: self.pdf_scan_recadrer,
+            marge_mm: self.pdf_scan_marge,
+            redresser: self.pdf_scan_redresser,
+            supprimer_blanches: self.pdf_scan_blanches,
+            seuil_encre: self.pdf_scan_seuil,
+        };
         let img_wm_texte = self.img_wm_texte.clone();
-        let img_wm_taille = self.img_wm_taille;
-        let img_wm_opacite = self.img_wm_opacite;
+        let img_police = Some(self.img_texte_police.trim())
+            .filter(|p| !p.is_empty())
+            .map(std::path::PathBuf::from);
+        let img_wm_options = modules::pic::OptionsWatermark {
+            style: modules::pic::StyleTexte {
+                police: img_police.clone(),
+                taille: self.img_wm_taille,
+                couleur: self.img_wm_couleur,
+                contour: (self.img_wm_contour > 0.0).then_some(([0, 0, 0, 255], self.img_wm_contour)),
+                ombre: self.img_wm_ombre.then(|| {
+                    let d = (self.img_wm_taille / 16.0).max(1.0);
+                    ([0, 0, 0, 160], (d, d))
+                }),
+                rotation: self.img_wm_rotation,
+            },
+            opacite: self.img_wm_opacite,
+            ancre: modules::doc::AncreTampon::depuis_nom(&self.img_wm_ancre).unwrap_or(modules::doc::AncreTampon::Centre),
+            diagonale: self.img_wm_diagonale,
+            mosaique: self.img_wm_mosaique,
+            ..Default::default()
+        };
         let img_meme_top = self.img_meme_top.clone();
         let img_meme_bottom = self.img_meme_bottom.clone();
+        let img_meme_style = modules::pic::StyleTexte { police: img_police, taille: 0.0, ..Default::default() };
+        let img_meme_bandes = self.img_meme_bandes;
+        let img_meta_options = modules::pic::OptionsNettoyageMeta {
+            gps: self.img_meta_gps,
+            numeros_serie: self.img_meta_serie,
+            miniature: self.img_meta_miniature,
+            sauf_essentiel: self.img_meta_essentiel,
+        };
+        let img_orientation_auto = self.img_orientation_auto;
+        let img_profil = modules::pic::ProfilCible::depuis_nom(&self.img_profil_couleur).unwrap_or_default();
+        let img_tons = self.img_tons;
+        let img_anim_liste = self.current_files.clone();
+        let img_anim_format = self.img_anim_format.clone();
+        let img_anim_delai = self.img_anim_delai;
+        let img_anim_boucles = self.img_anim_boucles;
+        // Champs vides : inchangés
+        let img_edition_meta: Result<modules::pic::EditionMeta, String> = (|| {
+            let mut edition = modules::pic::EditionMeta::default();
+            for (cle, valeur) in [
+                ("date", &self.img_edit_date),
+                ("artist", &self.img_edit_artiste),
+                ("copyright", &self.img_edit_copyright),
+                ("description", &self.img_edit_description),
+                ("keywords", &self.img_edit_mots_cles),
+                ("gps", &self.img_edit_gps),
+            ] {
+                if !valeur.trim().is_empty() { edition.affecter(&format!("{}={}", cle, valeur))?; }
+            }
+            if !self.img_edit_decalage.trim().is_empty() {
+                edition.decalage = Some(modules::pic::parser_decalage(&self.img_edit_decalage)?);
+            }
+            Ok(edition)
+        })();
         let img_upscale_factor = self.img_upscale_factor;
+        let img_reglages = self.img_reglages;
+        let img_doublons_algo = self.img_doublons_algo;
+        let img_doublons_seuil = self.img_doublons_seuil;
+        let img_doublons_garder = self.img_doublons_garder;
+        let img_doublons_simulation = self.img_doublons_simulation;
+        let img_montage = modules::pic::OptionsMontage {
+            page_mm: modules::pic::parser_format_page(&self.img_montage_page).ok()
+                .map(|(l, h)| if self.img_montage_paysage { (h, l) } else { (l, h) }),
+            ..self.img_montage.clone()
+        };
+        let img_montage_format = self.img_montage_format.clone();
+        let img_icones = self.img_icones.clone();
         let ico_sizes: Vec<u32> = {
             let mut s = Vec::new();
             if self.ico_size_16 { s.push(16); }
@@ -705,8 +1166,7 @@ impl OxyonApp {
                     },
                     #[cfg(feature = "api")]
                     ModuleType::Video => {
-                        log_info(&format!("Video: copie_flux={} speed={} | {:?}", copie, video_speed, input));
-                        match modules::video::traiter_video(&input, &out_str, copie, false, video_speed) {
+                        let attendre = |child: std::io::Result<std::process::Child>| match child {
                             Ok(mut child) => {
                                 match child.wait() {
                                     Ok(status) if status.success() => Ok(()),
@@ -715,6 +1175,29 @@ impl OxyonApp {
                                 }
                             },
                             Err(e) => Err(format!("failed to start ffmpeg video: {}", e)),
+                        };
+                        match video_action.as_str() {
+                            "animate" => video_anim_options.clone().and_then(|options| {
+                                let anim_out = sortie_extension(&input, &video_anim_format);
+                                log_info(&format!("Video animate: {:?} | {:?} -> {}", options, input, anim_out));
+                                attendre(modules::video::video_vers_animation(&input, &anim_out, &options))
+                            }),
+                            "thumbnail" => video_vignette.clone().and_then(|position| {
+                                let vignette_out = sortie_extension(&input, "jpg");
+                                log_info(&format!("Video thumbnail: {:?} | {:?} -> {}", position, input, vignette_out));
+                                attendre(modules::video::extraire_vignette(&input, &vignette_out, position, 0))
+                            }),
+                            "contact_sheet" => {
+                                let planche_out = sortie_extension(&input, "jpg");
+                                log_info(&format!("Video contact_sheet: {}x{} | {:?} -> {}", video_planche.colonnes, video_planche.lignes, input, planche_out));
+                                modules::video::planche_contact(&input, &planche_out, &video_planche)
+                                    .map(|_| ())
+                                    .map_err(|e| format!("video::planche_contact failed: {}", e))
+                            },
+                            _ => {
+                                log_info(&format!("Video: copie_flux={} speed={} | {:?}", copie, video_speed, input));
+                                attendre(modules::video::traiter_video(&input, &out_str, copie, false, video_speed))
+                            },
                         }
                     },
                     ModuleType::Doc => {
@@ -736,8 +1219,8 @@ impl OxyonApp {
                                     input.file_stem().unwrap_or_default().to_string_lossy()
                                 ));
                                 std::fs::create_dir_all(&output_dir).ok();
-                                log_info(&format!("Doc pdf_split: output_dir={:?}", output_dir));
-                                modules::doc::pdf_split(&input, output_dir.to_str().unwrap())
+                                log_info(&format!("Doc pdf_split: mode={:?} output_dir={:?}", pdf_split_mode, output_dir));
+                                modules::doc::pdf_split_mode(&input, output_dir.to_str().unwrap(), &pdf_split_mode, &pdf_split_modele)
                                     .map(|_| ())
                                     .map_err(|e| format!("pdf_split failed: {}", e))
                             },
@@ -749,10 +1232,11 @@ impl OxyonApp {
                                     .map_err(|e| format!("pdf_merge failed: {}", e))
                             },
                             "pdf_rotate" => {
-                                let pages_opt = parse_pages_spec(&pdf_pages);
-                                log_info(&format!("Doc pdf_rotate: angle={} pages={:?}", pdf_angle, pages_opt));
-                                modules::doc::pdf_rotate(&input, &out_str, pdf_angle, pages_opt.as_deref())
-                                    .map_err(|e| format!("pdf_rotate failed: {}", e))
+                                parse_pages_spec(&input, &pdf_pages).and_then(|(source, pages_opt)| {
+                                    log_info(&format!("Doc pdf_rotate: angle={} pages={:?}", pdf_angle, pages_opt));
+                                    modules::doc::pdf_rotate(source.chemin(), &out_str, pdf_angle, pages_opt.as_deref())
+                                        .map_err(|e| format!("pdf_rotate failed: {}", e))
+                                })
                             },
                             "pdf_compress" => {
                                 log_info(&format!("Doc pdf_compress: {:?}", input));
@@ -761,34 +1245,42 @@ impl OxyonApp {
                                     .map_err(|e| format!("pdf_compress failed: {}", e))
                             },
                             "pdf_crop" => {
-                                let pages_opt = parse_pages_spec(&pdf_pages);
-                                log_info(&format!("Doc pdf_crop: x={} y={} w={} h={} pages={:?}", pdf_crop_x, pdf_crop_y, pdf_crop_w, pdf_crop_h, pages_opt));
-                                modules::doc::pdf_crop(&input, &out_str, pdf_crop_x, pdf_crop_y, pdf_crop_w, pdf_crop_h, pages_opt.as_deref())
-                                    .map_err(|e| format!("pdf_crop failed: {}", e))
+                                parse_pages_spec(&input, &pdf_pages).and_then(|(source, pages_opt)| {
+                                    log_info(&format!("Doc pdf_crop: x={} y={} w={} h={} pages={:?}", pdf_crop_x, pdf_crop_y, pdf_crop_w, pdf_crop_h, pages_opt));
+                                    modules::doc::pdf_crop(source.chemin(), &out_str, pdf_crop_x, pdf_crop_y, pdf_crop_w, pdf_crop_h, pages_opt.as_deref())
+                                        .map_err(|e| format!("pdf_crop failed: {}", e))
+                                })
+                            },
+                            "pdf_clean_scan" => {
+                                parse_pages_spec(&input, &pdf_pages).and_then(|(source, pages_opt)| {
+                                    log_info(&format!("Doc pdf_clean_scan: {:?} pages={:?}", pdf_scan_options, pages_opt));
+                                    modules::doc::pdf_nettoyer_scan(source.chemin(), &out_str, &pdf_scan_options, pages_opt.as_deref())
+                                        .map(|_| ())
+                                        .map_err(|e| format!("pdf_clean_scan failed: {}", e))
+                                })
+                            },
+                            "pdf_impose" => {
+                                log_info(&format!("Doc pdf_impose: mode={:?}", pdf_impose_mode));
+                                modules::doc::pdf_imposer(&input, &out_str, &pdf_impose_mode)
+                                    .map_err(|e| format!("pdf_impose failed: {}", e))
                             },
                             "pdf_organize" => {
-                                let ordre: Vec<u32> = pdf_nouvel_ordre.split(',')
-                                    .filter_map(|s| s.trim().parse::<u32>().ok())
-                                    .collect();
-                                log_info(&format!("Doc pdf_organize: ordre={:?}", ordre));
-                                if ordre.is_empty() {
-                                    Err("pdf_organize: ordre vide ou invalide".to_string())
-                                } else {
-                                    modules::doc::pdf_organiser(&input, &out_str, &ordre)
-                                        .map_err(|e| format!("pdf_organize failed: {}", e))
-                                }
+                                modules::doc::pdf_preparer(&input)
+                                    .and_then(|source| {
+                                        let ordre = modules::doc::parser_selection_pages(&pdf_nouvel_ordre, source.nb_pages()?)?;
+                                        log_info(&format!("Doc pdf_organize: ordre={:?}", ordre));
+                                        modules::doc::pdf_organiser(source.chemin(), &out_str, &ordre)
+                                    })
+                                    .map_err(|e| format!("pdf_organize failed: {}", e))
                             },
                             "pdf_delete_pages" => {
-                                let pages_a_sup: Vec<u32> = pdf_pages.split(',')
-                                    .filter_map(|s| s.trim().parse::<u32>().ok())
-                                    .collect();
-                                log_info(&format!("Doc pdf_delete_pages: pages={:?}", pages_a_sup));
-                                if pages_a_sup.is_empty() {
-                                    Err("pdf_delete_pages: liste de pages vide ou invalide".to_string())
-                                } else {
-                                    modules::doc::pdf_supprimer_pages(&input, &out_str, &pages_a_sup)
-                                        .map_err(|e| format!("pdf_delete_pages failed: {}", e))
-                                }
+                                modules::doc::pdf_preparer(&input)
+                                    .and_then(|source| {
+                                        let pages_a_sup = modules::doc::parser_selection_pages(&pdf_pages, source.nb_pages()?)?;
+                                        log_info(&format!("Doc pdf_delete_pages: pages={:?}", pages_a_sup));
+                                        modules::doc::pdf_supprimer_pages(source.chemin(), &out_str, &pages_a_sup)
+                                    })
+                                    .map_err(|e| format!("pdf_delete_pages failed: {}", e))
                             },
                             "pdf_numbers" => {
                                 let position = match pdf_num_position.as_str() {
@@ -804,8 +1296,8 @@ impl OxyonApp {
                                     .map_err(|e| format!("pdf_number_pages failed: {}", e))
                             },
                             "pdf_protect" => {
-                                log_info(&format!("Doc pdf_protect: print={} copy={}", pdf_allow_print, pdf_allow_copy));
-                                modules::doc::pdf_proteger(&input, &out_str, &pdf_owner_pass, &pdf_user_pass, pdf_allow_print, pdf_allow_copy)
+                                log_info(&format!("Doc pdf_protect: {:?} {:?}", pdf_chiffrement, pdf_permissions));
+                                modules::doc::pdf_proteger_avance(&input, &out_str, &pdf_owner_pass, &pdf_user_pass, &pdf_permissions, pdf_chiffrement)
                                     .map_err(|e| format!("pdf_protect failed: {}", e))
                             },
                             "pdf_unlock" => {
@@ -819,30 +1311,130 @@ impl OxyonApp {
                                     .map_err(|e| format!("pdf_repair failed: {}", e))
                             },
                             "pdf_watermark" => {
-                                let pages_opt = parse_pages_spec(&pdf_pages);
-                                log_info(&format!("Doc pdf_watermark: texte='{}' taille={} opacite={}", pdf_wm_texte, pdf_wm_taille, pdf_wm_opacite));
-                                modules::doc::pdf_watermark(&input, &out_str, &pdf_wm_texte, pdf_wm_taille, pdf_wm_opacite, pages_opt.as_deref())
-                                    .map_err(|e| format!("pdf_watermark failed: {}", e))
+                                parse_pages_spec(&input, &pdf_pages).and_then(|(source, pages_opt)| {
+                                    log_info(&format!("Doc pdf_watermark: texte='{}' taille={} opacite={}", pdf_wm_texte, pdf_wm_taille, pdf_wm_opacite));
+                                    modules::doc::pdf_watermark(source.chemin(), &out_str, &pdf_wm_texte, pdf_wm_taille, pdf_wm_opacite, pages_opt.as_deref())
+                                        .map_err(|e| format!("pdf_watermark failed: {}", e))
+                                })
                             },
                             "pdf_annotate" => {
-                                let pages_opt = parse_pages_spec(&pdf_pages);
-                                log_info(&format!("Doc pdf_annotate: texte='{}' x={} y={} w={} h={}", pdf_annot_texte, pdf_annot_x, pdf_annot_y, pdf_annot_w, pdf_annot_h));
-                                modules::doc::pdf_annoter(&input, &out_str, &pdf_annot_texte, pdf_annot_x, pdf_annot_y, pdf_annot_w, pdf_annot_h, pages_opt.as_deref())
-                                    .map_err(|e| format!("pdf_annotate failed: {}", e))
+                                parse_pages_spec(&input, &pdf_pages).and_then(|(source, pages_opt)| {
+                                    log_info(&format!("Doc pdf_annotate: type={:?} texte='{}' x={} y={} w={} h={}", pdf_annot_type, pdf_annot_texte, pdf_annot_x, pdf_annot_y, pdf_annot_w, pdf_annot_h));
+                                    let annotation = modules::doc::NouvelleAnnotation::new(pdf_annot_type.clone(), &pdf_annot_texte, [pdf_annot_x, pdf_annot_y, pdf_annot_w, pdf_annot_h]);
+                                    modules::doc::pdf_ajouter_annotations(source.chemin(), &out_str, &[annotation], pages_opt.as_deref())
+                                        .map_err(|e| format!("pdf_annotate failed: {}", e))
+                                })
+                            },
+                            "pdf_annot_remove" => {
+                                parse_pages_spec(&input, &pdf_pages).and_then(|(source, pages_opt)| {
+                                    log_info(&format!("Doc pdf_annot_remove: {:?}", input));
+                                    modules::doc::pdf_supprimer_annotations(source.chemin(), &out_str, &[], pages_opt.as_deref())
+                                        .map(|_| ())
+                                        .map_err(|e| format!("pdf_annot_remove failed: {}", e))
+                                })
+                            },
+                            "pdf_attach" => {
+                                log_info(&format!("Doc pdf_attach: {} fichier(s)", pdf_pj_fichiers.len()));
+                                if pdf_pj_fichiers.is_empty() {
+                                    Err("pdf_attach failed: no file to attach".to_string())
+                                } else {
+                                    modules::doc::pdf_ajouter_pieces_jointes(&input, &out_str, &pdf_pj_fichiers)
+                                        .map_err(|e| format!("pdf_attach failed: {}", e))
+                                }
+                            },
+                            "pdf_attach_extract" => {
+                                let output_dir = input.parent().unwrap().join(format!(
+                                    "{}_attachments",
+                                    input.file_stem().unwrap_or_default().to_string_lossy()
+                                ));
+                                log_info(&format!("Doc pdf_attach_extract: output_dir={:?}", output_dir));
+                                modules::doc::pdf_extraire_pieces_jointes(&input, output_dir.to_str().unwrap())
+                                    .map(|_| ())
+                                    .map_err(|e| format!("pdf_attach_extract failed: {}", e))
+                            },
+                            "pdf_images" => {
+                                let output_dir = input.parent().unwrap().join(format!(
+                                    "{}_images",
+                                    input.file_stem().unwrap_or_default().to_string_lossy()
+                                ));
+                                parse_pages_spec(&input, &pdf_pages).and_then(|(source, pages_opt)| {
+                                    log_info(&format!("Doc pdf_images: format={} min={} output_dir={:?}", pdf_images_format, pdf_images_min, output_dir));
+                                    modules::doc::pdf_extraire_images(source.chemin(), output_dir.to_str().unwrap(), &pdf_images_format, pdf_images_min, pages_opt.as_deref())
+                                        .map(|_| ())
+                                        .map_err(|e| format!("pdf_images failed: {}", e))
+                                })
+                            },
+                            "pdf_detach" => {
+                                log_info(&format!("Doc pdf_detach: noms={:?}", pdf_pj_noms));
+                                modules::doc::pdf_supprimer_pieces_jointes(&input, &out_str, &pdf_pj_noms)
+                                    .map(|_| ())
+                                    .map_err(|e| format!("pdf_detach failed: {}", e))
+                            },
+                            "pdf_flatten" => {
+                                log_info(&format!("Doc pdf_flatten: {:?}", input));
+                                modules::doc::pdf_aplatir_annotations(&input, &out_str)
+                                    .map(|_| ())
+                                    .map_err(|e| format!("pdf_flatten failed: {}", e))
+                            },
+                            "pdf_pdfa" => {
+                                log_info(&format!("Doc pdf_pdfa: {:?}", input));
+                                modules::doc::pdf_vers_pdfa(&input, &out_str)
+                                    .and_then(|rapport| {
+                                        let rapport_path = Path::new(&out_str).with_extension("pdfa.txt");
+                                        std::fs::write(&rapport_path, rapport.en_texte())
+                                            .map_err(|e| format!("écriture rapport {:?} : {}", rapport_path, e))
+                                    })
+                                    .map_err(|e| format!("pdf_pdfa failed: {}", e))
+                            },
+                            "pdf_info" => {
+                                log_info(&format!("Doc pdf_info: {:?}", input));
+                                modules::doc::pdf_rapport(&input, &pdf_unlock_pass)
+                                    .and_then(|rapport| {
+                                        let rapport_path = input.with_extension("info.txt");
+                                        std::fs::write(&rapport_path, rapport.en_texte())
+                                            .map_err(|e| format!("écriture rapport {:?} : {}", rapport_path, e))
+                                    })
+                                    .map_err(|e| format!("pdf_info failed: {}", e))
+                            },
+                            "pdf_compare" => {
+                                // Lancé une seule fois, sur le second fichier de la sélection
+                                if pdf_merge_list.len() != 2 {
+                                    Err("pdf_compare failed: select exactly two files (old, new)".to_string())
+                                } else if input != pdf_merge_list[1] {
+                                    Ok(())
+                                } else {
+                                    let ancien = &pdf_merge_list[0];
+                                    log_info(&format!("Doc pdf_compare: {:?} vs {:?}", ancien, input));
+                                    modules::doc::pdf_comparer(ancien, &input, Some(&out_str))
+                                        .and_then(|rapport| {
+                                            let rapport_path = input.with_extension("compare.txt");
+                                            std::fs::write(&rapport_path, rapport.en_texte())
+                                                .map_err(|e| format!("écriture rapport {:?} : {}", rapport_path, e))
+                                        })
+                                        .map_err(|e| format!("pdf_compare failed: {}", e))
+                                }
+                            },
+                            "pdf_stamp" => {
+                                parse_pages_spec(&input, &pdf_pages).and_then(|(source, pages_opt)| {
+                                    log_info(&format!("Doc pdf_stamp: {} tampon(s) pages={:?}", pdf_tampons.len(), pages_opt));
+                                    modules::doc::pdf_tamponner(source.chemin(), &out_str, &pdf_tampons, pages_opt.as_deref())
+                                        .map_err(|e| format!("pdf_stamp failed: {}", e))
+                                })
                             },
                             "pdf_sign" => {
-                                let pages_opt = parse_pages_spec(&pdf_pages);
-                                let sign_pos = match pdf_sign_position.as_str() {
-                                    "BasGauche"  => modules::doc::PositionNumero::BasGauche,
-                                    "BasDroite"  => modules::doc::PositionNumero::BasDroite,
-                                    "HautCentre" => modules::doc::PositionNumero::HautCentre,
-                                    "HautGauche" => modules::doc::PositionNumero::HautGauche,
-                                    "HautDroite" => modules::doc::PositionNumero::HautDroite,
-                                    _            => modules::doc::PositionNumero::BasDroite,
-                                };
-                                log_info(&format!("Doc pdf_sign: nom='{}' position={} taille={}", pdf_sign_nom, pdf_sign_position, pdf_sign_taille));
-                                modules::doc::pdf_signer(&input, &out_str, &pdf_sign_nom, sign_pos, pdf_sign_taille, pages_opt.as_deref())
-                                    .map_err(|e| format!("pdf_sign failed: {}", e))
+                                parse_pages_spec(&input, &pdf_pages).and_then(|(source, pages_opt)| {
+                                    let sign_pos = match pdf_sign_position.as_str() {
+                                        "BasGauche"  => modules::doc::PositionNumero::BasGauche,
+                                        "BasDroite"  => modules::doc::PositionNumero::BasDroite,
+                                        "HautCentre" => modules::doc::PositionNumero::HautCentre,
+                                        "HautGauche" => modules::doc::PositionNumero::HautGauche,
+                                        "HautDroite" => modules::doc::PositionNumero::HautDroite,
+                                        _            => modules::doc::PositionNumero::BasDroite,
+                                    };
+                                    log_info(&format!("Doc pdf_sign: nom='{}' position={} taille={}", pdf_sign_nom, pdf_sign_position, pdf_sign_taille));
+                                    modules::doc::pdf_signer(source.chemin(), &out_str, &pdf_sign_nom, sign_pos, pdf_sign_taille, pages_opt.as_deref())
+                                        .map_err(|e| format!("pdf_sign failed: {}", e))
+                                })
                             },
                             autre => {
                                 log_warn(&format!("Doc: action inconnue '{}', fallback Convert()", autre));
@@ -861,9 +1453,9 @@ impl OxyonApp {
                                 // Si format JXL, dispatcher selon jxl_mode
                                 if fmt.to_uppercase() == "JXL" {
                                     match jxl_mode.as_str() {
-                                        "folder" => modules::pic::convertir_jxl_dossier(&input),
-                                        "pivot" => modules::pic::convertir_jxl_pivot(&input),
-                                        _ => modules::pic::convertir_jxl_lossless(&input),
+                                        "folder" => modules::pic::convertir_jxl_dossier(&input, img_orientation_auto),
+                                        "pivot" => modules::pic::convertir_jxl_pivot(&input, img_orientation_auto),
+                                        _ => modules::pic::convertir_jxl_lossless(&input, img_orientation_auto),
                                     }
                                 } else if fmt.to_uppercase() == "ICO" {
                                     // ICO : un fichier par taille
@@ -875,7 +1467,7 @@ impl OxyonApp {
                                         let ico_out = parent.join(format!("{}_{sz}x{sz}.ico", stem));
                                         let ico_str = ico_out.to_string_lossy().to_string();
                                         log_info(&format!("ICO entry: {}x{} -> {}", sz, sz, ico_str));
-                                        if !modules::pic::generer_ico_multi(&input, &ico_str, &[sz]) {
+                                        if !modules::pic::generer_ico_multi(&input, &ico_str, &[sz], img_orientation_auto) {
                                             log_error(&format!("pic::generer_ico_multi failed | {}x{} | {:?}", sz, sz, input));
                                             all_ok = false;
                                         }
@@ -886,8 +1478,8 @@ impl OxyonApp {
                                     // Resize before converting
                                     log_info(&format!("Image Convert+resize: {}x{} fmt={}", convert_resize_w, convert_resize_h, fmt));
                                     let temp = format!("{}_temp_cvt.png", out_str);
-                                    if modules::pic::redimensionner_pixels(&input, &temp, convert_resize_w, convert_resize_h) {
-                                        let result = if modules::pic::compresser(Path::new(&temp), &out_str, ratio) { Ok(()) }
+                                    if modules::pic::redimensionner_pixels(&input, &temp, convert_resize_w, convert_resize_h, img_orientation_auto, &img_tons) {
+                                        let result = if modules::pic::compresser(Path::new(&temp), &out_str, ratio, img_orientation_auto, &img_profil, &img_tons) { Ok(()) }
                                         else { Err(format!("pic::compresser after resize failed | {:?}", input)) };
                                         let _ = std::fs::remove_file(&temp);
                                         result
@@ -895,60 +1487,139 @@ impl OxyonApp {
                                         Err(format!("pic::resize for convert failed | {}x{} | {:?}", convert_resize_w, convert_resize_h, input))
                                     }
                                 } else {
-                                    if modules::pic::compresser(&input, &out_str, ratio) { Ok(()) }
+                                    if modules::pic::compresser(&input, &out_str, ratio, img_orientation_auto, &img_profil, &img_tons) { Ok(()) }
                                     else { Err(format!("pic::compresser failed | fmt={} ratio={} | {:?}", fmt, ratio, input)) }
                                 }
                             },
                             "resize" => {
-                                log_info(&format!("Image resize: w={} h={} kb={}", resize_w, resize_h, resize_kb));
-                                if resize_w > 0 && resize_h > 0 {
+                                log_info(&format!("Image resize: mode={} w={} h={} kb={}", resize_options.mode.nom(), resize_options.largeur, resize_options.hauteur, resize_kb));
+                                if resize_options.est_definie() {
                                     if resize_kb > 0 {
-                                        let temp = format!("{}_temp.{}", out_str, fmt);
-                                        if modules::pic::redimensionner_pixels(&input, &temp, resize_w, resize_h) {
-                                            if modules::pic::redimensionner_poids(Path::new(&temp), &out_str, resize_kb) {
-                                                Ok(())
-                                            } else {
-                                                Err(format!("resize by size failed | max_kb={} | file={:?}", resize_kb, input))
-                                            }
-                                        } else {
-                                            Err(format!("resize by pixels failed | w={} h={} | file={:?}", resize_w, resize_h, input))
-                                        }
+                                        modules::pic::redimensionner_poids_cible(&input, &out_str, &resize_options, resize_kb, modules::pic::QUALITE_MIN_POIDS, img_orientation_auto)
+                                            .map(|_| ())
+                                            .map_err(|e| format!("resize by size failed | max_kb={} | file={:?} | {}", resize_kb, input, e))
                                     } else {
-                                        if modules::pic::redimensionner_pixels(&input, &out_str, resize_w, resize_h) { Ok(()) }
-                                        else { Err(format!("resize by pixels failed | w={} h={} | file={:?}", resize_w, resize_h, input)) }
+                                        if modules::pic::redimensionner(&input, &out_str, &resize_options, img_orientation_auto, &img_tons) { Ok(()) }
+                                        else { Err(format!("resize failed | mode={} | file={:?}", resize_options.mode.nom(), input)) }
                                     }
                                 } else if resize_kb > 0 {
-                                    if modules::pic::redimensionner_poids(&input, &out_str, resize_kb) { Ok(()) }
+                                    if modules::pic::redimensionner_poids(&input, &out_str, resize_kb, img_orientation_auto) { Ok(()) }
                                     else { Err(format!("resize by size only failed | max_kb={} | file={:?}", resize_kb, input)) }
                                 } else {
                                     log_warn("Image resize: no w/h or kb specified, fallback to compress");
-                                    if modules::pic::compresser(&input, &out_str, 1) { Ok(()) }
+                                    if modules::pic::compresser(&input, &out_str, 1, img_orientation_auto, &modules::pic::ProfilCible::Conserver, &img_tons) { Ok(()) }
                                     else { Err(format!("pic::compresser fallback failed for {:?}", input)) }
                                 }
                             },
+                            "animate" => {
+                                // Lancé une seule fois, sur le dernier fichier de la sélection
+                                if Some(&input) != img_anim_liste.last() {
+                                    Ok(())
+                                } else {
+                                    let anim_out = input.parent().unwrap().join(format!("animation_oxyon.{}", img_anim_format));
+                                    log_info(&format!("Image animate: {} images delai={}ms boucles={} -> {:?}", img_anim_liste.len(), img_anim_delai, img_anim_boucles, anim_out));
+                                    modules::pic::assembler_animation(&img_anim_liste, &anim_out.to_string_lossy(), img_anim_delai, img_anim_boucles, img_orientation_auto)
+                                        .map(|_| ())
+                                        .map_err(|e| format!("pic::assembler_animation failed: {}", e))
+                                }
+                            },
+                            "duplicates" => {
+                                // Lancé une seule fois, sur le dernier fichier de la sélection
+                                if Some(&input) != img_anim_liste.last() {
+                                    Ok(())
+                                } else {
+                                    let parent = input.parent().unwrap();
+                                    log_info(&format!("Image duplicates: {} fichiers algo={} seuil={} garder={:?} simulation={}", img_anim_liste.len(), img_doublons_algo.nom(), img_doublons_seuil, img_doublons_garder, img_doublons_simulation));
+                                    modules::pic::chercher_doublons(&img_anim_liste, img_doublons_algo, img_doublons_seuil, img_doublons_garder.unwrap_or_default(), img_orientation_auto).and_then(|groupes| {
+                                        let corbeille = parent.join("duplicates_oxyon");
+                                        let bilan = modules::pic::traiter_doublons(&groupes, img_doublons_garder.is_some(), Some(&corbeille), img_doublons_simulation);
+                                        let rapport = parent.join("duplicates_oxyon.txt");
+                                        let texte = if groupes.is_empty() { "No duplicates found".to_string() } else { bilan.rapport };
+                                        std::fs::write(&rapport, texte).map_err(|e| format!("report {:?}: {}", rapport, e))?;
+                                        log_info(&format!("Image duplicates: {} groupes -> {:?}", groupes.len(), rapport));
+                                        if bilan.echecs > 0 { Err(format!("{} duplicate(s) could not be moved", bilan.echecs)) } else { Ok(()) }
+                                    })
+                                }
+                            },
+                            "montage" => {
+                                // Lancé une seule fois, sur le dernier fichier de la sélection
+                                if Some(&input) != img_anim_liste.last() {
+                                    Ok(())
+                                } else {
+                                    let montage_out = input.parent().unwrap().join(format!("montage_oxyon.{}", img_montage_format));
+                                    log_info(&format!("Image montage: {} images {:?} -> {:?}", img_anim_liste.len(), img_montage, montage_out));
+                                    modules::pic::montage(&img_anim_liste, &montage_out.to_string_lossy(), &img_montage, img_orientation_auto)
+                                        .map(|_| ())
+                                        .map_err(|e| format!("pic::montage failed: {}", e))
+                                }
+                            },
+                            "extract_frames" => {
+                                let dossier = input.parent().unwrap().join(format!(
+                                    "{}_frames",
+                                    input.file_stem().unwrap_or_default().to_string_lossy()
+                                ));
+                                log_info(&format!("Image extract_frames: {:?} -> {:?}", input, dossier));
+                                modules::pic::extraire_trames(&input, &dossier, img_orientation_auto)
+                                    .map(|_| ())
+                                    .map_err(|e| format!("pic::extraire_trames failed: {}", e))
+                            },
+                            "icon_set" => {
+                                let dossier = input.parent().unwrap().join(format!(
+                                    "{}_icons",
+                                    input.file_stem().unwrap_or_default().to_string_lossy()
+                                ));
+                                modules::pic::generer_jeu_icones(&input, &dossier, &img_icones, img_orientation_auto)
+                                    .map(|_| ())
+                                    .map_err(|e| format!("pic::generer_jeu_icones failed: {}", e))
+                            },
+                            "auto_orient" => {
+                                log_info(&format!("Image auto_orient: orientation EXIF={}", modules::pic::lire_orientation(&input)));
+                                if modules::pic::orienter_auto(&input, &out_str) { Ok(()) }
+                                else { Err(format!("pic::orienter_auto failed for {:?}", input)) }
+                            },
                             "rotate" => {
                                 log_info(&format!("Image rotate: angle={}", angle));
-                                if modules::pic::pivoter(&input, &out_str, angle) { Ok(()) }
+                                if modules::pic::pivoter(&input, &out_str, angle, img_orientation_auto, &img_tons) { Ok(()) }
                                 else { Err(format!("pic::rotate failed | angle={} | file={:?}", angle, input)) }
                             },
                             "crop" => {
                                 log_info(&format!("Image crop: x={} y={} w={} h={}", crop_x, crop_y, crop_w, crop_h));
-                                if modules::pic::recadrer(&input, &out_str, crop_x, crop_y, crop_w, crop_h) { Ok(()) }
+                                if modules::pic::recadrer(&input, &out_str, crop_x, crop_y, crop_w, crop_h, img_orientation_auto, &img_tons) { Ok(()) }
                                 else { Err(format!("pic::crop failed | x={} y={} w={} h={} | file={:?}", crop_x, crop_y, crop_w, crop_h, input)) }
                             },
                             "watermark" => {
-                                log_info(&format!("Image watermark: texte='{}' taille={} opacite={}", img_wm_texte, img_wm_taille, img_wm_opacite));
-                                if modules::pic::watermark(&input, &out_str, &img_wm_texte, img_wm_taille, img_wm_opacite) { Ok(()) }
+                                log_info(&format!("Image watermark: texte='{}' taille={} opacite={} ancre={:?}", img_wm_texte, img_wm_options.style.taille, img_wm_options.opacite, img_wm_options.ancre));
+                                if modules::pic::watermark_avance(&input, &out_str, &img_wm_texte, &img_wm_options, img_orientation_auto) { Ok(()) }
                                 else { Err(format!("pic::watermark failed for {:?}", input)) }
                             },
                             "meme" => {
-                                log_info(&format!("Image meme: top='{}' bottom='{}'", img_meme_top, img_meme_bottom));
-                                if modules::pic::meme(&input, &out_str, &img_meme_top, &img_meme_bottom) { Ok(()) }
+                                log_info(&format!("Image meme: top='{}' bottom='{}' bandes={}", img_meme_top, img_meme_bottom, img_meme_bandes));
+                                if modules::pic::meme_style(&input, &out_str, &img_meme_top, &img_meme_bottom, &img_meme_style, img_meme_bandes, img_orientation_auto) { Ok(()) }
                                 else { Err(format!("pic::meme failed for {:?}", input)) }
                             },
+                            "strip_metadata" => {
+                                // Nettoyage sans réencodage : la sortie garde le format de la source
+                                let meta_out = sortie_meme_format(&input);
+                                log_info(&format!("Image strip_metadata: {:?} -> {}", img_meta_options, meta_out));
+                                if modules::pic::nettoyer_metadonnees(&input, &meta_out, &img_meta_options) { Ok(()) }
+                                else { Err(format!("pic::nettoyer_metadonnees failed for {:?}", input)) }
+                            },
+                            "edit_metadata" => {
+                                img_edition_meta.clone().and_then(|edition| {
+                                    let meta_out = sortie_meme_format(&input);
+                                    log_info(&format!("Image edit_metadata: {:?} -> {}", edition, meta_out));
+                                    if modules::pic::editer_metadonnees(&input, &meta_out, &edition) { Ok(()) }
+                                    else { Err(format!("pic::editer_metadonnees failed for {:?}", input)) }
+                                })
+                            },
+                            "adjust" => {
+                                log_info(&format!("Image adjust: {:?}", img_reglages));
+                                if modules::pic::ajuster(&input, &out_str, &img_reglages, img_orientation_auto) { Ok(()) }
+                                else { Err(format!("pic::ajuster failed for {:?}", input)) }
+                            },
                             "upscale" => {
                                 log_info(&format!("Image upscale: factor={}x", img_upscale_factor));
-                                if modules::pic::upscale(&input, &out_str, img_upscale_factor) { Ok(()) }
+                                if modules::pic::upscale(&input, &out_str, img_upscale_factor, img_orientation_auto, &img_tons) { Ok(()) }
                                 else { Err(format!("pic::upscale failed for {:?}", input)) }
                             },
                             "html_to_image" => {
@@ -963,7 +1634,7 @@ impl OxyonApp {
                             },
                             autre => {
                                 log_warn(&format!("Image: action inconnue '{}', fallback compresser", autre));
-                                if modules::pic::compresser(&input, &out_str, ratio) { Ok(()) }
+                                if modules::pic::compresser(&input, &out_str, ratio, img_orientation_auto, &modules::pic::ProfilCible::Conserver, &img_tons) { Ok(()) }
                                 else { Err(format!("pic::compresser fallback failed for {:?}", input)) }
                             },
                         }
@@ -1247,17 +1918,29 @@ impl eframe::App for OxyonApp {
                         egui::ComboBox::from_id_salt("doc_action").selected_text(&self.doc_action).show_ui(ui, |ui| {
 							ui.selectable_value(&mut self.doc_action, "Convert".into(), self.lang.doc_convert);
                             ui.selectable_value(&mut self.doc_action, "pdf_annotate".into(), "PDF Annotate");
+                            ui.selectable_value(&mut self.doc_action, "pdf_annot_remove".into(), "PDF Remove annotations");
+                            ui.selectable_value(&mut self.doc_action, "pdf_attach".into(), "PDF Attach files");
+                            ui.selectable_value(&mut self.doc_action, "pdf_attach_extract".into(), "PDF Extract attachments");
+                            ui.selectable_value(&mut self.doc_action, "pdf_clean_scan".into(), "PDF Clean scans");
+                            ui.selectable_value(&mut self.doc_action, "pdf_compare".into(), "PDF Compare");
 							ui.selectable_value(&mut self.doc_action, "pdf_compress".into(), self.lang.doc_pdf_compress);
 							ui.selectable_value(&mut self.doc_action, "pdf_crop".into(), self.lang.doc_pdf_crop);
                             ui.selectable_value(&mut self.doc_action, "pdf_delete_pages".into(), self.lang.doc_pdf_delete_pages);
+                            ui.selectable_value(&mut self.doc_action, "pdf_detach".into(), "PDF Remove attachments");
+                            ui.selectable_value(&mut self.doc_action, "pdf_flatten".into(), "PDF Flatten annotations");
+                            ui.selectable_value(&mut self.doc_action, "pdf_images".into(), "PDF Extract images");
+                            ui.selectable_value(&mut self.doc_action, "pdf_impose".into(), "PDF Impose");
+                            ui.selectable_value(&mut self.doc_action, "pdf_info".into(), "PDF Info");
 							ui.selectable_value(&mut self.doc_action, "pdf_merge".into(), self.lang.doc_pdf_merge);
                             ui.selectable_value(&mut self.doc_action, "pdf_numbers".into(), self.lang.doc_pdf_number_pages);
                             ui.selectable_value(&mut self.doc_action, "pdf_organize".into(), self.lang.doc_pdf_organize);
+                            ui.selectable_value(&mut self.doc_action, "pdf_pdfa".into(), "PDF/A-2b");
 							ui.selectable_value(&mut self.doc_action, "pdf_protect".into(), self.lang.doc_pdf_protect);
                             ui.selectable_value(&mut self.doc_action, "pdf_repair".into(), self.lang.doc_pdf_repair);
 							ui.selectable_value(&mut self.doc_action, "pdf_rotate".into(), self.lang.doc_pdf_rotate);
                             ui.selectable_value(&mut self.doc_action, "pdf_sign".into(), "PDF Sign");
 							ui.selectable_value(&mut self.doc_action, "pdf_split".into(), self.lang.doc_pdf_split);
+                            ui.selectable_value(&mut self.doc_action, "pdf_stamp".into(), "PDF Stamp");
 							ui.selectable_value(&mut self.doc_action, "pdf_unlock".into(), self.lang.doc_pdf_unlock);
                             ui.selectable_value(&mut self.doc_action, "pdf_watermark".into(), self.lang.doc_pdf_watermark);
                         });
@@ -1280,6 +1963,47 @@ impl eframe::App for OxyonApp {
                         "pdf_split" => {
                             ui.label(self.lang.doc_split_hint1);
                             ui.label(self.lang.doc_split_hint2);
+                            ui.horizontal(|ui| {
+                                ui.label("Mode:");
+                                egui::ComboBox::from_id_salt("pdf_splitmode").selected_text(&self.pdf_split_mode).show_ui(ui, |ui| {
+                                    for mode in ["Pages","Plages","ToutesLesN","Signets","TailleMax","PagesBlanches"] {
+                                        ui.selectable_value(&mut self.pdf_split_mode, mode.into(), mode);
+                                    }
+                                });
+                            });
+                            match self.pdf_split_mode.as_str() {
+                                "Plages" => {
+                                    ui.horizontal(|ui| {
+                                        ui.label("Ranges:");
+                                        ui.text_edit_singleline(&mut self.pdf_split_plages);
+                                        ui.label("(1-3,4-10,11-)");
+                                    });
+                                },
+                                "ToutesLesN" => {
+                                    ui.horizontal(|ui| {
+                                        ui.label("Pages per file:");
+                                        ui.add(egui::DragValue::new(&mut self.pdf_split_n).range(1..=1000));
+                                    });
+                                },
+                                "TailleMax" => {
+                                    ui.horizontal(|ui| {
+                                        ui.label("Max size (MB):");
+                                        ui.add(egui::DragValue::new(&mut self.pdf_split_taille).range(0.1..=2000.0).speed(0.5));
+                                    });
+                                },
+                                "PagesBlanches" => {
+                                    ui.horizontal(|ui| {
+                                        ui.label("Blank threshold (% ink):");
+                                        ui.add(egui::Slider::new(&mut self.pdf_split_seuil, 0.0..=5.0).fixed_decimals(1));
+                                    });
+                                },
+                                _ => {}
+                            }
+                            ui.horizontal(|ui| {
+                                ui.label("Name template:");
+                                ui.text_edit_singleline(&mut self.pdf_split_modele);
+                            });
+                            ui.label("{name} {n} {start} {end} {title} — empty = default");
                         },
                         "pdf_merge" => {
                             ui.label(self.lang.doc_merge_hint1);
@@ -1322,13 +2046,51 @@ impl eframe::App for OxyonApp {
                                 ui.label(self.lang.pages_hint);
                             });
                         },
+                        "pdf_impose" => {
+                            ui.label("Lay out pages on A4 sheets: N-up, booklet or poster tiling.");
+                            ui.horizontal(|ui| {
+                                ui.label("Mode:");
+                                egui::ComboBox::from_id_salt("pdf_imposemode").selected_text(&self.pdf_impose_mode).show_ui(ui, |ui| {
+                                    for mode in ["NUp","Livret","Mosaique"] {
+                                        ui.selectable_value(&mut self.pdf_impose_mode, mode.into(), mode);
+                                    }
+                                });
+                            });
+                            match self.pdf_impose_mode.as_str() {
+                                "NUp" => {
+                                    ui.horizontal(|ui| {
+                                        ui.label("Pages per sheet:");
+                                        egui::ComboBox::from_id_salt("pdf_imposen").selected_text(self.pdf_impose_n.to_string()).show_ui(ui, |ui| {
+                                            for n in [2u32, 4, 6, 8, 9, 16] {
+                                                ui.selectable_value(&mut self.pdf_impose_n, n, n.to_string());
+                                            }
+                                        });
+                                    });
+                                },
+                                "Livret" => {
+                                    ui.label("Print duplex, flip on short edge, fold and staple.");
+                                },
+                                _ => {
+                                    ui.horizontal(|ui| {
+                                        ui.label("Scale:");
+                                        ui.add(egui::Slider::new(&mut self.pdf_impose_echelle, 1.0..=8.0).fixed_decimals(1));
+                                    });
+                                    ui.horizontal(|ui| {
+                                        ui.label("Overlap (mm):");
+                                        ui.add(egui::Slider::new(&mut self.pdf_impose_recouvrement, 0.0..=30.0).fixed_decimals(0));
+                                    });
+                                },
+                            }
+                        },
                         "pdf_organize" => {
                             ui.label(self.lang.doc_new_order);
                             ui.text_edit_singleline(&mut self.pdf_nouvel_ordre);
+                            ui.label(self.lang.pages_hint);
                         },
                         "pdf_delete_pages" => {
                             ui.label(self.lang.doc_delete_pages);
                             ui.text_edit_singleline(&mut self.pdf_pages_spec);
+                            ui.label(self.lang.pages_hint);
                         },
                         "pdf_numbers" => {
                             ui.horizontal(|ui| {
@@ -1359,6 +2121,23 @@ impl eframe::App for OxyonApp {
                             });
                             ui.checkbox(&mut self.pdf_allow_print, self.lang.doc_allow_print);
                             ui.checkbox(&mut self.pdf_allow_copy, self.lang.doc_allow_copy);
+                            ui.checkbox(&mut self.pdf_allow_modify, "Allow modification");
+                            ui.checkbox(&mut self.pdf_allow_annotate, "Allow annotations");
+                            ui.checkbox(&mut self.pdf_allow_forms, "Allow form filling");
+                            ui.checkbox(&mut self.pdf_allow_assemble, "Allow page assembly");
+                            ui.checkbox(&mut self.pdf_aes256, "AES-256 (PDF 2.0 readers; AES-128 otherwise)");
+                        },
+                        "pdf_compare" => {
+                            ui.label("Compares two PDFs page by page: added lines highlighted, deleted lines noted in the margin.");
+                            ui.label("💡 Select the old file first, then the new one — writes a .compare.txt report and a highlighted copy");
+                        },
+                        "pdf_info" => {
+                            ui.label("Security report: encryption, permissions, version, pages, fonts, JavaScript, forms.");
+                            ui.label("💡 Writes a .info.txt file next to the source file");
+                            ui.horizontal(|ui| {
+                                ui.label(self.lang.doc_password);
+                                ui.add(egui::TextEdit::singleline(&mut self.pdf_unlock_pass).password(true));
+                            });
                         },
                         "pdf_unlock" => {
                             ui.horizontal(|ui| {
@@ -1393,11 +2172,25 @@ impl eframe::App for OxyonApp {
                             });
                         },
                         "pdf_annotate" => {
-                            ui.label("Add a text annotation (FreeText) to PDF pages.");
+                            ui.label("Add a native annotation (movable and removable in PDF readers).");
+                            ui.horizontal(|ui| {
+                                ui.label("Type:");
+                                egui::ComboBox::from_id_salt("pdf_annottype").selected_text(&self.pdf_annot_type).show_ui(ui, |ui| {
+                                    for t in ["TexteLibre","Note","Surlignage","Soulignement","Barre","Lien"] {
+                                        ui.selectable_value(&mut self.pdf_annot_type, t.into(), t);
+                                    }
+                                });
+                            });
                             ui.horizontal(|ui| {
                                 ui.label("Text:");
                                 ui.text_edit_singleline(&mut self.pdf_annot_texte);
                             });
+                            if self.pdf_annot_type == "Lien" {
+                                ui.horizontal(|ui| {
+                                    ui.label("URL:");
+                                    ui.text_edit_singleline(&mut self.pdf_annot_url);
+                                });
+                            }
                             ui.label("Position & size (% of page):");
                             ui.horizontal(|ui| {
                                 ui.label("X:");
@@ -1417,6 +2210,145 @@ impl eframe::App for OxyonApp {
                                 ui.label(self.lang.pages_hint);
                             });
                         },
+                        "pdf_annot_remove" => {
+                            ui.label("Remove all annotations (comments, highlights, links) except form fields.");
+                            ui.horizontal(|ui| {
+                                ui.label(self.lang.pages_label);
+                                ui.text_edit_singleline(&mut self.pdf_pages_spec);
+                                ui.label(self.lang.pages_hint);
+                            });
+                        },
+                        "pdf_attach" => {
+                            ui.label("Embed files in the PDF (source spreadsheet, Factur-X XML…).");
+                            ui.horizontal(|ui| {
+                                if ui.button("📂 Files…").clicked()
+                                    && let Some(paths) = rfd::FileDialog::new().pick_files()
+                                {
+                                    self.pdf_pj_fichiers = paths;
+                                }
+                                if !self.pdf_pj_fichiers.is_empty() && ui.button("✖").clicked() {
+                                    self.pdf_pj_fichiers.clear();
+                                }
+                            });
+                            for f in &self.pdf_pj_fichiers {
+                                ui.label(format!("📎 {}", f.file_name().unwrap_or_default().to_string_lossy()));
+                            }
+                            ui.horizontal(|ui| {
+                                ui.label("Description:");
+                                ui.text_edit_singleline(&mut self.pdf_pj_description);
+                            });
+                        },
+                        "pdf_attach_extract" => {
+                            ui.label("Extract embedded files and file-attachment annotations.");
+                            ui.label("💡 Creates a _attachments/ folder next to the source file");
+                        },
+                        "pdf_images" => {
+                            ui.label("Extract the images of each page (JPEG kept as-is in auto mode).");
+                            ui.label("💡 Creates a _images/ folder next to the source file");
+                            ui.horizontal(|ui| {
+                                ui.label("Format:");
+                                egui::ComboBox::from_id_salt("pdf_imagesformat").selected_text(&self.pdf_images_format).show_ui(ui, |ui| {
+                                    for f in ["auto","png","jpg","webp","tiff"] {
+                                        ui.selectable_value(&mut self.pdf_images_format, f.into(), f);
+                                    }
+                                });
+                                ui.label("Min size (px):");
+                                ui.add(egui::Slider::new(&mut self.pdf_images_min, 1..=1000));
+                            });
+                            ui.horizontal(|ui| {
+                                ui.label(self.lang.pages_label);
+                                ui.text_edit_singleline(&mut self.pdf_pages_spec);
+                                ui.label(self.lang.pages_hint);
+                            });
+                        },
+                        "pdf_clean_scan" => {
+                            ui.label("Clean scanned batches: drop blank pages, straighten tilted scans, crop each page to its content.");
+                            ui.horizontal(|ui| {
+                                ui.checkbox(&mut self.pdf_scan_recadrer, "Auto-crop");
+                                ui.label("Margin (mm):");
+                                ui.add(egui::Slider::new(&mut self.pdf_scan_marge, 0.0..=30.0).fixed_decimals(1));
+                            });
+                            ui.checkbox(&mut self.pdf_scan_redresser, "Deskew image-only pages");
+                            ui.horizontal(|ui| {
+                                ui.checkbox(&mut self.pdf_scan_blanches, "Remove blank pages");
+                                ui.label("Ink threshold (%):");
+                                ui.add(egui::Slider::new(&mut self.pdf_scan_seuil, 0.0..=5.0).fixed_decimals(1));
+                            });
+                            ui.horizontal(|ui| {
+                                ui.label(self.lang.pages_label);
+                                ui.text_edit_singleline(&mut self.pdf_pages_spec);
+                                ui.label(self.lang.pages_hint);
+                            });
+                        },
+                        "pdf_detach" => {
+                            ui.label("Remove embedded files. Leave empty to remove all.");
+                            ui.horizontal(|ui| {
+                                ui.label("Names:");
+                                ui.text_edit_singleline(&mut self.pdf_pj_noms);
+                            });
+                        },
+                        "pdf_flatten" => {
+                            ui.label("Burn annotation appearances into the page content (no longer editable).");
+                            ui.label("Links and form fields are kept; hidden annotations are removed.");
+                        },
+                        "pdf_pdfa" => {
+                            ui.label("Convert to PDF/A-2b (archival): embed fonts, sRGB OutputIntent, XMP metadata.");
+                            ui.label("Encryption and JavaScript are removed. A validation report (.pdfa.txt) is written next to the output.");
+                        },
+                        "pdf_stamp" => {
+                            ui.label("Image stamp (PNG/JPEG) and header/footer templates.");
+                            ui.horizontal(|ui| {
+                                ui.label("Image:");
+                                ui.text_edit_singleline(&mut self.pdf_stamp_image);
+                                if ui.button("📂").clicked()
+                                    && let Some(path) = rfd::FileDialog::new()
+                                        .add_filter("Image", &["png", "jpg", "jpeg"])
+                                        .pick_file()
+                                {
+                                    self.pdf_stamp_image = path.to_string_lossy().to_string();
+                                }
+                            });
+                            if !self.pdf_stamp_image.trim().is_empty() {
+                                ui.horizontal(|ui| {
+                                    ui.label(self.lang.doc_position);
+                                    egui::ComboBox::from_id_salt("pdf_stampancre").selected_text(&self.pdf_stamp_ancre).show_ui(ui, |ui| {
+                                        for ancre in modules::doc::AncreTampon::TOUTES {
+                                            ui.selectable_value(&mut self.pdf_stamp_ancre, ancre.into(), ancre);
+                                        }
+                                    });
+                                });
+                                ui.horizontal(|ui| {
+                                    ui.label("Width (% page):");
+                                    ui.add(egui::Slider::new(&mut self.pdf_stamp_echelle, 1.0..=100.0).fixed_decimals(0));
+                                });
+                                ui.horizontal(|ui| {
+                                    ui.label("Rotation:");
+                                    ui.add(egui::Slider::new(&mut self.pdf_stamp_rotation, -180.0..=180.0).fixed_decimals(0));
+                                });
+                                ui.horizontal(|ui| {
+                                    ui.label(self.lang.doc_opacity);
+                                    ui.add(egui::Slider::new(&mut self.pdf_stamp_opacite, 0.05..=1.0).fixed_decimals(2));
+                                });
+                            }
+                            ui.horizontal(|ui| {
+                                ui.label("Header:");
+                                ui.text_edit_singleline(&mut self.pdf_stamp_entete);
+                            });
+                            ui.horizontal(|ui| {
+                                ui.label("Footer:");
+                                ui.text_edit_singleline(&mut self.pdf_stamp_pied);
+                            });
+                            ui.label("{page} {pages} {date} {filename} {title}");
+                            ui.horizontal(|ui| {
+                                ui.label(self.lang.doc_size);
+                                ui.add(egui::Slider::new(&mut self.pdf_stamp_taille, 6.0..=24.0).fixed_decimals(0));
+                            });
+                            ui.horizontal(|ui| {
+                                ui.label(self.lang.pages_label);
+                                ui.text_edit_singleline(&mut self.pdf_pages_spec);
+                                ui.label(self.lang.pages_hint);
+                            });
+                        },
                         "pdf_sign" => {
                             ui.label("Add a visual signature line with name and date.");
                             ui.horizontal(|ui| {
@@ -1449,15 +2381,61 @@ impl eframe::App for OxyonApp {
                         ui.label(self.lang.action_label);
                         egui::ComboBox::from_id_salt("img_action").selected_text(&self.image_action).show_ui(ui, |ui| {
                             ui.selectable_value(&mut self.image_action, "Convert".into(), self.lang.doc_convert);
+                            ui.selectable_value(&mut self.image_action, "adjust".into(), "Adjust colors");
+                            ui.selectable_value(&mut self.image_action, "animate".into(), "Assemble animation");
+                            ui.selectable_value(&mut self.image_action, "auto_orient".into(), "Auto-orient");
 							ui.selectable_value(&mut self.image_action, "crop".into(), self.lang.img_crop);
+                            ui.selectable_value(&mut self.image_action, "duplicates".into(), "Find duplicates");
+                            ui.selectable_value(&mut self.image_action, "edit_metadata".into(), "Edit metadata");
+                            ui.selectable_value(&mut self.image_action, "extract_frames".into(), "Extract frames");
                             ui.selectable_value(&mut self.image_action, "html_to_image".into(), "HTML to Image");
+                            ui.selectable_value(&mut self.image_action, "icon_set".into(), "Icon set (favicons, ICNS)");
                             ui.selectable_value(&mut self.image_action, "meme".into(), "Meme Generator");
+                            ui.selectable_value(&mut self.image_action, "montage".into(), "Contact sheet / montage");
                             ui.selectable_value(&mut self.image_action, "resize".into(), self.lang.img_resize);
                             ui.selectable_value(&mut self.image_action, "rotate".into(), self.lang.img_rotate);
+                            ui.selectable_value(&mut self.image_action, "strip_metadata".into(), "Strip metadata");
                             ui.selectable_value(&mut self.image_action, "upscale".into(), "Upscale");
                             ui.selectable_value(&mut self.image_action, "watermark".into(), "Watermark");
                         });
                     });
+                    if self.image_action != "auto_orient" {
+                        ui.checkbox(&mut self.img_orientation_auto, "Straighten photos using their EXIF orientation");
+                    }
+                    if self.image_action == "Convert" {
+                        ui.horizontal(|ui| {
+                            ui.label("Color profile:");
+                            egui::ComboBox::from_id_salt("img_profil").selected_text(&self.img_profil_couleur).show_ui(ui, |ui| {
+                                for p in modules::pic::ProfilCible::TOUS {
+                                    ui.selectable_value(&mut self.img_profil_couleur, p.to_string(), p);
+                                }
+                            });
+                        });
+                    }
+                    let hdr = self.current_files.iter().any(|f| {
+                        f.extension().is_some_and(|e| e.eq_ignore_ascii_case("exr") || e.eq_ignore_ascii_case("hdr"))
+                    });
+                    if hdr {
+                        ui.horizontal(|ui| {
+                            ui.label("HDR tone mapping:");
+                            egui::ComboBox::from_id_salt("img_tons").selected_text(self.img_tons.operateur.nom()).show_ui(ui, |ui| {
+                                for nom in modules::pic::OperateurTonal::TOUS {
+                                    if let Some(op) = modules::pic::OperateurTonal::depuis_nom(nom) {
+                                        ui.selectable_value(&mut self.img_tons.operateur, op, nom);
+                                    }
+                                }
+                            });
+                            let mut auto = self.img_tons.exposition.is_none();
+                            ui.checkbox(&mut auto, "Auto exposure");
+                            if auto {
+                                self.img_tons.exposition = None;
+                            } else {
+                                ui.add(egui::Slider::new(&mut self.img_tons_ev, -8.0..=8.0).fixed_decimals(1).text("EV"));
+                                self.img_tons.exposition = Some(self.img_tons_ev);
+                            }
+                            ui.add(egui::DragValue::new(&mut self.img_tons.gamma).range(1.0..=3.0).speed(0.05).prefix("Gamma "));
+                        });
+                    }
                     ui.separator();
                     match self.image_action.as_str() {
                         "Convert" => {
@@ -1547,11 +2525,48 @@ impl eframe::App for OxyonApp {
                             ui.separator();
                             ui.label(self.lang.img_resize_px);
                             ui.horizontal(|ui| {
-                                ui.label(self.lang.img_width);
-                                ui.text_edit_singleline(&mut self.resize_width);
-                                ui.label(self.lang.img_height);
-                                ui.text_edit_singleline(&mut self.resize_height);
+                                ui.label("Mode:");
+                                egui::ComboBox::from_id_salt("resize_mode").selected_text(&self.resize_mode).show_ui(ui, |ui| {
+                                    for mode in modules::pic::ModeRedimension::TOUS {
+                                        ui.selectable_value(&mut self.resize_mode, mode.into(), mode);
+                                    }
+                                });
+                                ui.label("Filter:");
+                                egui::ComboBox::from_id_salt("resize_filtre").selected_text(&self.resize_filtre).show_ui(ui, |ui| {
+                                    for (nom, _) in modules::pic::FILTRES_REDIMENSION {
+                                        ui.selectable_value(&mut self.resize_filtre, nom.into(), nom);
+                                    }
+                                });
                             });
+                            match modules::pic::ModeRedimension::depuis_nom(&self.resize_mode).unwrap_or_default() {
+                                modules::pic::ModeRedimension::Pourcentage => {
+                                    ui.horizontal(|ui| {
+                                        ui.label("Scale (%):");
+                                        ui.add(egui::Slider::new(&mut self.resize_pourcentage, 1.0..=400.0).fixed_decimals(0));
+                                    });
+                                },
+                                modules::pic::ModeRedimension::GrandCote | modules::pic::ModeRedimension::PetitCote => {
+                                    ui.horizontal(|ui| {
+                                        ui.label("Edge (px):");
+                                        ui.text_edit_singleline(&mut self.resize_cote);
+                                    });
+                                },
+                                mode => {
+                                    ui.horizontal(|ui| {
+                                        ui.label(self.lang.img_width);
+                                        ui.text_edit_singleline(&mut self.resize_width);
+                                        ui.label(self.lang.img_height);
+                                        ui.text_edit_singleline(&mut self.resize_height);
+                                    });
+                                    if mode == modules::pic::ModeRedimension::Marges {
+                                        ui.horizontal(|ui| {
+                                            ui.label("Padding color:");
+                                            ui.color_edit_button_srgba_unmultiplied(&mut self.resize_fond);
+                                        });
+                                    }
+                                },
+                            }
+                            ui.checkbox(&mut self.resize_reduire_seulement, "Only shrink (never enlarge)");
                             ui.label(self.lang.img_andor);
                             ui.horizontal(|ui| {
                                 ui.label(self.lang.img_max_size);
@@ -1597,9 +2612,45 @@ impl eframe::App for OxyonApp {
                                 ui.label("Opacity:");
                                 ui.add(egui::Slider::new(&mut self.img_wm_opacite, 0.05..=1.0).fixed_decimals(2));
                             });
+                            ui.horizontal(|ui| {
+                                ui.label("Color:");
+                                ui.color_edit_button_srgba_unmultiplied(&mut self.img_wm_couleur);
+                                ui.label("Outline (px):");
+                                ui.add(egui::Slider::new(&mut self.img_wm_contour, 0.0..=8.0).fixed_decimals(0));
+                                ui.checkbox(&mut self.img_wm_ombre, "Shadow");
+                            });
+                            ui.horizontal(|ui| {
+                                ui.label("Font (TTF/OTF):");
+                                ui.text_edit_singleline(&mut self.img_texte_police);
+                                if ui.button("📂").clicked()
+                                    && let Some(path) = rfd::FileDialog::new()
+                                        .add_filter("Font", &["ttf", "otf"])
+                                        .pick_file()
+                                {
+                                    self.img_texte_police = path.to_string_lossy().to_string();
+                                }
+                            });
+                            ui.horizontal(|ui| {
+                                ui.checkbox(&mut self.img_wm_diagonale, "Follow diagonal");
+                                if !self.img_wm_diagonale {
+                                    ui.label("Rotation (°):");
+                                    ui.add(egui::Slider::new(&mut self.img_wm_rotation, -180.0..=180.0).fixed_decimals(0));
+                                }
+                            });
+                            ui.horizontal(|ui| {
+                                ui.checkbox(&mut self.img_wm_mosaique, "Tile over the whole image");
+                                if !self.img_wm_mosaique {
+                                    ui.label("Position:");
+                                    egui::ComboBox::from_id_salt("img_wm_ancre").selected_text(&self.img_wm_ancre).show_ui(ui, |ui| {
+                                        for ancre in modules::doc::AncreTampon::TOUTES {
+                                            ui.selectable_value(&mut self.img_wm_ancre, ancre.into(), ancre);
+                                        }
+                                    });
+                                }
+                            });
                         },
                         "meme" => {
-                            ui.label("Add meme-style text (white on black bars).");
+                            ui.label("Add meme-style text (white on black bars, or outlined over the image).");
                             ui.horizontal(|ui| {
                                 ui.label("Top text:");
                                 ui.text_edit_singleline(&mut self.img_meme_top);
@@ -1608,6 +2659,205 @@ impl eframe::App for OxyonApp {
                                 ui.label("Bottom text:");
                                 ui.text_edit_singleline(&mut self.img_meme_bottom);
                             });
+                            ui.checkbox(&mut self.img_meme_bandes, "Black bars");
+                            ui.horizontal(|ui| {
+                                ui.label("Font (TTF/OTF):");
+                                ui.text_edit_singleline(&mut self.img_texte_police);
+                                if ui.button("📂").clicked()
+                                    && let Some(path) = rfd::FileDialog::new()
+                                        .add_filter("Font", &["ttf", "otf"])
+                                        .pick_file()
+                                {
+                                    self.img_texte_police = path.to_string_lossy().to_string();
+                                }
+                            });
+                        },
+                        "edit_metadata" => {
+                            ui.label("Edit EXIF/XMP fields without re-encoding (JPEG, PNG, WebP, TIFF). Empty fields are left unchanged.");
+                            egui::Grid::new("img_edit_meta").num_columns(2).show(ui, |ui| {
+                                for (libelle, champ, indice) in [
+                                    ("Date taken:", &mut self.img_edit_date, "YYYY:MM:DD HH:MM:SS"),
+                                    ("Shift dates:", &mut self.img_edit_decalage, "+1h30m, -2d, -01:30"),
+                                    ("Artist:", &mut self.img_edit_artiste, ""),
+                                    ("Copyright:", &mut self.img_edit_copyright, "© 2026 …"),
+                                    ("Description:", &mut self.img_edit_description, ""),
+                                    ("Keywords:", &mut self.img_edit_mots_cles, "comma separated"),
+                                    ("GPS:", &mut self.img_edit_gps, "48.8584, 2.2945"),
+                                ] {
+                                    ui.label(libelle);
+                                    ui.add(egui::TextEdit::singleline(champ).hint_text(indice));
+                                    ui.end_row();
+                                }
+                            });
+                        },
+                        "animate" => {
+                            ui.label("Assemble the selected images, in order, into one animation (animation_oxyon.*).");
+                            ui.horizontal(|ui| {
+                                ui.label(self.lang.format_label);
+                                egui::ComboBox::from_id_salt("anim_fmt").selected_text(&self.img_anim_format).show_ui(ui, |ui| {
+                                    for f in ["gif", "png", "webp"] {
+                                        ui.selectable_value(&mut self.img_anim_format, f.to_string(), f);
+                                    }
+                                });
+                            });
+                            ui.horizontal(|ui| {
+                                ui.label("Frame delay (ms):");
+                                ui.add(egui::DragValue::new(&mut self.img_anim_delai).range(10..=60_000));
+                                ui.label("Loops (0 = infinite):");
+                                ui.add(egui::DragValue::new(&mut self.img_anim_boucles).range(0..=1000));
+                            });
+                        },
+                        "duplicates" => {
+                            ui.label("Group near-identical images among the selected files (report: duplicates_oxyon.txt).");
+                            ui.horizontal(|ui| {
+                                ui.label("Hash:");
+                                egui::ComboBox::from_id_salt("dup_algo").selected_text(self.img_doublons_algo.nom()).show_ui(ui, |ui| {
+                                    for nom in modules::pic::Empreinte::TOUS {
+                                        if let Some(e) = modules::pic::Empreinte::depuis_nom(nom) {
+                                            ui.selectable_value(&mut self.img_doublons_algo, e, nom);
+                                        }
+                                    }
+                                });
+                                ui.label("Max distance:");
+                                ui.add(egui::Slider::new(&mut self.img_doublons_seuil, 0..=20));
+                            });
+                            ui.horizontal(|ui| {
+                                ui.label("Keep:");
+                                let texte = self.img_doublons_garder.map_or("report only", |c| c.nom());
+                                egui::ComboBox::from_id_salt("dup_keep").selected_text(texte).show_ui(ui, |ui| {
+                                    ui.selectable_value(&mut self.img_doublons_garder, None, "report only");
+                                    for nom in modules::pic::Conservation::TOUS {
+                                        ui.selectable_value(&mut self.img_doublons_garder, modules::pic::Conservation::depuis_nom(nom), nom);
+                                    }
+                                });
+                                if self.img_doublons_garder.is_some() {
+                                    ui.checkbox(&mut self.img_doublons_simulation, "Dry run");
+                                }
+                            });
+                            if self.img_doublons_garder.is_some() && !self.img_doublons_simulation {
+                                ui.small("Duplicates will be moved to a duplicates_oxyon folder.");
+                            }
+                        },
+                        "montage" => {
+                            ui.label("Lay out the selected images in a grid (montage_oxyon.*). Several pages are numbered, or grouped in one PDF.");
+                            let m = &mut self.img_montage;
+                            ui.horizontal(|ui| {
+                                ui.label(self.lang.format_label);
+                                egui::ComboBox::from_id_salt("montage_fmt").selected_text(&self.img_montage_format).show_ui(ui, |ui| {
+                                    for f in ["png", "jpg", "pdf"] {
+                                        ui.selectable_value(&mut self.img_montage_format, f.to_string(), f);
+                                    }
+                                });
+                                ui.label("Page:");
+                                egui::ComboBox::from_id_salt("montage_page").selected_text(&self.img_montage_page).show_ui(ui, |ui| {
+                                    for p in ["none", "a4", "a3", "a5", "letter", "legal"] {
+                                        ui.selectable_value(&mut self.img_montage_page, p.to_string(), p);
+                                    }
+                                });
+                                if self.img_montage_page != "none" {
+                                    ui.checkbox(&mut self.img_montage_paysage, "Landscape");
+                                    ui.label("DPI:");
+                                    ui.add(egui::DragValue::new(&mut m.dpi).range(36..=600));
+                                }
+                            });
+                            ui.horizontal(|ui| {
+                                ui.label("Columns (0 = auto):");
+                                ui.add(egui::DragValue::new(&mut m.colonnes).range(0..=50));
+                                ui.label("Rows (0 = auto):");
+                                ui.add(egui::DragValue::new(&mut m.lignes).range(0..=50));
+                                if self.img_montage_page == "none" {
+                                    ui.label("Cell size (px):");
+                                    ui.add(egui::DragValue::new(&mut m.taille_case).range(32..=2000));
+                                }
+                            });
+                            ui.horizontal(|ui| {
+                                ui.label("Spacing (px):");
+                                ui.add(egui::DragValue::new(&mut m.espacement).range(0..=200));
+                                ui.label("Background:");
+                                ui.color_edit_button_srgba_unmultiplied(&mut m.fond);
+                                ui.label("Caption:");
+                                egui::ComboBox::from_id_salt("montage_legende").selected_text(m.legende.nom()).show_ui(ui, |ui| {
+                                    for nom in modules::pic::Legende::TOUS {
+                                        if let Some(l) = modules::pic::Legende::depuis_nom(nom) {
+                                            ui.selectable_value(&mut m.legende, l, nom);
+                                        }
+                                    }
+                                });
+                            });
+                        },
+                        "extract_frames" => {
+                            ui.label("Save every frame of animated GIF/APNG/WebP files as PNG in <name>_frames.");
+                        },
+                        "icon_set" => {
+                            ui.label("Build favicon.ico, favicons, Apple touch and Android/PWA icons, icon.icns, site.webmanifest and icons.html in <name>_icons.");
+                            let ic = &mut self.img_icones;
+                            ui.horizontal(|ui| {
+                                ui.label("Padding (%):");
+                                ui.add(egui::Slider::new(&mut ic.marge, 0.0..=40.0).fixed_decimals(0));
+                                ui.label("Background:");
+                                ui.color_edit_button_srgba_unmultiplied(&mut ic.fond);
+                                ui.label("Theme color:");
+                                ui.color_edit_button_srgba_unmultiplied(&mut ic.couleur_theme);
+                            });
+                            ui.horizontal(|ui| {
+                                ui.label("App name:");
+                                ui.add(egui::TextEdit::singleline(&mut ic.nom).hint_text("file name"));
+                                ui.label("Short name:");
+                                ui.text_edit_singleline(&mut ic.nom_court);
+                            });
+                            ui.small("A transparent background stays transparent except on the Apple touch and maskable icons (white).");
+                        },
+                        "auto_orient" => {
+                            ui.label("Turn the pixels according to the EXIF Orientation tag and reset it to 1. Upright files are copied unchanged.");
+                        },
+                        "strip_metadata" => {
+                            ui.label("Remove metadata without re-encoding (JPEG, PNG, WebP). Output keeps the source format.");
+                            ui.checkbox(&mut self.img_meta_gps, "GPS location");
+                            ui.checkbox(&mut self.img_meta_serie, "Camera/lens serial numbers and maker notes");
+                            ui.checkbox(&mut self.img_meta_miniature, "Embedded thumbnail");
+                            ui.checkbox(&mut self.img_meta_essentiel, "Everything except copyright and orientation");
+                            ui.label("With nothing checked, all metadata is removed.");
+                        },
+                        "adjust" => {
+                            let r = &mut self.img_reglages;
+                            ui.horizontal(|ui| {
+                                ui.label("Brightness:");
+                                ui.add(egui::Slider::new(&mut r.luminosite, -100.0..=100.0).fixed_decimals(0));
+                                ui.label("Contrast:");
+                                ui.add(egui::Slider::new(&mut r.contraste, -100.0..=100.0).fixed_decimals(0));
+                            });
+                            ui.horizontal(|ui| {
+                                ui.label("Gamma:");
+                                ui.add(egui::Slider::new(&mut r.gamma, 0.2..=3.0).fixed_decimals(2));
+                                ui.label("Saturation:");
+                                ui.add(egui::Slider::new(&mut r.saturation, -100.0..=100.0).fixed_decimals(0));
+                            });
+                            ui.horizontal(|ui| {
+                                ui.label("Sharpen:");
+                                ui.add(egui::Slider::new(&mut r.nettete, 0.0..=3.0).fixed_decimals(1));
+                                ui.label("Radius:");
+                                ui.add(egui::Slider::new(&mut r.rayon_nettete, 0.3..=5.0).fixed_decimals(1));
+                            });
+                            ui.horizontal(|ui| {
+                                ui.label("Denoise:");
+                                ui.add(egui::Slider::new(&mut r.debruitage, 0.0..=5.0).fixed_decimals(1));
+                                ui.label("Tint:");
+                                egui::ComboBox::from_id_salt("img_virage").selected_text(r.virage.nom()).show_ui(ui, |ui| {
+                                    for nom in modules::pic::Virage::TOUS {
+                                        if let Some(v) = modules::pic::Virage::depuis_nom(nom) {
+                                            ui.selectable_value(&mut r.virage, v, nom);
+                                        }
+                                    }
+                                });
+                            });
+                            ui.horizontal(|ui| {
+                                ui.checkbox(&mut r.niveaux_auto, "Auto levels");
+                                ui.checkbox(&mut r.balance_auto, "Auto white balance");
+                                if ui.button("Reset").clicked() {
+                                    *r = modules::pic::OptionsReglages::default();
+                                }
+                            });
+                            self.apercu_reglages(ui);
                         },
                         "upscale" => {
                             ui.label("Enlarge image using Lanczos interpolation.");
@@ -1674,18 +2924,77 @@ impl eframe::App for OxyonApp {
                 #[cfg(feature = "api")]
                 ModuleType::Video => {
                     ui.horizontal(|ui| {
-                        egui::ComboBox::from_id_salt("vfmt").selected_text(&self.format_choisi).show_ui(ui, |ui| {
-                            for f in ["mkv","mp4","webm"] {
-                                ui.selectable_value(&mut self.format_choisi, f.into(), f);
-                            }
+                        ui.label(self.lang.action_label);
+                        egui::ComboBox::from_id_salt("video_action").selected_text(&self.video_action).show_ui(ui, |ui| {
+                            ui.selectable_value(&mut self.video_action, "Convert".into(), self.lang.doc_convert);
+                            ui.selectable_value(&mut self.video_action, "animate".into(), "GIF / animated WebP");
+                            ui.selectable_value(&mut self.video_action, "contact_sheet".into(), "Contact sheet");
+                            ui.selectable_value(&mut self.video_action, "thumbnail".into(), "Thumbnail");
                         });
-                        if ui.checkbox(&mut self.copie_flux, self.lang.video_stream_copy).changed() { self.save_config(); }
                     });
-                    if ui.add(egui::Slider::new(&mut self.video_speed, 0..=8).text(self.lang.video_quality_slider)).changed() {
-                        self.save_config();
-                    }
-                    if ui.checkbox(&mut self.save_video_format, self.lang.save_format).changed() {
-                        self.save_config();
+                    ui.separator();
+                    match self.video_action.as_str() {
+                        "animate" => {
+                            ui.horizontal(|ui| {
+                                ui.label(self.lang.format_label);
+                                egui::ComboBox::from_id_salt("video_anim_fmt").selected_text(&self.video_anim_format).show_ui(ui, |ui| {
+                                    for f in ["gif", "webp"] {
+                                        ui.selectable_value(&mut self.video_anim_format, f.to_string(), f);
+                                    }
+                                });
+                            });
+                            egui::Grid::new("video_anim_grid").num_columns(2).show(ui, |ui| {
+                                ui.label("Start (SS, MM:SS, HH:MM:SS):");
+                                ui.text_edit_singleline(&mut self.video_anim_debut);
+                                ui.end_row();
+                                ui.label("Duration (empty = to the end):");
+                                ui.text_edit_singleline(&mut self.video_anim_duree);
+                                ui.end_row();
+                                ui.label("FPS:");
+                                ui.add(egui::DragValue::new(&mut self.video_anim_fps).range(1..=60));
+                                ui.end_row();
+                                ui.label("Width (0 = source):");
+                                ui.add(egui::DragValue::new(&mut self.video_anim_largeur).range(0..=3840));
+                                ui.end_row();
+                            });
+                        },
+                        "thumbnail" => {
+                            ui.horizontal(|ui| {
+                                ui.label("Timestamp (SS, MM:SS, HH:MM:SS):");
+                                ui.text_edit_singleline(&mut self.video_vignette_instant);
+                            });
+                            ui.horizontal(|ui| {
+                                ui.checkbox(&mut self.video_vignette_scene, "First scene change after it, threshold:");
+                                ui.add_enabled(self.video_vignette_scene, egui::Slider::new(&mut self.video_vignette_seuil, 0.05..=0.95));
+                            });
+                        },
+                        "contact_sheet" => {
+                            ui.horizontal(|ui| {
+                                ui.label("Columns:");
+                                ui.add(egui::DragValue::new(&mut self.video_planche_colonnes).range(1..=12));
+                                ui.label("Rows:");
+                                ui.add(egui::DragValue::new(&mut self.video_planche_lignes).range(1..=20));
+                                ui.label("Thumbnail width:");
+                                ui.add(egui::DragValue::new(&mut self.video_planche_largeur).range(64..=1280));
+                            });
+                            ui.label("Timecodes use the font chosen in Image > Watermark (embedded font if empty).");
+                        },
+                        _ => {
+                            ui.horizontal(|ui| {
+                                egui::ComboBox::from_id_salt("vfmt").selected_text(&self.format_choisi).show_ui(ui, |ui| {
+                                    for f in ["mkv","mp4","webm"] {
+                                        ui.selectable_value(&mut self.format_choisi, f.into(), f);
+                                    }
+                                });
+                                if ui.checkbox(&mut self.copie_flux, self.lang.video_stream_copy).changed() { self.save_config(); }
+                            });
+                            if ui.add(egui::Slider::new(&mut self.video_speed, 0..=8).text(self.lang.video_quality_slider)).changed() {
+                                self.save_config();
+                            }
+                            if ui.checkbox(&mut self.save_video_format, self.lang.save_format).changed() {
+                                self.save_config();
+                            }
+                        },
                     }
                 },
                 #[cfg(feature = "api")]
@@ -2343,15 +3652,25 @@ fn percent_decode(input: &str) -> String {
     }
     String::from_utf8_lossy(&result).to_string()
 }
-fn parse_pages_spec(spec: &str) -> Option<Vec<u32>> {
-    let trimmed = spec.trim();
-    if trimmed.is_empty() || trimmed == "1-end" {
-        return None;
-    }
-    let pages: Vec<u32> = trimmed.split(',')
-    .filter_map(|s| s.trim().parse::<u32>().ok())
-    .collect();
-    if pages.is_empty() { None } else { Some(pages) }
+/// Sortie « _oxyon » gardant l'extension de la source (opérations sans réencodage)
+fn sortie_meme_format(input: &Path) -> String {
+    sortie_extension(input, &input.extension().unwrap_or_default().to_string_lossy())
+}
+
+/// Sortie « _oxyon » avec l'extension imposée par l'opération
+fn sortie_extension(input: &Path, ext: &str) -> String {
+    input.with_file_name(format!(
+        "{}_oxyon.{}",
+        input.file_stem().unwrap_or_default().to_string_lossy(),
+        ext
+    )).to_string_lossy().to_string()
+}
+
+/// PDF de travail (conversion unique si besoin) et sélection de pages résolue dessus
+fn parse_pages_spec(input: &Path, spec: &str) -> Result<(modules::doc::PdfPrepare, Option<Vec<u32>>), String> {
+    let source = modules::doc::pdf_preparer(input)?;
+    let pages = source.selection(spec)?;
+    Ok((source, pages))
 }
 fn main() -> eframe::Result {
     log_info(&format!("=== OXYON v{} START ===", VERSION));
@@ -20,9 +20,15 @@ fn main() -> ExitCode {
     let _ = modules::binaries::extraire_deps();
 
     let result = match args.command {
-        Command::Pic { action } => run_pic(action),
+        Command::Pic { action, no_auto_orient, tone_map, exposure, gamma } => {
+            modules::pic::OperateurTonal::depuis_nom(&tone_map)
+                .ok_or_else(|| format!("Unknown tone mapping '{}' (expected {})", tone_map, modules::pic::OperateurTonal::TOUS.join(", ")))
+                .and_then(|operateur| run_pic(action, !no_auto_orient, &modules::pic::OptionsTonales { operateur, exposition: exposure, gamma }))
+        }
         Command::Doc { action } => run_doc(action),
         #[cfg(feature = "api")]
+        Command::Video { action } => run_video(action),
+        #[cfg(feature = "api")]
         Command::Tag { action } => run_tag(action, lang_id),
         Command::Rename {
             files, find, replace, regex, list, ant, ant_set,
@@ -56,11 +62,14 @@ fn make_output(input: &Path, ext: &str) -> String {
     parent.join(format!("{}_oxyon.{}", stem, ext)).to_string_lossy().to_string()
 }
 
-fn parse_pages(spec: &Option<String>) -> Option<Vec<u32>> {
-    spec.as_ref().and_then(|s| {
-        let pages: Vec<u32> = s.split(',').filter_map(|p| p.trim().parse().ok()).collect();
-        if pages.is_empty() { None } else { Some(pages) }
-    })
+/// Prépare le fichier en PDF (une seule conversion) et résout la sélection de pages dessus
+fn parse_pages(file: &Path, spec: &Option<String>) -> Result<(modules::doc::PdfPrepare, Option<Vec<u32>>), String> {
+    let source = modules::doc::pdf_preparer(file)?;
+    let pages = match spec {
+        Some(s) => source.selection(s)?,
+        None => None,
+    };
+    Ok((source, pages))
 }
 
 fn ok_or_err(success: bool, context: &str) -> Result<(), String> {
@@ -69,54 +78,72 @@ fn ok_or_err(success: bool, context: &str) -> Result<(), String> {
 
 // ─── PIC ────────────────────────────────────────────────────────
 
-fn run_pic(action: PicAction) -> Result<(), String> {
+fn run_pic(action: PicAction, orientation_auto: bool, tons: &modules::pic::OptionsTonales) -> Result<(), String> {
     match action {
-        PicAction::Convert { files, to, quality, jxl_mode } => {
+        PicAction::Convert { files, to, quality, jxl_mode, profile } => {
+            let profil = modules::pic::ProfilCible::depuis_nom(&profile)?;
             for f in &files {
                 eprintln!("  pic convert: {:?} → {}", f, to);
                 if to.eq_ignore_ascii_case("jxl") {
                     match jxl_mode.as_str() {
-                        "folder" => modules::pic::convertir_jxl_dossier(f)?,
-                        "pivot" => modules::pic::convertir_jxl_pivot(f)?,
-                        _ => modules::pic::convertir_jxl_lossless(f)?,
+                        "folder" => modules::pic::convertir_jxl_dossier(f, orientation_auto)?,
+                        "pivot" => modules::pic::convertir_jxl_pivot(f, orientation_auto)?,
+                        _ => modules::pic::convertir_jxl_lossless(f, orientation_auto)?,
                     }
                 } else {
                     let out = make_output(f, &to);
-                    ok_or_err(modules::pic::compresser(f, &out, quality), "pic::compresser")?;
+                    ok_or_err(modules::pic::compresser(f, &out, quality, orientation_auto, &profil, tons), "pic::compresser")?;
                     eprintln!("    → {}", out);
                 }
             }
             Ok(())
         }
-        PicAction::Resize { files, width, height, max_kb } => {
+        PicAction::Resize { files, width, height, max_kb, min_quality, mode, percent, edge, background, only_shrink, filter } => {
+            let options = modules::pic::OptionsRedimension {
+                mode: modules::pic::ModeRedimension::depuis_nom(&mode)
+                    .ok_or_else(|| format!("Unknown resize mode '{}' (expected {})", mode, modules::pic::ModeRedimension::TOUS.join(", ")))?,
+                largeur: width.unwrap_or(0),
+                hauteur: height.unwrap_or(0),
+                pourcentage: percent.unwrap_or(0.0),
+                cote: edge.unwrap_or(0),
+                fond: modules::pic::parser_couleur(&background)?,
+                reduire_seulement: only_shrink,
+                filtre: modules::pic::filtre_depuis_nom(&filter).ok_or_else(|| format!("Unknown filter '{}'", filter))?,
+            };
+            let geometrie = options.est_definie();
             for f in &files {
                 let ext = f.extension().unwrap_or_default().to_string_lossy().to_string();
                 let out = make_output(f, &ext);
                 eprintln!("  pic resize: {:?}", f);
-                let w = width.unwrap_or(0);
-                let h = height.unwrap_or(0);
                 let kb = max_kb.unwrap_or(0);
-                if w > 0 && h > 0 && kb > 0 {
-                    let temp = format!("{}_temp.{}", out, ext);
-                    ok_or_err(modules::pic::redimensionner_pixels(f, &temp, w, h), "resize pixels")?;
-                    ok_or_err(modules::pic::redimensionner_poids(Path::new(&temp), &out, kb), "resize weight")?;
-                    let _ = std::fs::remove_file(&temp);
-                } else if w > 0 && h > 0 {
-                    ok_or_err(modules::pic::redimensionner_pixels(f, &out, w, h), "resize pixels")?;
+                if geometrie && kb > 0 {
+                    eprintln!("    {}", modules::pic::redimensionner_poids_cible(f, &out, &options, kb, min_quality, orientation_auto)?.resume());
+                } else if geometrie {
+                    ok_or_err(modules::pic::redimensionner(f, &out, &options, orientation_auto, tons), "resize pixels")?;
                 } else if kb > 0 {
-                    ok_or_err(modules::pic::redimensionner_poids(f, &out, kb), "resize weight")?;
+                    eprintln!("    {}", modules::pic::encoder_poids_cible(f, &out, kb, min_quality, orientation_auto)?.resume());
                 } else {
-                    return Err("Specify --width/--height and/or --max-kb".into());
+                    return Err("Specify a size for the mode (--width/--height, --percent or --edge) and/or --max-kb".into());
                 }
                 eprintln!("    → {}", out);
             }
             Ok(())
         }
+        PicAction::AutoOrient { files } => {
+            for f in &files {
+                let ext = f.extension().unwrap_or_default().to_string_lossy().to_string();
+                let out = make_output(f, &ext);
+                let orientation = modules::pic::lire_orientation(f);
+                ok_or_err(modules::pic::orienter_auto(f, &out), "pic::orienter_auto")?;
+                eprintln!("  oriented {:?} (EXIF orientation {}) → {}", f, orientation, out);
+            }
+            Ok(())
+        }
         PicAction::Rotate { files, angle } => {
             for f in &files {
                 let ext = f.extension().unwrap_or_default().to_string_lossy().to_string();
                 let out = make_output(f, &ext);
-                ok_or_err(modules::pic::pivoter(f, &out, angle), "pic::pivoter")?;
+                ok_or_err(modules::pic::pivoter(f, &out, angle, orientation_auto, tons), "pic::pivoter")?;
                 eprintln!("  rotated {:?} → {}", f, out);
             }
             Ok(())
@@ -125,12 +152,24 @@ fn run_pic(action: PicAction) -> Result<(), String> {
             for f in &files {
                 let ext = f.extension().unwrap_or_default().to_string_lossy().to_string();
                 let out = make_output(f, &ext);
-                ok_or_err(modules::pic::recadrer(f, &out, x, y, width, height), "pic::recadrer")?;
+                ok_or_err(modules::pic::recadrer(f, &out, x, y, width, height, orientation_auto, tons), "pic::recadrer")?;
                 eprintln!("  cropped {:?} → {}", f, out);
             }
             Ok(())
         }
-        PicAction::Exif { files } => {
+        PicAction::Exif { files, set, shift } => {
+            let mut edition = modules::pic::EditionMeta::default();
+            for affectation in &set { edition.affecter(affectation)?; }
+            if let Some(s) = &shift { edition.decalage = Some(modules::pic::parser_decalage(s)?); }
+            if !edition.est_vide() {
+                for f in &files {
+                    let ext = f.extension().unwrap_or_default().to_string_lossy().to_string();
+                    let out = make_output(f, &ext);
+                    ok_or_err(modules::pic::editer_metadonnees(f, &out, &edition), "pic::editer_metadonnees")?;
+                    eprintln!("  edited metadata {:?} → {}", f, out);
+                }
+                return Ok(());
+            }
             for f in &files {
                 let tags = modules::pic::lire_exif(f);
                 if tags.is_empty() {
@@ -139,15 +178,176 @@ fn run_pic(action: PicAction) -> Result<(), String> {
                     println!("{}:", f.display());
                     for t in &tags { println!("  {}", t); }
                 }
+                let meta = modules::pic::lire_metadonnees(f);
+                for (nom, bloc) in [("XMP", &meta.xmp), ("IPTC", &meta.iptc)] {
+                    if let Some(b) = bloc { println!("  {}: {} bytes", nom, b.len()); }
+                }
+                if let Some(icc) = meta.icc.or_else(|| modules::pic::lire_profil_icc(f)) {
+                    println!("  ICC profile: {} ({} bytes)", modules::pic::decrire_profil(&icc), icc.len());
+                }
             }
             Ok(())
         }
-        PicAction::StripExif { files } => {
+        PicAction::Adjust {
+            files, brightness, contrast, gamma, saturation, sharpen, sharpen_radius, sharpen_threshold,
+            denoise, tint, auto_levels, auto_wb,
+        } => {
+            let options = modules::pic::OptionsReglages {
+                luminosite: brightness,
+                contraste: contrast,
+                gamma,
+                saturation,
+                nettete: sharpen,
+                rayon_nettete: sharpen_radius,
+                seuil_nettete: sharpen_threshold,
+                debruitage: denoise,
+                virage: modules::pic::Virage::depuis_nom(&tint)
+                    .ok_or_else(|| format!("Unknown tint '{}' (expected {})", tint, modules::pic::Virage::TOUS.join(", ")))?,
+                niveaux_auto: auto_levels,
+                balance_auto: auto_wb,
+            };
+            if options.est_neutre() {
+                return Err("Nothing to adjust: set at least one option (e.g. --brightness, --auto-levels)".into());
+            }
             for f in &files {
                 let ext = f.extension().unwrap_or_default().to_string_lossy().to_string();
                 let out = make_output(f, &ext);
-                ok_or_err(modules::pic::supprimer_exif(f, &out), "pic::supprimer_exif")?;
-                eprintln!("  stripped EXIF {:?} → {}", f, out);
+                ok_or_err(modules::pic::ajuster(f, &out, &options, orientation_auto), "pic::ajuster")?;
+                eprintln!("  adjusted {:?} → {}", f, out);
+            }
+            Ok(())
+        }
+        PicAction::Duplicates { paths, hash, threshold, keep, move_to, delete, dry_run } => {
+            let algo = modules::pic::Empreinte::depuis_nom(&hash)
+                .ok_or_else(|| format!("Unknown hash '{}' (expected {})", hash, modules::pic::Empreinte::TOUS.join(", ")))?;
+            let regle = keep.map(|k| modules::pic::Conservation::depuis_nom(&k)
+                .ok_or_else(|| format!("Unknown keep rule '{}' (expected {})", k, modules::pic::Conservation::TOUS.join(", "))))
+                .transpose()?;
+            let groupes = modules::pic::chercher_doublons(&paths, algo, threshold.min(64), regle.unwrap_or_default(), orientation_auto)?;
+            if groupes.is_empty() {
+                eprintln!("  no duplicates found");
+                return Ok(());
+            }
+            // Rien n'est retiré sans destination explicite : --move-to ou --delete
+            let simulation = dry_run || (move_to.is_none() && !delete);
+            let bilan = modules::pic::traiter_doublons(&groupes, regle.is_some(), move_to.as_deref(), simulation);
+            println!("{}", bilan.rapport);
+            if regle.is_some() && simulation && !dry_run {
+                eprintln!("  nothing removed: pass --move-to <folder> or --delete to apply");
+            }
+            if bilan.echecs > 0 {
+                return Err(format!("{} duplicate(s) could not be removed", bilan.echecs));
+            }
+            Ok(())
+        }
+        PicAction::Frames { files, output_dir } => {
+            for f in &files {
+                let dossier = output_dir.clone().unwrap_or_else(|| f.parent().unwrap_or(Path::new(".")).join(
+                    format!("{}_frames", f.file_stem().unwrap_or_default().to_string_lossy())
+                ));
+                let n = modules::pic::extraire_trames(f, &dossier, orientation_auto)?;
+                eprintln!("  extracted {} frame(s) {:?} → {:?}", n, f, dossier);
+            }
+            Ok(())
+        }
+        PicAction::Animate { files, output, delay, loops } => {
+            let images = match files.as_slice() {
+                [dossier] if dossier.is_dir() => modules::pic::collecter_images_dossier(dossier)?,
+                _ => files,
+            };
+            let n = modules::pic::assembler_animation(&images, &output, delay, loops, orientation_auto)?;
+            eprintln!("  assembled {} frame(s) → {}", n, output);
+            Ok(())
+        }
+        PicAction::Icons { file, output, padding, background, name, short_name, theme_color, url_prefix } => {
+            let options = modules::pic::OptionsIcones {
+                marge: padding,
+                fond: modules::pic::parser_couleur(&background)?,
+                nom: name,
+                nom_court: short_name,
+                couleur_theme: modules::pic::parser_couleur(&theme_color)?,
+                prefixe_url: url_prefix,
+            };
+            let dossier = output.unwrap_or_else(|| file.parent().unwrap_or(Path::new(".")).join(
+                format!("{}_icons", file.file_stem().unwrap_or_default().to_string_lossy())
+            ));
+            let fichiers = modules::pic::generer_jeu_icones(&file, &dossier, &options, orientation_auto)?;
+            eprintln!("  {} icon file(s) {:?} → {:?}", fichiers.len(), file, dossier);
+            if let Ok(html) = std::fs::read_to_string(dossier.join("icons.html")) {
+                print!("{}", html);
+            }
+            Ok(())
+        }
+        PicAction::Montage { paths, output, cols, rows, cell_size, spacing, background, caption, page, landscape, dpi, font } => {
+            let page_mm = page.as_deref().map(modules::pic::parser_format_page).transpose()?
+                .map(|(l, h)| if landscape { (h, l) } else { (l, h) });
+            let options = modules::pic::OptionsMontage {
+                colonnes: cols,
+                lignes: rows,
+                taille_case: cell_size,
+                espacement: spacing,
+                fond: modules::pic::parser_couleur(&background)?,
+                legende: modules::pic::Legende::depuis_nom(&caption)
+                    .ok_or_else(|| format!("Unknown caption '{}' (expected {})", caption, modules::pic::Legende::TOUS.join(", ")))?,
+                page_mm,
+                dpi: dpi.clamp(36, 1200),
+                police: font,
+            };
+            let images = modules::pic::collecter_images(&paths);
+            if images.is_empty() {
+                return Err("No images found".into());
+            }
+            let n = modules::pic::montage(&images, &output, &options, orientation_auto)?;
+            eprintln!("  {} image(s) on {} page(s) → {}", images.len(), n, output);
+            Ok(())
+        }
+        PicAction::StripExif { files, gps, serials, thumbnail, keep_essential } => {
+            let options = modules::pic::OptionsNettoyageMeta {
+                gps,
+                numeros_serie: serials,
+                miniature: thumbnail,
+                sauf_essentiel: keep_essential,
+            };
+            for f in &files {
+                let ext = f.extension().unwrap_or_default().to_string_lossy().to_string();
+                let out = make_output(f, &ext);
+                ok_or_err(modules::pic::nettoyer_metadonnees(f, &out, &options), "pic::nettoyer_metadonnees")?;
+                eprintln!("  stripped metadata {:?} → {}", f, out);
+            }
+            Ok(())
+        }
+        PicAction::Watermark { files, text, size, opacity, font, color, outline, outline_color, shadow, rotation, anchor, tile } => {
+            let options = modules::pic::OptionsWatermark {
+                style: modules::pic::StyleTexte {
+                    police: font,
+                    taille: size,
+                    couleur: modules::pic::parser_couleur(&color)?,
+                    contour: if outline > 0.0 { Some((modules::pic::parser_couleur(&outline_color)?, outline)) } else { None },
+                    ombre: shadow.map(|d| ([0, 0, 0, 160], (d, d))),
+                    rotation: rotation.unwrap_or(0.0),
+                },
+                opacite: opacity,
+                ancre: modules::doc::AncreTampon::depuis_nom(&anchor)
+                    .ok_or_else(|| format!("Unknown anchor '{}'", anchor))?,
+                diagonale: rotation.is_none(),
+                mosaique: tile,
+                ..Default::default()
+            };
+            for f in &files {
+                let ext = f.extension().unwrap_or_default().to_string_lossy().to_string();
+                let out = make_output(f, &ext);
+                ok_or_err(modules::pic::watermark_avance(f, &out, &text, &options, orientation_auto), "pic::watermark")?;
+                eprintln!("  watermarked {:?} → {}", f, out);
+            }
+            Ok(())
+        }
+        PicAction::Meme { files, top, bottom, font, size, no_bars } => {
+            let style = modules::pic::StyleTexte { police: font, taille: size, ..Default::default() };
+            for f in &files {
+                let ext = f.extension().unwrap_or_default().to_string_lossy().to_string();
+                let out = make_output(f, &ext);
+                ok_or_err(modules::pic::meme_style(f, &out, &top, &bottom, &style, !no_bars, orientation_auto), "pic::meme")?;
+                eprintln!("  meme {:?} → {}", f, out);
             }
             Ok(())
         }
@@ -168,13 +368,26 @@ fn run_doc(action: DocAction) -> Result<(), String> {
             }
             Ok(())
         }
-        DocAction::PdfSplit { file } => {
+        DocAction::PdfSplit { file, ranges, every, bookmarks, max_size, blank, blank_threshold, name } => {
+            let mode = if let Some(spec) = ranges {
+                modules::doc::ModeSplit::Plages(spec)
+            } else if let Some(n) = every {
+                modules::doc::ModeSplit::ToutesLesN(n)
+            } else if bookmarks {
+                modules::doc::ModeSplit::Signets
+            } else if let Some(mo) = max_size {
+                modules::doc::ModeSplit::TailleMax(mo)
+            } else if blank {
+                modules::doc::ModeSplit::PagesBlanches(blank_threshold)
+            } else {
+                modules::doc::ModeSplit::Pages
+            };
             let output_dir = file.parent().unwrap_or(Path::new(".")).join(
                 format!("{}_pages", file.file_stem().unwrap_or_default().to_string_lossy())
             );
             std::fs::create_dir_all(&output_dir).map_err(|e| e.to_string())?;
-            let pages = modules::doc::pdf_split(&file, output_dir.to_str().unwrap())?;
-            eprintln!("  split {:?} → {} pages in {:?}", file, pages.len(), output_dir);
+            let fichiers = modules::doc::pdf_split_mode(&file, output_dir.to_str().unwrap(), &mode, name.as_deref().unwrap_or(""))?;
+            eprintln!("  split {:?} → {} files in {:?}", file, fichiers.len(), output_dir);
             Ok(())
         }
         DocAction::PdfMerge { files, output } => {
@@ -185,8 +398,8 @@ fn run_doc(action: DocAction) -> Result<(), String> {
         }
         DocAction::PdfRotate { file, angle, pages } => {
             let out = make_output(&file, "pdf");
-            let p = parse_pages(&pages);
-            modules::doc::pdf_rotate(&file, &out, angle, p.as_deref())?;
+            let (source, p) = parse_pages(&file, &pages)?;
+            modules::doc::pdf_rotate(source.chemin(), &out, angle, p.as_deref())?;
             eprintln!("  rotated {:?} → {}", file, out);
             Ok(())
         }
@@ -198,24 +411,58 @@ fn run_doc(action: DocAction) -> Result<(), String> {
         }
         DocAction::PdfCrop { file, x, y, width, height, pages } => {
             let out = make_output(&file, "pdf");
-            let p = parse_pages(&pages);
-            modules::doc::pdf_crop(&file, &out, x, y, width, height, p.as_deref())?;
+            let (source, p) = parse_pages(&file, &pages)?;
+            modules::doc::pdf_crop(source.chemin(), &out, x, y, width, height, p.as_deref())?;
             eprintln!("  cropped {:?} → {}", file, out);
             Ok(())
         }
+        DocAction::PdfCleanScan { files, no_crop, margin, no_deskew, keep_blank, blank_threshold, pages } => {
+            let options = modules::doc::OptionsScan {
+                recadrer: !no_crop,
+                marge_mm: margin,
+                redresser: !no_deskew,
+                supprimer_blanches: !keep_blank,
+                seuil_encre: blank_threshold,
+            };
+            for f in &files {
+                let out = make_output(f, "pdf");
+                let (source, p) = parse_pages(f, &pages)?;
+                let rapport = modules::doc::pdf_nettoyer_scan(source.chemin(), &out, &options, p.as_deref())?;
+                eprintln!(
+                    "  cleaned {:?} → {} (cropped {}, deskewed {:?}, removed blank {:?})",
+                    f, out, rapport.pages_recadrees.len(), rapport.pages_redressees, rapport.pages_supprimees
+                );
+            }
+            Ok(())
+        }
+        DocAction::PdfImpose { files, nup, booklet, tile, overlap } => {
+            let mode = if booklet {
+                modules::doc::ModeImposition::Livret
+            } else if let Some(echelle) = tile {
+                modules::doc::ModeImposition::Mosaique { echelle, recouvrement_mm: overlap }
+            } else {
+                modules::doc::ModeImposition::NUp(nup.unwrap_or(2))
+            };
+            for f in &files {
+                let out = make_output(f, "pdf");
+                modules::doc::pdf_imposer(f, &out, &mode)?;
+                eprintln!("  imposed {:?} → {}", f, out);
+            }
+            Ok(())
+        }
         DocAction::PdfOrganize { file, order } => {
             let out = make_output(&file, "pdf");
-            let ordre: Vec<u32> = order.split(',').filter_map(|s| s.trim().parse().ok()).collect();
-            if ordre.is_empty() { return Err("Invalid page order".into()); }
-            modules::doc::pdf_organiser(&file, &out, &ordre)?;
+            let source = modules::doc::pdf_preparer(&file)?;
+            let ordre = modules::doc::parser_selection_pages(&order, source.nb_pages()?)?;
+            modules::doc::pdf_organiser(source.chemin(), &out, &ordre)?;
             eprintln!("  reordered {:?} → {}", file, out);
             Ok(())
         }
         DocAction::PdfDelete { file, pages } => {
             let out = make_output(&file, "pdf");
-            let p: Vec<u32> = pages.split(',').filter_map(|s| s.trim().parse().ok()).collect();
-            if p.is_empty() { return Err("No valid pages specified".into()); }
-            modules::doc::pdf_supprimer_pages(&file, &out, &p)?;
+            let source = modules::doc::pdf_preparer(&file)?;
+            let p = modules::doc::parser_selection_pages(&pages, source.nb_pages()?)?;
+            modules::doc::pdf_supprimer_pages(source.chemin(), &out, &p)?;
             eprintln!("  deleted pages {:?} from {:?} → {}", p, file, out);
             Ok(())
         }
@@ -233,12 +480,39 @@ fn run_doc(action: DocAction) -> Result<(), String> {
             eprintln!("  numbered {:?} → {}", file, out);
             Ok(())
         }
-        DocAction::PdfProtect { file, owner_pass, user_pass, allow_print, allow_copy } => {
+        DocAction::PdfProtect { file, owner_pass, user_pass, allow_print, allow_copy, allow_modify, allow_annotate, allow_forms, allow_assemble, aes256 } => {
             let out = make_output(&file, "pdf");
-            modules::doc::pdf_proteger(&file, &out, &owner_pass, &user_pass, allow_print, allow_copy)?;
+            let permissions = modules::doc::PermissionsPdf {
+                impression: allow_print,
+                copie: allow_copy,
+                modification: allow_modify,
+                annotation: allow_annotate,
+                formulaires: allow_forms,
+                assemblage: allow_assemble,
+            };
+            let chiffrement = if aes256 { modules::doc::ChiffrementPdf::Aes256 } else { modules::doc::ChiffrementPdf::Aes128 };
+            modules::doc::pdf_proteger_avance(&file, &out, &owner_pass, &user_pass, &permissions, chiffrement)?;
             eprintln!("  protected {:?} → {}", file, out);
             Ok(())
         }
+        DocAction::PdfInfo { files, password } => {
+            for f in &files {
+                let rapport = modules::doc::pdf_rapport(f, &password)?;
+                eprintln!("  {:?}", f);
+                print!("{}", rapport.en_texte());
+            }
+            Ok(())
+        }
+        DocAction::PdfCompare { old, new, highlight } => {
+            let out = highlight.then(|| make_output(&new, "pdf"));
+            let rapport = modules::doc::pdf_comparer(&old, &new, out.as_deref())?;
+            eprintln!("  {:?} → {:?}", old, new);
+            print!("{}", rapport.en_texte());
+            if let Some(out) = out {
+                eprintln!("  highlighted → {}", out);
+            }
+            Ok(())
+        }
         DocAction::PdfUnlock { file, password } => {
             let out = make_output(&file, "pdf");
             modules::doc::pdf_dechiffrer(&file, &out, &password)?;
@@ -253,11 +527,233 @@ fn run_doc(action: DocAction) -> Result<(), String> {
         }
         DocAction::PdfWatermark { file, text, size, opacity, pages } => {
             let out = make_output(&file, "pdf");
-            let p = parse_pages(&pages);
-            modules::doc::pdf_watermark(&file, &out, &text, size, opacity, p.as_deref())?;
+            let (source, p) = parse_pages(&file, &pages)?;
+            modules::doc::pdf_watermark(source.chemin(), &out, &text, size, opacity, p.as_deref())?;
             eprintln!("  watermarked {:?} → {}", file, out);
             Ok(())
         }
+        DocAction::PdfStamp { files, image, anchor, offset, scale, rotation, opacity, header, footer, size, pages } => {
+            let ancre = modules::doc::AncreTampon::depuis_nom(&anchor)
+                .ok_or_else(|| format!("Unknown anchor '{}'", anchor))?;
+            let decalage_mm = offset.split_once(',')
+                .and_then(|(x, y)| Some((x.trim().parse().ok()?, y.trim().parse().ok()?)))
+                .ok_or_else(|| format!("Invalid offset '{}' (expected \"x,y\")", offset))?;
+            let mut tampons = Vec::new();
+            if let Some(chemin) = image {
+                let placement = modules::doc::PlacementTampon { ancre, decalage_mm, rotation, opacite: opacity };
+                tampons.push(modules::doc::Tampon::Image { chemin, echelle: scale, placement });
+            }
+            for (modele, ancre) in [(header, modules::doc::AncreTampon::HautCentre), (footer, modules::doc::AncreTampon::BasCentre)] {
+                if let Some(modele) = modele {
+                    let placement = modules::doc::PlacementTampon { ancre, ..Default::default() };
+                    tampons.push(modules::doc::Tampon::Texte { modele, taille_police: size, placement });
+                }
+            }
+            if tampons.is_empty() {
+                return Err("Nothing to stamp: use --image, --header or --footer".into());
+            }
+            for f in &files {
+                let out = make_output(f, "pdf");
+                let (source, p) = parse_pages(f, &pages)?;
+                modules::doc::pdf_tamponner(source.chemin(), &out, &tampons, p.as_deref())?;
+                eprintln!("  stamped {:?} → {}", f, out);
+            }
+            Ok(())
+        }
+        DocAction::PdfAnnotate { files, kind, text, url, x, y, width, height, color, author, pages } => {
+            let type_annotation = match kind.to_lowercase().as_str() {
+                "note" => modules::doc::TypeAnnotation::Note,
+                "text" => modules::doc::TypeAnnotation::TexteLibre,
+                "highlight" => modules::doc::TypeAnnotation::Surlignage,
+                "underline" => modules::doc::TypeAnnotation::Soulignement,
+                "strikeout" => modules::doc::TypeAnnotation::Barre,
+                "link" => modules::doc::TypeAnnotation::Lien(url.ok_or("--url is required with --kind link")?),
+                _ => return Err(format!("Unknown annotation kind '{}'", kind)),
+            };
+            let mut annotation = modules::doc::NouvelleAnnotation::new(type_annotation, &text, [x, y, width, height]);
+            annotation.auteur = author;
+            if let Some(c) = color {
+                let v: Vec<f64> = c.split(',').filter_map(|s| s.trim().parse().ok()).collect();
+                if v.len() != 3 {
+                    return Err(format!("Invalid color '{}' (expected \"r,g,b\")", c));
+                }
+                annotation.couleur = [v[0], v[1], v[2]];
+            }
+            for f in &files {
+                let out = make_output(f, "pdf");
+                let (source, p) = parse_pages(f, &pages)?;
+                modules::doc::pdf_ajouter_annotations(source.chemin(), &out, std::slice::from_ref(&annotation), p.as_deref())?;
+                eprintln!("  annotated {:?} → {}", f, out);
+            }
+            Ok(())
+        }
+        DocAction::PdfAnnotations { files } => {
+            for f in &files {
+                let annotations = modules::doc::pdf_lister_annotations(f)?;
+                eprintln!("  {:?} : {} annotation(s)", f, annotations.len());
+                for a in &annotations {
+                    println!("p{}\t{}\t[{:.0} {:.0} {:.0} {:.0}]\t{}\t{}",
+                        a.page, a.sous_type, a.rect[0], a.rect[1], a.rect[2], a.rect[3], a.auteur, a.contenu.replace('\n', " "));
+                }
+            }
+            Ok(())
+        }
+        DocAction::PdfAnnotRemove { files, kind, pages } => {
+            let sous_types: Vec<String> = kind.map(|k| k.split(',').map(|s| s.trim().to_string()).collect()).unwrap_or_default();
+            for f in &files {
+                let out = make_output(f, "pdf");
+                let (source, p) = parse_pages(f, &pages)?;
+                let n = modules::doc::pdf_supprimer_annotations(source.chemin(), &out, &sous_types, p.as_deref())?;
+                eprintln!("  removed {} annotation(s) {:?} → {}", n, f, out);
+            }
+            Ok(())
+        }
+        DocAction::PdfFlatten { files } => {
+            for f in &files {
+                let out = make_output(f, "pdf");
+                let n = modules::doc::pdf_aplatir_annotations(f, &out)?;
+                eprintln!("  flattened {} annotation(s) {:?} → {}", n, f, out);
+            }
+            Ok(())
+        }
+        DocAction::PdfAttachments { files } => {
+            for f in &files {
+                let pieces = modules::doc::pdf_lister_pieces_jointes(f)?;
+                eprintln!("  {:?} : {} attachment(s)", f, pieces.len());
+                for p in &pieces {
+                    let page = p.page.map(|n| format!("p{}", n)).unwrap_or_else(|| "doc".into());
+                    println!("{}\t{}\t{}\t{} bytes\t{}", page, p.nom, p.mime, p.taille, p.description);
+                }
+            }
+            Ok(())
+        }
+        DocAction::PdfAttach { file, add, description } => {
+            let out = make_output(&file, "pdf");
+            let fichiers: Vec<(std::path::PathBuf, String)> = add.into_iter().map(|f| (f, description.clone())).collect();
+            modules::doc::pdf_ajouter_pieces_jointes(&file, &out, &fichiers)?;
+            eprintln!("  attached {} file(s) {:?} → {}", fichiers.len(), file, out);
+            Ok(())
+        }
+        DocAction::PdfAttachExtract { files } => {
+            for f in &files {
+                let output_dir = f.parent().unwrap_or(Path::new(".")).join(
+                    format!("{}_attachments", f.file_stem().unwrap_or_default().to_string_lossy())
+                );
+                let ecrits = modules::doc::pdf_extraire_pieces_jointes(f, output_dir.to_str().unwrap())?;
+                eprintln!("  extracted {} file(s) from {:?} → {:?}", ecrits.len(), f, output_dir);
+            }
+            Ok(())
+        }
+        DocAction::PdfDetach { files, name } => {
+            let noms: Vec<String> = name.map(|n| n.split(',').map(|s| s.trim().to_string()).collect()).unwrap_or_default();
+            for f in &files {
+                let out = make_output(f, "pdf");
+                let n = modules::doc::pdf_supprimer_pieces_jointes(f, &out, &noms)?;
+                eprintln!("  removed {} attachment(s) {:?} → {}", n, f, out);
+            }
+            Ok(())
+        }
+        DocAction::PdfImages { files, format, min_size, pages } => {
+            for f in &files {
+                let output_dir = f.parent().unwrap_or(Path::new(".")).join(
+                    format!("{}_images", f.file_stem().unwrap_or_default().to_string_lossy())
+                );
+                let (source, p) = parse_pages(f, &pages)?;
+                let ecrits = modules::doc::pdf_extraire_images(source.chemin(), output_dir.to_str().unwrap(), &format, min_size, p.as_deref())?;
+                eprintln!("  extracted {} image(s) from {:?} → {:?}", ecrits.len(), f, output_dir);
+            }
+            Ok(())
+        }
+        DocAction::PdfA { files } => {
+            for f in &files {
+                let out = make_output(f, "pdf");
+                let rapport = modules::doc::pdf_vers_pdfa(f, &out)?;
+                eprintln!("  pdf/a {:?} → {}", f, out);
+                eprint!("{}", rapport.en_texte());
+            }
+            Ok(())
+        }
+        DocAction::PdfaCheck { files } => {
+            let mut conformes = true;
+            for f in &files {
+                let rapport = modules::doc::pdfa_valider(f)?;
+                eprintln!("  {:?} : {}", f, if rapport.est_conforme() { "PDF/A-2b OK" } else { "non conforme" });
+                for v in &rapport.violations {
+                    eprintln!("    ! {}", v);
+                }
+                conformes &= rapport.est_conforme();
+            }
+            if conformes { Ok(()) } else { Err("PDF/A check failed".into()) }
+        }
+    }
+}
+
+// ─── VIDEO ──────────────────────────────────────────────────────
+
+#[cfg(feature = "api")]
+fn attendre_ffmpeg(child: std::io::Result<std::process::Child>, context: &str) -> Result<(), String> {
+    let sortie = child
+        .and_then(|c| c.wait_with_output())
+        .map_err(|e| format!("{} : ffmpeg {}", context, e))?;
+    if sortie.status.success() { return Ok(()); }
+    let stderr = String::from_utf8_lossy(&sortie.stderr);
+    Err(format!("{} failed (code={:?}) {}", context, sortie.status.code(), stderr.lines().last().unwrap_or("")))
+}
+
+#[cfg(feature = "api")]
+fn run_video(action: VideoAction) -> Result<(), String> {
+    use modules::video;
+    match action {
+        VideoAction::Animate { files, to, start, duration, fps, width, loops, quality } => {
+            let options = video::OptionsAnimationVideo {
+                debut: video::parser_temps(&start)?,
+                duree: duration.as_deref().map(video::parser_temps).transpose()?,
+                fps,
+                largeur: width,
+                boucles: loops,
+                qualite: quality,
+            };
+            for f in &files {
+                let out = make_output(f, &to);
+                attendre_ffmpeg(video::video_vers_animation(f, &out, &options), "video::video_vers_animation")?;
+                eprintln!("  animated {:?} → {}", f, out);
+            }
+            Ok(())
+        }
+        VideoAction::Thumbnail { files, at, scene, width, to } => {
+            let instant = video::parser_temps(&at)?;
+            let position = match scene {
+                Some(seuil) => video::PositionVignette::Scene { apres: instant, seuil },
+                None => video::PositionVignette::Instant(instant),
+            };
+            for f in &files {
+                let out = make_output(f, &to);
+                let _ = std::fs::remove_file(&out);
+                attendre_ffmpeg(video::extraire_vignette(f, &out, position, width), "video::extraire_vignette")?;
+                if !Path::new(&out).exists() {
+                    return Err(format!("No frame found for {:?} (no scene change after {}?)", f, at));
+                }
+                eprintln!("  thumbnail {:?} → {}", f, out);
+            }
+            Ok(())
+        }
+        VideoAction::Sheet { files, cols, rows, width, no_timecodes, no_header, font, to } => {
+            let options = video::OptionsPlanche {
+                colonnes: cols,
+                lignes: rows,
+                largeur_vignette: width,
+                timecodes: !no_timecodes,
+                entete: !no_header,
+                police: font,
+                ..Default::default()
+            };
+            for f in &files {
+                let out = make_output(f, &to);
+                let n = video::planche_contact(f, &out, &options)?;
+                eprintln!("  contact sheet {:?} ({} thumbnails) → {}", f, n, out);
+            }
+            Ok(())
+        }
     }
 }
 
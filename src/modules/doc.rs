@@ -1,11 +1,11 @@
 #![allow(dead_code)]
 use std::collections::BTreeMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::io::Read;
 
 use lopdf::content::{Content, Operation};
-use lopdf::encryption::crypt_filters::{Aes128CryptFilter, CryptFilter};
+use lopdf::encryption::crypt_filters::{Aes128CryptFilter, Aes256CryptFilter, CryptFilter};
 use lopdf::encryption::{EncryptionState, EncryptionVersion, Permissions};
 use lopdf::{dictionary, Document, Object, ObjectId, SaveOptions, Stream};
 
@@ -354,6 +354,65 @@ fn texte_vers_pdf(texte: &str, output: &str) -> Result<(), String> {
     sauvegarder(&mut doc, output)
 }
 
+/// Assemble des images en PDF, une par page. La page prend la taille de l'image à `dpi`
+/// (points = pixels × 72 / dpi) ; les pixels sont encodés en JPEG (DCTDecode), transparence ignorée.
+pub fn images_vers_pdf(images: &[image::DynamicImage], output: &str, dpi: f32) -> Result<(), String> {
+    crate::log_info(&format!("doc::images_vers_pdf | {} pages dpi={} -> {}", images.len(), dpi, output));
+    if images.is_empty() { return Err("Aucune image à assembler".into()); }
+    let echelle = 72.0 / dpi.max(1.0) as f64;
+    let mut doc = Document::with_version("1.5");
+    let mut page_ids = Vec::new();
+    for img in images {
+        let rgb = img.to_rgb8();
+        let mut jpeg = Vec::new();
+        image::codecs::jpeg::JpegEncoder::new_with_quality(&mut jpeg, 90)
+            .encode_image(&rgb)
+            .map_err(|e| format!("Encodage JPEG de la page : {}", e))?;
+        let image_id = doc.add_object(Stream::new(dictionary! {
+            "Type" => "XObject",
+            "Subtype" => "Image",
+            "Width" => rgb.width() as i64,
+            "Height" => rgb.height() as i64,
+            "ColorSpace" => "DeviceRGB",
+            "BitsPerComponent" => 8,
+            "Filter" => "DCTDecode",
+        }, jpeg));
+        let (largeur, hauteur) = (rgb.width() as f64 * echelle, rgb.height() as f64 * echelle);
+        let contenu = Content { operations: vec![
+            Operation::new("q", vec![]),
+            Operation::new("cm", vec![largeur.into(), 0.into(), 0.into(), hauteur.into(), 0.into(), 0.into()]),
+            Operation::new("Do", vec!["Im1".into()]),
+            Operation::new("Q", vec![]),
+        ] }.encode().map_err(|e| format!("Erreur encodage : {}", e))?;
+        let contenu_id = doc.add_object(Stream::new(dictionary! {}, contenu));
+        page_ids.push(doc.add_object(dictionary! {
+            "Type" => "Page",
+            "MediaBox" => vec![0.into(), 0.into(), largeur.into(), hauteur.into()],
+            "Resources" => dictionary! { "XObject" => dictionary! { "Im1" => Object::Reference(image_id) } },
+            "Contents" => Object::Reference(contenu_id),
+        }));
+    }
+
+    let pages_id = doc.add_object(dictionary! {
+        "Type" => "Pages",
+        "Kids" => page_ids.iter().map(|id| Object::Reference(*id)).collect::<Vec<_>>(),
+        "Count" => Object::Integer(page_ids.len() as i64),
+    });
+    for &pid in &page_ids {
+        if let Ok(Object::Dictionary(dict)) = doc.get_object_mut(pid) {
+            dict.set("Parent", Object::Reference(pages_id));
+        }
+    }
+    let catalog_id = doc.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => Object::Reference(pages_id),
+    });
+    doc.trailer.set("Root", catalog_id);
+    doc.compress();
+
+    sauvegarder(&mut doc, output)
+}
+
 /// Découpe le texte en lignes en respectant une largeur max en caractères
 fn decouper_texte(texte: &str, max_chars: usize) -> Vec<String> {
     let mut lignes = Vec::new();
@@ -716,6 +775,66 @@ fn nettoyer_temp(path: &str) {
     let _ = std::fs::remove_file(path);
 }
 
+/// Document prêt pour les opérations PDF : le fichier lui-même s'il est déjà en PDF,
+/// sinon sa conversion temporaire, supprimée quand la valeur est libérée.
+/// Permet de compter les pages et d'appliquer l'opération sur une seule conversion.
+/// Le PDF temporaire garde le nom de base de la source (noms des images extraites…).
+pub struct PdfPrepare {
+    chemin: PathBuf,
+    temporaire: bool,
+}
+
+impl PdfPrepare {
+    /// PDF à passer aux opérations
+    pub fn chemin(&self) -> &Path {
+        &self.chemin
+    }
+
+    pub fn nb_pages(&self) -> Result<u32, String> {
+        let doc = Document::load(&self.chemin).map_err(|e| format!("Erreur : {}", e))?;
+        Ok(doc.get_pages().len() as u32)
+    }
+
+    /// Résout une sélection de pages. Sélection vide ou "all" → None (toutes les pages).
+    pub fn selection(&self, spec: &str) -> Result<Option<Vec<u32>>, String> {
+        let spec = spec.trim();
+        if spec.is_empty() || spec.eq_ignore_ascii_case("all") || spec.eq_ignore_ascii_case("1-end") {
+            return Ok(None);
+        }
+        parser_selection_pages(spec, self.nb_pages()?).map(Some)
+    }
+}
+
+impl Drop for PdfPrepare {
+    fn drop(&mut self) {
+        if self.temporaire {
+            nettoyer_temp(&self.chemin.to_string_lossy());
+            if let Some(dossier) = self.chemin.parent() {
+                let _ = std::fs::remove_dir(dossier);
+            }
+        }
+    }
+}
+
+/// Prépare un document pour les opérations PDF (conversion temporaire si besoin)
+pub fn pdf_preparer(input: &Path) -> Result<PdfPrepare, String> {
+    if est_pdf(input) {
+        return Ok(PdfPrepare { chemin: input.to_path_buf(), temporaire: false });
+    }
+    let stem = input.file_stem().unwrap_or_default().to_string_lossy().to_string();
+    let dossier = std::env::temp_dir().join(format!("oxyon_prep_{}", stem));
+    std::fs::create_dir_all(&dossier).map_err(|e| format!("Dossier temporaire {:?} : {}", dossier, e))?;
+    let chemin = dossier.join(format!("{}.pdf", stem));
+    // Construit avant la conversion : le dossier est nettoyé même en cas d'échec
+    let prepare = PdfPrepare { chemin, temporaire: true };
+    let chemin_str = prepare.chemin.to_str().ok_or("Chemin temp invalide")?;
+    crate::log_info(&format!("pdf_preparer | {:?} -> {}", input, chemin_str));
+    if !convertir(input, chemin_str) {
+        return Err(format!("Conversion vers PDF temporaire échouée pour {}", input.display()));
+    }
+    Ok(prepare)
+}
+
 fn appliquer_operation_doc<F>(input: &Path, output: &str, op_pdf: F) -> Result<(), String>
 where
     F: FnOnce(&Path, &str) -> Result<(), String>,
@@ -738,6 +857,102 @@ where
     reconvert
 }
 
+// ════════════════════════════════════════════════════════════════════════
+//  SÉLECTION DE PAGES — "1-3,7,10-,odd,!5,-1"
+// ════════════════════════════════════════════════════════════════════════
+
+/// Résout une référence de page : "12", "last"/"end", "-1" (dernière), "-2"…
+fn resoudre_ref_page(texte: &str, nb_pages: u32) -> Result<u32, String> {
+    let texte = texte.trim();
+    if texte.eq_ignore_ascii_case("last") || texte.eq_ignore_ascii_case("end") {
+        return Ok(nb_pages);
+    }
+    let valeur = texte.parse::<i64>().map_err(|_| format!("Page invalide : '{}'", texte))?;
+    let page = if valeur < 0 { nb_pages as i64 + 1 + valeur } else { valeur };
+    if page < 1 || page > nb_pages as i64 {
+        return Err(format!("Page {} hors document ({} pages)", texte, nb_pages));
+    }
+    Ok(page as u32)
+}
+
+/// Résout un élément de sélection (sans exclusion) en liste ordonnée de pages
+fn resoudre_element_pages(element: &str, nb_pages: u32) -> Result<Vec<u32>, String> {
+    let (corps, parite) = match element.rsplit_once(':') {
+        Some((c, p)) if p.eq_ignore_ascii_case("odd") => (c, Some(1)),
+        Some((c, p)) if p.eq_ignore_ascii_case("even") => (c, Some(0)),
+        Some(_) => return Err(format!("Filtre invalide : '{}' (odd/even)", element)),
+        None => (element, None),
+    };
+    let corps = corps.trim();
+
+    let pages: Vec<u32> = match corps.to_ascii_lowercase().as_str() {
+        "all" => (1..=nb_pages).collect(),
+        "odd" => (1..=nb_pages).filter(|p| p % 2 == 1).collect(),
+        "even" => (1..=nb_pages).filter(|p| p % 2 == 0).collect(),
+        "reverse" => (1..=nb_pages).rev().collect(),
+        _ => {
+            // Le '-' initial est un index négatif, pas un séparateur de plage
+            match corps.get(1..).and_then(|reste| reste.find('-')).map(|i| i + 1) {
+                Some(i) => {
+                    let debut = resoudre_ref_page(&corps[..i], nb_pages)?;
+                    let fin_txt = corps[i + 1..].trim();
+                    let fin = if fin_txt.is_empty() { nb_pages } else { resoudre_ref_page(fin_txt, nb_pages)? };
+                    if debut <= fin { (debut..=fin).collect() } else { (fin..=debut).rev().collect() }
+                }
+                None => vec![resoudre_ref_page(corps, nb_pages)?],
+            }
+        }
+    };
+
+    Ok(match parite {
+        Some(r) => pages.into_iter().filter(|p| p % 2 == r).collect(),
+        None => pages,
+    })
+}
+
+/// Parse une sélection de pages → numéros 1-based, dans l'ordre demandé.
+///
+/// Syntaxe (éléments séparés par des virgules) :
+/// `7`, `1-3`, `10-` (jusqu'à la fin), `10-1` (ordre inverse), `last`, `-1` (dernière),
+/// `-3--1` (3 dernières), `odd`, `even`, `all`, `reverse`, `1-10:odd`,
+/// `!5` / `!2-4` (exclusions — seules, elles s'appliquent à tout le document).
+pub fn parser_selection_pages(spec: &str, nb_pages: u32) -> Result<Vec<u32>, String> {
+    if nb_pages == 0 {
+        return Err("Le PDF ne contient aucune page".into());
+    }
+    let mut inclus: Vec<u32> = Vec::new();
+    let mut exclus: Vec<u32> = Vec::new();
+    let mut a_inclusion = false;
+
+    for element in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        if let Some(exclusion) = element.strip_prefix('!') {
+            exclus.extend(resoudre_element_pages(exclusion, nb_pages)?);
+        } else {
+            a_inclusion = true;
+            inclus.extend(resoudre_element_pages(element, nb_pages)?);
+        }
+    }
+    if !a_inclusion {
+        inclus = (1..=nb_pages).collect();
+    }
+    inclus.retain(|p| !exclus.contains(p));
+    if inclus.is_empty() {
+        return Err(format!("Sélection de pages vide : '{}'", spec));
+    }
+    Ok(inclus)
+}
+
+/// Nombre de pages d'un document (converti en PDF temporaire si besoin)
+pub fn pdf_nombre_pages(input: &Path) -> Result<u32, String> {
+    pdf_preparer(input)?.nb_pages()
+}
+
+/// Résout une sélection de pages pour un fichier. Sélection vide ou "all" → None (toutes les pages).
+/// Pour enchaîner avec une opération sans reconvertir, passer par `pdf_preparer`.
+pub fn pdf_selection_pages(input: &Path, spec: &str) -> Result<Option<Vec<u32>>, String> {
+    pdf_preparer(input)?.selection(spec)
+}
+
 // ════════════════════════════════════════════════════════════════════════
 //  HELPERS PDF INTERNES
 // ════════════════════════════════════════════════════════════════════════
@@ -776,6 +991,19 @@ fn obtenir_mediabox(doc: &Document, page_id: ObjectId) -> Option<[f64; 4]> {
     None
 }
 
+/// Valeur d'un attribut de page, éventuellement héritée d'un nœud Pages parent
+fn attribut_herite(doc: &Document, page_id: ObjectId, cle: &[u8]) -> Option<Object> {
+    let mut courant = doc.get_dictionary(page_id).ok();
+    while let Some(d) = courant {
+        if let Ok(v) = d.get(cle) {
+            return Some(v.clone());
+        }
+        courant = d.get(b"Parent").and_then(|p| p.as_reference()).ok()
+            .and_then(|p| doc.get_dictionary(p).ok());
+    }
+    None
+}
+
 fn collecter_references(doc: &Document, obj: &Object, ids: &mut Vec<ObjectId>) {
     match obj {
         Object::Reference(id) => {
@@ -808,85 +1036,73 @@ fn ajouter_overlay_page(
     font_id: ObjectId,
     extra_gs: Option<(&str, ObjectId)>,
 ) -> Result<(), String> {
-    let stream_id = doc.add_object(Stream::new(dictionary! {}, content_bytes));
-
-    // Phase 1 : lire resources et contents (emprunt immutable via get_object)
-    let (res_dict, font_ref_id, existing_contents) = {
-        let page_obj = doc.get_object(page_id)
-            .map_err(|e| format!("Page introuvable : {}", e))?;
-        let dict = match page_obj {
-            Object::Dictionary(d) => d,
-            _ => return Err("L'objet page n'est pas un dictionnaire".into()),
-        };
+    let mut ressources = vec![("Font", font_name, font_id)];
+    if let Some((gs_name, gs_id)) = extra_gs {
+        ressources.push(("ExtGState", gs_name, gs_id));
+    }
+    ajouter_overlay_ressources(doc, page_id, content_bytes, &ressources)
+}
 
-        let res = match dict.get(b"Resources") {
-            Ok(Object::Dictionary(r)) => r.clone(),
-            Ok(Object::Reference(_id)) => { lopdf::Dictionary::default() }  // marqueur : on résoudra après
-            _ => lopdf::Dictionary::default(),
-        };
-        let res_ref = match dict.get(b"Resources") {
-            Ok(Object::Reference(id)) => Some(*id),
-            _ => None,
-        };
+/// Contenus existants d'une page, sous forme de liste de références
+fn contenus_page(doc: &Document, page_id: ObjectId) -> Result<Vec<Object>, String> {
+    let page_dict = doc.get_dictionary(page_id)
+        .map_err(|e| format!("Page introuvable : {}", e))?;
+    Ok(match page_dict.get(b"Contents") {
+        Ok(Object::Array(arr)) => arr.clone(),
+        Ok(Object::Reference(ref_id)) => vec![Object::Reference(*ref_id)],
+        _ => Vec::new(),
+    })
+}
 
-        let contents = match dict.get(b"Contents") {
-            Ok(Object::Array(arr)) => {
-                let mut arr = arr.clone();
-                arr.push(Object::Reference(stream_id));
-                Object::Array(arr)
-            }
-            Ok(Object::Reference(ref_id)) => {
-                Object::Array(vec![Object::Reference(*ref_id), Object::Reference(stream_id)])
-            }
-            _ => Object::Reference(stream_id),
-        };
+/// Helper : encadre le contenu existant d'une page par q/Q, pour que son état graphique
+/// (matrice, couleurs…) ne déborde pas sur un overlay ajouté ensuite.
+fn isoler_contenu_page(doc: &mut Document, page_id: ObjectId) -> Result<(), String> {
+    encadrer_contenu_page(doc, page_id, "q\n")
+}
 
-        (res, res_ref, contents)
-    };
+/// Helper : encadre le contenu existant d'une page entre `debut` (qui ouvre par « q ») et « Q »
+fn encadrer_contenu_page(doc: &mut Document, page_id: ObjectId, debut: &str) -> Result<(), String> {
+    let existants = contenus_page(doc, page_id)?;
+    let debut_id = doc.add_object(Stream::new(dictionary! {}, debut.as_bytes().to_vec()));
+    let fin_id = doc.add_object(Stream::new(dictionary! {}, b"Q\n".to_vec()));
+    let contents: Vec<Object> = std::iter::once(Object::Reference(debut_id))
+        .chain(existants)
+        .chain(std::iter::once(Object::Reference(fin_id)))
+        .collect();
+    if let Ok(Object::Dictionary(dict)) = doc.get_object_mut(page_id) {
+        dict.set("Contents", Object::Array(contents));
+    }
+    Ok(())
+}
 
-    // Résoudre les Resources si c'était une référence
-    let mut resources = if let Some(ref_id) = font_ref_id {
-        doc.get_dictionary(ref_id).cloned().unwrap_or(res_dict)
-    } else {
-        res_dict
-    };
+/// Helper : ajouter un overlay à une page avec ses ressources (catégorie, nom, objet)
+fn ajouter_overlay_ressources(
+    doc: &mut Document,
+    page_id: ObjectId,
+    content_bytes: Vec<u8>,
+    ressources: &[(&str, &str, ObjectId)],
+) -> Result<(), String> {
+    let stream_id = doc.add_object(Stream::new(dictionary! {}, content_bytes));
 
-    // Résoudre Font
-    let font_ref = match resources.get(b"Font") {
-        Ok(Object::Reference(id)) => Some(*id),
-        _ => None,
-    };
-    let mut fonts = match resources.get(b"Font") {
-        Ok(Object::Dictionary(f)) => f.clone(),
-        _ => lopdf::Dictionary::default(),
-    };
-    if let Some(fid) = font_ref {
-        fonts = doc.get_dictionary(fid).cloned().unwrap_or(fonts);
-    }
-    fonts.set(font_name, Object::Reference(font_id));
-    resources.set("Font", Object::Dictionary(fonts));
+    // Phase 1 : lire resources (éventuellement héritées) et contents
+    let mut contents = contenus_page(doc, page_id)?;
+    contents.push(Object::Reference(stream_id));
 
-    // Résoudre ExtGState si nécessaire
-    if let Some((gs_name, gs_id)) = extra_gs {
-        let gs_ref = match resources.get(b"ExtGState") {
-            Ok(Object::Reference(id)) => Some(*id),
-            _ => None,
-        };
-        let mut ext_gstate = match resources.get(b"ExtGState") {
-            Ok(Object::Dictionary(g)) => g.clone(),
-            _ => lopdf::Dictionary::default(),
-        };
-        if let Some(gid) = gs_ref {
-            ext_gstate = doc.get_dictionary(gid).cloned().unwrap_or(ext_gstate);
-        }
-        ext_gstate.set(gs_name, Object::Reference(gs_id));
-        resources.set("ExtGState", Object::Dictionary(ext_gstate));
+    let mut resources = attribut_herite(doc, page_id, b"Resources")
+        .and_then(|r| resoudre_dict(doc, &r).cloned())
+        .unwrap_or_default();
+    for &(categorie, nom, id) in ressources {
+        let mut sous_dict = resources.get(categorie.as_bytes()).ok()
+            .and_then(|d| resoudre_dict(doc, d).cloned())
+            .unwrap_or_default();
+        sous_dict.set(nom, Object::Reference(id));
+        resources.set(categorie, Object::Dictionary(sous_dict));
     }
 
     // Phase 2 : écrire (emprunt mutable)
     if let Ok(Object::Dictionary(dict)) = doc.get_object_mut(page_id) {
         dict.set("Resources", Object::Dictionary(resources));
-        dict.set("Contents", existing_contents);
+        dict.set("Contents", Object::Array(contents));
     }
 
     Ok(())
@@ -901,63 +1117,424 @@ fn sauvegarder(doc: &mut Document, output: &str) -> Result<(), String> {
 //  PDF SPLIT
 // ════════════════════════════════════════════════════════════════════════
 
-fn pdf_split_interne(input: &Path, output_dir: &str) -> Result<Vec<String>, String> {
-    std::fs::create_dir_all(output_dir)
-        .map_err(|e| format!("Impossible de créer le dossier : {}", e))?;
-
-    let doc = Document::load(input)
-        .map_err(|e| format!("Erreur chargement PDF : {}", e))?;
-    let pages = obtenir_pages_ordonnees(&doc);
+/// Mode de découpage d'un PDF
+#[derive(Debug, Clone, PartialEq)]
+pub enum ModeSplit {
+    /// Une page par fichier
+    Pages,
+    /// Plages explicites, ex. "1-3,4-10,11-" (une plage = un fichier)
+    Plages(String),
+    /// Toutes les N pages
+    ToutesLesN(u32),
+    /// Un fichier par signet de premier niveau
+    Signets,
+    /// Taille maximale par fichier (Mo)
+    TailleMax(f64),
+    /// Pages blanches séparatrices (lots numérisés) — les séparateurs sont retirés.
+    /// Le seuil est le pourcentage d'encre sous lequel une page est considérée blanche.
+    PagesBlanches(f64),
+}
 
-    if pages.is_empty() {
-        return Err("Le PDF ne contient aucune page".into());
+impl ModeSplit {
+    fn modele_defaut(&self) -> &'static str {
+        match self {
+            ModeSplit::Pages => "{name}_page_{n}",
+            ModeSplit::Signets => "{name}_{n}_{title}",
+            _ => "{name}_part_{n}",
+        }
     }
+}
 
-    crate::log_info(&format!("pdf_split_interne | {:?} | {} pages -> {}", input, pages.len(), output_dir));
+/// Une partie du découpage : pages (indices 0-based) + titre éventuel (signet)
+struct PartieSplit {
+    pages: Vec<usize>,
+    titre: String,
+}
 
-    let base_name = input.file_stem().unwrap_or_default().to_string_lossy();
-    let mut fichiers = Vec::new();
+/// Parse une spec de plages de découpage ("1-3,4-10,11-") → plages 1-based inclusives
+fn parser_plages_split(spec: &str, nb_pages: u32) -> Result<Vec<(u32, u32)>, String> {
+    let mut plages = Vec::new();
+    for morceau in spec.split(',').map(str::trim).filter(|m| !m.is_empty()) {
+        let (debut, fin) = match morceau.split_once('-') {
+            Some((a, b)) => {
+                let debut = if a.trim().is_empty() { 1 } else {
+                    a.trim().parse::<u32>().map_err(|_| format!("Plage invalide : '{}'", morceau))?
+                };
+                let fin = if b.trim().is_empty() || b.trim().eq_ignore_ascii_case("end") { nb_pages } else {
+                    b.trim().parse::<u32>().map_err(|_| format!("Plage invalide : '{}'", morceau))?
+                };
+                (debut, fin)
+            }
+            None => {
+                let p = morceau.parse::<u32>().map_err(|_| format!("Page invalide : '{}'", morceau))?;
+                (p, p)
+            }
+        };
+        if debut == 0 || debut > fin || debut > nb_pages {
+            return Err(format!("Plage hors document ({} pages) : '{}'", nb_pages, morceau));
+        }
+        plages.push((debut, fin.min(nb_pages)));
+    }
+    if plages.is_empty() {
+        return Err("Aucune plage de pages".into());
+    }
+    Ok(plages)
+}
 
-    for (i, &page_id) in pages.iter().enumerate() {
-        let mut new_doc = Document::with_version(&doc.version);
+/// Attributs hérités des nœuds Pages (à recopier sur une page extraite)
+const ATTRIBUTS_HERITES: &[&[u8]] = &[b"Resources", b"MediaBox", b"CropBox", b"Rotate"];
 
-        let mut objets = vec![page_id];
-        if let Ok(page_obj) = doc.get_object(page_id) {
-            collecter_references(&doc, page_obj, &mut objets);
+/// Collecte les objets nécessaires à des pages, sans remonter dans l'arbre des pages
+/// (évite de recopier tout le document via /Parent ou /P)
+fn collecter_objets_pages(doc: &Document, obj: &Object, pages: &[ObjectId], ids: &mut Vec<ObjectId>) {
+    match obj {
+        Object::Reference(id) => {
+            if ids.contains(id) { return; }
+            let Ok(referenced) = doc.get_object(*id) else { return };
+            if let Ok(d) = referenced.as_dict() {
+                let type_nom = d.get(b"Type").and_then(|t| t.as_name()).unwrap_or(b"");
+                if type_nom == b"Pages" || (type_nom == b"Page" && !pages.contains(id)) {
+                    return;
+                }
+            }
+            ids.push(*id);
+            collecter_objets_pages(doc, referenced, pages, ids);
         }
-
-        let mut id_map: BTreeMap<ObjectId, ObjectId> = BTreeMap::new();
-        for &old_id in &objets {
-            if !id_map.contains_key(&old_id) {
-                if let Ok(obj) = doc.get_object(old_id) {
-                    let new_id = new_doc.add_object(obj.clone());
-                    id_map.insert(old_id, new_id);
+        Object::Array(arr) => {
+            for item in arr { collecter_objets_pages(doc, item, pages, ids); }
+        }
+        Object::Dictionary(dict) => {
+            for (cle, val) in dict.iter() {
+                if cle == b"Parent" && dict.get(b"Type").and_then(|t| t.as_name()).map(|t| t == b"Page").unwrap_or(false) {
+                    continue;
                 }
+                collecter_objets_pages(doc, val, pages, ids);
             }
         }
+        Object::Stream(stream) => {
+            for (_, val) in stream.dict.iter() { collecter_objets_pages(doc, val, pages, ids); }
+        }
+        _ => {}
+    }
+}
+
+/// Construit un nouveau document ne contenant que les pages données (dans l'ordre).
+/// Les identifiants d'objets d'origine sont conservés, donc aucune renumérotation.
+fn extraire_pages_document(doc: &Document, pages: &[ObjectId]) -> Document {
+    let mut new_doc = Document::with_version(&doc.version);
+    new_doc.max_id = doc.max_id;
 
-        let new_page_id = id_map.get(&page_id).copied().unwrap_or(page_id);
-        let pages_id = new_doc.new_object_id();
+    let mut objets: Vec<ObjectId> = Vec::new();
+    for &page_id in pages {
+        collecter_objets_pages(doc, &Object::Reference(page_id), pages, &mut objets);
+    }
+    for id in objets {
+        if let Ok(obj) = doc.get_object(id) {
+            new_doc.objects.insert(id, obj.clone());
+        }
+    }
 
-        if let Ok(Object::Dictionary(dict)) = new_doc.get_object_mut(new_page_id) {
+    let pages_id = new_doc.new_object_id();
+    for &page_id in pages {
+        // Recopier les attributs hérités avant de détacher la page de son parent
+        let herites: Vec<(&[u8], Object)> = ATTRIBUTS_HERITES.iter()
+            .filter_map(|&cle| attribut_herite(doc, page_id, cle).map(|v| (cle, v)))
+            .collect();
+        if let Ok(Object::Dictionary(dict)) = new_doc.get_object_mut(page_id) {
+            for (cle, v) in herites {
+                if !dict.has(cle) { dict.set(cle, v); }
+            }
             dict.set("Parent", Object::Reference(pages_id));
         }
+    }
 
-        let pages_dict = dictionary! {
-            "Type" => "Pages",
-            "Kids" => vec![Object::Reference(new_page_id)],
-            "Count" => Object::Integer(1),
+    new_doc.objects.insert(pages_id, Object::Dictionary(dictionary! {
+        "Type" => "Pages",
+        "Kids" => pages.iter().map(|&p| Object::Reference(p)).collect::<Vec<_>>(),
+        "Count" => Object::Integer(pages.len() as i64),
+    }));
+    let catalog_id = new_doc.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+    });
+    new_doc.trailer.set("Root", catalog_id);
+    new_doc.compress();
+    new_doc
+}
+
+/// Catalogue, arbre des pages, table xref et trailer d'un PDF extrait (octets, estimation)
+const TAILLE_BASE_PDF: usize = 400;
+/// En-tête "n 0 obj … endobj" et entrée xref d'un objet
+const TAILLE_ENTETE_OBJET: usize = 40;
+
+/// Taille sérialisée approximative d'un objet (contenu des flux tel quel, déjà compressé ou non)
+fn taille_estimee_objet(obj: &Object) -> usize {
+    match obj {
+        Object::Stream(s) => s.content.len() + taille_estimee_dict(&s.dict) + 20,
+        Object::Dictionary(d) => taille_estimee_dict(d),
+        Object::Array(a) => a.iter().map(|v| taille_estimee_objet(v) + 1).sum::<usize>() + 2,
+        Object::String(o, _) => o.len() + 2,
+        Object::Name(n) => n.len() + 1,
+        _ => 10,
+    }
+}
+
+fn taille_estimee_dict(d: &lopdf::Dictionary) -> usize {
+    d.iter().map(|(k, v)| k.len() + 2 + taille_estimee_objet(v)).sum::<usize>() + 4
+}
+
+/// Proportion de pixels « encrés » (sombres) d'une image XObject, en %.
+/// None si le filtre n'est pas décodable (CCITT, JBIG2…).
+fn taux_encre_image(doc: &Document, image_id: ObjectId) -> Option<f64> {
+    let stream = doc.get_object(image_id).ok()?.as_stream().ok()?;
+    let dict = &stream.dict;
+    let largeur = dict.get(b"Width").and_then(|w| w.as_i64()).ok()? as usize;
+    let hauteur = dict.get(b"Height").and_then(|h| h.as_i64()).ok()? as usize;
+    let filtres = stream.filters().unwrap_or_default();
+
+    let lumas: Vec<u8> = if filtres.iter().any(|f| *f == b"DCTDecode") {
+        image::load_from_memory_with_format(&stream.content, image::ImageFormat::Jpeg).ok()?
+            .to_luma8()
+            .into_raw()
+    } else if filtres.iter().all(|f| *f == b"FlateDecode") {
+        let data = if filtres.is_empty() { stream.content.clone() } else { stream.decompressed_content().ok()? };
+        let bpc = dict.get(b"BitsPerComponent").and_then(|b| b.as_i64()).unwrap_or(8);
+        let composantes = match dict.get(b"ColorSpace").ok().map(|c| resoudre(doc, c)) {
+            Some(Object::Name(n)) if n == b"DeviceRGB" || n == b"CalRGB" => 3,
+            Some(Object::Name(n)) if n == b"DeviceCMYK" => 4,
+            Some(Object::Array(arr)) if arr.first().and_then(|c| c.as_name().ok()) == Some(b"ICCBased") => {
+                arr.get(1).and_then(|p| resoudre_dict(doc, p))
+                    .and_then(|p| p.get(b"N").and_then(|n| n.as_i64()).ok())
+                    .unwrap_or(3) as usize
+            }
+            Some(Object::Array(_)) => return None, // Indexed, Separation… : pas de décodage ici
+            _ => 1,
         };
-        new_doc.objects.insert(pages_id, Object::Dictionary(pages_dict));
+        match (bpc, composantes) {
+            (1, 1) => {
+                let ligne = largeur.div_ceil(8);
+                (0..hauteur)
+                    .flat_map(|y| (0..largeur).map(move |x| (y, x)))
+                    .map(|(y, x)| {
+                        let octet = data.get(y * ligne + x / 8).copied().unwrap_or(0xFF);
+                        if octet & (0x80 >> (x % 8)) != 0 { 255 } else { 0 }
+                    })
+                    .collect()
+            }
+            (8, 1) => data,
+            (8, 3) => data.chunks_exact(3)
+                .map(|p| ((p[0] as u32 * 299 + p[1] as u32 * 587 + p[2] as u32 * 114) / 1000) as u8)
+                .collect(),
+            (8, 4) => data.chunks_exact(4)
+                .map(|p| 255 - ((p[0] as u32 + p[1] as u32 + p[2] as u32) / 3 + p[3] as u32).min(255) as u8)
+                .collect(),
+            _ => return None,
+        }
+    } else {
+        return None;
+    };
 
-        let catalog_id = new_doc.add_object(dictionary! {
-            "Type" => "Catalog",
-            "Pages" => pages_id,
-        });
-        new_doc.trailer.set("Root", catalog_id);
-        new_doc.compress();
+    if lumas.is_empty() || largeur * hauteur == 0 {
+        return Some(0.0);
+    }
+    let encre = lumas.iter().filter(|&&l| l < 128).count();
+    Some(encre as f64 * 100.0 / lumas.len() as f64)
+}
+
+/// XObjects (nom, id) des ressources d'une page, y compris héritées
+fn xobjects_page(doc: &Document, page_id: ObjectId) -> Vec<(Vec<u8>, ObjectId)> {
+    doc.get_page_resources(page_id).ok()
+        .map(|(res, res_ids)| {
+            let mut dicts: Vec<&lopdf::Dictionary> = res.into_iter().collect();
+            dicts.extend(res_ids.into_iter().filter_map(|id| doc.get_dictionary(id).ok()));
+            dicts
+        })
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|res| res.get(b"XObject").ok().and_then(|x| resoudre_dict(doc, x)))
+        .flat_map(|x| x.iter().filter_map(|(nom, v)| v.as_reference().ok().map(|id| (nom.clone(), id))).collect::<Vec<_>>())
+        .collect()
+}
+
+/// Détecte une page blanche : ni texte, ni tracé, et images avec moins de `seuil_encre` % d'encre
+fn page_est_blanche(doc: &Document, page_id: ObjectId, seuil_encre: f64) -> bool {
+    let contenu = doc.get_page_content(page_id).unwrap_or_default();
+    let Ok(content) = Content::decode(&contenu) else { return false };
+    let images = xobjects_page(doc, page_id);
+
+    for op in &content.operations {
+        match op.operator.as_str() {
+            "Tj" | "TJ" | "'" | "\"" | "S" | "s" | "f" | "F" | "f*" | "B" | "B*" | "b" | "b*" | "sh" | "BI" => return false,
+            "Do" => {
+                let Some(nom) = op.operands.first().and_then(|n| n.as_name().ok()) else { continue };
+                let Some(&(_, xobj_id)) = images.iter().find(|(n, _)| n == nom) else { continue };
+                let est_image = doc.get_object(xobj_id).ok()
+                    .and_then(|o| o.as_stream().ok())
+                    .and_then(|s| s.dict.get(b"Subtype").and_then(|t| t.as_name()).ok())
+                    .map(|t| t == b"Image")
+                    .unwrap_or(false);
+                if !est_image {
+                    return false;
+                }
+                match taux_encre_image(doc, xobj_id) {
+                    Some(taux) if taux < seuil_encre => {}
+                    _ => return false,
+                }
+            }
+            _ => {}
+        }
+    }
+    true
+}
+
+/// Remplace les jetons du modèle de nom : {name}, {n}, {start}, {end}, {title}
+fn appliquer_modele_nom(modele: &str, base: &str, n: usize, debut: usize, fin: usize, titre: &str) -> String {
+    let titre: String = titre.chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' || c == ' ' { c } else { '_' })
+        .collect::<String>()
+        .trim()
+        .to_string();
+    let nom = modele
+        .replace("{name}", base)
+        .replace("{n}", &format!("{:04}", n))
+        .replace("{start}", &debut.to_string())
+        .replace("{end}", &fin.to_string())
+        .replace("{title}", &titre);
+    nom.trim_end_matches(['_', ' ', '-']).to_string()
+}
+
+/// Regroupe les pages selon le mode demandé
+fn calculer_parties_split(doc: &Document, pages: &[ObjectId], mode: &ModeSplit) -> Result<Vec<PartieSplit>, String> {
+    let nb = pages.len();
+    let partie = |r: std::ops::Range<usize>| PartieSplit { pages: r.collect(), titre: String::new() };
+
+    let parties = match mode {
+        ModeSplit::Pages => (0..nb).map(|i| partie(i..i + 1)).collect(),
+        ModeSplit::Plages(spec) => parser_plages_split(spec, nb as u32)?
+            .into_iter()
+            .map(|(debut, fin)| partie(debut as usize - 1..fin as usize))
+            .collect(),
+        ModeSplit::ToutesLesN(n) => {
+            if *n == 0 {
+                return Err("Le nombre de pages par fichier doit être ≥ 1".into());
+            }
+            (0..nb).step_by(*n as usize).map(|i| partie(i..(i + *n as usize).min(nb))).collect()
+        }
+        ModeSplit::Signets => {
+            let toc = doc.get_toc().map_err(|_| "Le PDF ne contient aucun signet".to_string())?;
+            let mut debuts: Vec<(usize, String)> = toc.toc.into_iter()
+                .filter(|t| t.level == 1 && t.page >= 1 && t.page <= nb)
+                .map(|t| (t.page - 1, t.title))
+                .collect();
+            debuts.sort_by_key(|(p, _)| *p);
+            debuts.dedup_by_key(|(p, _)| *p);
+            if debuts.is_empty() {
+                return Err("Aucun signet de premier niveau exploitable".into());
+            }
+            if debuts[0].0 > 0 {
+                debuts.insert(0, (0, String::new()));
+            }
+            debuts.iter().enumerate()
+                .map(|(i, (debut, titre))| {
+                    let fin = debuts.get(i + 1).map(|(p, _)| *p).unwrap_or(nb);
+                    PartieSplit { pages: (*debut..fin).collect(), titre: titre.clone() }
+                })
+                .collect()
+        }
+        ModeSplit::TailleMax(mo) => {
+            if *mo <= 0.0 {
+                return Err("La taille maximale doit être > 0".into());
+            }
+            let limite = (*mo * 1024.0 * 1024.0) as usize;
+            let mut parties: Vec<PartieSplit> = Vec::new();
+            let mut courante: Vec<usize> = Vec::new();
+            // Taille cumulée des objets de la partie ; les ressources partagées (polices…) ne comptent qu'une fois
+            let mut objets_partie: std::collections::HashSet<ObjectId> = std::collections::HashSet::new();
+            let mut taille = TAILLE_BASE_PDF;
+            for (i, &page_id) in pages.iter().enumerate() {
+                let mut objets = Vec::new();
+                collecter_objets_pages(doc, &Object::Reference(page_id), &[page_id], &mut objets);
+                let taille_objets = |partie: &std::collections::HashSet<ObjectId>| -> usize {
+                    objets.iter()
+                        .filter(|id| !partie.contains(id))
+                        .filter_map(|id| doc.get_object(*id).ok())
+                        .map(|o| taille_estimee_objet(o) + TAILLE_ENTETE_OBJET)
+                        .sum()
+                };
+                let ajout = taille_objets(&objets_partie);
+                if taille + ajout > limite && !courante.is_empty() {
+                    parties.push(PartieSplit { pages: std::mem::take(&mut courante), titre: String::new() });
+                    objets_partie.clear();
+                    taille = TAILLE_BASE_PDF + taille_objets(&objets_partie);
+                } else {
+                    taille += ajout;
+                }
+                objets_partie.extend(objets.iter().copied());
+                courante.push(i);
+            }
+            if !courante.is_empty() {
+                parties.push(PartieSplit { pages: courante, titre: String::new() });
+            }
+            parties
+        }
+        ModeSplit::PagesBlanches(seuil) => {
+            let mut parties: Vec<PartieSplit> = Vec::new();
+            let mut courante: Vec<usize> = Vec::new();
+            for (i, &page_id) in pages.iter().enumerate() {
+                if page_est_blanche(doc, page_id, *seuil) {
+                    if !courante.is_empty() {
+                        parties.push(PartieSplit { pages: std::mem::take(&mut courante), titre: String::new() });
+                    }
+                } else {
+                    courante.push(i);
+                }
+            }
+            if !courante.is_empty() {
+                parties.push(PartieSplit { pages: courante, titre: String::new() });
+            }
+            if parties.is_empty() {
+                return Err("Toutes les pages sont blanches".into());
+            }
+            parties
+        }
+    };
+    Ok(parties)
+}
+
+fn pdf_split_interne(input: &Path, output_dir: &str, mode: &ModeSplit, modele_nom: &str) -> Result<Vec<String>, String> {
+    std::fs::create_dir_all(output_dir)
+        .map_err(|e| format!("Impossible de créer le dossier : {}", e))?;
+
+    let doc = Document::load(input)
+        .map_err(|e| format!("Erreur chargement PDF : {}", e))?;
+    let pages = obtenir_pages_ordonnees(&doc);
+
+    if pages.is_empty() {
+        return Err("Le PDF ne contient aucune page".into());
+    }
+
+    let parties = calculer_parties_split(&doc, &pages, mode)?;
+    crate::log_info(&format!("pdf_split_interne | {:?} | {} pages -> {} fichier(s) ({:?}) -> {}", input, pages.len(), parties.len(), mode, output_dir));
 
-        let output_path = format!("{}/{}_page_{:04}.pdf", output_dir, base_name, i + 1);
+    let base_name = input.file_stem().unwrap_or_default().to_string_lossy();
+    let modele = if modele_nom.trim().is_empty() { mode.modele_defaut() } else { modele_nom };
+    let mut fichiers: Vec<String> = Vec::new();
+
+    for (i, partie) in parties.iter().enumerate() {
+        let ids: Vec<ObjectId> = partie.pages.iter().map(|&p| pages[p]).collect();
+        let mut new_doc = extraire_pages_document(&doc, &ids);
+
+        let debut = partie.pages.first().map(|p| p + 1).unwrap_or(0);
+        let fin = partie.pages.last().map(|p| p + 1).unwrap_or(0);
+        let n = if *mode == ModeSplit::Pages { debut } else { i + 1 };
+        let mut nom = appliquer_modele_nom(modele, &base_name, n, debut, fin, &partie.titre);
+        if nom.is_empty() {
+            nom = format!("{}_{:04}", base_name, i + 1);
+        }
+        let mut output_path = format!("{}/{}.pdf", output_dir, nom);
+        if fichiers.contains(&output_path) {
+            output_path = format!("{}/{}_{:04}.pdf", output_dir, nom, i + 1);
+        }
         sauvegarder(&mut new_doc, &output_path)?;
         fichiers.push(output_path);
     }
@@ -967,12 +1544,19 @@ fn pdf_split_interne(input: &Path, output_dir: &str) -> Result<Vec<String>, Stri
 
 /// Split : fonctionne sur PDF et autres formats (convertit d'abord en PDF)
 pub fn pdf_split(input: &Path, output_dir: &str) -> Result<Vec<String>, String> {
+    pdf_split_mode(input, output_dir, &ModeSplit::Pages, "")
+}
+
+/// Split avec mode de découpage et modèle de nom.
+/// Jetons du modèle : {name} (nom source), {n} (numéro de partie), {start}/{end} (pages), {title} (signet).
+/// Modèle vide = modèle par défaut du mode.
+pub fn pdf_split_mode(input: &Path, output_dir: &str, mode: &ModeSplit, modele_nom: &str) -> Result<Vec<String>, String> {
     if est_pdf(input) {
-        return pdf_split_interne(input, output_dir);
+        return pdf_split_interne(input, output_dir, mode, modele_nom);
     }
     // Non-PDF : convertir d'abord
     let pdf_tmp = vers_pdf_temp(input)?;
-    let result = pdf_split_interne(Path::new(&pdf_tmp), output_dir);
+    let result = pdf_split_interne(Path::new(&pdf_tmp), output_dir, mode, modele_nom);
     nettoyer_temp(&pdf_tmp);
     result
 }
@@ -1224,26 +1808,376 @@ pub fn pdf_crop(
 }
 
 // ════════════════════════════════════════════════════════════════════════
-//  PDF ORGANIZE — Réorganise / supprime des pages
+//  PDF SCANS — recadrage automatique, redressement, pages blanches
 // ════════════════════════════════════════════════════════════════════════
 
-fn pdf_organiser_interne(input: &Path, output: &str, nouvel_ordre: &[u32]) -> Result<(), String> {
-    let mut doc = Document::load(input)
-        .map_err(|e| format!("Erreur chargement PDF : {}", e))?;
+/// Options de nettoyage d'un lot de pages numérisées
+#[derive(Debug, Clone)]
+pub struct OptionsScan {
+    /// Recadre chaque page sur les limites de son contenu
+    pub recadrer: bool,
+    /// Marge conservée autour du contenu (mm)
+    pub marge_mm: f64,
+    /// Redresse les pages constituées d'une seule image
+    pub redresser: bool,
+    /// Supprime les pages blanches
+    pub supprimer_blanches: bool,
+    /// Taux d'encre (%) sous lequel une page numérisée est considérée blanche
+    pub seuil_encre: f64,
+}
 
-    let pages = obtenir_pages_ordonnees(&doc);
-    let total = pages.len() as u32;
+impl Default for OptionsScan {
+    fn default() -> Self {
+        OptionsScan { recadrer: true, marge_mm: 5.0, redresser: true, supprimer_blanches: true, seuil_encre: 1.0 }
+    }
+}
 
-    for &num in nouvel_ordre {
-        if num == 0 || num > total {
-            return Err(format!("Page invalide : {}. Le PDF a {} pages.", num, total));
-        }
+/// Pages traitées (numéros du document d'origine)
+#[derive(Debug, Clone, Default)]
+pub struct RapportScan {
+    pub pages_recadrees: Vec<u32>,
+    /// (page, angle corrigé en degrés, positif = sens trigonométrique)
+    pub pages_redressees: Vec<(u32, f64)>,
+    pub pages_supprimees: Vec<u32>,
+}
+
+/// Luminance sous laquelle un pixel numérisé compte comme encre
+const SEUIL_LUMA_ENCRE: u8 = 128;
+/// Inclinaison maximale recherchée (degrés)
+const INCLINAISON_MAX: f64 = 8.0;
+/// En dessous de cet angle (degrés), la page est laissée telle quelle
+const INCLINAISON_MIN: f64 = 0.1;
+
+/// Limites (pixels, x0 y0 x1 y1, fin exclusive) du contenu encré d'une image.
+/// Les lignes / colonnes presque entièrement noires (bords de numérisation) et les poussières sont ignorées.
+fn limites_encre_image(luma: &image::GrayImage) -> Option<[u32; 4]> {
+    let (w, h) = luma.dimensions();
+    let est_bord = |n: u32, total: u32| n * 10 > total * 9;
+    let mut lignes = vec![0u32; h as usize];
+    for (_, y, p) in luma.enumerate_pixels() {
+        if p[0] < SEUIL_LUMA_ENCRE { lignes[y as usize] += 1; }
     }
+    let mut colonnes = vec![0u32; w as usize];
+    for (x, y, p) in luma.enumerate_pixels() {
+        if p[0] < SEUIL_LUMA_ENCRE && !est_bord(lignes[y as usize], w) { colonnes[x as usize] += 1; }
+    }
+    let utiles = |comptes: &[u32], total: u32| -> Option<(u32, u32)> {
+        let min = (total / 500).max(2);
+        let valide = |n: &u32| *n >= min && !est_bord(*n, total);
+        let debut = comptes.iter().position(valide)?;
+        let fin = comptes.iter().rposition(valide)?;
+        Some((debut as u32, fin as u32 + 1))
+    };
+    let (y0, y1) = utiles(&lignes, w)?;
+    let (x0, x1) = utiles(&colonnes, h)?;
+    Some([x0, y0, x1, y1])
+}
 
-    let new_kids: Vec<Object> = nouvel_ordre.iter()
-        .map(|&n| Object::Reference(pages[n as usize - 1]))
+/// Angle d'inclinaison (degrés) des lignes d'une image, positif quand elles descendent vers la droite.
+/// Profil de projection : l'angle retenu maximise la concentration de l'encre sur des lignes horizontales.
+fn estimer_inclinaison(luma: &image::GrayImage) -> Option<f64> {
+    let (w, h) = luma.dimensions();
+    let echelle = (1000.0 / w.max(h) as f64).min(1.0);
+    let reduite = if echelle < 1.0 {
+        image::imageops::resize(luma, ((w as f64 * echelle) as u32).max(1), ((h as f64 * echelle) as u32).max(1), image::imageops::FilterType::Triangle)
+    } else {
+        luma.clone()
+    };
+    let [x0, y0, x1, y1] = limites_encre_image(&reduite)?;
+    let (cx, cy) = ((x0 + x1) as f64 / 2.0, (y0 + y1) as f64 / 2.0);
+    let points: Vec<(f64, f64)> = reduite.enumerate_pixels()
+        .filter(|(x, y, p)| p[0] < SEUIL_LUMA_ENCRE && (x0..x1).contains(x) && (y0..y1).contains(y))
+        .map(|(x, y, _)| (x as f64 - cx, y as f64 - cy))
         .collect();
-    let new_count = new_kids.len() as i64;
+    if points.len() < 50 {
+        return None;
+    }
+
+    let demi_diagonale = ((x1 - x0) as f64).hypot((y1 - y0) as f64) / 2.0 + 1.0;
+    let mut histogramme = vec![0u32; (2.0 * demi_diagonale) as usize + 2];
+    let mut score = |angle: f64| -> f64 {
+        let (sin, cos) = angle.to_radians().sin_cos();
+        histogramme.iter_mut().for_each(|n| *n = 0);
+        for &(x, y) in &points {
+            histogramme[(y * cos - x * sin + demi_diagonale) as usize] += 1;
+        }
+        histogramme.iter().map(|&n| (n as f64) * (n as f64)).sum()
+    };
+    let reference = score(0.0);
+    let mut meilleur = |debut: f64, fin: f64, pas: f64| -> (f64, f64) {
+        let mut retenu = (0.0, f64::MIN);
+        let mut angle = debut;
+        while angle <= fin + 1e-9 {
+            let s = score(angle);
+            if s > retenu.1 { retenu = (angle, s); }
+            angle += pas;
+        }
+        retenu
+    };
+    let (grossier, _) = meilleur(-INCLINAISON_MAX, INCLINAISON_MAX, 0.5);
+    let (fin, score_fin) = meilleur(grossier - 0.5, grossier + 0.5, 0.05);
+    // Gain négligeable sur l'angle nul : page droite (écart dû à la quantification)
+    if score_fin < reference * 1.02 {
+        return Some(0.0);
+    }
+    // Un optimum en bordure de plage signale une image sans lignes de texte
+    (fin.abs() < INCLINAISON_MAX - 0.25).then_some(fin)
+}
+
+fn appliquer_matrice(m: &Matrice, x: f64, y: f64) -> (f64, f64) {
+    (m[0] * x + m[2] * y + m[4], m[1] * x + m[3] * y + m[5])
+}
+
+fn etendre_boite(boite: &mut Option<[f64; 4]>, (x, y): (f64, f64)) {
+    let b = boite.get_or_insert([x, y, x, y]);
+    *b = [b[0].min(x), b[1].min(y), b[2].max(x), b[3].max(y)];
+}
+
+/// Seule image d'une page numérisée (ni texte, ni tracé) et sa matrice de placement
+fn image_unique_page(doc: &Document, page_id: ObjectId) -> Option<(ObjectId, Matrice)> {
+    let content = Content::decode(&doc.get_page_content(page_id).ok()?).ok()?;
+    let xobjects = xobjects_page(doc, page_id);
+    let mut pile = Vec::new();
+    let mut ctm = MATRICE_IDENTITE;
+    let mut image = None;
+    for op in &content.operations {
+        match op.operator.as_str() {
+            "q" => pile.push(ctm),
+            "Q" => if let Some(m) = pile.pop() { ctm = m },
+            "cm" if op.operands.len() == 6 => {
+                let mut m = MATRICE_IDENTITE;
+                for (v, o) in m.iter_mut().zip(&op.operands) { *v = o.as_float().ok()? as f64; }
+                ctm = produit_matrices(&m, &ctm);
+            }
+            "Tj" | "TJ" | "'" | "\"" | "S" | "s" | "f" | "F" | "f*" | "B" | "B*" | "b" | "b*" | "sh" | "BI" => return None,
+            "Do" => {
+                let nom = op.operands.first()?.as_name().ok()?;
+                let &(_, id) = xobjects.iter().find(|(n, _)| n == nom)?;
+                let est_image = doc.get_object(id).ok()?.as_stream().ok()?
+                    .dict.get(b"Subtype").and_then(|t| t.as_name()).ok() == Some(b"Image".as_slice());
+                if !est_image || image.is_some() {
+                    return None;
+                }
+                image = Some((id, ctm));
+            }
+            _ => {}
+        }
+    }
+    image
+}
+
+/// Limites du contenu visible d'une page (texte, tracés non blancs, encre des images),
+/// exprimées après application de `ctm_initiale`
+fn limites_contenu_page(doc: &Document, page_id: ObjectId, ctm_initiale: &Matrice) -> Option<[f64; 4]> {
+    let content = Content::decode(&doc.get_page_content(page_id).ok()?).ok()?;
+    let xobjects = xobjects_page(doc, page_id);
+    let mut boite = None;
+
+    for f in extraire_fragments_page(doc, page_id) {
+        let (y0, y1) = (f.y - 0.25 * f.taille, f.y + 0.85 * f.taille);
+        for (x, y) in [(f.x0, y0), (f.x1, y0), (f.x0, y1), (f.x1, y1)] {
+            etendre_boite(&mut boite, appliquer_matrice(ctm_initiale, x, y));
+        }
+    }
+
+    // Couleur blanche : g/rg à 1, k à 0 (fonds de page ignorés)
+    let est_blanc = |op: &Operation| -> bool {
+        let v: Vec<f64> = op.operands.iter().filter_map(|o| o.as_float().ok().map(|f| f as f64)).collect();
+        match op.operator.as_str() {
+            "g" | "G" | "rg" | "RG" => !v.is_empty() && v.iter().all(|&c| c >= 0.99),
+            _ => !v.is_empty() && v.iter().all(|&c| c <= 0.01),
+        }
+    };
+    let mut pile = Vec::new();
+    let mut etat = (*ctm_initiale, false, false); // (CTM, remplissage blanc, trait blanc)
+    let mut chemin: Vec<(f64, f64)> = Vec::new();
+    for op in &content.operations {
+        let n: Vec<f64> = op.operands.iter().filter_map(|o| o.as_float().ok().map(|f| f as f64)).collect();
+        let ctm = etat.0;
+        match op.operator.as_str() {
+            "q" => pile.push(etat),
+            "Q" => if let Some(e) = pile.pop() { etat = e },
+            "cm" if n.len() == 6 => etat.0 = produit_matrices(&[n[0], n[1], n[2], n[3], n[4], n[5]], &ctm),
+            "g" | "rg" | "k" => etat.1 = est_blanc(op),
+            "G" | "RG" | "K" => etat.2 = est_blanc(op),
+            "m" | "l" | "c" | "v" | "y" => {
+                chemin.extend(n.chunks_exact(2).map(|p| appliquer_matrice(&ctm, p[0], p[1])));
+            }
+            "re" if n.len() == 4 => {
+                let (x, y, w, h) = (n[0], n[1], n[2], n[3]);
+                chemin.extend([(x, y), (x + w, y), (x, y + h), (x + w, y + h)].map(|(px, py)| appliquer_matrice(&ctm, px, py)));
+            }
+            "S" | "s" | "f" | "F" | "f*" | "B" | "B*" | "b" | "b*" => {
+                let trace = matches!(op.operator.as_str(), "S" | "s");
+                let visible = if trace { !etat.2 } else { !etat.1 || (op.operator.starts_with(['B', 'b']) && !etat.2) };
+                for p in chemin.drain(..) {
+                    if visible { etendre_boite(&mut boite, p); }
+                }
+            }
+            "n" => chemin.clear(),
+            "Do" => {
+                let Some(nom) = op.operands.first().and_then(|n| n.as_name().ok()) else { continue };
+                let Some(&(_, id)) = xobjects.iter().find(|(n, _)| n == nom) else { continue };
+                let Ok(flux) = doc.get_object(id).and_then(|o| o.as_stream()) else { continue };
+                let coins: Vec<(f64, f64)> = match flux.dict.get(b"Subtype").and_then(|t| t.as_name()) {
+                    Ok(b"Image") => match decoder_image_pdf(doc, flux).map(|img| img.to_luma8()) {
+                        Ok(luma) => {
+                            let (w, h) = (luma.width() as f64, luma.height() as f64);
+                            let Some([x0, y0, x1, y1]) = limites_encre_image(&luma) else { continue };
+                            // Pixel (x, y) → carré unité (x / w, 1 − y / h)
+                            [(x0, y0), (x1, y0), (x0, y1), (x1, y1)].iter()
+                                .map(|&(x, y)| appliquer_matrice(&ctm, x as f64 / w, 1.0 - y as f64 / h))
+                                .collect()
+                        }
+                        Err(_) => [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0), (1.0, 1.0)].iter()
+                            .map(|&(x, y)| appliquer_matrice(&ctm, x, y))
+                            .collect(),
+                    },
+                    _ => {
+                        // Formulaire : sa BBox, transformée par sa /Matrix
+                        let nombres = |cle: &[u8]| -> Vec<f64> {
+                            flux.dict.get(cle).and_then(|o| o.as_array()).map(|a| a.iter()
+                                .filter_map(|o| o.as_float().ok().map(|f| f as f64)).collect()).unwrap_or_default()
+                        };
+                        let bbox = nombres(b"BBox");
+                        if bbox.len() != 4 { continue; }
+                        let matrice = nombres(b"Matrix");
+                        let m = if matrice.len() == 6 {
+                            produit_matrices(&[matrice[0], matrice[1], matrice[2], matrice[3], matrice[4], matrice[5]], &ctm)
+                        } else {
+                            ctm
+                        };
+                        [(bbox[0], bbox[1]), (bbox[2], bbox[1]), (bbox[0], bbox[3]), (bbox[2], bbox[3])].iter()
+                            .map(|&(x, y)| appliquer_matrice(&m, x, y))
+                            .collect()
+                    }
+                };
+                for p in coins { etendre_boite(&mut boite, p); }
+            }
+            _ => {}
+        }
+    }
+    boite
+}
+
+/// Encadre le contenu d'une page par « q <matrice> cm … Q »
+fn transformer_contenu_page(doc: &mut Document, page_id: ObjectId, m: &Matrice) -> Result<(), String> {
+    let debut = format!("q {:.6} {:.6} {:.6} {:.6} {:.3} {:.3} cm\n", m[0], m[1], m[2], m[3], m[4], m[5]);
+    encadrer_contenu_page(doc, page_id, &debut)
+}
+
+/// Angle de redressement (degrés, sens trigonométrique) d'une page numérisée
+fn angle_redressement(doc: &Document, page_id: ObjectId) -> Option<f64> {
+    let (image_id, placement) = image_unique_page(doc, page_id)?;
+    let flux = doc.get_object(image_id).ok()?.as_stream().ok()?;
+    let luma = decoder_image_pdf(doc, flux).ok()?.to_luma8();
+    let inclinaison = estimer_inclinaison(&luma)?;
+    // Les lignes qui descendent à l'écran se corrigent par une rotation trigonométrique,
+    // sauf si l'image est placée en miroir
+    let determinant = placement[0] * placement[3] - placement[1] * placement[2];
+    let angle = if determinant < 0.0 { -inclinaison } else { inclinaison };
+    (angle.abs() >= INCLINAISON_MIN).then_some(angle)
+}
+
+fn pdf_nettoyer_scan_interne(
+    input: &Path, output: &str,
+    options: &OptionsScan,
+    pages_cibles: Option<&[u32]>,
+) -> Result<RapportScan, String> {
+    let mut doc = Document::load(input)
+        .map_err(|e| format!("Erreur chargement PDF : {}", e))?;
+    let pages = obtenir_pages_ordonnees(&doc);
+    let mut rapport = RapportScan::default();
+
+    for (i, &page_id) in pages.iter().enumerate() {
+        let page_num = (i + 1) as u32;
+        if !pages_cibles.is_none_or(|c| c.contains(&page_num)) { continue; }
+
+        if options.supprimer_blanches && page_est_blanche(&doc, page_id, options.seuil_encre) {
+            rapport.pages_supprimees.push(page_num);
+            continue;
+        }
+        let Some([mx0, my0, mx1, my1]) = obtenir_mediabox(&doc, page_id) else { continue };
+
+        let mut ctm = MATRICE_IDENTITE;
+        if options.redresser
+            && let Some(angle) = angle_redressement(&doc, page_id)
+        {
+            let (cx, cy) = ((mx0 + mx1) / 2.0, (my0 + my1) / 2.0);
+            let (sin, cos) = angle.to_radians().sin_cos();
+            ctm = produit_matrices(&translation(-cx, -cy), &[cos, sin, -sin, cos, 0.0, 0.0]);
+            ctm = produit_matrices(&ctm, &translation(cx, cy));
+            transformer_contenu_page(&mut doc, page_id, &ctm)?;
+            rapport.pages_redressees.push((page_num, (angle * 100.0).round() / 100.0));
+        }
+
+        if options.recadrer
+            && let Some([x0, y0, x1, y1]) = limites_contenu_page(&doc, page_id, &ctm)
+        {
+            let marge = options.marge_mm * MM;
+            let boite = [(x0 - marge).max(mx0), (y0 - marge).max(my0), (x1 + marge).min(mx1), (y1 + marge).min(my1)];
+            if boite[2] > boite[0] && boite[3] > boite[1] {
+                if let Ok(Object::Dictionary(dict)) = doc.get_object_mut(page_id) {
+                    dict.set("CropBox", Object::Array(boite.iter()
+                        .map(|v| Object::Real(format!("{:.2}", v).parse().unwrap_or(0.0)))
+                        .collect()));
+                }
+                rapport.pages_recadrees.push(page_num);
+            }
+        }
+    }
+
+    if rapport.pages_supprimees.len() == pages.len() {
+        return Err("Toutes les pages sont blanches".into());
+    }
+    if !rapport.pages_supprimees.is_empty() {
+        doc.delete_pages(&rapport.pages_supprimees);
+        doc.prune_objects();
+    }
+    crate::log_info(&format!(
+        "pdf_nettoyer_scan | recadrées={:?} redressées={:?} supprimées={:?}",
+        rapport.pages_recadrees, rapport.pages_redressees, rapport.pages_supprimees
+    ));
+    sauvegarder(&mut doc, output)?;
+    Ok(rapport)
+}
+
+/// Nettoie un lot de pages numérisées : suppression des pages blanches,
+/// redressement des pages-images inclinées, recadrage sur le contenu de chaque page
+pub fn pdf_nettoyer_scan(
+    input: &Path, output: &str,
+    options: &OptionsScan,
+    pages_cibles: Option<&[u32]>,
+) -> Result<RapportScan, String> {
+    let mut rapport = RapportScan::default();
+    appliquer_operation_doc(input, output, |pdf_in, pdf_out| {
+        rapport = pdf_nettoyer_scan_interne(pdf_in, pdf_out, options, pages_cibles)?;
+        Ok(())
+    })?;
+    Ok(rapport)
+}
+
+// ════════════════════════════════════════════════════════════════════════
+//  PDF ORGANIZE — Réorganise / supprime des pages
+// ════════════════════════════════════════════════════════════════════════
+
+fn pdf_organiser_interne(input: &Path, output: &str, nouvel_ordre: &[u32]) -> Result<(), String> {
+    let mut doc = Document::load(input)
+        .map_err(|e| format!("Erreur chargement PDF : {}", e))?;
+
+    let pages = obtenir_pages_ordonnees(&doc);
+    let total = pages.len() as u32;
+
+    for &num in nouvel_ordre {
+        if num == 0 || num > total {
+            return Err(format!("Page invalide : {}. Le PDF a {} pages.", num, total));
+        }
+    }
+
+    let new_kids: Vec<Object> = nouvel_ordre.iter()
+        .map(|&n| Object::Reference(pages[n as usize - 1]))
+        .collect();
+    let new_count = new_kids.len() as i64;
 
     let catalog = doc.catalog()
         .map_err(|e| format!("Catalogue introuvable : {}", e))?;
@@ -1268,27 +2202,302 @@ pub fn pdf_organiser(input: &Path, output: &str, nouvel_ordre: &[u32]) -> Result
 }
 
 pub fn pdf_supprimer_pages(input: &Path, output: &str, pages_a_supprimer: &[u32]) -> Result<(), String> {
-    // On a besoin du nombre total de pages → charger d'abord
-    let total = if est_pdf(input) {
-        let doc = Document::load(input).map_err(|e| format!("Erreur : {}", e))?;
-        doc.get_pages().len() as u32
-    } else {
-        let pdf_tmp = vers_pdf_temp(input)?;
-        let doc = Document::load(&pdf_tmp).map_err(|e| format!("Erreur : {}", e))?;
-        let total = doc.get_pages().len() as u32;
-        nettoyer_temp(&pdf_tmp);
-        total
+    let pages_a_supprimer = pages_a_supprimer.to_vec();
+    appliquer_operation_doc(input, output, |pdf_in, pdf_out| {
+        // Le nombre total de pages est lu sur le PDF déjà converti
+        let total = Document::load(pdf_in).map_err(|e| format!("Erreur : {}", e))?.get_pages().len() as u32;
+        let pages_a_garder: Vec<u32> = (1..=total)
+            .filter(|n| !pages_a_supprimer.contains(n))
+            .collect();
+        if pages_a_garder.is_empty() {
+            return Err("Impossible de supprimer toutes les pages".into());
+        }
+        pdf_organiser_interne(pdf_in, pdf_out, &pages_a_garder)
+    })
+}
+
+// ════════════════════════════════════════════════════════════════════════
+//  PDF IMPOSITION — N-up, livret, mosaïque (poster)
+// ════════════════════════════════════════════════════════════════════════
+
+/// Mode d'imposition sur feuilles A4
+#[derive(Debug, Clone, PartialEq)]
+pub enum ModeImposition {
+    /// N pages par feuille (2, 4, 6, 8, 9, 16…) — grille et orientation choisies au mieux
+    NUp(u32),
+    /// Livret piqûre à cheval : 2 pages par face, recto-verso, ordre de pliage
+    Livret,
+    /// Mosaïque : chaque page agrandie (`echelle`) et répartie sur plusieurs A4,
+    /// avec une bande de recouvrement (mm) marquée en pointillés pour l'assemblage
+    Mosaique { echelle: f64, recouvrement_mm: f64 },
+}
+
+const MM: f64 = 72.0 / 25.4;
+const FEUILLE_A4: (f64, f64) = (595.28, 841.89);
+const MARGE_IMPOSITION: f64 = 5.0 * MM;
+
+/// Page source convertie en Form XObject, dimensions affichées (rotation appliquée)
+struct PageXObject {
+    id: ObjectId,
+    largeur: f64,
+    hauteur: f64,
+}
+
+/// Feuille imposée : taille, opérations de contenu, XObjects utilisés
+struct FeuilleImposee {
+    largeur: f64,
+    hauteur: f64,
+    operations: Vec<Operation>,
+    xobjects: Vec<(String, ObjectId)>,
+}
+
+/// Convertit une page en Form XObject normalisé : origine en (0,0), /Rotate appliqué.
+fn page_vers_xobject(doc: &mut Document, page_id: ObjectId) -> Result<PageXObject, String> {
+    let [x0, y0, x1, y1] = obtenir_mediabox(doc, page_id).unwrap_or([0.0, 0.0, FEUILLE_A4.0, FEUILLE_A4.1]);
+    let rotation = attribut_herite(doc, page_id, b"Rotate")
+        .and_then(|r| r.as_i64().ok())
+        .unwrap_or(0)
+        .rem_euclid(360);
+    let resources = attribut_herite(doc, page_id, b"Resources")
+        .unwrap_or_else(|| Object::Dictionary(dictionary! {}));
+    let contenu = doc.get_page_content(page_id)
+        .map_err(|e| format!("Contenu de page illisible : {}", e))?;
+
+    let (matrice, largeur, hauteur) = match rotation {
+        90 => ([0.0, -1.0, 1.0, 0.0, -y0, x1], y1 - y0, x1 - x0),
+        180 => ([-1.0, 0.0, 0.0, -1.0, x1, y1], x1 - x0, y1 - y0),
+        270 => ([0.0, 1.0, -1.0, 0.0, y1, -x0], y1 - y0, x1 - x0),
+        _ => ([1.0, 0.0, 0.0, 1.0, -x0, -y0], x1 - x0, y1 - y0),
     };
 
-    let pages_a_garder: Vec<u32> = (1..=total)
-        .filter(|n| !pages_a_supprimer.contains(n))
-        .collect();
+    let id = doc.add_object(Stream::new(dictionary! {
+        "Type" => "XObject",
+        "Subtype" => "Form",
+        "BBox" => vec![x0.into(), y0.into(), x1.into(), y1.into()],
+        "Matrix" => matrice.iter().map(|&v| v.into()).collect::<Vec<Object>>(),
+        "Resources" => resources,
+    }, contenu));
+    Ok(PageXObject { id, largeur, hauteur })
+}
+
+/// Place une page dans une cellule (coin bas-gauche `x`,`y`), mise à l'échelle et centrée
+fn placer_page(operations: &mut Vec<Operation>, nom: &str, page: &PageXObject, x: f64, y: f64, largeur: f64, hauteur: f64) {
+    let echelle = (largeur / page.largeur).min(hauteur / page.hauteur);
+    let dx = x + (largeur - page.largeur * echelle) / 2.0;
+    let dy = y + (hauteur - page.hauteur * echelle) / 2.0;
+    operations.extend([
+        Operation::new("q", vec![]),
+        Operation::new("cm", vec![echelle.into(), 0.into(), 0.into(), echelle.into(), dx.into(), dy.into()]),
+        Operation::new("Do", vec![Object::Name(nom.as_bytes().to_vec())]),
+        Operation::new("Q", vec![]),
+    ]);
+}
+
+/// Grille (colonnes, lignes, paysage) maximisant la taille des pages pour N pages par feuille
+fn grille_nup(n: u32, page_l: f64, page_h: f64) -> (u32, u32, bool) {
+    let mut meilleure = (n, 1, false, 0.0);
+    for cols in (1..=n).filter(|c| n.is_multiple_of(*c)) {
+        let lignes = n / cols;
+        for paysage in [false, true] {
+            let (fl, fh) = if paysage { (FEUILLE_A4.1, FEUILLE_A4.0) } else { FEUILLE_A4 };
+            let cell_l = (fl - 2.0 * MARGE_IMPOSITION) / cols as f64;
+            let cell_h = (fh - 2.0 * MARGE_IMPOSITION) / lignes as f64;
+            let echelle = (cell_l / page_l).min(cell_h / page_h);
+            if echelle > meilleure.3 {
+                meilleure = (cols, lignes, paysage, echelle);
+            }
+        }
+    }
+    (meilleure.0, meilleure.1, meilleure.2)
+}
+
+/// Feuilles à grille fixe : chaque feuille reçoit `cols × lignes` emplacements (None = vide)
+fn imposer_grille(sources: &[PageXObject], emplacements: &[Option<usize>], cols: u32, lignes: u32, paysage: bool) -> Vec<FeuilleImposee> {
+    let (fl, fh) = if paysage { (FEUILLE_A4.1, FEUILLE_A4.0) } else { FEUILLE_A4 };
+    let cell_l = (fl - 2.0 * MARGE_IMPOSITION) / cols as f64;
+    let cell_h = (fh - 2.0 * MARGE_IMPOSITION) / lignes as f64;
+
+    emplacements.chunks((cols * lignes) as usize)
+        .map(|slots| {
+            let mut feuille = FeuilleImposee { largeur: fl, hauteur: fh, operations: Vec::new(), xobjects: Vec::new() };
+            for (k, slot) in slots.iter().enumerate() {
+                let Some(idx) = slot else { continue };
+                let (col, ligne) = (k as u32 % cols, k as u32 / cols);
+                let x = MARGE_IMPOSITION + col as f64 * cell_l;
+                let y = fh - MARGE_IMPOSITION - (ligne + 1) as f64 * cell_h;
+                let nom = format!("P{}", idx + 1);
+                placer_page(&mut feuille.operations, &nom, &sources[*idx], x, y, cell_l, cell_h);
+                feuille.xobjects.push((nom, sources[*idx].id));
+            }
+            feuille
+        })
+        .collect()
+}
+
+/// Ordre piqûre à cheval : pour chaque feuille, recto (n-1-2i, 2i) puis verso (2i+1, n-2-2i)
+fn ordre_livret(nb_pages: usize) -> Vec<Option<usize>> {
+    let total = nb_pages.div_ceil(4) * 4;
+    let page = |p: usize| if p < nb_pages { Some(p) } else { None };
+    (0..total / 4)
+        .flat_map(|i| [
+            page(total - 1 - 2 * i), page(2 * i),
+            page(2 * i + 1), page(total - 2 - 2 * i),
+        ])
+        .collect()
+}
+
+/// Mosaïque d'une page agrandie sur plusieurs A4, avec repères de recouvrement
+fn imposer_mosaique(idx: usize, page: &PageXObject, echelle: f64, recouvrement: f64) -> Vec<FeuilleImposee> {
+    let grande_l = page.largeur * echelle;
+    let grande_h = page.hauteur * echelle;
+    let nb_tuiles = |zone: f64, taille: f64| -> u32 {
+        if taille <= zone { 1 } else { ((taille - recouvrement) / (zone - recouvrement)).ceil() as u32 }
+    };
+
+    // Orientation des feuilles : celle qui demande le moins de tuiles
+    let (fl, fh) = [FEUILLE_A4, (FEUILLE_A4.1, FEUILLE_A4.0)].into_iter()
+        .min_by_key(|(l, h)| {
+            nb_tuiles(l - 2.0 * MARGE_IMPOSITION, grande_l) * nb_tuiles(h - 2.0 * MARGE_IMPOSITION, grande_h)
+        })
+        .unwrap_or(FEUILLE_A4);
+    let zone_l = fl - 2.0 * MARGE_IMPOSITION;
+    let zone_h = fh - 2.0 * MARGE_IMPOSITION;
+    let (nx, ny) = (nb_tuiles(zone_l, grande_l), nb_tuiles(zone_h, grande_h));
+    let nom = format!("P{}", idx + 1);
+
+    let mut feuilles = Vec::new();
+    for iy in 0..ny {
+        for ix in 0..nx {
+            // Fenêtre de la page agrandie couverte par cette tuile (lignes de haut en bas)
+            let ox = ix as f64 * (zone_l - recouvrement);
+            let oy = grande_h - zone_h - iy as f64 * (zone_h - recouvrement);
+            let (m, droite, haut) = (MARGE_IMPOSITION, MARGE_IMPOSITION + zone_l, MARGE_IMPOSITION + zone_h);
+
+            let mut ops = vec![
+                Operation::new("q", vec![]),
+                Operation::new("re", vec![m.into(), m.into(), zone_l.into(), zone_h.into()]),
+                Operation::new("W", vec![]),
+                Operation::new("n", vec![]),
+                Operation::new("cm", vec![echelle.into(), 0.into(), 0.into(), echelle.into(), (m - ox).into(), (m - oy).into()]),
+                Operation::new("Do", vec![Object::Name(nom.as_bytes().to_vec())]),
+                Operation::new("Q", vec![]),
+                // Repères : coins de la zone imprimée, dans la marge
+                Operation::new("q", vec![]),
+                Operation::new("w", vec![0.3.into()]),
+                Operation::new("G", vec![0.into()]),
+            ];
+            let trait_ = |ops: &mut Vec<Operation>, x1: f64, y1: f64, x2: f64, y2: f64| {
+                ops.push(Operation::new("m", vec![x1.into(), y1.into()]));
+                ops.push(Operation::new("l", vec![x2.into(), y2.into()]));
+            };
+            for (cx, cy) in [(m, m), (droite, m), (m, haut), (droite, haut)] {
+                let sx = if cx == m { -1.0 } else { 1.0 };
+                let sy = if cy == m { -1.0 } else { 1.0 };
+                trait_(&mut ops, cx + sx * 1.0, cy, cx + sx * m, cy);
+                trait_(&mut ops, cx, cy + sy * 1.0, cx, cy + sy * m);
+            }
+            ops.push(Operation::new("S", vec![]));
+            // Bandes de recouvrement partagées avec la tuile de droite / du dessous
+            ops.push(Operation::new("d", vec![vec![3.into(), 3.into()].into(), 0.into()]));
+            if ix + 1 < nx {
+                trait_(&mut ops, droite - recouvrement, 0.0, droite - recouvrement, fh);
+            }
+            if iy + 1 < ny {
+                trait_(&mut ops, 0.0, m + recouvrement, fl, m + recouvrement);
+            }
+            ops.push(Operation::new("S", vec![]));
+            ops.push(Operation::new("Q", vec![]));
+
+            feuilles.push(FeuilleImposee { largeur: fl, hauteur: fh, operations: ops, xobjects: vec![(nom.clone(), page.id)] });
+        }
+    }
+    feuilles
+}
+
+fn pdf_imposer_interne(input: &Path, output: &str, mode: &ModeImposition) -> Result<(), String> {
+    let mut doc = Document::load(input)
+        .map_err(|e| format!("Erreur chargement PDF : {}", e))?;
+    let pages = obtenir_pages_ordonnees(&doc);
+    if pages.is_empty() {
+        return Err("Le PDF ne contient aucune page".into());
+    }
+
+    let sources = pages.iter()
+        .map(|&page_id| page_vers_xobject(&mut doc, page_id))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let feuilles = match mode {
+        ModeImposition::NUp(n) => {
+            if *n == 0 {
+                return Err("Le nombre de pages par feuille doit être ≥ 1".into());
+            }
+            let (cols, lignes, paysage) = grille_nup(*n, sources[0].largeur, sources[0].hauteur);
+            let emplacements: Vec<Option<usize>> = (0..sources.len()).map(Some).collect();
+            imposer_grille(&sources, &emplacements, cols, lignes, paysage)
+        }
+        ModeImposition::Livret => imposer_grille(&sources, &ordre_livret(sources.len()), 2, 1, true),
+        ModeImposition::Mosaique { echelle, recouvrement_mm } => {
+            let recouvrement = recouvrement_mm * MM;
+            if *echelle <= 0.0 {
+                return Err("L'échelle doit être > 0".into());
+            }
+            if recouvrement < 0.0 || recouvrement >= FEUILLE_A4.0 / 2.0 {
+                return Err(format!("Recouvrement invalide : {} mm", recouvrement_mm));
+            }
+            sources.iter().enumerate()
+                .flat_map(|(i, page)| imposer_mosaique(i, page, *echelle, recouvrement))
+                .collect()
+        }
+    };
 
-    if pages_a_garder.is_empty() {
-        return Err("Impossible de supprimer toutes les pages".into());
+    crate::log_info(&format!("pdf_imposer | {:?} | {} pages -> {} feuilles ({:?})", input, pages.len(), feuilles.len(), mode));
+
+    // Remplacer l'arbre des pages par les feuilles imposées
+    let pages_id = match doc.catalog().map_err(|e| format!("Catalogue introuvable : {}", e))?.get(b"Pages") {
+        Ok(Object::Reference(id)) => *id,
+        _ => return Err("Référence Pages introuvable".into()),
+    };
+    let mut kids = Vec::new();
+    for feuille in feuilles {
+        let contenu = Content { operations: feuille.operations }.encode()
+            .map_err(|e| format!("Erreur encodage imposition : {}", e))?;
+        let contenu_id = doc.add_object(Stream::new(dictionary! {}, contenu));
+        let mut xobjects = lopdf::Dictionary::new();
+        for (nom, id) in feuille.xobjects {
+            xobjects.set(nom, Object::Reference(id));
+        }
+        let page_id = doc.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "MediaBox" => vec![0.into(), 0.into(), feuille.largeur.into(), feuille.hauteur.into()],
+            "Resources" => dictionary! { "XObject" => xobjects },
+            "Contents" => contenu_id,
+        });
+        kids.push(Object::Reference(page_id));
+    }
+    if let Ok(Object::Dictionary(dict)) = doc.get_object_mut(pages_id) {
+        dict.set("Count", Object::Integer(kids.len() as i64));
+        dict.set("Kids", Object::Array(kids));
+        dict.remove(b"Rotate");
     }
+    // Signets et action d'ouverture pointent vers les anciennes pages
+    if let Ok(catalog) = doc.catalog_mut() {
+        catalog.remove(b"Outlines");
+        catalog.remove(b"OpenAction");
+    }
+    doc.prune_objects();
+    doc.compress();
+
+    sauvegarder(&mut doc, output)
+}
 
-    pdf_organiser(input, output, &pages_a_garder)
+/// Imposition : N-up, livret ou mosaïque sur A4.
+/// Les annotations et formulaires des pages sources ne sont pas reportés.
+pub fn pdf_imposer(input: &Path, output: &str, mode: &ModeImposition) -> Result<(), String> {
+    let mode = mode.clone();
+    appliquer_operation_doc(input, output, |pdf_in, pdf_out| {
+        pdf_imposer_interne(pdf_in, pdf_out, &mode)
+    })
 }
 
 // ════════════════════════════════════════════════════════════════════════
@@ -1364,59 +2573,148 @@ pub fn pdf_numeroter(
 }
 
 // ════════════════════════════════════════════════════════════════════════
-//  PDF PROTECT — AES-128, V4
+//  PDF PROTECT — AES-128 (V4) / AES-256 (V5, R6)
 // ════════════════════════════════════════════════════════════════════════
 
+/// Algorithme de chiffrement d'un PDF protégé
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ChiffrementPdf {
+    /// AES-128 (V4, PDF 1.6) — lu par tous les lecteurs
+    #[default]
+    Aes128,
+    /// AES-256 (V5 R6, PDF 2.0)
+    Aes256,
+}
+
+/// Droits accordés à l'ouverture avec le mot de passe utilisateur
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PermissionsPdf {
+    pub impression: bool,
+    pub copie: bool,
+    pub modification: bool,
+    pub annotation: bool,
+    pub formulaires: bool,
+    pub assemblage: bool,
+}
+
+impl Default for PermissionsPdf {
+    fn default() -> Self {
+        PermissionsPdf { impression: true, copie: true, modification: true, annotation: true, formulaires: true, assemblage: true }
+    }
+}
+
+impl PermissionsPdf {
+    fn vers_lopdf(&self) -> Permissions {
+        let mut perms = Permissions::empty();
+        if self.impression { perms |= Permissions::PRINTABLE | Permissions::PRINTABLE_IN_HIGH_QUALITY; }
+        if self.copie { perms |= Permissions::COPYABLE | Permissions::COPYABLE_FOR_ACCESSIBILITY; }
+        if self.modification { perms |= Permissions::MODIFIABLE; }
+        if self.annotation { perms |= Permissions::ANNOTABLE; }
+        if self.formulaires { perms |= Permissions::FILLABLE; }
+        if self.assemblage { perms |= Permissions::ASSEMBLABLE; }
+        perms
+    }
+
+    fn depuis_lopdf(perms: Permissions) -> Self {
+        PermissionsPdf {
+            impression: perms.contains(Permissions::PRINTABLE),
+            copie: perms.contains(Permissions::COPYABLE),
+            modification: perms.contains(Permissions::MODIFIABLE),
+            annotation: perms.contains(Permissions::ANNOTABLE),
+            // Le remplissage est implicite quand l'annotation est autorisée
+            formulaires: perms.contains(Permissions::FILLABLE) || perms.contains(Permissions::ANNOTABLE),
+            assemblage: perms.contains(Permissions::ASSEMBLABLE) || perms.contains(Permissions::MODIFIABLE),
+        }
+    }
+}
+
 fn pdf_proteger_interne(
     input: &Path, output: &str,
     mot_de_passe_owner: &str,
     mot_de_passe_user: &str,
-    autoriser_impression: bool,
-    autoriser_copie: bool,
+    permissions: &PermissionsPdf,
+    chiffrement: ChiffrementPdf,
 ) -> Result<(), String> {
     let mut doc = Document::load(input)
         .map_err(|e| format!("Erreur chargement PDF : {}", e))?;
-
-    let mut perms = Permissions::empty();
-    if autoriser_impression {
-        perms |= Permissions::PRINTABLE;
-        perms |= Permissions::PRINTABLE_IN_HIGH_QUALITY;
-    }
-    if autoriser_copie {
-        perms |= Permissions::COPYABLE;
-        perms |= Permissions::COPYABLE_FOR_ACCESSIBILITY;
-    }
-
-    let crypt_filter: Arc<dyn CryptFilter> = Arc::new(Aes128CryptFilter);
-    let version = EncryptionVersion::V4 {
-        document: &doc,
-        encrypt_metadata: true,
-        crypt_filters: BTreeMap::from([(b"StdCF".to_vec(), crypt_filter)]),
-        stream_filter: b"StdCF".to_vec(),
-        string_filter: b"StdCF".to_vec(),
-        owner_password: mot_de_passe_owner,
-        user_password: mot_de_passe_user,
-        permissions: perms,
-    };
-
-    let state = EncryptionState::try_from(version)
-        .map_err(|e| format!("Erreur création chiffrement : {}", e))?;
+    let perms = permissions.vers_lopdf();
+
+    let state = match chiffrement {
+        ChiffrementPdf::Aes128 => {
+            let crypt_filter: Arc<dyn CryptFilter> = Arc::new(Aes128CryptFilter);
+            EncryptionState::try_from(EncryptionVersion::V4 {
+                document: &doc,
+                encrypt_metadata: true,
+                crypt_filters: BTreeMap::from([(b"StdCF".to_vec(), crypt_filter)]),
+                stream_filter: b"StdCF".to_vec(),
+                string_filter: b"StdCF".to_vec(),
+                owner_password: mot_de_passe_owner,
+                user_password: mot_de_passe_user,
+                permissions: perms,
+            })
+        }
+        ChiffrementPdf::Aes256 => {
+            let mut cle = [0u8; 32];
+            getrandom::fill(&mut cle)
+                .map_err(|e| format!("Erreur génération clé : {}", e))?;
+            let crypt_filter: Arc<dyn CryptFilter> = Arc::new(Aes256CryptFilter);
+            EncryptionState::try_from(EncryptionVersion::V5 {
+                encrypt_metadata: true,
+                crypt_filters: BTreeMap::from([(b"StdCF".to_vec(), crypt_filter)]),
+                file_encryption_key: &cle,
+                stream_filter: b"StdCF".to_vec(),
+                string_filter: b"StdCF".to_vec(),
+                owner_password: mot_de_passe_owner,
+                user_password: mot_de_passe_user,
+                permissions: perms,
+            })
+        }
+    }.map_err(|e| format!("Erreur création chiffrement : {}", e))?;
 
     doc.encrypt(&state)
         .map_err(|e| format!("Erreur chiffrement : {}", e))?;
+    if chiffrement == ChiffrementPdf::Aes256 && version_pdf(&doc.version) < (2, 0) {
+        doc.version = "2.0".into();
+    }
 
     sauvegarder(&mut doc, output)
 }
 
+/// Version d'en-tête PDF "1.7" → (1, 7) ; illisible → (0, 0)
+fn version_pdf(version: &str) -> (u32, u32) {
+    let (majeure, mineure) = version.trim().split_once('.').unwrap_or((version.trim(), "0"));
+    (majeure.parse().unwrap_or(0), mineure.parse().unwrap_or(0))
+}
+
+/// Protège un PDF (AES-128) en autorisant ou non l'impression et la copie
 pub fn pdf_proteger(
     input: &Path, output: &str,
     mot_de_passe_owner: &str,
     mot_de_passe_user: &str,
     autoriser_impression: bool,
     autoriser_copie: bool,
+) -> Result<(), String> {
+    let permissions = PermissionsPdf {
+        impression: autoriser_impression,
+        copie: autoriser_copie,
+        modification: false,
+        annotation: false,
+        formulaires: false,
+        assemblage: false,
+    };
+    pdf_proteger_avance(input, output, mot_de_passe_owner, mot_de_passe_user, &permissions, ChiffrementPdf::Aes128)
+}
+
+/// Protège un PDF avec l'algorithme et les droits détaillés choisis
+pub fn pdf_proteger_avance(
+    input: &Path, output: &str,
+    mot_de_passe_owner: &str,
+    mot_de_passe_user: &str,
+    permissions: &PermissionsPdf,
+    chiffrement: ChiffrementPdf,
 ) -> Result<(), String> {
     appliquer_operation_doc(input, output, |pdf_in, pdf_out| {
-        pdf_proteger_interne(pdf_in, pdf_out, mot_de_passe_owner, mot_de_passe_user, autoriser_impression, autoriser_copie)
+        pdf_proteger_interne(pdf_in, pdf_out, mot_de_passe_owner, mot_de_passe_user, permissions, chiffrement)
     })
 }
 
@@ -1440,6 +2738,164 @@ pub fn pdf_dechiffrer(input: &Path, output: &str, mot_de_passe: &str) -> Result<
     sauvegarder(&mut doc, output)
 }
 
+// ════════════════════════════════════════════════════════════════════════
+//  PDF INFO — version, chiffrement, droits, polices, scripts, formulaires
+// ════════════════════════════════════════════════════════════════════════
+
+/// Rapport de sécurité et de structure d'un PDF
+#[derive(Debug, Clone, Default)]
+pub struct RapportPdf {
+    pub version: String,
+    /// Méthode de chiffrement (None = document non chiffré)
+    pub chiffrement: Option<String>,
+    /// Un mot de passe est nécessaire pour ouvrir le document
+    pub mot_de_passe_ouverture: bool,
+    /// Contenu lisible (faux si le mot de passe d'ouverture manque)
+    pub contenu_lisible: bool,
+    /// Droits de l'utilisateur (None = document non chiffré : tout est permis)
+    pub permissions: Option<PermissionsPdf>,
+    pub pages: u32,
+    /// Polices : "Nom (Sous-type, incorporée|non incorporée)"
+    pub polices: Vec<String>,
+    /// Nombre de scripts JavaScript (actions et arbre /Names)
+    pub javascript: usize,
+    /// Nombre de champs de formulaire (AcroForm)
+    pub champs_formulaire: usize,
+    pub formulaire_xfa: bool,
+}
+
+impl RapportPdf {
+    /// Rapport lisible (une ligne par entrée)
+    pub fn en_texte(&self) -> String {
+        let oui_non = |b: bool| if b { "oui" } else { "non" };
+        let mut s = format!("Version PDF : {}\n", self.version);
+        match &self.chiffrement {
+            Some(c) => {
+                s.push_str(&format!("Chiffrement : {}\n", c));
+                s.push_str(&format!("Mot de passe d'ouverture : {}\n", oui_non(self.mot_de_passe_ouverture)));
+            }
+            None => s.push_str("Chiffrement : aucun\n"),
+        }
+        if let Some(p) = &self.permissions {
+            s.push_str("Droits :\n");
+            for (nom, autorise) in [
+                ("impression", p.impression), ("copie", p.copie), ("modification", p.modification),
+                ("annotation", p.annotation), ("formulaires", p.formulaires), ("assemblage", p.assemblage),
+            ] {
+                s.push_str(&format!("  {} {}\n", if autorise { "+" } else { "-" }, nom));
+            }
+        }
+        if !self.contenu_lisible {
+            s.push_str("Contenu verrouillé : mot de passe requis\n");
+            return s;
+        }
+        s.push_str(&format!("Pages : {}\n", self.pages));
+        s.push_str(&format!("Polices : {}\n", self.polices.len()));
+        for p in &self.polices { s.push_str(&format!("  {}\n", p)); }
+        s.push_str(&format!("JavaScript : {}\n", if self.javascript > 0 { format!("oui ({})", self.javascript) } else { "non".into() }));
+        s.push_str(&format!("Formulaire : {}{}\n",
+            if self.champs_formulaire > 0 { format!("oui ({} champ(s))", self.champs_formulaire) } else { "non".into() },
+            if self.formulaire_xfa { ", XFA" } else { "" }));
+        s
+    }
+}
+
+/// Décrit une méthode de chiffrement à partir de /V, /Length et de la méthode du filtre par défaut
+fn decrire_chiffrement(v: i64, longueur: Option<i64>, methode: Option<&[u8]>) -> String {
+    match (v, methode) {
+        (5, _) | (_, Some(b"AESV3")) => "AES-256 (V5)".into(),
+        (4, Some(b"AESV2")) => "AES-128 (V4)".into(),
+        (4, Some(b"Identity")) => "aucun (filtre Identity)".into(),
+        (4, _) => "RC4 128 bits (V4)".into(),
+        (2, _) => format!("RC4 {} bits (V2)", longueur.unwrap_or(40)),
+        (1, _) => "RC4 40 bits (V1)".into(),
+        (v, _) => format!("inconnu (V{})", v),
+    }
+}
+
+/// Analyse la protection et la structure d'un PDF. `mot_de_passe` sert si l'ouverture est protégée.
+pub fn pdf_rapport(input: &Path, mot_de_passe: &str) -> Result<RapportPdf, String> {
+    let doc = if mot_de_passe.is_empty() {
+        Document::load(input)
+    } else {
+        Document::load_with_password(input, mot_de_passe)
+    }.map_err(|e| format!("Erreur chargement PDF : {}", e))?;
+
+    let mut rapport = RapportPdf { version: doc.version.clone(), ..Default::default() };
+
+    // Document resté chiffré : mot de passe d'ouverture inconnu, seul /Encrypt est lisible
+    if let Ok(encrypt) = doc.get_encrypted() {
+        let methode = encrypt.get(b"CF").ok()
+            .and_then(|cf| resoudre_dict(&doc, cf))
+            .and_then(|cf| cf.get(encrypt.get(b"StmF").and_then(|f| f.as_name()).unwrap_or(b"StdCF")).ok())
+            .and_then(|f| resoudre_dict(&doc, f))
+            .and_then(|f| f.get(b"CFM").and_then(|m| m.as_name()).ok());
+        rapport.chiffrement = Some(decrire_chiffrement(
+            encrypt.get(b"V").and_then(|v| v.as_i64()).unwrap_or(0),
+            encrypt.get(b"Length").and_then(|l| l.as_i64()).ok(),
+            methode,
+        ));
+        rapport.mot_de_passe_ouverture = true;
+        rapport.permissions = encrypt.get(b"P").and_then(|p| p.as_i64()).ok()
+            .map(|p| PermissionsPdf::depuis_lopdf(Permissions::from_bits_truncate(p as u32 as u64)));
+        return Ok(rapport);
+    }
+
+    rapport.contenu_lisible = true;
+    if let Some(etat) = &doc.encryption_state {
+        let methode = etat.crypt_filters().get(etat.default_stream_filter()).map(|f| f.method().to_vec());
+        rapport.chiffrement = Some(decrire_chiffrement(etat.version(), etat.key_length().map(|l| l as i64), methode.as_deref()));
+        rapport.mot_de_passe_ouverture = !mot_de_passe.is_empty();
+        rapport.permissions = Some(PermissionsPdf::depuis_lopdf(etat.permissions()));
+    }
+    rapport.pages = doc.get_pages().len() as u32;
+
+    let catalog = doc.catalog().ok();
+    if catalog.and_then(|c| c.get(b"Names").ok())
+        .and_then(|n| resoudre_dict(&doc, n))
+        .is_some_and(|n| n.has(b"JavaScript"))
+    {
+        rapport.javascript += 1;
+    }
+    if let Some(form) = catalog.and_then(|c| c.get(b"AcroForm").ok()).and_then(|a| resoudre_dict(&doc, a)) {
+        rapport.formulaire_xfa = form.has(b"XFA");
+        if let Ok(Object::Array(champs)) = form.get(b"Fields").map(|f| resoudre(&doc, f)) {
+            rapport.champs_formulaire = champs.len();
+        }
+    }
+
+    for obj in doc.objects.values() {
+        let dict = match obj {
+            Object::Dictionary(d) => d,
+            Object::Stream(s) => &s.dict,
+            _ => continue,
+        };
+        if dict.get(b"S").and_then(|s| s.as_name()).ok() == Some(b"JavaScript") {
+            rapport.javascript += 1;
+        }
+        if dict.get(b"Type").and_then(|t| t.as_name()).ok() != Some(b"Font") { continue; }
+        let sous_type = dict.get(b"Subtype").and_then(|t| t.as_name()).unwrap_or(b"");
+        // Les polices descendantes (CIDFont) sont décrites par leur police Type0
+        if sous_type.starts_with(b"CIDFontType") { continue; }
+        let descripteur = match sous_type {
+            b"Type0" => dict.get(b"DescendantFonts").ok()
+                .and_then(|d| resoudre(&doc, d).as_array().ok())
+                .and_then(|arr| arr.first())
+                .and_then(|f| resoudre_dict(&doc, f)),
+            _ => Some(dict),
+        };
+        let incorporee = sous_type == b"Type3" || descripteur.is_some_and(|d| police_incorporee(&doc, d));
+        let nom = String::from_utf8_lossy(dict.get(b"BaseFont").and_then(|n| n.as_name()).unwrap_or(b"(sans nom)")).to_string();
+        let ligne = format!("{} ({}, {})", nom, String::from_utf8_lossy(sous_type),
+            if incorporee { "incorporée" } else { "non incorporée" });
+        if !rapport.polices.contains(&ligne) {
+            rapport.polices.push(ligne);
+        }
+    }
+    rapport.polices.sort();
+    Ok(rapport)
+}
+
 // ════════════════════════════════════════════════════════════════════════
 //  PDF REPAIR
 // ════════════════════════════════════════════════════════════════════════
@@ -1551,84 +3007,1994 @@ pub fn pdf_watermark(
 }
 
 // ════════════════════════════════════════════════════════════════════════
-//  PDF ANNOTATE — ajoute une note de texte (annotation) sur les pages
+//  PDF TAMPONS — images et en-têtes / pieds de page à modèle
 // ════════════════════════════════════════════════════════════════════════
 
-fn pdf_annoter_interne(
-    input: &Path, output: &str,
-    texte: &str,
-    x: f64, y: f64,
-    largeur: f64, hauteur: f64,
-    pages_cibles: Option<&[u32]>,
-) -> Result<(), String> {
-    let mut doc = Document::load(input)
-        .map_err(|e| format!("Erreur chargement PDF : {}", e))?;
+/// Point d'ancrage d'un tampon sur la page
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AncreTampon {
+    HautGauche, HautCentre, HautDroite,
+    MilieuGauche, Centre, MilieuDroite,
+    BasGauche, BasCentre, BasDroite,
+}
 
-    let pages = obtenir_pages_ordonnees(&doc);
+impl AncreTampon {
+    pub const TOUTES: [&'static str; 9] = [
+        "HautGauche", "HautCentre", "HautDroite",
+        "MilieuGauche", "Centre", "MilieuDroite",
+        "BasGauche", "BasCentre", "BasDroite",
+    ];
+
+    pub fn depuis_nom(nom: &str) -> Option<Self> {
+        Some(match nom {
+            "HautGauche" => AncreTampon::HautGauche,
+            "HautCentre" => AncreTampon::HautCentre,
+            "HautDroite" => AncreTampon::HautDroite,
+            "MilieuGauche" => AncreTampon::MilieuGauche,
+            "Centre" => AncreTampon::Centre,
+            "MilieuDroite" => AncreTampon::MilieuDroite,
+            "BasGauche" => AncreTampon::BasGauche,
+            "BasCentre" => AncreTampon::BasCentre,
+            "BasDroite" => AncreTampon::BasDroite,
+            _ => return None,
+        })
+    }
 
-    for (i, &page_id) in pages.iter().enumerate() {
-        let page_num = (i + 1) as u32;
-        if !pages_cibles.map_or(true, |c| c.contains(&page_num)) { continue; }
+    /// Alignement horizontal et vertical : -1 (gauche/bas), 0 (centre), 1 (droite/haut)
+    pub(crate) fn alignement(self) -> (i8, i8) {
+        match self {
+            AncreTampon::HautGauche => (-1, 1),
+            AncreTampon::HautCentre => (0, 1),
+            AncreTampon::HautDroite => (1, 1),
+            AncreTampon::MilieuGauche => (-1, 0),
+            AncreTampon::Centre => (0, 0),
+            AncreTampon::MilieuDroite => (1, 0),
+            AncreTampon::BasGauche => (-1, -1),
+            AncreTampon::BasCentre => (0, -1),
+            AncreTampon::BasDroite => (1, -1),
+        }
+    }
+}
 
-        let mediabox = obtenir_mediabox(&doc, page_id).unwrap_or([0.0, 0.0, 595.0, 842.0]);
-        let page_w = mediabox[2] - mediabox[0];
-        let page_h = mediabox[3] - mediabox[1];
+/// Placement commun à tous les tampons
+#[derive(Debug, Clone)]
+pub struct PlacementTampon {
+    pub ancre: AncreTampon,
+    /// Distance (mm) depuis les bords de l'ancre ; sur un axe centré, décalage vers la droite / le haut
+    pub decalage_mm: (f64, f64),
+    /// Rotation en degrés (sens trigonométrique), autour du centre du tampon
+    pub rotation: f64,
+    /// Opacité 0.0 – 1.0
+    pub opacite: f64,
+}
+
+impl Default for PlacementTampon {
+    fn default() -> Self {
+        PlacementTampon { ancre: AncreTampon::Centre, decalage_mm: (10.0, 10.0), rotation: 0.0, opacite: 1.0 }
+    }
+}
+
+/// Tampon à appliquer sur les pages
+#[derive(Debug, Clone)]
+pub enum Tampon {
+    /// Image PNG (transparence conservée) ou JPEG ; `echelle` = largeur en % de la largeur de page
+    Image { chemin: std::path::PathBuf, echelle: f64, placement: PlacementTampon },
+    /// Texte à modèle : {page}, {pages}, {date}, {filename}, {title}
+    Texte { modele: String, taille_police: f64, placement: PlacementTampon },
+}
+
+/// Largeurs Helvetica (1/1000 em) pour les codes ASCII 32–126
+const LARGEURS_HELVETICA: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278,
+    556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556,
+    1015, 667, 667, 722, 722, 667, 611, 778, 722, 278, 500, 667, 556, 833, 722, 778,
+    667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 278, 278, 278, 469, 556,
+    333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500, 222, 833, 556, 556,
+    556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+
+/// Largeur d'un texte WinAnsi en Helvetica (points) — hors ASCII, largeur moyenne
+fn largeur_texte_helvetica(octets: &[u8], taille: f64) -> f64 {
+    let total: u32 = octets.iter()
+        .map(|&o| match o {
+            32..=126 => LARGEURS_HELVETICA[(o - 32) as usize] as u32,
+            _ => 556,
+        })
+        .sum();
+    total as f64 * taille / 1000.0
+}
+
+/// Remplace les jetons d'un modèle d'en-tête / pied de page
+fn appliquer_modele_tampon(modele: &str, page: usize, pages: usize, date: &str, fichier: &str, titre: &str) -> String {
+    modele
+        .replace("{page}", &page.to_string())
+        .replace("{pages}", &pages.to_string())
+        .replace("{date}", date)
+        .replace("{filename}", fichier)
+        .replace("{title}", titre)
+}
+
+/// Charge une image en XObject : JPEG RVB/gris tel quel (DCTDecode), sinon RVB + SMask alpha
+fn ajouter_image_xobject(doc: &mut Document, chemin: &Path) -> Result<(ObjectId, u32, u32), String> {
+    let octets = std::fs::read(chemin)
+        .map_err(|e| format!("Lecture image {} : {}", chemin.display(), e))?;
+    let format = image::guess_format(&octets)
+        .map_err(|e| format!("Format d'image inconnu {} : {}", chemin.display(), e))?;
+    let img = image::load_from_memory_with_format(&octets, format)
+        .map_err(|e| format!("Décodage image {} : {}", chemin.display(), e))?;
+    let (largeur, hauteur) = (img.width(), img.height());
+
+    if format == image::ImageFormat::Jpeg && matches!(img.color(), image::ColorType::L8 | image::ColorType::Rgb8) {
+        let espace = if img.color() == image::ColorType::L8 { "DeviceGray" } else { "DeviceRGB" };
+        let id = doc.add_object(Stream::new(dictionary! {
+            "Type" => "XObject",
+            "Subtype" => "Image",
+            "Width" => largeur as i64,
+            "Height" => hauteur as i64,
+            "ColorSpace" => espace,
+            "BitsPerComponent" => 8,
+            "Filter" => "DCTDecode",
+        }, octets));
+        return Ok((id, largeur, hauteur));
+    }
+
+    let rgba = img.to_rgba8();
+    let rgb: Vec<u8> = rgba.pixels().flat_map(|p| [p[0], p[1], p[2]]).collect();
+    let alpha: Vec<u8> = rgba.pixels().map(|p| p[3]).collect();
+
+    let mut dict = dictionary! {
+        "Type" => "XObject",
+        "Subtype" => "Image",
+        "Width" => largeur as i64,
+        "Height" => hauteur as i64,
+        "ColorSpace" => "DeviceRGB",
+        "BitsPerComponent" => 8,
+    };
+    if alpha.iter().any(|&a| a < 255) {
+        let mut masque = Stream::new(dictionary! {
+            "Type" => "XObject",
+            "Subtype" => "Image",
+            "Width" => largeur as i64,
+            "Height" => hauteur as i64,
+            "ColorSpace" => "DeviceGray",
+            "BitsPerComponent" => 8,
+        }, alpha);
+        let _ = masque.compress();
+        dict.set("SMask", Object::Reference(doc.add_object(masque)));
+    }
+    let mut stream = Stream::new(dict, rgb);
+    let _ = stream.compress();
+    Ok((doc.add_object(stream), largeur, hauteur))
+}
+
+/// Matrice plaçant une boîte `largeur × hauteur` selon le placement, avec rotation autour de son centre
+fn matrice_tampon(placement: &PlacementTampon, page: [f64; 4], largeur: f64, hauteur: f64) -> [f64; 6] {
+    let (ax, ay) = placement.ancre.alignement();
+    let (dx, dy) = (placement.decalage_mm.0 * MM, placement.decalage_mm.1 * MM);
+    let [x0, y0, x1, y1] = page;
+    let cx = match ax {
+        -1 => x0 + dx + largeur / 2.0,
+        1 => x1 - dx - largeur / 2.0,
+        _ => (x0 + x1) / 2.0 + dx,
+    };
+    let cy = match ay {
+        -1 => y0 + dy + hauteur / 2.0,
+        1 => y1 - dy - hauteur / 2.0,
+        _ => (y0 + y1) / 2.0 + dy,
+    };
+    let (sin, cos) = placement.rotation.to_radians().sin_cos();
+    // translate(cx, cy) · rotate · translate(-l/2, -h/2)
+    let tx = cx - cos * largeur / 2.0 + sin * hauteur / 2.0;
+    let ty = cy - sin * largeur / 2.0 - cos * hauteur / 2.0;
+    [cos, sin, -sin, cos, tx, ty]
+}
+
+/// Repère de la page telle qu'affichée : matrice vers l'espace utilisateur et boîte visible.
+/// `/Rotate` tourne l'affichage dans le sens horaire.
+fn repere_page_affichee(mediabox: [f64; 4], rotation: i64) -> ([f64; 6], [f64; 4]) {
+    let [x0, y0, x1, y1] = mediabox;
+    let (largeur, hauteur) = (x1 - x0, y1 - y0);
+    match rotation.rem_euclid(360) {
+        90 => ([0.0, 1.0, -1.0, 0.0, x1, y0], [0.0, 0.0, hauteur, largeur]),
+        180 => ([-1.0, 0.0, 0.0, -1.0, x1, y1], [0.0, 0.0, largeur, hauteur]),
+        270 => ([0.0, -1.0, 1.0, 0.0, x0, y1], [0.0, 0.0, hauteur, largeur]),
+        _ => ([1.0, 0.0, 0.0, 1.0, x0, y0], [0.0, 0.0, largeur, hauteur]),
+    }
+}
+
+fn pdf_tamponner_interne(
+    input: &Path, output: &str,
+    tampons: &[Tampon],
+    pages_cibles: Option<&[u32]>,
+    nom_fichier: &str,
+) -> Result<(), String> {
+    let mut doc = Document::load(input)
+        .map_err(|e| format!("Erreur chargement PDF : {}", e))?;
+
+    let titre = doc.trailer.get(b"Info").ok()
+        .and_then(|i| resoudre_dict(&doc, i))
+        .and_then(|info| info.get(b"Title").ok())
+        .and_then(|t| t.as_str().ok())
+        .map(decoder_chaine_pdf)
+        .filter(|t| !t.trim().is_empty())
+        .unwrap_or_else(|| Path::new(nom_fichier).file_stem().unwrap_or_default().to_string_lossy().to_string());
+    let date = chrono::Local::now().format("%Y-%m-%d").to_string();
+
+    let font_id = doc.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type1",
+        "BaseFont" => "Helvetica",
+        "Encoding" => "WinAnsiEncoding",
+    });
+
+    // Ressources partagées par toutes les pages : une image et un état graphique par tampon
+    let mut images: Vec<Option<(ObjectId, u32, u32)>> = Vec::new();
+    let mut etats: Vec<ObjectId> = Vec::new();
+    for tampon in tampons {
+        let (image, opacite) = match tampon {
+            Tampon::Image { chemin, placement, .. } => (Some(ajouter_image_xobject(&mut doc, chemin)?), placement.opacite),
+            Tampon::Texte { placement, .. } => (None, placement.opacite),
+        };
+        images.push(image);
+        etats.push(doc.add_object(dictionary! {
+            "Type" => "ExtGState",
+            "CA" => opacite.clamp(0.0, 1.0),
+            "ca" => opacite.clamp(0.0, 1.0),
+        }));
+    }
+
+    let pages = obtenir_pages_ordonnees(&doc);
+    let total = pages.len();
+
+    for (i, &page_id) in pages.iter().enumerate() {
+        let page_num = (i + 1) as u32;
+        if !pages_cibles.is_none_or(|c| c.contains(&page_num)) { continue; }
+
+        let mediabox = obtenir_mediabox(&doc, page_id).unwrap_or([0.0, 0.0, 595.0, 842.0]);
+        let rotation = attribut_herite(&doc, page_id, b"Rotate")
+            .and_then(|r| r.as_i64().ok())
+            .unwrap_or(0);
+        // Les tampons sont placés dans le repère de la page affichée (après /Rotate)
+        let (orientation, boite) = repere_page_affichee(mediabox, rotation);
+        let noms: Vec<(String, String)> = (0..tampons.len())
+            .map(|k| (format!("Imtp{}", k), format!("GStp{}", k)))
+            .collect();
+        let [a, b, c, d, e, f] = orientation;
+        let mut operations = vec![
+            Operation::new("q", vec![]),
+            Operation::new("cm", vec![a.into(), b.into(), c.into(), d.into(), e.into(), f.into()]),
+        ];
+        let mut ressources: Vec<(&str, &str, ObjectId)> = vec![("Font", "Ftp", font_id)];
+
+        for (k, tampon) in tampons.iter().enumerate() {
+            let (nom_image, nom_gs) = &noms[k];
+            ressources.push(("ExtGState", nom_gs, etats[k]));
+            operations.push(Operation::new("q", vec![]));
+            operations.push(Operation::new("gs", vec![Object::Name(nom_gs.as_bytes().to_vec())]));
+
+            match tampon {
+                Tampon::Image { echelle, placement, .. } => if let Some((image_id, px_l, px_h)) = images[k] {
+                    let largeur = (boite[2] - boite[0]) * echelle / 100.0;
+                    let hauteur = largeur * px_h as f64 / px_l.max(1) as f64;
+                    let [a, b, c, d, e, f] = matrice_tampon(placement, boite, largeur, hauteur);
+                    ressources.push(("XObject", nom_image, image_id));
+                    operations.extend([
+                        Operation::new("cm", vec![a.into(), b.into(), c.into(), d.into(), e.into(), f.into()]),
+                        Operation::new("cm", vec![largeur.into(), 0.into(), 0.into(), hauteur.into(), 0.into(), 0.into()]),
+                        Operation::new("Do", vec![Object::Name(nom_image.as_bytes().to_vec())]),
+                    ]);
+                }
+                Tampon::Texte { modele, taille_police, placement } => {
+                    let texte = appliquer_modele_tampon(modele, i + 1, total, &date, nom_fichier, &titre);
+                    let encode = encoder_winansi(&texte);
+                    let largeur = largeur_texte_helvetica(&encode, *taille_police);
+                    let [a, b, c, d, e, f] = matrice_tampon(placement, boite, largeur, *taille_police);
+                    operations.extend([
+                        Operation::new("cm", vec![a.into(), b.into(), c.into(), d.into(), e.into(), f.into()]),
+                        Operation::new("BT", vec![]),
+                        Operation::new("Tf", vec!["Ftp".into(), (*taille_police).into()]),
+                        Operation::new("Td", vec![0.into(), (taille_police * 0.22).into()]),
+                        Operation::new("Tj", vec![Object::String(encode, lopdf::StringFormat::Literal)]),
+                        Operation::new("ET", vec![]),
+                    ]);
+                }
+            }
+            operations.push(Operation::new("Q", vec![]));
+        }
+        operations.push(Operation::new("Q", vec![]));
+
+        let content_bytes = Content { operations }.encode()
+            .map_err(|e| format!("Erreur encodage tampon : {}", e))?;
+        isoler_contenu_page(&mut doc, page_id)?;
+        ajouter_overlay_ressources(&mut doc, page_id, content_bytes, &ressources)?;
+    }
+
+    sauvegarder(&mut doc, output)
+}
+
+/// Applique des tampons (images, en-têtes / pieds de page) sur une sélection de pages
+pub fn pdf_tamponner(
+    input: &Path, output: &str,
+    tampons: &[Tampon],
+    pages_cibles: Option<&[u32]>,
+) -> Result<(), String> {
+    let tampons = tampons.to_vec();
+    let pages = pages_cibles.map(|p| p.to_vec());
+    let nom_fichier = input.file_name().unwrap_or_default().to_string_lossy().to_string();
+    appliquer_operation_doc(input, output, |pdf_in, pdf_out| {
+        pdf_tamponner_interne(pdf_in, pdf_out, &tampons, pages.as_deref(), &nom_fichier)
+    })
+}
+
+// ════════════════════════════════════════════════════════════════════════
+//  PDF ANNOTATIONS — notes, texte libre, surlignage, liens (objets /Annot natifs)
+// ════════════════════════════════════════════════════════════════════════
+
+/// Type d'annotation à créer
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeAnnotation {
+    /// Note repliable (icône) — seul le coin bas-gauche du rectangle est utilisé
+    Note,
+    /// Texte affiché dans un cadre
+    TexteLibre,
+    /// Surlignage du rectangle
+    Surlignage,
+    /// Soulignement du rectangle
+    Soulignement,
+    /// Texte barré
+    Barre,
+    /// Lien vers une URL
+    Lien(String),
+}
+
+impl TypeAnnotation {
+    fn sous_type(&self) -> &'static str {
+        match self {
+            TypeAnnotation::Note => "Text",
+            TypeAnnotation::TexteLibre => "FreeText",
+            TypeAnnotation::Surlignage => "Highlight",
+            TypeAnnotation::Soulignement => "Underline",
+            TypeAnnotation::Barre => "StrikeOut",
+            TypeAnnotation::Lien(_) => "Link",
+        }
+    }
+
+    /// Couleur par défaut du type
+    pub fn couleur_defaut(&self) -> [f64; 3] {
+        match self {
+            TypeAnnotation::Note | TypeAnnotation::Surlignage => [1.0, 0.9, 0.0],
+            TypeAnnotation::TexteLibre => [1.0, 1.0, 0.8],
+            TypeAnnotation::Soulignement => [0.0, 0.4, 1.0],
+            TypeAnnotation::Barre => [0.9, 0.0, 0.0],
+            TypeAnnotation::Lien(_) => [0.0, 0.0, 1.0],
+        }
+    }
+}
+
+/// Annotation à ajouter (rectangle en % de la page, origine en bas à gauche)
+#[derive(Debug, Clone)]
+pub struct NouvelleAnnotation {
+    pub type_annotation: TypeAnnotation,
+    pub texte: String,
+    /// x, y, largeur, hauteur (%)
+    pub rect: [f64; 4],
+    pub couleur: [f64; 3],
+    pub auteur: String,
+}
+
+impl NouvelleAnnotation {
+    pub fn new(type_annotation: TypeAnnotation, texte: &str, rect: [f64; 4]) -> Self {
+        let couleur = type_annotation.couleur_defaut();
+        NouvelleAnnotation { type_annotation, texte: texte.to_string(), rect, couleur, auteur: String::new() }
+    }
+}
+
+/// Annotation existante (liste)
+#[derive(Debug, Clone)]
+pub struct InfoAnnotation {
+    pub page: u32,
+    pub sous_type: String,
+    /// Rectangle en points
+    pub rect: [f64; 4],
+    pub contenu: String,
+    pub auteur: String,
+    /// Possède un flux d'apparence (donc aplatissable)
+    pub apparence: bool,
+}
+
+/// Sous-types conservés par l'aplatissement : liens, popups (gérés avec leur parent), champs de formulaire
+const ANNOTATIONS_NON_APLATIES: &[&[u8]] = &[b"Link", b"Popup", b"Widget"];
+
+/// Chaîne de texte PDF : littérale si ASCII, sinon UTF-16BE avec BOM
+fn chaine_texte_pdf(texte: &str) -> Object {
+    if texte.is_ascii() {
+        return Object::string_literal(texte.to_string());
+    }
+    let mut octets = vec![0xFE, 0xFF];
+    for u in texte.encode_utf16() { octets.extend_from_slice(&u.to_be_bytes()); }
+    Object::String(octets, lopdf::StringFormat::Hexadecimal)
+}
+
+/// Lit le tableau /Annots d'une page (direct ou référencé)
+fn lire_annots_page(doc: &Document, page_id: ObjectId) -> Vec<Object> {
+    doc.get_dictionary(page_id).ok()
+        .and_then(|d| d.get(b"Annots").ok())
+        .and_then(|a| match resoudre(doc, a) {
+            Object::Array(arr) => Some(arr.clone()),
+            _ => None,
+        })
+        .unwrap_or_default()
+}
+
+/// Remplace le tableau /Annots d'une page (supprimé s'il est vide)
+fn ecrire_annots_page(doc: &mut Document, page_id: ObjectId, annots: Vec<Object>) {
+    if let Ok(Object::Dictionary(dict)) = doc.get_object_mut(page_id) {
+        if annots.is_empty() {
+            dict.remove(b"Annots");
+        } else {
+            dict.set("Annots", Object::Array(annots));
+        }
+    }
+}
+
+/// Découpe un texte en lignes tenant dans `largeur_max` (Helvetica, octets WinAnsi)
+fn couper_lignes_helvetica(texte: &str, taille: f64, largeur_max: f64) -> Vec<Vec<u8>> {
+    let mut lignes = Vec::new();
+    for paragraphe in texte.lines() {
+        let mut courante: Vec<u8> = Vec::new();
+        for mot in paragraphe.split_whitespace().map(encoder_winansi) {
+            let mut essai = courante.clone();
+            if !essai.is_empty() { essai.push(b' '); }
+            essai.extend(&mot);
+            if !courante.is_empty() && largeur_texte_helvetica(&essai, taille) > largeur_max {
+                lignes.push(std::mem::replace(&mut courante, mot));
+            } else {
+                courante = essai;
+            }
+        }
+        lignes.push(courante);
+    }
+    lignes
+}
+
+/// Flux d'apparence (/AP /N) d'une annotation créée, dans un repère 0,0 – largeur,hauteur
+fn apparence_annotation(doc: &mut Document, annotation: &NouvelleAnnotation, largeur: f64, hauteur: f64, font_id: ObjectId) -> Result<Option<ObjectId>, String> {
+    let [r, g, b] = annotation.couleur;
+    let couleur = |op: &str| Operation::new(op, vec![r.into(), g.into(), b.into()]);
+    let mut ressources = lopdf::Dictionary::new();
+
+    let operations = match &annotation.type_annotation {
+        TypeAnnotation::Lien(_) => return Ok(None),
+        TypeAnnotation::Note => vec![
+            couleur("rg"),
+            Operation::new("RG", vec![0.3.into(), 0.3.into(), 0.3.into()]),
+            Operation::new("re", vec![0.5.into(), 0.5.into(), (largeur - 1.0).into(), (hauteur - 1.0).into()]),
+            Operation::new("B", vec![]),
+            Operation::new("m", vec![5.into(), (hauteur * 0.65).into()]),
+            Operation::new("l", vec![(largeur - 5.0).into(), (hauteur * 0.65).into()]),
+            Operation::new("m", vec![5.into(), (hauteur * 0.45).into()]),
+            Operation::new("l", vec![(largeur - 5.0).into(), (hauteur * 0.45).into()]),
+            Operation::new("m", vec![5.into(), (hauteur * 0.25).into()]),
+            Operation::new("l", vec![(largeur * 0.6).into(), (hauteur * 0.25).into()]),
+            Operation::new("S", vec![]),
+        ],
+        TypeAnnotation::TexteLibre => {
+            ressources.set("Font", dictionary! { "Helv" => font_id });
+            let taille = 10.0;
+            let mut ops = vec![
+                couleur("rg"),
+                Operation::new("RG", vec![0.into(), 0.into(), 0.into()]),
+                Operation::new("w", vec![0.5.into()]),
+                Operation::new("re", vec![0.25.into(), 0.25.into(), (largeur - 0.5).into(), (hauteur - 0.5).into()]),
+                Operation::new("B", vec![]),
+                Operation::new("re", vec![2.into(), 2.into(), (largeur - 4.0).into(), (hauteur - 4.0).into()]),
+                Operation::new("W", vec![]),
+                Operation::new("n", vec![]),
+                Operation::new("BT", vec![]),
+                Operation::new("g", vec![0.into()]),
+                Operation::new("Tf", vec!["Helv".into(), taille.into()]),
+                Operation::new("TL", vec![(taille * 1.2).into()]),
+                Operation::new("Td", vec![4.into(), (hauteur - 3.0 - taille).into()]),
+            ];
+            for (i, ligne) in couper_lignes_helvetica(&annotation.texte, taille, largeur - 8.0).into_iter().enumerate() {
+                if i > 0 { ops.push(Operation::new("T*", vec![])); }
+                ops.push(Operation::new("Tj", vec![Object::String(ligne, lopdf::StringFormat::Literal)]));
+            }
+            ops.push(Operation::new("ET", vec![]));
+            ops
+        }
+        TypeAnnotation::Surlignage => {
+            let gs_id = doc.add_object(dictionary! { "Type" => "ExtGState", "BM" => "Multiply" });
+            ressources.set("ExtGState", dictionary! { "GSsur" => gs_id });
+            vec![
+                Operation::new("gs", vec!["GSsur".into()]),
+                couleur("rg"),
+                Operation::new("re", vec![0.into(), 0.into(), largeur.into(), hauteur.into()]),
+                Operation::new("f", vec![]),
+            ]
+        }
+        TypeAnnotation::Soulignement | TypeAnnotation::Barre => {
+            let epaisseur = (hauteur * 0.07).max(1.0);
+            let y = if annotation.type_annotation == TypeAnnotation::Barre { hauteur / 2.0 } else { epaisseur };
+            vec![
+                couleur("RG"),
+                Operation::new("w", vec![epaisseur.into()]),
+                Operation::new("m", vec![0.into(), y.into()]),
+                Operation::new("l", vec![largeur.into(), y.into()]),
+                Operation::new("S", vec![]),
+            ]
+        }
+    };
+
+    let contenu = Content { operations }.encode()
+        .map_err(|e| format!("Erreur encodage apparence : {}", e))?;
+    Ok(Some(doc.add_object(Stream::new(dictionary! {
+        "Type" => "XObject",
+        "Subtype" => "Form",
+        "BBox" => vec![0.into(), 0.into(), largeur.into(), hauteur.into()],
+        "Resources" => ressources,
+    }, contenu))))
+}
+
+fn pdf_ajouter_annotations_interne(
+    input: &Path, output: &str,
+    annotations: &[NouvelleAnnotation],
+    pages_cibles: Option<&[u32]>,
+) -> Result<(), String> {
+    let mut doc = Document::load(input)
+        .map_err(|e| format!("Erreur chargement PDF : {}", e))?;
+
+    let font_id = doc.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type1",
+        "BaseFont" => "Helvetica",
+        "Encoding" => "WinAnsiEncoding",
+    });
+    let date = chrono::Utc::now().format("D:%Y%m%d%H%M%S+00'00'").to_string();
+    let pages = obtenir_pages_ordonnees(&doc);
+
+    for (i, &page_id) in pages.iter().enumerate() {
+        let page_num = (i + 1) as u32;
+        if !pages_cibles.is_none_or(|c| c.contains(&page_num)) { continue; }
+
+        let [mx0, my0, mx1, my1] = obtenir_mediabox(&doc, page_id).unwrap_or([0.0, 0.0, 595.0, 842.0]);
+        let (page_w, page_h) = (mx1 - mx0, my1 - my0);
+        let mut annots = lire_annots_page(&doc, page_id);
+
+        for annotation in annotations {
+            let [x, y, w, h] = annotation.rect;
+            let x0 = mx0 + page_w * x / 100.0;
+            let y0 = my0 + page_h * y / 100.0;
+            let (largeur, hauteur) = match annotation.type_annotation {
+                TypeAnnotation::Note => (20.0, 20.0),
+                _ => (page_w * w / 100.0, page_h * h / 100.0),
+            };
+            let (x1, y1) = (x0 + largeur, y0 + hauteur);
+            let [r, g, b] = annotation.couleur;
+
+            let mut dict = dictionary! {
+                "Type" => "Annot",
+                "Subtype" => annotation.type_annotation.sous_type(),
+                "Rect" => vec![x0.into(), y0.into(), x1.into(), y1.into()],
+                "P" => page_id,
+                "M" => Object::string_literal(date.clone()),
+                "F" => 4,
+                "C" => vec![r.into(), g.into(), b.into()],
+            };
+            if !annotation.texte.is_empty() {
+                dict.set("Contents", chaine_texte_pdf(&annotation.texte));
+            }
+            match &annotation.type_annotation {
+                TypeAnnotation::Lien(url) => {
+                    dict.set("Border", vec![0.into(), 0.into(), 0.into()]);
+                    dict.set("A", dictionary! { "S" => "URI", "URI" => Object::string_literal(url.clone()) });
+                }
+                type_annotation => {
+                    dict.set("CreationDate", Object::string_literal(date.clone()));
+                    if !annotation.auteur.is_empty() {
+                        dict.set("T", chaine_texte_pdf(&annotation.auteur));
+                    }
+                    match type_annotation {
+                        TypeAnnotation::Note => {
+                            dict.set("Name", "Comment");
+                            dict.set("Open", false);
+                        }
+                        TypeAnnotation::TexteLibre => {
+                            dict.set("DA", Object::string_literal("/Helv 10 Tf 0 g"));
+                            dict.set("Border", vec![0.into(), 0.into(), 1.into()]);
+                        }
+                        _ => {
+                            // QuadPoints : haut-gauche, haut-droite, bas-gauche, bas-droite
+                            dict.set("QuadPoints", vec![
+                                x0.into(), y1.into(), x1.into(), y1.into(),
+                                x0.into(), y0.into(), x1.into(), y0.into(),
+                            ]);
+                        }
+                    }
+                }
+            }
+            if let Some(ap_id) = apparence_annotation(&mut doc, annotation, largeur, hauteur, font_id)? {
+                dict.set("AP", dictionary! { "N" => ap_id });
+            }
+            annots.push(Object::Reference(doc.add_object(dict)));
+        }
+        ecrire_annots_page(&mut doc, page_id, annots);
+    }
+
+    sauvegarder(&mut doc, output)
+}
+
+/// Ajoute des annotations natives (déplaçables / supprimables dans les lecteurs) sur les pages
+pub fn pdf_ajouter_annotations(
+    input: &Path, output: &str,
+    annotations: &[NouvelleAnnotation],
+    pages_cibles: Option<&[u32]>,
+) -> Result<(), String> {
+    let annotations = annotations.to_vec();
+    let pages = pages_cibles.map(|p| p.to_vec());
+    appliquer_operation_doc(input, output, |pdf_in, pdf_out| {
+        pdf_ajouter_annotations_interne(pdf_in, pdf_out, &annotations, pages.as_deref())
+    })
+}
+
+/// Ajoute une annotation texte libre (rectangle en % de la page)
+pub fn pdf_annoter(
+    input: &Path, output: &str,
+    texte: &str,
+    x: f64, y: f64,
+    largeur: f64, hauteur: f64,
+    pages_cibles: Option<&[u32]>,
+) -> Result<(), String> {
+    let annotation = NouvelleAnnotation::new(TypeAnnotation::TexteLibre, texte, [x, y, largeur, hauteur]);
+    pdf_ajouter_annotations(input, output, &[annotation], pages_cibles)
+}
+
+/// Liste les annotations d'un PDF
+pub fn pdf_lister_annotations(input: &Path) -> Result<Vec<InfoAnnotation>, String> {
+    let doc = Document::load(input)
+        .map_err(|e| format!("Erreur chargement PDF : {}", e))?;
+    let mut liste = Vec::new();
+    for (i, &page_id) in obtenir_pages_ordonnees(&doc).iter().enumerate() {
+        for annot in lire_annots_page(&doc, page_id) {
+            let Some(dict) = resoudre_dict(&doc, &annot) else { continue };
+            let lire = |cle: &[u8]| dict.get(cle).and_then(|v| v.as_str()).map(decoder_chaine_pdf).unwrap_or_default();
+            let mut rect = [0.0; 4];
+            if let Ok(Object::Array(arr)) = dict.get(b"Rect").map(|r| resoudre(&doc, r)) {
+                for (k, v) in arr.iter().take(4).enumerate() {
+                    rect[k] = v.as_float().map(|f| f as f64).unwrap_or(0.0);
+                }
+            }
+            liste.push(InfoAnnotation {
+                page: (i + 1) as u32,
+                sous_type: dict.get(b"Subtype").and_then(|s| s.as_name())
+                    .map(|s| String::from_utf8_lossy(s).to_string())
+                    .unwrap_or_default(),
+                rect,
+                contenu: lire(b"Contents"),
+                auteur: lire(b"T"),
+                apparence: dict.get(b"AP").ok().and_then(|ap| resoudre_dict(&doc, ap)).is_some_and(|ap| ap.has(b"N")),
+            });
+        }
+    }
+    Ok(liste)
+}
+
+/// Filtre les annotations des pages ciblées. `garder` reçoit (id, dict) et indique si l'annotation reste.
+/// Les popups dont le parent disparaît sont retirées aussi. Renvoie le nombre d'annotations retirées.
+fn filtrer_annotations<F>(doc: &mut Document, pages_cibles: Option<&[u32]>, mut garder: F) -> usize
+where
+    F: FnMut(&Document, ObjectId, ObjectId, &lopdf::Dictionary) -> bool,
+{
+    let mut retirees = 0;
+    for (i, page_id) in obtenir_pages_ordonnees(doc).into_iter().enumerate() {
+        let page_num = (i + 1) as u32;
+        if !pages_cibles.is_none_or(|c| c.contains(&page_num)) { continue; }
+
+        let annots = lire_annots_page(doc, page_id);
+        let mut supprimees: Vec<ObjectId> = Vec::new();
+        for annot in &annots {
+            let Ok(id) = annot.as_reference() else { continue };
+            let Ok(dict) = doc.get_dictionary(id) else { continue };
+            if dict.get(b"Subtype").and_then(|s| s.as_name()).ok() == Some(b"Popup") { continue; }
+            if !garder(doc, page_id, id, dict) {
+                supprimees.push(id);
+            }
+        }
+        let restantes: Vec<Object> = annots.into_iter()
+            .filter(|annot| {
+                let Ok(id) = annot.as_reference() else { return true };
+                if supprimees.contains(&id) { return false; }
+                let parent = doc.get_dictionary(id).ok()
+                    .and_then(|d| d.get(b"Parent").and_then(|p| p.as_reference()).ok());
+                !parent.is_some_and(|p| supprimees.contains(&p))
+            })
+            .collect();
+        retirees += supprimees.len();
+        ecrire_annots_page(doc, page_id, restantes);
+    }
+    retirees
+}
+
+fn pdf_supprimer_annotations_interne(
+    input: &Path, output: &str,
+    sous_types: &[String],
+    pages_cibles: Option<&[u32]>,
+) -> Result<usize, String> {
+    let mut doc = Document::load(input)
+        .map_err(|e| format!("Erreur chargement PDF : {}", e))?;
+    let retirees = filtrer_annotations(&mut doc, pages_cibles, |_, _, _, dict| {
+        let sous_type = dict.get(b"Subtype").and_then(|s| s.as_name()).unwrap_or(b"");
+        if sous_types.is_empty() {
+            sous_type == b"Widget"
+        } else {
+            !sous_types.iter().any(|t| t.as_bytes().eq_ignore_ascii_case(sous_type))
+        }
+    });
+    doc.prune_objects();
+    crate::log_info(&format!("pdf_supprimer_annotations | {:?} | {} supprimée(s)", input, retirees));
+    sauvegarder(&mut doc, output)?;
+    Ok(retirees)
+}
+
+/// Supprime des annotations. `sous_types` vide = toutes sauf les champs de formulaire (Widget).
+/// Renvoie le nombre d'annotations supprimées.
+pub fn pdf_supprimer_annotations(
+    input: &Path, output: &str,
+    sous_types: &[String],
+    pages_cibles: Option<&[u32]>,
+) -> Result<usize, String> {
+    let mut retirees = 0;
+    appliquer_operation_doc(input, output, |pdf_in, pdf_out| {
+        retirees = pdf_supprimer_annotations_interne(pdf_in, pdf_out, sous_types, pages_cibles)?;
+        Ok(())
+    })?;
+    Ok(retirees)
+}
+
+/// Flux d'apparence normal d'une annotation (en tenant compte de l'état /AS)
+fn apparence_normale(doc: &Document, dict: &lopdf::Dictionary) -> Option<ObjectId> {
+    let ap = resoudre_dict(doc, dict.get(b"AP").ok()?)?;
+    match ap.get(b"N").ok()? {
+        Object::Reference(id) if doc.get_object(*id).ok()?.as_stream().is_ok() => Some(*id),
+        n => {
+            let etats = resoudre_dict(doc, n)?;
+            let etat = dict.get(b"AS").and_then(|s| s.as_name()).ok()?;
+            etats.get(etat).and_then(|e| e.as_reference()).ok()
+        }
+    }
+}
+
+fn pdf_aplatir_annotations_interne(input: &Path, output: &str) -> Result<usize, String> {
+    let mut doc = Document::load(input)
+        .map_err(|e| format!("Erreur chargement PDF : {}", e))?;
+
+    // Passe 1 : repérer les annotations aplatissables et calculer leur placement
+    let mut a_incruster: Vec<(ObjectId, ObjectId, [f64; 6])> = Vec::new();
+    let aplaties = filtrer_annotations(&mut doc, None, |doc, page_id, _, dict| {
+        let sous_type = dict.get(b"Subtype").and_then(|s| s.as_name()).unwrap_or(b"");
+        if ANNOTATIONS_NON_APLATIES.contains(&sous_type) { return true; }
+        let drapeaux = dict.get(b"F").and_then(|f| f.as_i64()).unwrap_or(0);
+        if drapeaux & 2 != 0 { return false; } // masquée : retirée sans rendu
+        let Some(ap_id) = apparence_normale(doc, dict) else { return true };
+        let Ok(ap) = doc.get_object(ap_id).and_then(|o| o.as_stream()) else { return true };
+
+        let nombres = |cle: &[u8]| -> Vec<f64> {
+            match dict.get(cle).or_else(|_| ap.dict.get(cle)).map(|o| resoudre(doc, o)) {
+                Ok(Object::Array(arr)) => arr.iter().filter_map(|v| v.as_float().ok().map(|f| f as f64)).collect(),
+                _ => Vec::new(),
+            }
+        };
+        let rect = nombres(b"Rect");
+        let bbox = match ap.dict.get(b"BBox").map(|o| resoudre(doc, o)) {
+            Ok(Object::Array(arr)) => arr.iter().filter_map(|v| v.as_float().ok().map(|f| f as f64)).collect(),
+            _ => Vec::new(),
+        };
+        if rect.len() != 4 || bbox.len() != 4 { return true; }
+        let m = match ap.dict.get(b"Matrix").map(|o| resoudre(doc, o)) {
+            Ok(Object::Array(arr)) if arr.len() == 6 => arr.iter().map(|v| v.as_float().unwrap_or(0.0) as f64).collect(),
+            _ => vec![1.0, 0.0, 0.0, 1.0, 0.0, 0.0],
+        };
+
+        // BBox transformée par /Matrix, puis ajustée sur /Rect (PDF 32000, 12.5.5)
+        let coins: Vec<(f64, f64)> = [(bbox[0], bbox[1]), (bbox[2], bbox[1]), (bbox[0], bbox[3]), (bbox[2], bbox[3])]
+            .iter()
+            .map(|&(x, y)| (m[0] * x + m[2] * y + m[4], m[1] * x + m[3] * y + m[5]))
+            .collect();
+        let bx0 = coins.iter().map(|c| c.0).fold(f64::INFINITY, f64::min);
+        let bx1 = coins.iter().map(|c| c.0).fold(f64::NEG_INFINITY, f64::max);
+        let by0 = coins.iter().map(|c| c.1).fold(f64::INFINITY, f64::min);
+        let by1 = coins.iter().map(|c| c.1).fold(f64::NEG_INFINITY, f64::max);
+        let (rx0, rx1) = (rect[0].min(rect[2]), rect[0].max(rect[2]));
+        let (ry0, ry1) = (rect[1].min(rect[3]), rect[1].max(rect[3]));
+        let sx = if bx1 > bx0 { (rx1 - rx0) / (bx1 - bx0) } else { 1.0 };
+        let sy = if by1 > by0 { (ry1 - ry0) / (by1 - by0) } else { 1.0 };
+        a_incruster.push((page_id, ap_id, [sx, 0.0, 0.0, sy, rx0 - bx0 * sx, ry0 - by0 * sy]));
+        false
+    });
+
+    // Passe 2 : dessiner les apparences dans le contenu de chaque page
+    let mut pages_traitees: Vec<ObjectId> = a_incruster.iter().map(|(p, _, _)| *p).collect();
+    pages_traitees.dedup();
+    for page_id in pages_traitees {
+        let elements: Vec<(ObjectId, [f64; 6])> = a_incruster.iter()
+            .filter(|(p, _, _)| *p == page_id)
+            .map(|(_, ap, m)| (*ap, *m))
+            .collect();
+        let noms: Vec<String> = (0..elements.len()).map(|k| format!("Aplat{}", k)).collect();
+        let mut operations = Vec::new();
+        for ((ap_id, [a, b, c, d, e, f]), nom) in elements.iter().zip(&noms) {
+            if let Ok(Object::Stream(s)) = doc.get_object_mut(*ap_id) {
+                s.dict.set("Type", "XObject");
+                s.dict.set("Subtype", "Form");
+            }
+            operations.extend([
+                Operation::new("q", vec![]),
+                Operation::new("cm", vec![(*a).into(), (*b).into(), (*c).into(), (*d).into(), (*e).into(), (*f).into()]),
+                Operation::new("Do", vec![Object::Name(nom.as_bytes().to_vec())]),
+                Operation::new("Q", vec![]),
+            ]);
+        }
+        let ressources: Vec<(&str, &str, ObjectId)> = elements.iter().zip(&noms)
+            .map(|((ap_id, _), nom)| ("XObject", nom.as_str(), *ap_id))
+            .collect();
+        let contenu = Content { operations }.encode()
+            .map_err(|e| format!("Erreur encodage aplatissement : {}", e))?;
+        ajouter_overlay_ressources(&mut doc, page_id, contenu, &ressources)?;
+    }
+
+    doc.prune_objects();
+    crate::log_info(&format!("pdf_aplatir_annotations | {:?} | {} aplatie(s)", input, aplaties));
+    sauvegarder(&mut doc, output)?;
+    Ok(aplaties)
+}
+
+/// Incruste l'apparence des annotations dans le contenu des pages puis les retire.
+/// Liens et champs de formulaire sont conservés. Renvoie le nombre d'annotations aplaties.
+pub fn pdf_aplatir_annotations(input: &Path, output: &str) -> Result<usize, String> {
+    let mut aplaties = 0;
+    appliquer_operation_doc(input, output, |pdf_in, pdf_out| {
+        aplaties = pdf_aplatir_annotations_interne(pdf_in, pdf_out)?;
+        Ok(())
+    })?;
+    Ok(aplaties)
+}
+
+// ════════════════════════════════════════════════════════════════════════
+//  PDF PIÈCES JOINTES — arbre /EmbeddedFiles et annotations FileAttachment
+// ════════════════════════════════════════════════════════════════════════
+
+/// Fichier joint à un PDF
+#[derive(Debug, Clone)]
+pub struct PieceJointe {
+    pub nom: String,
+    pub description: String,
+    pub mime: String,
+    pub taille: usize,
+    /// Page de l'annotation FileAttachment (None = pièce jointe du document)
+    pub page: Option<u32>,
+}
+
+/// Noms de fichiers des factures électroniques (Factur-X / ZUGFeRD / XRechnung)
+const NOMS_FACTURE_XML: &[&str] = &["factur-x.xml", "zugferd-invoice.xml", "xrechnung.xml"];
+
+/// Type MIME déduit de l'extension
+fn mime_depuis_extension(chemin: &Path) -> &'static str {
+    let ext = chemin.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+    match ext.as_str() {
+        "pdf" => "application/pdf",
+        "xml" => "text/xml",
+        "txt" | "log" => "text/plain",
+        "csv" => "text/csv",
+        "html" | "htm" => "text/html",
+        "json" => "application/json",
+        "xlsx" => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        "xls" => "application/vnd.ms-excel",
+        "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        "odt" => "application/vnd.oasis.opendocument.text",
+        "ods" => "application/vnd.oasis.opendocument.spreadsheet",
+        "zip" => "application/zip",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Parcourt un arbre de noms (/Names ou /Kids) et collecte les paires clé → valeur
+fn collecter_arbre_noms(doc: &Document, noeud: &Object, sortie: &mut Vec<(Vec<u8>, Object)>, profondeur: u32) {
+    let Some(dict) = resoudre_dict(doc, noeud) else { return };
+    if profondeur > 32 { return; }
+    if let Ok(Object::Array(noms)) = dict.get(b"Names").map(|n| resoudre(doc, n)) {
+        for paire in noms.chunks(2) {
+            if let [cle, valeur] = paire
+                && let Ok(cle) = resoudre(doc, cle).as_str()
+            {
+                sortie.push((cle.to_vec(), valeur.clone()));
+            }
+        }
+    }
+    if let Ok(Object::Array(enfants)) = dict.get(b"Kids").map(|k| resoudre(doc, k)) {
+        for enfant in enfants {
+            collecter_arbre_noms(doc, enfant, sortie, profondeur + 1);
+        }
+    }
+}
+
+/// Entrées de l'arbre /Names /EmbeddedFiles du catalogue
+fn lire_fichiers_incorpores(doc: &Document) -> Vec<(Vec<u8>, Object)> {
+    let mut entrees = Vec::new();
+    if let Some(arbre) = doc.catalog().ok()
+        .and_then(|c| c.get(b"Names").ok())
+        .and_then(|n| resoudre_dict(doc, n))
+        .and_then(|n| n.get(b"EmbeddedFiles").ok())
+    {
+        collecter_arbre_noms(doc, arbre, &mut entrees, 0);
+    }
+    entrees
+}
+
+/// Réécrit l'arbre /EmbeddedFiles à plat, trié par nom (supprimé s'il est vide)
+fn ecrire_fichiers_incorpores(doc: &mut Document, mut entrees: Vec<(Vec<u8>, Object)>) -> Result<(), String> {
+    entrees.sort_by(|a, b| a.0.cmp(&b.0));
+    let mut names = doc.catalog().ok()
+        .and_then(|c| c.get(b"Names").ok())
+        .and_then(|n| resoudre_dict(doc, n))
+        .cloned()
+        .unwrap_or_default();
+    if entrees.is_empty() {
+        names.remove(b"EmbeddedFiles");
+    } else {
+        let tableau: Vec<Object> = entrees.into_iter()
+            .flat_map(|(cle, valeur)| [Object::String(cle, lopdf::StringFormat::Literal), valeur])
+            .collect();
+        names.set("EmbeddedFiles", dictionary! { "Names" => tableau });
+    }
+    let catalog = doc.catalog_mut().map_err(|e| format!("Catalogue introuvable : {}", e))?;
+    if names.is_empty() {
+        catalog.remove(b"Names");
+    } else {
+        catalog.set("Names", names);
+    }
+    Ok(())
+}
+
+/// Nom de fichier d'une spécification de fichier (/UF prioritaire sur /F)
+fn nom_filespec(doc: &Document, spec: &lopdf::Dictionary) -> Option<String> {
+    [b"UF".as_slice(), b"F".as_slice()].iter()
+        .find_map(|cle| spec.get(cle).ok().and_then(|v| resoudre(doc, v).as_str().ok()))
+        .map(decoder_chaine_pdf)
+}
+
+/// Lit une spécification de fichier incorporé : métadonnées et contenu décompressé
+fn lire_filespec(doc: &Document, spec: &Object, nom_defaut: &[u8], page: Option<u32>) -> Option<(PieceJointe, Vec<u8>)> {
+    let dict = resoudre_dict(doc, spec)?;
+    let ef = resoudre_dict(doc, dict.get(b"EF").ok()?)?;
+    let Object::Stream(flux) = resoudre(doc, ef.get(b"UF").or_else(|_| ef.get(b"F")).ok()?) else { return None };
+    let contenu = flux.get_plain_content().ok()?;
+    let piece = PieceJointe {
+        nom: nom_filespec(doc, dict).unwrap_or_else(|| decoder_chaine_pdf(nom_defaut)),
+        description: dict.get(b"Desc").ok()
+            .and_then(|d| resoudre(doc, d).as_str().ok())
+            .map(decoder_chaine_pdf)
+            .unwrap_or_default(),
+        mime: flux.dict.get(b"Subtype").and_then(|s| s.as_name())
+            .map(|s| String::from_utf8_lossy(s).to_string())
+            .unwrap_or_default(),
+        taille: contenu.len(),
+        page,
+    };
+    Some((piece, contenu))
+}
+
+/// Toutes les pièces jointes : arbre du document puis annotations, page par page
+fn collecter_pieces_jointes(doc: &Document) -> Vec<(PieceJointe, Vec<u8>)> {
+    let mut pieces: Vec<(PieceJointe, Vec<u8>)> = lire_fichiers_incorpores(doc).iter()
+        .filter_map(|(cle, spec)| lire_filespec(doc, spec, cle, None))
+        .collect();
+    for (i, &page_id) in obtenir_pages_ordonnees(doc).iter().enumerate() {
+        for annot in lire_annots_page(doc, page_id) {
+            let Some(dict) = resoudre_dict(doc, &annot) else { continue };
+            if dict.get(b"Subtype").and_then(|s| s.as_name()).ok() != Some(b"FileAttachment") { continue; }
+            let Ok(spec) = dict.get(b"FS") else { continue };
+            if let Some((mut piece, contenu)) = lire_filespec(doc, spec, b"attachment", Some((i + 1) as u32)) {
+                if piece.description.is_empty() {
+                    piece.description = dict.get(b"Contents").and_then(|c| c.as_str())
+                        .map(decoder_chaine_pdf)
+                        .unwrap_or_default();
+                }
+                pieces.push((piece, contenu));
+            }
+        }
+    }
+    pieces
+}
+
+/// Liste les fichiers joints d'un PDF (document et annotations)
+pub fn pdf_lister_pieces_jointes(input: &Path) -> Result<Vec<PieceJointe>, String> {
+    let doc = Document::load(input)
+        .map_err(|e| format!("Erreur chargement PDF : {}", e))?;
+    Ok(collecter_pieces_jointes(&doc).into_iter().map(|(piece, _)| piece).collect())
+}
+
+/// Extrait les fichiers joints dans `output_dir`. Renvoie les chemins écrits.
+pub fn pdf_extraire_pieces_jointes(input: &Path, output_dir: &str) -> Result<Vec<String>, String> {
+    let doc = Document::load(input)
+        .map_err(|e| format!("Erreur chargement PDF : {}", e))?;
+    std::fs::create_dir_all(output_dir)
+        .map_err(|e| format!("Erreur création dossier {} : {}", output_dir, e))?;
+
+    let mut ecrits = Vec::new();
+    for (piece, contenu) in collecter_pieces_jointes(&doc) {
+        // Nom de fichier seul : un nom incorporé ne doit pas pouvoir sortir du dossier
+        let nom = Path::new(&piece.nom.replace('\\', "/")).file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .filter(|n| !n.is_empty())
+            .unwrap_or_else(|| "attachment".into());
+        let (stem, ext) = match nom.rsplit_once('.') {
+            Some((s, e)) if !s.is_empty() => (s.to_string(), format!(".{}", e)),
+            _ => (nom.clone(), String::new()),
+        };
+        let mut chemin = Path::new(output_dir).join(&nom);
+        let mut k = 2;
+        while chemin.exists() || ecrits.contains(&chemin.to_string_lossy().to_string()) {
+            chemin = Path::new(output_dir).join(format!("{} ({}){}", stem, k, ext));
+            k += 1;
+        }
+        std::fs::write(&chemin, &contenu)
+            .map_err(|e| format!("Erreur écriture {:?} : {}", chemin, e))?;
+        ecrits.push(chemin.to_string_lossy().to_string());
+    }
+    crate::log_info(&format!("pdf_extraire_pieces_jointes | {:?} | {} fichier(s)", input, ecrits.len()));
+    Ok(ecrits)
+}
+
+/// Joint des fichiers au document (arbre /EmbeddedFiles). Un fichier de même nom est remplacé.
+/// `fichiers` : (chemin, description)
+pub fn pdf_ajouter_pieces_jointes(input: &Path, output: &str, fichiers: &[(std::path::PathBuf, String)]) -> Result<(), String> {
+    let mut doc = Document::load(input)
+        .map_err(|e| format!("Erreur chargement PDF : {}", e))?;
+    let mut entrees = lire_fichiers_incorpores(&doc);
+    let mut fichiers_associes: Vec<Object> = doc.catalog().ok()
+        .and_then(|c| c.get(b"AF").ok())
+        .and_then(|af| match resoudre(&doc, af) {
+            Object::Array(arr) => Some(arr.clone()),
+            _ => None,
+        })
+        .unwrap_or_default();
+
+    for (chemin, description) in fichiers {
+        let contenu = std::fs::read(chemin)
+            .map_err(|e| format!("Erreur lecture {:?} : {}", chemin, e))?;
+        let nom = chemin.file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .ok_or_else(|| format!("Nom de fichier invalide : {:?}", chemin))?;
+        let date_modif = std::fs::metadata(chemin).and_then(|m| m.modified()).ok()
+            .map(chrono::DateTime::<chrono::Utc>::from)
+            .unwrap_or_else(chrono::Utc::now)
+            .format("D:%Y%m%d%H%M%S+00'00'")
+            .to_string();
+
+        let mut flux = Stream::new(dictionary! {
+            "Type" => "EmbeddedFile",
+            "Subtype" => mime_depuis_extension(chemin),
+            "Params" => dictionary! {
+                "Size" => contenu.len() as i64,
+                "ModDate" => Object::string_literal(date_modif),
+            },
+        }, contenu);
+        let _ = flux.compress();
+        let flux_id = doc.add_object(flux);
+
+        let nom_ascii: String = nom.chars().map(|c| if c.is_ascii() { c } else { '_' }).collect();
+        let est_facture = NOMS_FACTURE_XML.iter().any(|n| n.eq_ignore_ascii_case(&nom));
+        let mut spec = dictionary! {
+            "Type" => "Filespec",
+            "F" => Object::string_literal(nom_ascii),
+            "UF" => chaine_texte_pdf(&nom),
+            "EF" => dictionary! { "F" => flux_id, "UF" => flux_id },
+            "AFRelationship" => if est_facture { "Alternative" } else { "Unspecified" },
+        };
+        if !description.is_empty() {
+            spec.set("Desc", chaine_texte_pdf(description));
+        }
+        let spec_id = doc.add_object(spec);
+
+        let cle = chaine_texte_pdf(&nom).as_str().map(|s| s.to_vec()).unwrap_or_default();
+        let remplaces: Vec<Object> = entrees.iter().filter(|(c, _)| *c == cle).map(|(_, v)| v.clone()).collect();
+        entrees.retain(|(c, _)| *c != cle);
+        fichiers_associes.retain(|af| !remplaces.contains(af));
+        entrees.push((cle, Object::Reference(spec_id)));
+        fichiers_associes.push(Object::Reference(spec_id));
+    }
+
+    ecrire_fichiers_incorpores(&mut doc, entrees)?;
+    if !fichiers_associes.is_empty() {
+        doc.catalog_mut().map_err(|e| format!("Catalogue introuvable : {}", e))?
+            .set("AF", fichiers_associes);
+    }
+    doc.prune_objects();
+    crate::log_info(&format!("pdf_ajouter_pieces_jointes | {:?} | {} fichier(s)", input, fichiers.len()));
+    sauvegarder(&mut doc, output)
+}
+
+/// Retire des fichiers joints (arbre du document et annotations FileAttachment).
+/// `noms` vide = tous. Renvoie le nombre de pièces jointes retirées.
+pub fn pdf_supprimer_pieces_jointes(input: &Path, output: &str, noms: &[String]) -> Result<usize, String> {
+    let mut doc = Document::load(input)
+        .map_err(|e| format!("Erreur chargement PDF : {}", e))?;
+    let cible = |nom: Option<String>| noms.is_empty() || nom.is_some_and(|n| noms.contains(&n));
+
+    let (retirees, gardees): (Vec<_>, Vec<_>) = lire_fichiers_incorpores(&doc).into_iter()
+        .partition(|(cle, spec)| {
+            let nom = resoudre_dict(&doc, spec).and_then(|d| nom_filespec(&doc, d));
+            cible(nom.or_else(|| Some(decoder_chaine_pdf(cle))))
+        });
+    let mut total = retirees.len();
+    ecrire_fichiers_incorpores(&mut doc, gardees)?;
+
+    let specs_retirees: Vec<Object> = retirees.into_iter().map(|(_, spec)| spec).collect();
+    if let Ok(catalog) = doc.catalog_mut()
+        && let Ok(Object::Array(af)) = catalog.get_mut(b"AF")
+    {
+        af.retain(|spec| !specs_retirees.contains(spec));
+        if af.is_empty() {
+            catalog.remove(b"AF");
+        }
+    }
+
+    total += filtrer_annotations(&mut doc, None, |doc, _, _, dict| {
+        if dict.get(b"Subtype").and_then(|s| s.as_name()).ok() != Some(b"FileAttachment") { return true; }
+        let nom = dict.get(b"FS").ok().and_then(|fs| resoudre_dict(doc, fs)).and_then(|fs| nom_filespec(doc, fs));
+        !cible(nom)
+    });
+
+    doc.prune_objects();
+    crate::log_info(&format!("pdf_supprimer_pieces_jointes | {:?} | {} retirée(s)", input, total));
+    sauvegarder(&mut doc, output)?;
+    Ok(total)
+}
+
+// ════════════════════════════════════════════════════════════════════════
+//  PDF EXTRACTION D'IMAGES — XObjects image des pages (et des formulaires imbriqués)
+// ════════════════════════════════════════════════════════════════════════
+
+/// Espace colorimétrique d'une image PDF, réduit à ce que l'extraction sait convertir
+enum EspaceImage {
+    Gris,
+    Rgb,
+    Cmjn,
+    /// Teintes de N encres (Separation = 1, DeviceN = nombre de noms) : 1 = pleine encre
+    Teintes(usize),
+    Indexe { base: Box<EspaceImage>, palette: Vec<u8> },
+}
+
+impl EspaceImage {
+    fn composantes(&self) -> usize {
+        match self {
+            EspaceImage::Gris | EspaceImage::Indexe { .. } => 1,
+            EspaceImage::Teintes(n) => *n,
+            EspaceImage::Rgb => 3,
+            EspaceImage::Cmjn => 4,
+        }
+    }
+
+    /// Convertit des composantes 8 bits en RGB
+    fn vers_rgb(&self, c: &[u8]) -> [u8; 3] {
+        match self {
+            EspaceImage::Gris => [c[0]; 3],
+            // Approximation : la plus forte des encres donne la densité
+            EspaceImage::Teintes(_) => [255 - c.iter().copied().max().unwrap_or(0); 3],
+            EspaceImage::Rgb => [c[0], c[1], c[2]],
+            EspaceImage::Cmjn => {
+                let k = 255 - c[3] as u32;
+                let canal = |v: u8| ((255 - v as u32) * k / 255) as u8;
+                [canal(c[0]), canal(c[1]), canal(c[2])]
+            }
+            EspaceImage::Indexe { base, palette } => {
+                let n = base.composantes();
+                let debut = c[0] as usize * n;
+                match palette.get(debut..debut + n) {
+                    Some(entree) => base.vers_rgb(entree),
+                    None => [0, 0, 0],
+                }
+            }
+        }
+    }
+}
+
+/// Interprète l'entrée /ColorSpace d'une image
+fn espace_image(doc: &Document, cs: Option<&Object>) -> Result<EspaceImage, String> {
+    let Some(cs) = cs.map(|c| resoudre(doc, c)) else { return Ok(EspaceImage::Gris) };
+    let (nom, args): (&[u8], &[Object]) = match cs {
+        Object::Name(n) => (n, &[]),
+        Object::Array(arr) if !arr.is_empty() => match resoudre(doc, &arr[0]) {
+            Object::Name(n) => (n, &arr[1..]),
+            _ => return Err("espace colorimétrique invalide".into()),
+        },
+        _ => return Err("espace colorimétrique invalide".into()),
+    };
+    match nom {
+        b"DeviceGray" | b"CalGray" | b"G" => Ok(EspaceImage::Gris),
+        b"DeviceRGB" | b"CalRGB" | b"RGB" => Ok(EspaceImage::Rgb),
+        b"DeviceCMYK" | b"CMYK" => Ok(EspaceImage::Cmjn),
+        b"Separation" => Ok(EspaceImage::Teintes(1)),
+        b"DeviceN" => match args.first().map(|noms| resoudre(doc, noms)) {
+            Some(Object::Array(noms)) if !noms.is_empty() => Ok(EspaceImage::Teintes(noms.len())),
+            _ => Err("DeviceN sans noms d'encres".into()),
+        },
+        b"ICCBased" => {
+            let n = args.first()
+                .and_then(|p| match resoudre(doc, p) {
+                    Object::Stream(s) => s.dict.get(b"N").and_then(|n| n.as_i64()).ok(),
+                    _ => None,
+                })
+                .unwrap_or(3);
+            Ok(match n {
+                1 => EspaceImage::Gris,
+                4 => EspaceImage::Cmjn,
+                _ => EspaceImage::Rgb,
+            })
+        }
+        b"Indexed" | b"I" => {
+            let base = espace_image(doc, args.first())?;
+            let palette = match args.get(2).map(|p| resoudre(doc, p)) {
+                Some(Object::String(octets, _)) => octets.clone(),
+                Some(Object::Stream(s)) => s.get_plain_content()
+                    .map_err(|e| format!("palette illisible : {}", e))?,
+                _ => return Err("palette absente".into()),
+            };
+            Ok(EspaceImage::Indexe { base: Box::new(base), palette })
+        }
+        autre => Err(format!("espace colorimétrique non pris en charge : {}", String::from_utf8_lossy(autre))),
+    }
+}
+
+/// Décompose des lignes d'échantillons de `bpc` bits (lignes alignées sur l'octet) en valeurs brutes
+fn lire_echantillons(donnees: &[u8], largeur: usize, hauteur: usize, composantes: usize, bpc: usize) -> Result<Vec<u8>, String> {
+    if !matches!(bpc, 1 | 2 | 4 | 8 | 16) {
+        return Err(format!("{} bits par composante non pris en charge", bpc));
+    }
+    let par_ligne = (largeur * composantes * bpc).div_ceil(8);
+    if donnees.len() < par_ligne * hauteur {
+        return Err(format!("données image tronquées ({} / {} octets)", donnees.len(), par_ligne * hauteur));
+    }
+    let mut valeurs = Vec::with_capacity(largeur * hauteur * composantes);
+    for ligne in donnees.chunks(par_ligne).take(hauteur) {
+        match bpc {
+            8 => valeurs.extend_from_slice(&ligne[..largeur * composantes]),
+            16 => valeurs.extend(ligne.chunks(2).take(largeur * composantes).map(|c| c[0])),
+            1 | 2 | 4 => {
+                let masque = (1u8 << bpc) - 1;
+                for i in 0..largeur * composantes {
+                    let bit = i * bpc;
+                    valeurs.push((ligne[bit / 8] >> (8 - bpc - bit % 8)) & masque);
+                }
+            }
+            _ => return Err(format!("{} bits par composante non pris en charge", bpc)),
+        }
+    }
+    Ok(valeurs)
+}
+
+/// Côté maximal accepté pour une image PDF (pixels) : au-delà, dictionnaire jugé corrompu
+const COTE_MAX_IMAGE_PDF: i64 = 65_535;
+
+/// Décode un XObject image en pixels RGB(A). Les masques doux (/SMask) deviennent le canal alpha.
+fn decoder_image_pdf(doc: &Document, flux: &Stream) -> Result<image::DynamicImage, String> {
+    let dict = &flux.dict;
+    let cote = |cle: &[u8]| -> Result<usize, String> {
+        let nom = String::from_utf8_lossy(cle);
+        let valeur = dict.get(cle).and_then(|v| v.as_i64()).map_err(|_| format!("{} absent", nom))?;
+        if !(1..=COTE_MAX_IMAGE_PDF).contains(&valeur) {
+            return Err(format!("{} invalide : {}", nom, valeur));
+        }
+        Ok(valeur as usize)
+    };
+    let largeur = cote(b"Width")?;
+    let hauteur = cote(b"Height")?;
+    let filtres = flux.filters().unwrap_or_default();
+
+    let inverse = match dict.get(b"Decode").map(|d| resoudre(doc, d)) {
+        Ok(Object::Array(d)) => d.first().and_then(|v| v.as_float().ok()).unwrap_or(0.0) > 0.5,
+        _ => false,
+    };
+
+    let mut img = if filtres.last() == Some(&b"DCTDecode".as_slice()) {
+        if filtres.len() > 1 {
+            return Err("DCTDecode combiné à d'autres filtres non pris en charge".into());
+        }
+        // Le décodeur JPEG rend déjà du RGB/gris (CMJN converti) ; reste /Decode [1 0 …]
+        let mut img = image::load_from_memory_with_format(&flux.content, image::ImageFormat::Jpeg)
+            .map_err(|e| format!("JPEG illisible : {}", e))?;
+        if inverse {
+            img.invert();
+        }
+        img
+    } else {
+        if let Some(f) = filtres.iter().find(|f| !matches!(**f, b"FlateDecode" | b"LZWDecode" | b"ASCII85Decode")) {
+            return Err(format!("filtre {} non pris en charge", String::from_utf8_lossy(f)));
+        }
+        let donnees = flux.get_plain_content().map_err(|e| format!("décompression : {}", e))?;
+        let masque = dict.get(b"ImageMask").and_then(|m| m.as_bool()).unwrap_or(false);
+        let espace = if masque { EspaceImage::Gris } else { espace_image(doc, dict.get(b"ColorSpace").ok())? };
+        let bpc = if masque { 1 } else { dict.get(b"BitsPerComponent").and_then(|b| b.as_i64()).unwrap_or(8) as usize };
+        let n = espace.composantes();
+        let mut valeurs = lire_echantillons(&donnees, largeur, hauteur, n, bpc)?;
+
+        // Mise à l'échelle 0-255 (sauf indices de palette) puis /Decode [1 0] = inversion
+        if !matches!(espace, EspaceImage::Indexe { .. }) && bpc < 8 {
+            let max = (1u32 << bpc) - 1;
+            valeurs.iter_mut().for_each(|v| *v = (*v as u32 * 255 / max) as u8);
+        }
+        if inverse && !matches!(espace, EspaceImage::Indexe { .. }) {
+            valeurs.iter_mut().for_each(|v| *v = 255 - *v);
+        }
+
+        let pixels: Vec<u8> = valeurs.chunks(n).flat_map(|c| espace.vers_rgb(c)).collect();
+        let rgb = image::RgbImage::from_raw(largeur as u32, hauteur as u32, pixels)
+            .ok_or("dimensions incohérentes")?;
+        image::DynamicImage::ImageRgb8(rgb)
+    };
+
+    if let Ok(smask) = dict.get(b"SMask")
+        && let Object::Stream(masque) = resoudre(doc, smask)
+        && let Ok(alpha) = decoder_image_pdf(doc, masque)
+    {
+        let alpha = alpha.resize_exact(img.width(), img.height(), image::imageops::FilterType::Triangle).to_luma8();
+        let mut rgba = img.to_rgba8();
+        for (p, a) in rgba.pixels_mut().zip(alpha.pixels()) {
+            p[3] = a[0];
+        }
+        img = image::DynamicImage::ImageRgba8(rgba);
+    }
+    Ok(img)
+}
+
+/// Collecte les XObjects image d'un dictionnaire de ressources, en descendant dans les formulaires
+fn collecter_images_ressources(doc: &Document, ressources: &Object, vus: &mut Vec<ObjectId>, images: &mut Vec<ObjectId>) {
+    let Some(xobjects) = resoudre_dict(doc, ressources)
+        .and_then(|r| r.get(b"XObject").ok())
+        .and_then(|x| resoudre_dict(doc, x))
+    else { return };
+    for (_, valeur) in xobjects.iter() {
+        let Ok(id) = valeur.as_reference() else { continue };
+        if vus.contains(&id) { continue; }
+        vus.push(id);
+        let Ok(Object::Stream(flux)) = doc.get_object(id) else { continue };
+        match flux.dict.get(b"Subtype").and_then(|s| s.as_name()) {
+            Ok(b"Image") => images.push(id),
+            Ok(b"Form") => {
+                if let Ok(sous) = flux.dict.get(b"Resources") {
+                    collecter_images_ressources(doc, sous, vus, images);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// JPEG intégré utilisable tel quel : DCTDecode seul, en RGB ou gris, sans /Decode ni masque.
+/// Les autres (CMJN, palettes, encres, inversés) sont décodés puis réencodés.
+fn jpeg_transmissible(doc: &Document, flux: &Stream) -> bool {
+    let dict = &flux.dict;
+    let espace = dict.get(b"ColorSpace").map(|c| resoudre(doc, c));
+    flux.filters().map(|f| f == [b"DCTDecode".as_slice()]).unwrap_or(false)
+        && !dict.has(b"SMask")
+        && !dict.has(b"Decode")
+        && matches!(espace, Ok(Object::Name(n)) if n == b"DeviceRGB" || n == b"DeviceGray")
+}
+
+fn pdf_extraire_images_interne(
+    input: &Path, output_dir: &str, stem: &str,
+    format: &str, taille_min: u32,
+    pages_cibles: Option<&[u32]>,
+) -> Result<Vec<String>, String> {
+    let doc = Document::load(input)
+        .map_err(|e| format!("Erreur chargement PDF : {}", e))?;
+    std::fs::create_dir_all(output_dir)
+        .map_err(|e| format!("Erreur création dossier {} : {}", output_dir, e))?;
+    let format = format.trim().trim_start_matches('.').to_lowercase();
+
+    let mut vus = Vec::new();
+    let mut ecrits = Vec::new();
+    for (i, &page_id) in obtenir_pages_ordonnees(&doc).iter().enumerate() {
+        let page_num = (i + 1) as u32;
+        if !pages_cibles.is_none_or(|c| c.contains(&page_num)) { continue; }
+        let Some(ressources) = attribut_herite(&doc, page_id, b"Resources") else { continue };
+        let mut images = Vec::new();
+        collecter_images_ressources(&doc, &ressources, &mut vus, &mut images);
+
+        for (k, id) in images.into_iter().enumerate() {
+            let Ok(Object::Stream(flux)) = doc.get_object(id) else { continue };
+            let dimension = |cle: &[u8]| flux.dict.get(cle).and_then(|v| v.as_i64()).unwrap_or(0);
+            if dimension(b"Width") < taille_min as i64 || dimension(b"Height") < taille_min as i64 { continue; }
+
+            let base = format!("{}/{}_p{:03}_{}", output_dir, stem, page_num, k + 1);
+            if jpeg_transmissible(&doc, flux) && (format.is_empty() || format == "auto" || format == "jpg" || format == "jpeg") {
+                let chemin = format!("{}.jpg", base);
+                std::fs::write(&chemin, &flux.content)
+                    .map_err(|e| format!("Erreur écriture {} : {}", chemin, e))?;
+                ecrits.push(chemin);
+                continue;
+            }
+            let img = match decoder_image_pdf(&doc, flux) {
+                Ok(img) => img,
+                Err(e) => {
+                    crate::log_warn(&format!("pdf_extraire_images | page {} image {:?} ignorée : {}", page_num, id, e));
+                    continue;
+                }
+            };
+            let ext = if format.is_empty() || format == "auto" { "png" } else { format.as_str() };
+            let chemin = format!("{}.{}", base, ext);
+            if crate::modules::pic::sauvegarder_image(&img, &chemin, 9) {
+                ecrits.push(chemin);
+            } else {
+                return Err(format!("Encodage {} échoué", chemin));
+            }
+        }
+    }
+    crate::log_info(&format!("pdf_extraire_images | {:?} | {} image(s) → {}", input, ecrits.len(), output_dir));
+    Ok(ecrits)
+}
+
+/// Extrait les images des pages dans `output_dir`, nommées `{nom}_p{page}_{n}`.
+/// `format` : "auto" (JPEG RGB / gris conservés tels quels, PNG sinon) ou une extension (png, jpg, webp, tiff…).
+/// Les images dont un côté est inférieur à `taille_min` pixels sont ignorées.
+pub fn pdf_extraire_images(
+    input: &Path, output_dir: &str,
+    format: &str, taille_min: u32,
+    pages_cibles: Option<&[u32]>,
+) -> Result<Vec<String>, String> {
+    let stem = input.file_stem().unwrap_or_default().to_string_lossy().to_string();
+    if est_pdf(input) {
+        return pdf_extraire_images_interne(input, output_dir, &stem, format, taille_min, pages_cibles);
+    }
+    let pdf_temp = vers_pdf_temp(input)?;
+    let result = pdf_extraire_images_interne(Path::new(&pdf_temp), output_dir, &stem, format, taille_min, pages_cibles);
+    nettoyer_temp(&pdf_temp);
+    result
+}
+
+// ════════════════════════════════════════════════════════════════════════
+//  PDF COMPARAISON — diff texte page à page + PDF surligné
+// ════════════════════════════════════════════════════════════════════════
+
+/// Matrice de transformation PDF [a b c d e f]
+type Matrice = [f64; 6];
+
+const MATRICE_IDENTITE: Matrice = [1.0, 0.0, 0.0, 1.0, 0.0, 0.0];
+
+/// m1 × m2 (m1 appliquée en premier)
+fn produit_matrices(m1: &Matrice, m2: &Matrice) -> Matrice {
+    [
+        m1[0] * m2[0] + m1[1] * m2[2],
+        m1[0] * m2[1] + m1[1] * m2[3],
+        m1[2] * m2[0] + m1[3] * m2[2],
+        m1[2] * m2[1] + m1[3] * m2[3],
+        m1[4] * m2[0] + m1[5] * m2[2] + m2[4],
+        m1[4] * m2[1] + m1[5] * m2[3] + m2[5],
+    ]
+}
+
+fn translation(tx: f64, ty: f64) -> Matrice {
+    [1.0, 0.0, 0.0, 1.0, tx, ty]
+}
+
+/// Police d'une page, réduite à ce qu'il faut pour décoder et mesurer le texte
+struct PoliceTexte<'a> {
+    encodage: Option<lopdf::Encoding<'a>>,
+    /// Police composite (Type0) : codes sur deux octets
+    composite: bool,
+    premier_code: u32,
+    /// /Widths d'une police simple (1/1000 em)
+    largeurs: Vec<f64>,
+    /// /W d'une police CID : code → largeur
+    largeurs_cid: BTreeMap<u32, f64>,
+    largeur_defaut: f64,
+    /// Police standard sans /Widths : métriques Helvetica
+    helvetica: bool,
+}
+
+impl<'a> PoliceTexte<'a> {
+    fn depuis_dict(doc: &'a Document, dict: &'a lopdf::Dictionary) -> Self {
+        let nombre = |o: &Object| resoudre(doc, o).as_float().ok().map(|f| f as f64);
+        let encodage = dict.get_font_encoding(doc).ok();
+        let composite = dict.get(b"Subtype").and_then(|s| s.as_name()).ok() == Some(b"Type0".as_slice());
+        let mut police = PoliceTexte {
+            encodage, composite,
+            premier_code: 0,
+            largeurs: Vec::new(),
+            largeurs_cid: BTreeMap::new(),
+            largeur_defaut: 500.0,
+            helvetica: false,
+        };
+
+        if composite {
+            let descendante = dict.get(b"DescendantFonts").ok()
+                .and_then(|d| resoudre(doc, d).as_array().ok())
+                .and_then(|a| a.first())
+                .and_then(|d| resoudre_dict(doc, d));
+            if let Some(cid) = descendante {
+                police.largeur_defaut = cid.get(b"DW").ok().and_then(nombre).unwrap_or(1000.0);
+                let w = cid.get(b"W").ok().and_then(|w| resoudre(doc, w).as_array().ok());
+                // /W : [c [w1 w2 …]] ou [c_debut c_fin w]
+                let elements: Vec<&Object> = w.map(|w| w.iter().map(|o| resoudre(doc, o)).collect()).unwrap_or_default();
+                let mut i = 0;
+                while i + 1 < elements.len() {
+                    let Some(debut) = nombre(elements[i]) else { break };
+                    if let Ok(liste) = elements[i + 1].as_array() {
+                        for (k, l) in liste.iter().enumerate() {
+                            if let Some(l) = nombre(l) {
+                                police.largeurs_cid.insert(debut as u32 + k as u32, l);
+                            }
+                        }
+                        i += 2;
+                    } else if i + 2 < elements.len() {
+                        let (fin, l) = (nombre(elements[i + 1]).unwrap_or(debut), nombre(elements[i + 2]).unwrap_or(0.0));
+                        for code in debut as u32..=fin as u32 {
+                            police.largeurs_cid.insert(code, l);
+                        }
+                        i += 3;
+                    } else {
+                        break;
+                    }
+                }
+            }
+        } else {
+            police.premier_code = dict.get(b"FirstChar").ok().and_then(nombre).unwrap_or(0.0) as u32;
+            police.largeurs = dict.get(b"Widths").ok()
+                .and_then(|w| resoudre(doc, w).as_array().ok())
+                .map(|w| w.iter().map(|l| nombre(l).unwrap_or(0.0)).collect())
+                .unwrap_or_default();
+            police.largeur_defaut = dict.get(b"FontDescriptor").ok()
+                .and_then(|d| resoudre_dict(doc, d))
+                .and_then(|d| d.get(b"MissingWidth").ok().and_then(nombre))
+                .filter(|&l| l > 0.0)
+                .unwrap_or(500.0);
+            police.helvetica = police.largeurs.is_empty();
+        }
+        police
+    }
+
+    fn codes(&self, octets: &[u8]) -> Vec<u32> {
+        if self.composite {
+            octets.chunks(2).map(|c| c.iter().fold(0u32, |acc, &o| (acc << 8) | o as u32)).collect()
+        } else {
+            octets.iter().map(|&o| o as u32).collect()
+        }
+    }
+
+    /// Largeur d'un code (1/1000 em)
+    fn largeur(&self, code: u32) -> f64 {
+        if self.composite {
+            return self.largeurs_cid.get(&code).copied().unwrap_or(self.largeur_defaut);
+        }
+        if self.helvetica {
+            return largeur_texte_helvetica(&[code as u8], 1000.0);
+        }
+        code.checked_sub(self.premier_code)
+            .and_then(|i| self.largeurs.get(i as usize).copied())
+            .unwrap_or(self.largeur_defaut)
+    }
+
+    fn decoder(&self, octets: &[u8]) -> String {
+        match self.encodage.as_ref().map(|e| e.bytes_to_string(octets)) {
+            Some(Ok(texte)) => texte,
+            _ if self.composite => String::new(),
+            _ => octets.iter().filter_map(|&o| winansi_vers_unicode(o)).collect(),
+        }
+    }
+}
+
+/// Morceau de texte positionné (coordonnées utilisateur de la page)
+struct FragmentTexte {
+    texte: String,
+    x0: f64,
+    x1: f64,
+    y: f64,
+    taille: f64,
+}
+
+/// Ligne de texte reconstituée, avec sa boîte englobante
+#[derive(Debug, Clone)]
+struct LigneTexte {
+    texte: String,
+    boite: [f64; 4],
+}
+
+/// Extrait les fragments de texte d'une page en suivant l'état graphique et l'état texte
+fn extraire_fragments_page(doc: &Document, page_id: ObjectId) -> Vec<FragmentTexte> {
+    let polices: BTreeMap<Vec<u8>, PoliceTexte> = doc.get_page_fonts(page_id).unwrap_or_default()
+        .into_iter()
+        .map(|(nom, dict)| (nom, PoliceTexte::depuis_dict(doc, dict)))
+        .collect();
+    let Some(contenu) = doc.get_page_content(page_id).ok().and_then(|c| Content::decode(&c).ok()) else {
+        return Vec::new();
+    };
+    let nombre = |o: &Object| o.as_float().map(|f| f as f64).unwrap_or(0.0);
+    let matrice = |args: &[Object]| -> Matrice {
+        let mut m = MATRICE_IDENTITE;
+        for (v, a) in m.iter_mut().zip(args) { *v = nombre(a); }
+        m
+    };
+
+    let mut fragments = Vec::new();
+    let mut pile = Vec::new();
+    let mut ctm = MATRICE_IDENTITE;
+    let (mut tm, mut tlm) = (MATRICE_IDENTITE, MATRICE_IDENTITE);
+    let mut police: Option<&PoliceTexte> = None;
+    let (mut taille, mut tc, mut tw, mut th, mut tl) = (0.0, 0.0, 0.0, 1.0, 0.0);
+
+    for op in &contenu.operations {
+        let args = op.operands.as_slice();
+        let elements: Vec<&Object> = match op.operator.as_str() {
+            "q" => { pile.push(ctm); continue; }
+            "Q" => { if let Some(m) = pile.pop() { ctm = m; } continue; }
+            "cm" if args.len() == 6 => { ctm = produit_matrices(&matrice(args), &ctm); continue; }
+            "BT" => { tm = MATRICE_IDENTITE; tlm = MATRICE_IDENTITE; continue; }
+            "Tf" if args.len() == 2 => {
+                police = args[0].as_name().ok().and_then(|n| polices.get(n));
+                taille = nombre(&args[1]);
+                continue;
+            }
+            "Tc" if args.len() == 1 => { tc = nombre(&args[0]); continue; }
+            "Tw" if args.len() == 1 => { tw = nombre(&args[0]); continue; }
+            "Tz" if args.len() == 1 => { th = nombre(&args[0]) / 100.0; continue; }
+            "TL" if args.len() == 1 => { tl = nombre(&args[0]); continue; }
+            "Td" | "TD" if args.len() == 2 => {
+                if op.operator == "TD" { tl = -nombre(&args[1]); }
+                tlm = produit_matrices(&translation(nombre(&args[0]), nombre(&args[1])), &tlm);
+                tm = tlm;
+                continue;
+            }
+            "Tm" if args.len() == 6 => { tlm = matrice(args); tm = tlm; continue; }
+            "T*" => { tlm = produit_matrices(&translation(0.0, -tl), &tlm); tm = tlm; continue; }
+            "Tj" if args.len() == 1 => vec![&args[0]],
+            "TJ" if args.len() == 1 => args[0].as_array().map(|a| a.iter().collect()).unwrap_or_default(),
+            "'" | "\"" if !args.is_empty() => {
+                if op.operator == "\"" && args.len() == 3 {
+                    tw = nombre(&args[0]);
+                    tc = nombre(&args[1]);
+                }
+                tlm = produit_matrices(&translation(0.0, -tl), &tlm);
+                tm = tlm;
+                vec![&args[args.len() - 1]]
+            }
+            _ => continue,
+        };
+        let Some(p) = police else { continue };
+
+        let trm = produit_matrices(&tm, &ctm);
+        let (x0, y) = (trm[4], trm[5]);
+        let taille_visuelle = taille * (trm[2] * trm[2] + trm[3] * trm[3]).sqrt();
+        let mut texte = String::new();
+        for element in elements {
+            match element {
+                Object::String(octets, _) => {
+                    texte.push_str(&p.decoder(octets));
+                    for code in p.codes(octets) {
+                        let espace = if !p.composite && code == 32 { tw } else { 0.0 };
+                        let tx = (p.largeur(code) / 1000.0 * taille + tc + espace) * th;
+                        tm = produit_matrices(&translation(tx, 0.0), &tm);
+                    }
+                }
+                ajustement => {
+                    let k = nombre(ajustement);
+                    // Un fort crénage négatif sert souvent d'espace entre mots
+                    if k < -200.0 && !texte.ends_with(' ') { texte.push(' '); }
+                    tm = produit_matrices(&translation(-k / 1000.0 * taille * th, 0.0), &tm);
+                }
+            }
+        }
+        let x1 = produit_matrices(&tm, &ctm)[4];
+        if !texte.trim().is_empty() {
+            fragments.push(FragmentTexte { texte, x0: x0.min(x1), x1: x0.max(x1), y, taille: taille_visuelle.max(1.0) });
+        }
+    }
+    fragments
+}
+
+/// Regroupe les fragments par ligne de base (haut de page en premier), de gauche à droite
+fn regrouper_lignes(mut fragments: Vec<FragmentTexte>) -> Vec<LigneTexte> {
+    fragments.sort_by(|a, b| b.y.total_cmp(&a.y));
+    let mut groupes: Vec<Vec<FragmentTexte>> = Vec::new();
+    for fragment in fragments {
+        match groupes.last_mut() {
+            Some(groupe) if (groupe[0].y - fragment.y).abs() <= 0.3 * groupe[0].taille.max(fragment.taille) => {
+                groupe.push(fragment)
+            }
+            _ => groupes.push(vec![fragment]),
+        }
+    }
+
+    groupes.into_iter().filter_map(|mut groupe| {
+        groupe.sort_by(|a, b| a.x0.total_cmp(&b.x0));
+        let mut texte = String::new();
+        let mut fin_precedente: Option<f64> = None;
+        let (mut y0, mut y1) = (f64::MAX, f64::MIN);
+        for f in &groupe {
+            if fin_precedente.is_some_and(|fin| f.x0 - fin > 0.15 * f.taille) {
+                texte.push(' ');
+            }
+            texte.push_str(&f.texte);
+            fin_precedente = Some(fin_precedente.map_or(f.x1, |fin| fin.max(f.x1)));
+            y0 = y0.min(f.y - 0.25 * f.taille);
+            y1 = y1.max(f.y + 0.85 * f.taille);
+        }
+        let texte = texte.split_whitespace().collect::<Vec<_>>().join(" ");
+        if texte.is_empty() { return None; }
+        let x0 = groupe.iter().map(|f| f.x0).fold(f64::MAX, f64::min);
+        let x1 = groupe.iter().map(|f| f.x1).fold(f64::MIN, f64::max);
+        Some(LigneTexte { texte, boite: [x0, y0, x1, y1] })
+    }).collect()
+}
+
+fn extraire_lignes_page(doc: &Document, page_id: ObjectId) -> Vec<LigneTexte> {
+    regrouper_lignes(extraire_fragments_page(doc, page_id))
+}
+
+/// Édition élémentaire d'un diff de lignes (indices dans a / b)
+enum EditionLigne {
+    Egal(usize, usize),
+    Suppression(usize),
+    Ajout(usize),
+}
+
+/// Diff minimal par plus longue sous-suite commune
+fn diff_lignes(a: &[&str], b: &[&str]) -> Vec<EditionLigne> {
+    let (n, m) = (a.len(), b.len());
+    let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if a[i] == b[j] { lcs[i + 1][j + 1] + 1 } else { lcs[i + 1][j].max(lcs[i][j + 1]) };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    let mut editions = Vec::new();
+    while i < n || j < m {
+        if i < n && j < m && a[i] == b[j] {
+            editions.push(EditionLigne::Egal(i, j));
+            i += 1;
+            j += 1;
+        } else if j < m && (i == n || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            editions.push(EditionLigne::Ajout(j));
+            j += 1;
+        } else {
+            editions.push(EditionLigne::Suppression(i));
+            i += 1;
+        }
+    }
+    editions
+}
+
+/// Nature d'une différence de texte
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TypeDifference {
+    /// Ligne présente uniquement dans le nouveau document
+    Ajout,
+    /// Ligne présente uniquement dans l'ancien document
+    Suppression,
+}
+
+/// Ligne ajoutée ou supprimée ; `ligne` est numérotée dans le nouveau document
+/// pour un ajout, dans l'ancien pour une suppression. `page` est le numéro dans le
+/// nouveau document (dans l'ancien pour une page supprimée).
+#[derive(Debug, Clone)]
+pub struct DifferenceTexte {
+    pub page: u32,
+    pub ligne: usize,
+    pub type_difference: TypeDifference,
+    pub texte: String,
+}
+
+/// Résultat de la comparaison de deux PDF
+#[derive(Debug, Clone, Default)]
+pub struct RapportComparaison {
+    pub pages_ancien: u32,
+    pub pages_nouveau: u32,
+    pub differences: Vec<DifferenceTexte>,
+}
+
+impl RapportComparaison {
+    pub fn est_identique(&self) -> bool {
+        self.pages_ancien == self.pages_nouveau && self.differences.is_empty()
+    }
+
+    /// Rapport lisible, groupé par page : "- l.12 …" / "+ l.12 …"
+    pub fn en_texte(&self) -> String {
+        let ajouts = self.differences.iter().filter(|d| d.type_difference == TypeDifference::Ajout).count();
+        let mut texte = format!(
+            "Pages : {} → {}\nDifférences : {} ajout(s), {} suppression(s)\n",
+            self.pages_ancien, self.pages_nouveau, ajouts, self.differences.len() - ajouts,
+        );
+        let mut page_courante = 0;
+        for d in &self.differences {
+            if d.page != page_courante {
+                page_courante = d.page;
+                texte.push_str(&format!("\nPage {}\n", d.page));
+            }
+            let signe = if d.type_difference == TypeDifference::Ajout { '+' } else { '-' };
+            texte.push_str(&format!("  {} l.{:<4} {}\n", signe, d.ligne, d.texte));
+        }
+        texte
+    }
+}
+
+/// Charge un PDF, en passant par une conversion temporaire pour les autres formats
+fn charger_pdf_ou_convertir(input: &Path) -> Result<Document, String> {
+    if est_pdf(input) {
+        return Document::load(input).map_err(|e| format!("Erreur chargement PDF {} : {}", input.display(), e));
+    }
+    let pdf_temp = vers_pdf_temp(input)?;
+    let doc = Document::load(&pdf_temp).map_err(|e| format!("Erreur chargement PDF {} : {}", input.display(), e));
+    nettoyer_temp(&pdf_temp);
+    doc
+}
 
-        // Convertir pourcentages en points
-        let abs_x = mediabox[0] + (page_w * x / 100.0);
-        let abs_y = mediabox[1] + (page_h * y / 100.0);
-        let abs_w = page_w * largeur / 100.0;
-        let abs_h = page_h * hauteur / 100.0;
+/// Différences à marquer sur une page du nouveau document
+struct MarquesPage {
+    page_id: ObjectId,
+    lignes: Vec<LigneTexte>,
+    /// Indices des lignes ajoutées
+    ajouts: Vec<usize>,
+    /// (indice de la ligne qui suit, texte supprimé)
+    suppressions: Vec<(usize, String)>,
+}
 
-        let annot_dict = dictionary! {
+/// Surligne les ajouts (vert) et signale les suppressions (barre rouge en marge + note) sur une page
+fn marquer_differences_page(doc: &mut Document, marques: &MarquesPage, gs_id: ObjectId, font_id: ObjectId, date: &str) -> Result<(), String> {
+    let MarquesPage { page_id, lignes, ajouts, suppressions } = marques;
+    let page_id = *page_id;
+    let [mx0, my0, _, my1] = obtenir_mediabox(doc, page_id).unwrap_or([0.0, 0.0, 595.0, 842.0]);
+    let mut contenu = String::from("q /GScmp gs\n0.55 0.9 0.55 rg\n");
+    for &j in ajouts {
+        let [x0, y0, x1, y1] = lignes[j].boite;
+        contenu.push_str(&format!("{:.2} {:.2} {:.2} {:.2} re f\n", x0 - 1.0, y0, x1 - x0 + 2.0, y1 - y0));
+    }
+    contenu.push_str("0.85 0.1 0.1 rg\n");
+    let mut annots = lire_annots_page(doc, page_id);
+    for (ancre, texte) in suppressions {
+        // Position de la ligne du nouveau document qui suit la suppression (bas de page sinon)
+        let y = lignes.get(*ancre).map(|l| l.boite[3]).unwrap_or(my0 + 36.0).min(my1 - 4.0);
+        contenu.push_str(&format!("{:.2} {:.2} 3 10 re f\n", mx0 + 8.0, y - 8.0));
+
+        // Note rouge en marge, au format des notes de pdf_ajouter_annotations
+        let mut note = NouvelleAnnotation::new(TypeAnnotation::Note, &format!("Supprimé :\n{}", texte), [0.0; 4]);
+        note.couleur = [0.85, 0.1, 0.1];
+        let (x0, y0) = (mx0 + 14.0, y - 18.0);
+        let mut dict = dictionary! {
             "Type" => "Annot",
-            "Subtype" => "FreeText",
-            "Rect" => vec![
-                Object::Real(abs_x as f32),
-                Object::Real(abs_y as f32),
-                Object::Real((abs_x + abs_w) as f32),
-                Object::Real((abs_y + abs_h) as f32),
-            ],
-            "Contents" => Object::string_literal(texte.to_string()),
-            "DA" => Object::string_literal("/Helv 12 Tf 0 0 0 rg".to_string()),
-            "C" => vec![Object::Real(1.0), Object::Real(1.0), Object::Real(0.8)],
-            "Border" => vec![0.into(), 0.into(), 1.into()],
+            "Subtype" => note.type_annotation.sous_type(),
+            "Rect" => vec![x0.into(), y0.into(), (x0 + 20.0).into(), (y0 + 20.0).into()],
+            "P" => page_id,
+            "M" => Object::string_literal(date),
+            "CreationDate" => Object::string_literal(date),
+            "F" => 4,
+            "C" => vec![0.85.into(), 0.1.into(), 0.1.into()],
+            "Contents" => chaine_texte_pdf(&note.texte),
+            "Name" => "Comment",
+            "Open" => false,
         };
-        let annot_id = doc.add_object(annot_dict);
+        if let Some(ap_id) = apparence_annotation(doc, &note, 20.0, 20.0, font_id)? {
+            dict.set("AP", dictionary! { "N" => ap_id });
+        }
+        annots.push(Object::Reference(doc.add_object(dict)));
+    }
+    contenu.push_str("Q\n");
+    ajouter_overlay_ressources(doc, page_id, contenu.into_bytes(), &[("ExtGState", "GScmp", gs_id)])?;
+    ecrire_annots_page(doc, page_id, annots);
+    Ok(())
+}
 
-        if let Ok(Object::Dictionary(dict)) = doc.get_object_mut(page_id) {
-            let annots = match dict.get(b"Annots") {
-                Ok(Object::Array(arr)) => {
-                    let mut arr = arr.clone();
-                    arr.push(Object::Reference(annot_id));
-                    arr
+/// Premier identifiant du trailer (/ID), stable d'une révision à l'autre d'un même document
+fn identifiant_document(doc: &Document) -> Option<Vec<u8>> {
+    let id = doc.trailer.get(b"ID").ok().map(|i| resoudre(doc, i))?;
+    id.as_array().ok()?.first()?.as_str().ok().map(|octets| octets.to_vec())
+}
+
+/// Apparie les pages de l'ancien et du nouveau document (indices, `None` = page absente).
+/// Si le nouveau dérive de l'ancien (même /ID), les pages gardent leur ObjectId : l'appariement
+/// suit alors les insertions, suppressions et réordonnancements. Sinon, appariement par position.
+fn apparier_pages(doc_a: &Document, pages_a: &[ObjectId], doc_b: &Document, pages_b: &[ObjectId]) -> Vec<(Option<usize>, Option<usize>)> {
+    let meme_origine = identifiant_document(doc_a).is_some() && identifiant_document(doc_a) == identifiant_document(doc_b);
+    if !meme_origine || !pages_b.iter().any(|id| pages_a.contains(id)) {
+        return (0..pages_a.len().max(pages_b.len()))
+            .map(|i| ((i < pages_a.len()).then_some(i), (i < pages_b.len()).then_some(i)))
+            .collect();
+    }
+    let mut paires = Vec::new();
+    let mut prochaine_a = 0;
+    for (j, id) in pages_b.iter().enumerate() {
+        let i = pages_a.iter().position(|a| a == id);
+        // Pages de l'ancien disparues avant celle-ci : supprimées
+        if let Some(i) = i {
+            for (k, ancienne) in pages_a.iter().enumerate().take(i).skip(prochaine_a) {
+                if !pages_b.contains(ancienne) { paires.push((Some(k), None)); }
+            }
+            prochaine_a = prochaine_a.max(i + 1);
+        }
+        paires.push((i, Some(j)));
+    }
+    for (k, id) in pages_a.iter().enumerate().skip(prochaine_a) {
+        if !pages_b.contains(id) { paires.push((Some(k), None)); }
+    }
+    paires
+}
+
+/// Compare deux documents page à page (texte ligne par ligne).
+/// Si `sortie` est fourni, écrit une copie du nouveau document avec les ajouts surlignés
+/// et les suppressions signalées par une note dans la marge.
+pub fn pdf_comparer(ancien: &Path, nouveau: &Path, sortie: Option<&str>) -> Result<RapportComparaison, String> {
+    let doc_a = charger_pdf_ou_convertir(ancien)?;
+    let mut doc_b = charger_pdf_ou_convertir(nouveau)?;
+    let pages_a = obtenir_pages_ordonnees(&doc_a);
+    let pages_b = obtenir_pages_ordonnees(&doc_b);
+    crate::log_info(&format!("pdf_comparer | {:?} ({} p.) vs {:?} ({} p.)", ancien, pages_a.len(), nouveau, pages_b.len()));
+
+    let mut rapport = RapportComparaison {
+        pages_ancien: pages_a.len() as u32,
+        pages_nouveau: pages_b.len() as u32,
+        differences: Vec::new(),
+    };
+    let mut marques: Vec<MarquesPage> = Vec::new();
+
+    for (ia, ib) in apparier_pages(&doc_a, &pages_a, &doc_b, &pages_b) {
+        let page = ib.or(ia).map_or(0, |i| i as u32 + 1);
+        let lignes_a = ia.map(|i| extraire_lignes_page(&doc_a, pages_a[i])).unwrap_or_default();
+        let lignes_b = ib.map(|j| extraire_lignes_page(&doc_b, pages_b[j])).unwrap_or_default();
+        let textes_a: Vec<&str> = lignes_a.iter().map(|l| l.texte.as_str()).collect();
+        let textes_b: Vec<&str> = lignes_b.iter().map(|l| l.texte.as_str()).collect();
+
+        let mut ajouts = Vec::new();
+        let mut suppressions: Vec<(usize, String)> = Vec::new();
+        let mut prochaine_b = 0;
+        for edition in diff_lignes(&textes_a, &textes_b) {
+            let (type_difference, ligne, texte) = match edition {
+                EditionLigne::Egal(_, j) => { prochaine_b = j + 1; continue; }
+                EditionLigne::Ajout(j) => {
+                    prochaine_b = j + 1;
+                    ajouts.push(j);
+                    (TypeDifference::Ajout, j, textes_b[j])
                 }
-                Ok(Object::Reference(ref_id)) => {
-                    vec![Object::Reference(*ref_id), Object::Reference(annot_id)]
+                EditionLigne::Suppression(k) => {
+                    // Suppressions consécutives regroupées sur la même ancre
+                    match suppressions.last_mut() {
+                        Some((ancre, t)) if *ancre == prochaine_b => { t.push('\n'); t.push_str(textes_a[k]); }
+                        _ => suppressions.push((prochaine_b, textes_a[k].to_string())),
+                    }
+                    (TypeDifference::Suppression, k, textes_a[k])
                 }
-                _ => vec![Object::Reference(annot_id)],
             };
-            dict.set("Annots", Object::Array(annots));
+            rapport.differences.push(DifferenceTexte { page, ligne: ligne + 1, type_difference, texte: texte.to_string() });
+        }
+        if let Some(page_id) = ib.map(|j| pages_b[j])
+            && (!ajouts.is_empty() || !suppressions.is_empty())
+        {
+            marques.push(MarquesPage { page_id, lignes: lignes_b, ajouts, suppressions });
         }
     }
 
-    sauvegarder(&mut doc, output)
-}
+    if let Some(sortie) = sortie {
+        let gs_id = doc_b.add_object(dictionary! {
+            "Type" => "ExtGState",
+            "ca" => 0.45,
+            "BM" => "Multiply",
+        });
+        let font_id = doc_b.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Helvetica",
+            "Encoding" => "WinAnsiEncoding",
+        });
+        let date = chrono::Utc::now().format("D:%Y%m%d%H%M%S+00'00'").to_string();
+        for m in &marques {
+            marquer_differences_page(&mut doc_b, m, gs_id, font_id, &date)?;
+        }
+        sauvegarder(&mut doc_b, sortie)?;
+    }
 
-pub fn pdf_annoter(
-    input: &Path, output: &str,
-    texte: &str,
-    x: f64, y: f64,
-    largeur: f64, hauteur: f64,
-    pages_cibles: Option<&[u32]>,
-) -> Result<(), String> {
-    let texte = texte.to_string();
-    let x = x; let y = y;
-    let largeur = largeur; let hauteur = hauteur;
-    let pages = pages_cibles.map(|p| p.to_vec());
-    appliquer_operation_doc(input, output, move |pdf_in, pdf_out| {
-        pdf_annoter_interne(pdf_in, pdf_out, &texte, x, y, largeur, hauteur, pages.as_deref())
-    })
+    Ok(rapport)
 }
 
 // ════════════════════════════════════════════════════════════════════════
@@ -1741,4 +5107,884 @@ pub fn pdf_signer(
     appliquer_operation_doc(input, output, move |pdf_in, pdf_out| {
         pdf_signer_interne(pdf_in, pdf_out, &nom, position, taille, pages.as_deref())
     })
-}
\ No newline at end of file
+}
+
+// ════════════════════════════════════════════════════════════════════════
+//  PDF/A — conversion PDF/A-2b + rapport de validation
+// ════════════════════════════════════════════════════════════════════════
+
+/// Police embarquée de substitution pour les polices non incorporées
+const POLICE_PDFA: &[u8] = include_bytes!("../../assets/fonts/DejaVuSans.ttf");
+
+/// Résultat d'une conversion / validation PDF/A
+#[derive(Debug, Clone, Default)]
+pub struct RapportPdfA {
+    /// Corrections appliquées pendant la conversion
+    pub corrections: Vec<String>,
+    /// Violations restantes (non corrigeables automatiquement)
+    pub violations: Vec<String>,
+}
+
+impl RapportPdfA {
+    pub fn est_conforme(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    /// Rapport lisible (une ligne par entrée)
+    pub fn en_texte(&self) -> String {
+        let mut s = String::from("PDF/A-2b\n");
+        s.push_str(&format!("Corrections : {}\n", self.corrections.len()));
+        for c in &self.corrections { s.push_str(&format!("  + {}\n", c)); }
+        s.push_str(&format!("Violations : {}\n", self.violations.len()));
+        for v in &self.violations { s.push_str(&format!("  ! {}\n", v)); }
+        s
+    }
+}
+
+/// Types d'action interdits en PDF/A-2
+const ACTIONS_INTERDITES: &[&[u8]] = &[
+    b"JavaScript", b"Launch", b"Sound", b"Movie", b"ResetForm",
+    b"ImportData", b"Hide", b"SetOCGState", b"Rendition", b"Trans", b"GoTo3DView",
+];
+
+/// Sous-types d'annotation interdits en PDF/A-2
+const ANNOTATIONS_INTERDITES: &[&[u8]] = &[b"3D", b"Sound", b"Screen", b"Movie"];
+
+/// Modes de fusion standard autorisés
+const MODES_FUSION: &[&[u8]] = &[
+    b"Normal", b"Compatible", b"Multiply", b"Screen", b"Overlay", b"Darken", b"Lighten",
+    b"ColorDodge", b"ColorBurn", b"HardLight", b"SoftLight", b"Difference", b"Exclusion",
+    b"Hue", b"Saturation", b"Color", b"Luminosity",
+];
+
+/// Polices standard 14 (jamais incorporées par les générateurs)
+fn est_police_standard(nom: &[u8]) -> bool {
+    let nom = std::str::from_utf8(nom).unwrap_or("");
+    ["Helvetica", "Times", "Courier", "Symbol", "ZapfDingbats", "Arial"]
+        .iter()
+        .any(|p| nom.starts_with(p))
+}
+
+fn resoudre<'a>(doc: &'a Document, obj: &'a Object) -> &'a Object {
+    match obj {
+        Object::Reference(id) => doc.get_object(*id).unwrap_or(obj),
+        _ => obj,
+    }
+}
+
+fn resoudre_dict<'a>(doc: &'a Document, obj: &'a Object) -> Option<&'a lopdf::Dictionary> {
+    match resoudre(doc, obj) {
+        Object::Dictionary(d) => Some(d),
+        Object::Stream(s) => Some(&s.dict),
+        _ => None,
+    }
+}
+
+/// Décode une chaîne du dictionnaire Info (UTF-16BE avec BOM ou PDFDocEncoding ≈ Latin-1)
+fn decoder_chaine_pdf(bytes: &[u8]) -> String {
+    if bytes.len() >= 2 && bytes[0] == 0xFE && bytes[1] == 0xFF {
+        let utf16: Vec<u16> = bytes[2..].chunks(2)
+            .filter(|c| c.len() == 2)
+            .map(|c| u16::from_be_bytes([c[0], c[1]]))
+            .collect();
+        String::from_utf16_lossy(&utf16)
+    } else {
+        bytes.iter().map(|&b| b as char).collect()
+    }
+}
+
+fn echapper_xml(texte: &str) -> String {
+    texte.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Caractère Unicode correspondant à un octet WinAnsi (inverse de `encoder_winansi`)
+fn winansi_vers_unicode(octet: u8) -> Option<char> {
+    let code = match octet {
+        0x20..=0x7E | 0xA0..=0xFF => octet as u32,
+        0x80 => 0x20AC, 0x82 => 0x201A, 0x83 => 0x0192, 0x84 => 0x201E,
+        0x85 => 0x2026, 0x86 => 0x2020, 0x87 => 0x2021, 0x88 => 0x02C6,
+        0x89 => 0x2030, 0x8A => 0x0160, 0x8B => 0x2039, 0x8C => 0x0152,
+        0x8E => 0x017D, 0x91 => 0x2018, 0x92 => 0x2019, 0x93 => 0x201C,
+        0x94 => 0x201D, 0x95 => 0x2022, 0x96 => 0x2013, 0x97 => 0x2014,
+        0x98 => 0x02DC, 0x99 => 0x2122, 0x9A => 0x0161, 0x9B => 0x203A,
+        0x9C => 0x0153, 0x9E => 0x017E, 0x9F => 0x0178,
+        _ => return None,
+    };
+    char::from_u32(code)
+}
+
+/// Profil ICC sRGB IEC61966-2.1 (v2, courbes tabulées) généré à la volée
+pub(crate) fn profil_icc_srgb() -> Vec<u8> {
+    fn s15f16(v: f64) -> [u8; 4] { ((v * 65536.0).round() as i32).to_be_bytes() }
+    fn xyz(x: f64, y: f64, z: f64) -> Vec<u8> {
+        let mut t = b"XYZ \0\0\0\0".to_vec();
+        t.extend_from_slice(&s15f16(x));
+        t.extend_from_slice(&s15f16(y));
+        t.extend_from_slice(&s15f16(z));
+        t
+    }
+    fn desc(texte: &str) -> Vec<u8> {
+        let mut t = b"desc\0\0\0\0".to_vec();
+        t.extend_from_slice(&((texte.len() + 1) as u32).to_be_bytes());
+        t.extend_from_slice(texte.as_bytes());
+        t.push(0);
+        t.extend_from_slice(&[0u8; 4 + 4 + 2 + 1 + 67]);
+        t
+    }
+
+    let mut trc = b"curv\0\0\0\0".to_vec();
+    let n = 1024u32;
+    trc.extend_from_slice(&n.to_be_bytes());
+    for i in 0..n {
+        let v = i as f64 / (n - 1) as f64;
+        let lin = if v <= 0.04045 { v / 12.92 } else { ((v + 0.055) / 1.055).powf(2.4) };
+        trc.extend_from_slice(&((lin * 65535.0).round() as u16).to_be_bytes());
+    }
+
+    let mut cprt = b"text\0\0\0\0".to_vec();
+    cprt.extend_from_slice(b"No copyright, use freely\0");
+
+    // Primaires sRGB adaptées D50 (Bradford)
+    let tags: Vec<(&[u8; 4], Vec<u8>)> = vec![
+        (b"desc", desc("sRGB IEC61966-2.1")),
+        (b"cprt", cprt),
+        (b"wtpt", xyz(0.9642, 1.0, 0.8249)),
+        (b"rXYZ", xyz(0.4361, 0.2225, 0.0139)),
+        (b"gXYZ", xyz(0.3851, 0.7169, 0.0971)),
+        (b"bXYZ", xyz(0.1431, 0.0606, 0.7141)),
+        (b"rTRC", trc.clone()),
+        (b"gTRC", trc.clone()),
+        (b"bTRC", trc),
+    ];
+
+    let mut table = Vec::new();
+    let mut donnees = Vec::new();
+    let mut offset = 128 + 4 + 12 * tags.len();
+    for (sig, data) in &tags {
+        table.extend_from_slice(*sig);
+        table.extend_from_slice(&(offset as u32).to_be_bytes());
+        table.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        donnees.extend_from_slice(data);
+        while donnees.len() % 4 != 0 { donnees.push(0); }
+        offset = 128 + 4 + 12 * tags.len() + donnees.len();
+    }
+
+    let taille = 128 + 4 + table.len() + donnees.len();
+    let mut icc = Vec::with_capacity(taille);
+    icc.extend_from_slice(&(taille as u32).to_be_bytes());
+    icc.extend_from_slice(&[0; 4]);                        // CMM
+    icc.extend_from_slice(&[0x02, 0x10, 0, 0]);            // version 2.1
+    icc.extend_from_slice(b"mntrRGB XYZ ");
+    icc.extend_from_slice(&[0x07, 0xE0, 0, 1, 0, 1, 0, 0, 0, 0, 0, 0]); // date 2016-01-01
+    icc.extend_from_slice(b"acsp");
+    icc.extend_from_slice(&[0; 4 + 4 + 4 + 4 + 8 + 4]);    // plateforme, flags, fabricant, modèle, attributs, intent
+    icc.extend_from_slice(&s15f16(0.9642));
+    icc.extend_from_slice(&s15f16(1.0));
+    icc.extend_from_slice(&s15f16(0.8249));
+    icc.extend_from_slice(&[0; 4 + 16 + 28]);              // créateur, ID, réservé
+    icc.extend_from_slice(&(tags.len() as u32).to_be_bytes());
+    icc.extend_from_slice(&table);
+    icc.extend_from_slice(&donnees);
+    icc
+}
+
+/// Crée la police TrueType DejaVu Sans incorporée (WinAnsiEncoding, codes 32-255)
+fn ajouter_police_pdfa(doc: &mut Document) -> Result<ObjectId, String> {
+    let face = ttf_parser::Face::parse(POLICE_PDFA, 0)
+        .map_err(|e| format!("Police de substitution illisible : {}", e))?;
+    let echelle = 1000.0 / face.units_per_em() as f64;
+    let mesure = |v: i16| (v as f64 * echelle).round() as i64;
+
+    let largeurs: Vec<Object> = (32u8..=255)
+        .map(|code| {
+            let avance = winansi_vers_unicode(code)
+                .and_then(|c| face.glyph_index(c))
+                .and_then(|g| face.glyph_hor_advance(g))
+                .unwrap_or(0);
+            Object::Integer((avance as f64 * echelle).round() as i64)
+        })
+        .collect();
+
+    let bbox = face.global_bounding_box();
+    let fichier_id = doc.add_object(Stream::new(
+        dictionary! { "Length1" => POLICE_PDFA.len() as i64 },
+        POLICE_PDFA.to_vec(),
+    ));
+    let descripteur_id = doc.add_object(dictionary! {
+        "Type" => "FontDescriptor",
+        "FontName" => "DejaVuSans",
+        "Flags" => 32,
+        "FontBBox" => vec![mesure(bbox.x_min).into(), mesure(bbox.y_min).into(), mesure(bbox.x_max).into(), mesure(bbox.y_max).into()],
+        "ItalicAngle" => 0,
+        "Ascent" => mesure(face.ascender()),
+        "Descent" => mesure(face.descender()),
+        "CapHeight" => mesure(face.capital_height().unwrap_or(face.ascender())),
+        "StemV" => 80,
+        "FontFile2" => Object::Reference(fichier_id),
+    });
+    Ok(doc.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "TrueType",
+        "BaseFont" => "DejaVuSans",
+        "FirstChar" => 32,
+        "LastChar" => 255,
+        "Widths" => largeurs,
+        "Encoding" => "WinAnsiEncoding",
+        "FontDescriptor" => Object::Reference(descripteur_id),
+    }))
+}
+
+fn police_incorporee(doc: &Document, police: &lopdf::Dictionary) -> bool {
+    let descripteur = police.get(b"FontDescriptor").ok().and_then(|d| resoudre_dict(doc, d));
+    match descripteur {
+        Some(d) => d.has(b"FontFile") || d.has(b"FontFile2") || d.has(b"FontFile3"),
+        None => false,
+    }
+}
+
+/// Applique les corrections PDF/A-2b sur un document chargé
+fn pdfa_corriger(doc: &mut Document, rapport: &mut RapportPdfA) -> Result<(), String> {
+    // ── Chiffrement ──
+    if doc.is_encrypted() {
+        doc.decrypt("")
+            .map_err(|_| "PDF protégé par mot de passe : déverrouillez-le d'abord".to_string())?;
+        rapport.corrections.push("Chiffrement supprimé".into());
+    }
+    if doc.trailer.remove(b"Encrypt").is_some() && !rapport.corrections.iter().any(|c| c.starts_with("Chiffrement")) {
+        rapport.corrections.push("Chiffrement supprimé".into());
+    }
+    doc.encryption_state = None;
+
+    // ── Filtres LZW → Flate ──
+    let a_lzw = doc.objects.values().any(|o| matches!(o, Object::Stream(s)
+        if s.filters().map(|f| f.iter().any(|n| *n == b"LZWDecode")).unwrap_or(false)));
+    if a_lzw {
+        doc.decompress();
+        rapport.corrections.push("Flux LZW recompressés en Flate".into());
+    }
+
+    // ── JavaScript et actions interdites ──
+    let mut js_catalogue = false;
+    if let Ok(catalog) = doc.catalog_mut() {
+        catalog.remove(b"AA");
+        if let Ok(Object::Dictionary(names)) = catalog.get_mut(b"Names") {
+            js_catalogue = names.remove(b"JavaScript").is_some();
+        }
+    }
+    if let Ok(Object::Reference(names_id)) = doc.catalog().map(|c| c.get(b"Names").cloned().unwrap_or(Object::Null))
+        && let Ok(names) = doc.get_dictionary_mut(names_id)
+    {
+        js_catalogue |= names.remove(b"JavaScript").is_some();
+    }
+    if js_catalogue {
+        rapport.corrections.push("Scripts JavaScript du document supprimés".into());
+    }
+
+    let actions_interdites: Vec<ObjectId> = doc.objects.iter()
+        .filter_map(|(id, o)| match o {
+            Object::Dictionary(d) => d.get(b"S").ok()
+                .and_then(|s| s.as_name().ok())
+                .filter(|s| ACTIONS_INTERDITES.contains(s))
+                .map(|_| *id),
+            _ => None,
+        })
+        .collect();
+
+    let annotations: Vec<ObjectId> = obtenir_pages_ordonnees(doc).into_iter()
+        .filter_map(|p| doc.get_dictionary(p).ok()?.get(b"Annots").ok().map(|a| resoudre(doc, a)))
+        .filter_map(|a| a.as_array().ok())
+        .flatten()
+        .filter_map(|a| a.as_reference().ok())
+        .collect();
+
+    let mut nb_actions = 0usize;
+    let mut nb_annots_print = 0usize;
+    let mut nb_annots_supprimees = 0usize;
+    let mut nb_interpolate = 0usize;
+    let mut nb_fusion = 0usize;
+    let mut nb_groupes = 0usize;
+    let ids: Vec<ObjectId> = doc.objects.keys().copied().collect();
+    for id in ids {
+        let Some(obj) = doc.objects.get_mut(&id) else { continue };
+        let dict = match obj {
+            Object::Dictionary(d) => d,
+            Object::Stream(s) => &mut s.dict,
+            _ => continue,
+        };
+        if dict.remove(b"AA").is_some() { nb_actions += 1; }
+        for cle in [b"A".as_slice(), b"OpenAction".as_slice()] {
+            let interdite = match dict.get(cle) {
+                Ok(Object::Reference(r)) => actions_interdites.contains(r),
+                Ok(Object::Dictionary(a)) => a.get(b"S").ok()
+                    .and_then(|s| s.as_name().ok())
+                    .map(|s| ACTIONS_INTERDITES.contains(&s))
+                    .unwrap_or(false),
+                _ => false,
+            };
+            if interdite {
+                dict.remove(cle);
+                nb_actions += 1;
+            }
+        }
+
+        let type_nom = dict.get(b"Type").and_then(|t| t.as_name()).unwrap_or(b"").to_vec();
+        let sous_type = dict.get(b"Subtype").and_then(|t| t.as_name()).unwrap_or(b"").to_vec();
+
+        // Annotations : drapeau Print obligatoire, Hidden/Invisible/NoView interdits
+        if (type_nom == b"Annot" || annotations.contains(&id)) && sous_type != b"Popup" {
+            let f = dict.get(b"F").and_then(|f| f.as_i64()).unwrap_or(0);
+            let corrige = (f | 4) & !(1 | 2 | 32);
+            if corrige != f {
+                dict.set("F", Object::Integer(corrige));
+                nb_annots_print += 1;
+            }
+        }
+
+        // Images : Interpolate, Alternates et OPI interdits
+        if sous_type == b"Image" {
+            if dict.get(b"Interpolate").and_then(|v| v.as_bool()).unwrap_or(false) {
+                dict.set("Interpolate", false);
+                nb_interpolate += 1;
+            }
+            dict.remove(b"Alternates");
+            dict.remove(b"OPI");
+        }
+
+        // ExtGState : modes de fusion non standard, TR/TR2
+        if type_nom == b"ExtGState" || dict.has(b"BM") {
+            let bm_invalide = match dict.get(b"BM") {
+                Ok(Object::Name(n)) => !MODES_FUSION.contains(&n.as_slice()),
+                Ok(Object::Array(arr)) => arr.iter().any(|n| n.as_name().map(|n| !MODES_FUSION.contains(&n)).unwrap_or(true)),
+                _ => false,
+            };
+            if bm_invalide {
+                dict.set("BM", "Normal");
+                nb_fusion += 1;
+            }
+            dict.remove(b"TR");
+            if dict.get(b"TR2").and_then(|t| t.as_name()).map(|n| n != b"Default").unwrap_or(false) {
+                dict.remove(b"TR2");
+            }
+        }
+
+        // Groupes de transparence sans espace colorimétrique
+        if dict.get(b"S").and_then(|s| s.as_name()).map(|s| s == b"Transparency").unwrap_or(false)
+            && (type_nom == b"Group" || type_nom.is_empty())
+            && !dict.has(b"CS")
+        {
+            dict.set("CS", "DeviceRGB");
+            nb_groupes += 1;
+        }
+    }
+    if nb_actions > 0 {
+        rapport.corrections.push(format!("{} action(s) interdite(s) supprimée(s) (JavaScript, Launch…)", nb_actions));
+    }
+    if nb_interpolate > 0 {
+        rapport.corrections.push(format!("Interpolate désactivé sur {} image(s)", nb_interpolate));
+    }
+    if nb_fusion > 0 {
+        rapport.corrections.push(format!("{} mode(s) de fusion non standard remplacé(s) par Normal", nb_fusion));
+    }
+    if nb_groupes > 0 {
+        rapport.corrections.push(format!("Espace colorimétrique ajouté à {} groupe(s) de transparence", nb_groupes));
+    }
+
+    // ── Annotations interdites (3D, multimédia) ──
+    for page_id in obtenir_pages_ordonnees(doc) {
+        let annots: Vec<Object> = match doc.get_dictionary(page_id).ok().and_then(|d| d.get(b"Annots").ok()) {
+            Some(a) => match resoudre(doc, a) {
+                Object::Array(arr) => arr.clone(),
+                _ => continue,
+            },
+            None => continue,
+        };
+        let gardees: Vec<Object> = annots.iter()
+            .filter(|a| {
+                let interdite = resoudre_dict(doc, a)
+                    .and_then(|d| d.get(b"Subtype").ok())
+                    .and_then(|s| s.as_name().ok())
+                    .map(|s| ANNOTATIONS_INTERDITES.contains(&s))
+                    .unwrap_or(false);
+                !interdite
+            })
+            .cloned()
+            .collect();
+        if gardees.len() != annots.len() {
+            nb_annots_supprimees += annots.len() - gardees.len();
+            if let Ok(dict) = doc.get_dictionary_mut(page_id) {
+                dict.set("Annots", Object::Array(gardees));
+            }
+        }
+    }
+    if nb_annots_print > 0 {
+        rapport.corrections.push(format!("Drapeau Print activé sur {} annotation(s)", nb_annots_print));
+    }
+    if nb_annots_supprimees > 0 {
+        rapport.corrections.push(format!("{} annotation(s) 3D/multimédia supprimée(s)", nb_annots_supprimees));
+    }
+
+    // ── Formulaires : NeedAppearances interdit ──
+    let acroform_id = doc.catalog().ok()
+        .and_then(|c| c.get(b"AcroForm").ok())
+        .and_then(|a| a.as_reference().ok());
+    let need_app = |d: &mut lopdf::Dictionary| -> bool {
+        if d.get(b"NeedAppearances").and_then(|v| v.as_bool()).unwrap_or(false) {
+            d.set("NeedAppearances", false);
+            true
+        } else {
+            false
+        }
+    };
+    let corrige_form = match acroform_id {
+        Some(id) => doc.get_dictionary_mut(id).map(need_app).unwrap_or(false),
+        None => match doc.catalog_mut().ok().and_then(|c| c.get_mut(b"AcroForm").ok()) {
+            Some(Object::Dictionary(d)) => need_app(d),
+            _ => false,
+        },
+    };
+    if corrige_form {
+        rapport.corrections.push("NeedAppearances du formulaire désactivé".into());
+    }
+
+    // ── Polices non incorporées → DejaVu Sans incorporée ──
+    // Seules les polices en codage latin standard sont substituées : un codage propre
+    // (/Differences, police symbolique) donnerait d'autres glyphes → laissé au rapport
+    let a_remplacer: Vec<(ObjectId, String, lopdf::Dictionary)> = doc.objects.iter()
+        .filter_map(|(id, o)| {
+            let d = o.as_dict().ok()?;
+            if d.get(b"Type").and_then(|t| t.as_name()).ok()? != b"Font" { return None; }
+            let sous_type = d.get(b"Subtype").and_then(|t| t.as_name()).ok()?;
+            if !matches!(sous_type, b"Type1" | b"TrueType" | b"MMType1") { return None; }
+            if police_incorporee(doc, d) { return None; }
+            let codage_latin = match d.get(b"Encoding") {
+                Err(_) => true,
+                Ok(Object::Name(n)) => matches!(n.as_slice(), b"WinAnsiEncoding" | b"StandardEncoding"),
+                Ok(_) => false,
+            };
+            let drapeau_symbolique = d.get(b"FontDescriptor").ok()
+                .and_then(|f| resoudre_dict(doc, f))
+                .and_then(|f| f.get(b"Flags").and_then(|v| v.as_i64()).ok())
+                .is_some_and(|f| f & 4 != 0);
+            let nom = String::from_utf8_lossy(d.get(b"BaseFont").and_then(|n| n.as_name()).unwrap_or(b"?")).to_string();
+            let symbolique = nom.starts_with("Symbol") || nom.starts_with("ZapfDingbats");
+            if !codage_latin || drapeau_symbolique || (symbolique && est_police_standard(nom.as_bytes())) {
+                return None;
+            }
+            Some((*id, nom, d.clone()))
+        })
+        .collect();
+    if !a_remplacer.is_empty() {
+        let police_id = ajouter_police_pdfa(doc)?;
+        let police = doc.get_dictionary(police_id).cloned().map_err(|e| e.to_string())?;
+        let mut noms: Vec<String> = Vec::new();
+        for (id, nom, source) in a_remplacer {
+            let mut substituee = police.clone();
+            // Codage, chasses et table Unicode de la source sont gardés : le texte garde sa mise en page
+            if source.has(b"Widths") {
+                for cle in [b"FirstChar".as_slice(), b"LastChar", b"Widths"] {
+                    match source.get(cle) {
+                        Ok(v) => substituee.set(cle, v.clone()),
+                        Err(_) => { substituee.remove(cle); }
+                    }
+                }
+            }
+            for cle in [b"Encoding".as_slice(), b"ToUnicode"] {
+                if let Ok(v) = source.get(cle) {
+                    substituee.set(cle, v.clone());
+                }
+            }
+            doc.objects.insert(id, Object::Dictionary(substituee));
+            if !noms.contains(&nom) { noms.push(nom); }
+        }
+        if !noms.is_empty() {
+            rapport.corrections.push(format!("Police(s) incorporée(s) par substitution DejaVu Sans : {}", noms.join(", ")));
+        }
+    }
+
+    // ── OutputIntent sRGB ──
+    let a_output_intent = doc.catalog().map(|c| c.has(b"OutputIntents")).unwrap_or(false);
+    if !a_output_intent {
+        let icc_id = doc.add_object(Stream::new(dictionary! { "N" => 3 }, profil_icc_srgb()));
+        let intent_id = doc.add_object(dictionary! {
+            "Type" => "OutputIntent",
+            "S" => "GTS_PDFA1",
+            "OutputConditionIdentifier" => Object::string_literal("sRGB IEC61966-2.1"),
+            "Info" => Object::string_literal("sRGB IEC61966-2.1"),
+            "RegistryName" => Object::string_literal("http://www.color.org"),
+            "DestOutputProfile" => Object::Reference(icc_id),
+        });
+        doc.catalog_mut().map_err(|e| format!("Catalogue introuvable : {}", e))?
+            .set("OutputIntents", vec![Object::Reference(intent_id)]);
+        rapport.corrections.push("OutputIntent sRGB IEC61966-2.1 ajouté".into());
+    }
+
+    // ── Métadonnées XMP + Info synchronisés ──
+    let info_id = doc.trailer.get(b"Info").and_then(|i| i.as_reference()).ok();
+    let mut info = info_id
+        .and_then(|id| doc.get_dictionary(id).ok().cloned())
+        .unwrap_or_default();
+    let lire = |cle: &[u8]| info.get(cle).and_then(|v| v.as_str()).map(decoder_chaine_pdf).ok();
+    let titre = lire(b"Title");
+    let auteur = lire(b"Author");
+    let sujet = lire(b"Subject");
+    let mots_cles = lire(b"Keywords");
+    let createur = lire(b"Creator");
+
+    let maintenant = chrono::Utc::now();
+    let date_pdf = maintenant.format("D:%Y%m%d%H%M%S+00'00'").to_string();
+    let date_xmp = maintenant.format("%Y-%m-%dT%H:%M:%S+00:00").to_string();
+    // La date de création d'origine est conservée (Info, sinon XMP) ; seule la modification change
+    let creation = info.get(b"CreationDate").ok()
+        .and_then(|v| v.as_str().ok())
+        .and_then(|v| lire_date_pdf(&decoder_chaine_pdf(v)))
+        .or_else(|| lire_date_creation_xmp(doc))
+        .unwrap_or_else(|| maintenant.fixed_offset());
+    let creation_pdf = ecrire_date_pdf(&creation);
+    let creation_xmp = creation.format("%Y-%m-%dT%H:%M:%S%:z").to_string();
+    let producteur = format!("Oxyon {}", env!("CARGO_PKG_VERSION"));
+
+    // Les chaînes Info doivent correspondre exactement au XMP : on les réécrit en UTF-16BE
+    let encoder_info = |t: &str| -> Object {
+        let mut b = vec![0xFE, 0xFF];
+        for u in t.encode_utf16() { b.extend_from_slice(&u.to_be_bytes()); }
+        Object::String(b, lopdf::StringFormat::Hexadecimal)
+    };
+    for (cle, val) in [(b"Title".as_slice(), &titre), (b"Author", &auteur), (b"Subject", &sujet), (b"Keywords", &mots_cles), (b"Creator", &createur)] {
+        if let Some(v) = val { info.set(cle, encoder_info(v)); }
+    }
+    info.set("Producer", encoder_info(&producteur));
+    info.set("CreationDate", Object::string_literal(creation_pdf));
+    info.set("ModDate", Object::string_literal(date_pdf));
+    info.remove(b"Trapped");
+    match info_id {
+        Some(id) => { doc.objects.insert(id, Object::Dictionary(info)); }
+        None => {
+            let id = doc.add_object(info);
+            doc.trailer.set("Info", Object::Reference(id));
+        }
+    }
+
+    let mut dc = String::new();
+    if let Some(t) = &titre {
+        dc.push_str(&format!("<dc:title><rdf:Alt><rdf:li xml:lang=\"x-default\">{}</rdf:li></rdf:Alt></dc:title>", echapper_xml(t)));
+    }
+    if let Some(a) = &auteur {
+        dc.push_str(&format!("<dc:creator><rdf:Seq><rdf:li>{}</rdf:li></rdf:Seq></dc:creator>", echapper_xml(a)));
+    }
+    if let Some(s) = &sujet {
+        dc.push_str(&format!("<dc:description><rdf:Alt><rdf:li xml:lang=\"x-default\">{}</rdf:li></rdf:Alt></dc:description>", echapper_xml(s)));
+    }
+    let mut pdf_ns = format!("<pdf:Producer>{}</pdf:Producer>", echapper_xml(&producteur));
+    if let Some(k) = &mots_cles {
+        pdf_ns.push_str(&format!("<pdf:Keywords>{}</pdf:Keywords>", echapper_xml(k)));
+    }
+    let outil = createur.as_deref().map(|c| format!("<xmp:CreatorTool>{}</xmp:CreatorTool>", echapper_xml(c))).unwrap_or_default();
+
+    let xmp = format!(
+        "<?xpacket begin=\"\u{FEFF}\" id=\"W5M0MpCehiHzreSzNTczkc9d\"?>\n\
+<x:xmpmeta xmlns:x=\"adobe:ns:meta/\">\n\
+<rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">\n\
+<rdf:Description rdf:about=\"\" xmlns:pdfaid=\"http://www.aiim.org/pdfa/ns/id/\"><pdfaid:part>2</pdfaid:part><pdfaid:conformance>B</pdfaid:conformance></rdf:Description>\n\
+<rdf:Description rdf:about=\"\" xmlns:dc=\"http://purl.org/dc/elements/1.1/\"><dc:format>application/pdf</dc:format>{dc}</rdf:Description>\n\
+<rdf:Description rdf:about=\"\" xmlns:xmp=\"http://ns.adobe.com/xap/1.0/\"><xmp:CreateDate>{creation}</xmp:CreateDate><xmp:ModifyDate>{date}</xmp:ModifyDate><xmp:MetadataDate>{date}</xmp:MetadataDate>{outil}</rdf:Description>\n\
+<rdf:Description rdf:about=\"\" xmlns:pdf=\"http://ns.adobe.com/pdf/1.3/\">{pdf_ns}</rdf:Description>\n\
+</rdf:RDF>\n\
+</x:xmpmeta>\n\
+<?xpacket end=\"w\"?>",
+        date = date_xmp,
+        creation = creation_xmp,
+    );
+    let meta_id = doc.add_object(
+        Stream::new(dictionary! { "Type" => "Metadata", "Subtype" => "XML" }, xmp.into_bytes())
+            .with_compression(false),
+    );
+    doc.catalog_mut().map_err(|e| format!("Catalogue introuvable : {}", e))?
+        .set("Metadata", Object::Reference(meta_id));
+    rapport.corrections.push("Métadonnées XMP pdfaid (part 2, conformance B) écrites".into());
+
+    // ── Identifiant de fichier ──
+    if !doc.trailer.has(b"ID") {
+        use std::hash::{Hash, Hasher};
+        let mut h1 = std::collections::hash_map::DefaultHasher::new();
+        maintenant.timestamp_nanos_opt().hash(&mut h1);
+        doc.objects.len().hash(&mut h1);
+        let mut h2 = std::collections::hash_map::DefaultHasher::new();
+        h1.finish().hash(&mut h2);
+        std::process::id().hash(&mut h2);
+        let mut id = h1.finish().to_be_bytes().to_vec();
+        id.extend_from_slice(&h2.finish().to_be_bytes());
+        let id_obj = Object::String(id, lopdf::StringFormat::Hexadecimal);
+        doc.trailer.set("ID", vec![id_obj.clone(), id_obj]);
+    }
+
+    doc.version = "1.7".into();
+    doc.binary_mark = vec![0xE2, 0xE3, 0xCF, 0xD3];
+    Ok(())
+}
+
+/// Lit une date PDF « D:AAAAMMJJHHmmSSOHH'mm' » (champs finaux facultatifs, UTC par défaut)
+fn lire_date_pdf(texte: &str) -> Option<chrono::DateTime<chrono::FixedOffset>> {
+    use chrono::TimeZone;
+    let texte = texte.trim();
+    let texte = texte.strip_prefix("D:").unwrap_or(texte);
+    let longueur = texte.bytes().take_while(u8::is_ascii_digit).count();
+    if longueur < 4 {
+        return None;
+    }
+    let champ = |debut: usize, defaut: u32| texte.get(debut..debut + 2)
+        .filter(|_| debut + 2 <= longueur)
+        .and_then(|c| c.parse().ok())
+        .unwrap_or(defaut);
+    let annee: i32 = texte[..4].parse().ok()?;
+    let decalage = match texte[longueur..].chars().next() {
+        Some(signe @ ('+' | '-')) => {
+            let chiffres: Vec<u32> = texte[longueur + 1..].chars().filter_map(|c| c.to_digit(10)).collect();
+            let nombre = |i: usize| chiffres.get(i..i + 2).map_or(0, |d| (d[0] * 10 + d[1]) as i32);
+            let secondes = nombre(0) * 3600 + nombre(2) * 60;
+            chrono::FixedOffset::east_opt(if signe == '-' { -secondes } else { secondes })?
+        }
+        _ => chrono::FixedOffset::east_opt(0)?,
+    };
+    decalage
+        .with_ymd_and_hms(annee, champ(4, 1), champ(6, 1), champ(8, 0), champ(10, 0), champ(12, 0))
+        .single()
+}
+
+/// Écrit une date au format PDF en gardant son fuseau (« +02'00' »)
+fn ecrire_date_pdf(date: &chrono::DateTime<chrono::FixedOffset>) -> String {
+    let decalage = date.offset().local_minus_utc();
+    format!(
+        "D:{}{}{:02}'{:02}'",
+        date.format("%Y%m%d%H%M%S"),
+        if decalage < 0 { '-' } else { '+' },
+        decalage.abs() / 3600,
+        decalage.abs() % 3600 / 60,
+    )
+}
+
+/// Date de création déclarée dans le XMP existant (xmp:CreateDate, élément ou attribut)
+fn lire_date_creation_xmp(doc: &Document) -> Option<chrono::DateTime<chrono::FixedOffset>> {
+    let id = doc.catalog().ok()?.get(b"Metadata").ok()?.as_reference().ok()?;
+    let flux = doc.get_object(id).ok()?.as_stream().ok()?;
+    let contenu = flux.decompressed_content().unwrap_or_else(|_| flux.content.clone());
+    let xml = String::from_utf8_lossy(&contenu);
+    let debut = xml.find(":CreateDate")? + ":CreateDate".len();
+    let valeur = xml[debut..].trim_start_matches(['>', '=', '"', '\'', ' ']).split(['<', '"', '\'']).next()?.trim();
+    chrono::DateTime::parse_from_rfc3339(valeur).ok().or_else(|| {
+        let jour = chrono::NaiveDate::parse_from_str(valeur.get(..10)?, "%Y-%m-%d").ok()?;
+        Some(jour.and_hms_opt(0, 0, 0)?.and_utc().fixed_offset())
+    })
+}
+
+/// Liste les violations PDF/A-2b détectables sur un document chargé
+fn pdfa_analyser(doc: &Document) -> Vec<String> {
+    let mut violations = Vec::new();
+
+    if doc.trailer.has(b"Encrypt") || doc.is_encrypted() {
+        violations.push("Document chiffré".into());
+    }
+    if !doc.trailer.has(b"ID") {
+        violations.push("Identifiant de fichier (ID) absent du trailer".into());
+    }
+
+    let catalog = match doc.catalog() {
+        Ok(c) => c,
+        Err(_) => {
+            violations.push("Catalogue introuvable".into());
+            return violations;
+        }
+    };
+
+    let output_intent_ok = catalog.get(b"OutputIntents").ok()
+        .map(|o| resoudre(doc, o))
+        .and_then(|o| o.as_array().ok())
+        .map(|arr| arr.iter().any(|i| {
+            resoudre_dict(doc, i)
+                .map(|d| d.has(b"DestOutputProfile"))
+                .unwrap_or(false)
+        }))
+        .unwrap_or(false);
+    if !output_intent_ok {
+        violations.push("OutputIntent avec profil ICC absent".into());
+    }
+
+    let xmp = catalog.get(b"Metadata").ok()
+        .and_then(|m| m.as_reference().ok())
+        .and_then(|id| doc.get_object(id).ok())
+        .and_then(|o| o.as_stream().ok());
+    match xmp {
+        Some(s) => {
+            if s.dict.has(b"Filter") {
+                violations.push("Flux de métadonnées XMP compressé".into());
+            }
+            let contenu = String::from_utf8_lossy(&s.content);
+            let part_ok = contenu.contains("<pdfaid:part>2</pdfaid:part>") || contenu.contains("pdfaid:part=\"2\"");
+            let conf_ok = contenu.contains("<pdfaid:conformance>B</pdfaid:conformance>") || contenu.contains("pdfaid:conformance=\"B\"");
+            if !part_ok || !conf_ok {
+                violations.push("Identification pdfaid (part 2, conformance B) absente du XMP".into());
+            }
+        }
+        None => violations.push("Métadonnées XMP absentes".into()),
+    }
+
+    if let Some(n) = catalog.get(b"Names").ok().and_then(|n| resoudre_dict(doc, n)) {
+        if n.has(b"JavaScript") {
+            violations.push("JavaScript présent (Names/JavaScript)".into());
+        }
+        if n.has(b"EmbeddedFiles") {
+            violations.push("Fichiers joints présents (PDF/A-2 n'autorise que des pièces jointes PDF/A)".into());
+        }
+    }
+    if let Some(form) = catalog.get(b"AcroForm").ok().and_then(|a| resoudre_dict(doc, a)) {
+        if form.get(b"NeedAppearances").and_then(|v| v.as_bool()).unwrap_or(false) {
+            violations.push("Formulaire avec NeedAppearances = true".into());
+        }
+        if form.has(b"XFA") {
+            violations.push("Formulaire XFA présent".into());
+        }
+    }
+
+    let mut polices_non_incorporees: Vec<String> = Vec::new();
+    let mut nb_js = 0usize;
+    let mut nb_aa = 0usize;
+    let mut nb_lzw = 0usize;
+    let mut nb_externes = 0usize;
+    let mut nb_ps = 0usize;
+    for obj in doc.objects.values() {
+        let dict = match obj {
+            Object::Dictionary(d) => d,
+            Object::Stream(s) => {
+                if s.filters().map(|f| f.iter().any(|n| *n == b"LZWDecode")).unwrap_or(false) { nb_lzw += 1; }
+                if s.dict.has(b"F") || s.dict.has(b"FFilter") { nb_externes += 1; }
+                if s.dict.get(b"Subtype").and_then(|t| t.as_name()).map(|t| t == b"PS").unwrap_or(false)
+                    || s.dict.get(b"Subtype2").and_then(|t| t.as_name()).map(|t| t == b"PS").unwrap_or(false) {
+                    nb_ps += 1;
+                }
+                &s.dict
+            }
+            _ => continue,
+        };
+        if dict.has(b"AA") { nb_aa += 1; }
+        if dict.get(b"S").and_then(|s| s.as_name()).map(|s| ACTIONS_INTERDITES.contains(&s)).unwrap_or(false) {
+            nb_js += 1;
+        }
+        if dict.get(b"Type").and_then(|t| t.as_name()).map(|t| t == b"Font").unwrap_or(false) {
+            let sous_type = dict.get(b"Subtype").and_then(|t| t.as_name()).unwrap_or(b"");
+            let a_verifier = match sous_type {
+                b"Type1" | b"TrueType" | b"MMType1" => Some(dict),
+                b"Type0" => dict.get(b"DescendantFonts").ok()
+                    .map(|d| resoudre(doc, d))
+                    .and_then(|d| d.as_array().ok())
+                    .and_then(|arr| arr.first())
+                    .and_then(|f| resoudre_dict(doc, f)),
+                _ => None,
+            };
+            if let Some(f) = a_verifier
+                && !police_incorporee(doc, f)
+            {
+                let nom = String::from_utf8_lossy(dict.get(b"BaseFont").and_then(|n| n.as_name()).unwrap_or(b"?")).to_string();
+                if !polices_non_incorporees.contains(&nom) { polices_non_incorporees.push(nom); }
+            }
+        }
+    }
+    if !polices_non_incorporees.is_empty() {
+        violations.push(format!("Police(s) non incorporée(s) : {}", polices_non_incorporees.join(", ")));
+    }
+    if nb_js > 0 { violations.push(format!("{} action(s) interdite(s) (JavaScript, Launch…)", nb_js)); }
+    if nb_aa > 0 { violations.push(format!("{} action(s) additionnelle(s) (AA)", nb_aa)); }
+    if nb_lzw > 0 { violations.push(format!("{} flux compressé(s) en LZW", nb_lzw)); }
+    if nb_externes > 0 { violations.push(format!("{} flux référençant un fichier externe", nb_externes)); }
+    if nb_ps > 0 { violations.push(format!("{} XObject(s) PostScript", nb_ps)); }
+
+    let mut nb_annots_print = 0usize;
+    let mut nb_annots_ap = 0usize;
+    let mut nb_annots_interdites = 0usize;
+    let mut pages_cmyk: Vec<u32> = Vec::new();
+    for (i, &page_id) in obtenir_pages_ordonnees(doc).iter().enumerate() {
+        let Ok(page) = doc.get_dictionary(page_id) else { continue };
+        if let Some(Object::Array(annots)) = page.get(b"Annots").ok().map(|a| resoudre(doc, a)) {
+            for a in annots {
+                let Some(ad) = resoudre_dict(doc, a) else { continue };
+                let sous_type = ad.get(b"Subtype").and_then(|s| s.as_name()).unwrap_or(b"");
+                if ANNOTATIONS_INTERDITES.contains(&sous_type) { nb_annots_interdites += 1; continue; }
+                if sous_type == b"Popup" { continue; }
+                let f = ad.get(b"F").and_then(|f| f.as_i64()).unwrap_or(0);
+                if f & 4 == 0 || f & (1 | 2 | 32) != 0 { nb_annots_print += 1; }
+                let rect_vide = ad.get(b"Rect").ok()
+                    .and_then(|r| resoudre(doc, r).as_array().ok())
+                    .map(|r| {
+                        let v: Vec<f32> = r.iter().filter_map(|x| x.as_float().ok()).collect();
+                        v.len() == 4 && (v[2] - v[0]).abs() < f32::EPSILON && (v[3] - v[1]).abs() < f32::EPSILON
+                    })
+                    .unwrap_or(false);
+                if sous_type != b"Link" && !rect_vide && !ad.has(b"AP") { nb_annots_ap += 1; }
+            }
+        }
+
+        // Espaces DeviceCMYK sans OutputIntent CMYK
+        let contenu = doc.get_page_content(page_id).unwrap_or_default();
+        let utilise_cmyk = Content::decode(&contenu)
+            .map(|c| c.operations.iter().any(|op| op.operator == "k" || op.operator == "K"))
+            .unwrap_or(false)
+            || doc.get_page_images(page_id).map(|imgs| imgs.iter().any(|img| img.color_space.as_deref() == Some("DeviceCMYK"))).unwrap_or(false);
+        if utilise_cmyk { pages_cmyk.push(i as u32 + 1); }
+    }
+    if nb_annots_interdites > 0 { violations.push(format!("{} annotation(s) 3D/multimédia", nb_annots_interdites)); }
+    if nb_annots_print > 0 { violations.push(format!("{} annotation(s) sans drapeau Print ou masquée(s)", nb_annots_print)); }
+    if nb_annots_ap > 0 { violations.push(format!("{} annotation(s) sans apparence (AP)", nb_annots_ap)); }
+    if !pages_cmyk.is_empty() {
+        violations.push(format!("DeviceCMYK utilisé avec un OutputIntent RGB (pages {:?})", pages_cmyk));
+    }
+
+    violations
+}
+
+fn pdf_vers_pdfa_interne(input: &Path, output: &str) -> Result<RapportPdfA, String> {
+    let mut doc = Document::load(input)
+        .map_err(|e| format!("Erreur chargement PDF : {}", e))?;
+
+    let mut rapport = RapportPdfA::default();
+    pdfa_corriger(&mut doc, &mut rapport)?;
+    doc.compress();
+    sauvegarder(&mut doc, output)?;
+
+    // Revalider le fichier écrit pour ne lister que ce qui reste à corriger
+    let final_doc = Document::load(output)
+        .map_err(|e| format!("Erreur relecture PDF/A : {}", e))?;
+    rapport.violations = pdfa_analyser(&final_doc);
+    Ok(rapport)
+}
+
+/// Convertit un document (PDF ou tout format convertible) en PDF/A-2b.
+/// Retourne le rapport des corrections et des violations restantes.
+pub fn pdf_vers_pdfa(input: &Path, output: &str) -> Result<RapportPdfA, String> {
+    crate::log_info(&format!("pdf_vers_pdfa | {:?} -> {}", input, output));
+    let result = if est_pdf(input) {
+        pdf_vers_pdfa_interne(input, output)
+    } else {
+        let pdf_tmp = vers_pdf_temp(input)?;
+        let result = pdf_vers_pdfa_interne(Path::new(&pdf_tmp), output);
+        nettoyer_temp(&pdf_tmp);
+        result
+    };
+    if let Ok(r) = &result {
+        crate::log_info(&format!("pdf_vers_pdfa OK | {} correction(s), {} violation(s)", r.corrections.len(), r.violations.len()));
+        for v in &r.violations {
+            crate::log_warn(&format!("pdf_vers_pdfa violation : {}", v));
+        }
+    }
+    result
+}
+
+/// Vérifie la conformité PDF/A-2b sans modifier le fichier
+pub fn pdfa_valider(input: &Path) -> Result<RapportPdfA, String> {
+    let doc = Document::load(input)
+        .map_err(|e| format!("Erreur chargement PDF : {}", e))?;
+    Ok(RapportPdfA {
+        corrections: Vec::new(),
+        violations: pdfa_analyser(&doc),
+    })
+}
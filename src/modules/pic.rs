@@ -1,9 +1,12 @@
 #![allow(dead_code)]
 use image::imageops::FilterType;
-use image::ImageEncoder;
+use image::metadata::Orientation;
+use image::{ImageDecoder, ImageEncoder};
 use std::fs::File;
 use std::io::{BufReader, Read};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use ab_glyph::{Font, FontArc, PxScale, ScaleFont};
+use crate::modules::doc::AncreTampon;
 use zune_jpegxl::JxlSimpleEncoder;
 use zune_core::options::EncoderOptions;
 use zune_core::colorspace::ColorSpace;
@@ -50,13 +53,13 @@ impl ImageFormat {
 
 /// Convertit/compresse l'image avec un contrôle de qualité (1=rapide, 10=max qualité).
 /// La résolution n'est JAMAIS modifiée.
-pub fn compresser(input: &Path, output: &str, qualite: u32) -> bool {
+pub fn compresser(input: &Path, output: &str, qualite: u32, orientation_auto: bool, profil: &ProfilCible, tons: &OptionsTonales) -> bool {
     crate::log_info(&format!("pic::compresser | qualite={} | {:?} -> {}", qualite, input, output));
     // Détection du format d'entrée
     if let Some(ext) = input.extension().and_then(|e| e.to_str()) {
         match ext.to_lowercase().as_str() {
             "svg" => return convertir_svg(input, output),
-            "psd" => return convertir_psd(input, output),
+            "psd" => return convertir_psd(input, output, profil),
             "jxl" => return compresser_jxl_qualite(input, output, qualite),
             "dng" | "cr2" | "nef" | "arw" | "orf" | "rw2" => {
                 crate::log_warn(&format!("pic::compresser format RAW non supporté pour {:?}", input));
@@ -65,31 +68,15 @@ pub fn compresser(input: &Path, output: &str, qualite: u32) -> bool {
             _ => {}
         }
     }
+    if let Some(ok) = traiter_animation(input, output, Ok) {
+        return ok;
+    }
 
     // Format standard supporté par image crate
-    match image::open(input) {
+    match ouvrir_image_profil(input, orientation_auto, profil) {
         Ok(img) => {
-            // Si la sortie est JXL, encoder via zune-jpegxl
-            if output.to_lowercase().ends_with(".jxl") {
-                return encoder_jxl(&img, output);
-            }
-            // Si la sortie est ICO, passer par le convertisseur dédié
-            if output.to_lowercase().ends_with(".ico") {
-                return convertir_ico_sizes(&img, output, &[256]);
-            }
-            // Si la sortie est JPEG, appliquer la qualité
-            if output.to_lowercase().ends_with(".jpg") || output.to_lowercase().ends_with(".jpeg") {
-                return sauvegarder_jpeg(&img, output, qualite);
-            }
-            // Si la sortie est WebP, appliquer la qualité
-            if output.to_lowercase().ends_with(".webp") {
-                return sauvegarder_webp(&img, output, qualite);
-            }
-            // Formats lossless (PNG, TIFF, etc.) : sauvegarde directe
-            let ok = img.save(output).is_ok();
-            if !ok {
-                crate::log_error(&format!("pic::compresser échec save | {:?} -> {}", input, output));
-            }
+            let ok = sauvegarder_image_tons(&img, output, qualite, tons);
+            if ok { reporter_metadonnees(input, output, true, orientation_auto, profil); }
             ok
         },
         Err(e) => {
@@ -99,6 +86,40 @@ pub fn compresser(input: &Path, output: &str, qualite: u32) -> bool {
     }
 }
 
+/// Encode une image décodée selon l'extension de sortie (qualité 1-10 pour JPEG/WebP).
+/// Une image flottante écrite en 8 bits passe par le mappage tonal par défaut.
+pub fn sauvegarder_image(img: &image::DynamicImage, output: &str, qualite: u32) -> bool {
+    sauvegarder_image_tons(img, output, qualite, &OptionsTonales::DEFAUT)
+}
+
+/// Comme `sauvegarder_image`, avec le mappage tonal `tons`
+fn sauvegarder_image_tons(img: &image::DynamicImage, output: &str, qualite: u32, tons: &OptionsTonales) -> bool {
+    let img = &*adapter_sortie(img, output, tons);
+    let output_lower = output.to_lowercase();
+    // Si la sortie est JXL, encoder via zune-jpegxl
+    if output_lower.ends_with(".jxl") {
+        return encoder_jxl(img, output);
+    }
+    // Si la sortie est ICO, passer par le convertisseur dédié
+    if output_lower.ends_with(".ico") {
+        return convertir_ico_sizes(img, output, &[256]);
+    }
+    // Si la sortie est JPEG, appliquer la qualité
+    if output_lower.ends_with(".jpg") || output_lower.ends_with(".jpeg") {
+        return sauvegarder_jpeg(img, output, qualite);
+    }
+    // Si la sortie est WebP, appliquer la qualité
+    if output_lower.ends_with(".webp") {
+        return sauvegarder_webp(img, output, qualite);
+    }
+    // Formats lossless (PNG, TIFF, etc.) : sauvegarde directe
+    let ok = img.save(output).is_ok();
+    if !ok {
+        crate::log_error(&format!("pic::sauvegarder_image échec save | {}", output));
+    }
+    ok
+}
+
 /// Sauvegarde JPEG avec qualité (1-10 → 10-100%)
 fn sauvegarder_jpeg(img: &image::DynamicImage, output: &str, qualite: u32) -> bool {
     use image::codecs::jpeg::JpegEncoder;
@@ -221,7 +242,7 @@ fn convertir_ico_sizes(img: &image::DynamicImage, output: &str, sizes: &[u32]) -
 /// Point d'entrée public pour la conversion ICO multi-tailles.
 /// `sizes` contient les tailles demandées (ex: [16, 32, 64, 256]).
 /// Génère un fichier .ico avec toutes les tailles.
-pub fn generer_ico_multi(input: &Path, output: &str, sizes: &[u32]) -> bool {
+pub fn generer_ico_multi(input: &Path, output: &str, sizes: &[u32], orientation_auto: bool) -> bool {
     crate::log_info(&format!("pic::generer_ico_multi | sizes={:?} | {:?} -> {}", sizes, input, output));
 
     // Ouvrir selon le format d'entrée
@@ -253,23 +274,205 @@ pub fn generer_ico_multi(input: &Path, output: &str, sizes: &[u32]) -> bool {
                 match decoder_jxl(input) { Some(img) => img, None => return false }
             }
             _ => {
-                match image::open(input) { Ok(img) => img, Err(_) => return false }
+                match ouvrir_image(input, orientation_auto) { Ok(img) => img, Err(_) => return false }
             }
         }
     } else {
-        match image::open(input) { Ok(img) => img, Err(_) => return false }
+        match ouvrir_image(input, orientation_auto) { Ok(img) => img, Err(_) => return false }
     };
 
     convertir_ico_sizes(&img, output, sizes)
 }
 
+// ════════════════════════════════════════════════════════════════════════
+//  JEU D'ICÔNES — favicons, Apple touch, Android/PWA, manifeste web, ICNS
+// ════════════════════════════════════════════════════════════════════════
+
+/// Côté de l'image maîtresse dont dérivent toutes les tailles
+const COTE_ICONE_MAITRESSE: u32 = 1024;
+
+/// Entrées PNG d'un fichier ICNS : type OSType et côté en pixels (les types @2x comptent en pixels réels)
+const ENTREES_ICNS: [(&[u8; 4], u32); 11] = [
+    (b"icp4", 16), (b"icp5", 32), (b"icp6", 64), (b"ic07", 128), (b"ic08", 256), (b"ic09", 512),
+    (b"ic10", 1024), (b"ic11", 32), (b"ic12", 64), (b"ic13", 256), (b"ic14", 512),
+];
+
+/// Options du jeu d'icônes
+#[derive(Clone, Debug, PartialEq)]
+pub struct OptionsIcones {
+    /// Marge autour du motif, en % du côté (0-40)
+    pub marge: f32,
+    /// Fond RGBA ; alpha 0 = transparent. Apple touch et icône maskable sont toujours opaques.
+    pub fond: [u8; 4],
+    /// Nom de l'application dans le manifeste (nom du fichier source si vide)
+    pub nom: String,
+    /// Nom court sous l'icône (nom complet si vide)
+    pub nom_court: String,
+    /// Couleur du thème (barre d'adresse, écran de lancement)
+    pub couleur_theme: [u8; 4],
+    /// Préfixe des URL dans le manifeste et l'extrait HTML
+    pub prefixe_url: String,
+}
+
+impl Default for OptionsIcones {
+    fn default() -> Self {
+        OptionsIcones {
+            marge: 0.0,
+            fond: [0, 0, 0, 0],
+            nom: String::new(),
+            nom_court: String::new(),
+            couleur_theme: [255, 255, 255, 255],
+            prefixe_url: "/".into(),
+        }
+    }
+}
+
+/// Rasterise un SVG de sorte que son plus grand côté mesure `cote` pixels
+fn rasteriser_svg(input: &Path, cote: u32) -> Result<image::DynamicImage, String> {
+    let data = std::fs::read(input).map_err(|e| format!("Lecture {:?} : {}", input, e))?;
+    let tree = resvg::usvg::Tree::from_data(&data, &resvg::usvg::Options::default())
+        .map_err(|e| format!("SVG illisible : {}", e))?;
+    let taille = tree.size();
+    let echelle = cote as f32 / taille.width().max(taille.height());
+    let (w, h) = ((taille.width() * echelle).round().max(1.0) as u32, (taille.height() * echelle).round().max(1.0) as u32);
+    let mut pixmap = resvg::tiny_skia::Pixmap::new(w, h).ok_or("Dimensions SVG invalides")?;
+    resvg::render(&tree, resvg::tiny_skia::Transform::from_scale(echelle, echelle), &mut pixmap.as_mut());
+    // tiny-skia travaille en alpha prémultiplié
+    let rgba = pixmap.pixels().iter().flat_map(|p| { let c = p.demultiply(); [c.red(), c.green(), c.blue(), c.alpha()] }).collect();
+    image::RgbaImage::from_raw(w, h, rgba).map(image::DynamicImage::ImageRgba8).ok_or_else(|| "Rendu SVG incohérent".into())
+}
+
+/// Place l'image centrée, sans déformation, dans un carré `cote` × `cote` avec marge (%) et fond
+fn icone_carree(img: &image::DynamicImage, cote: u32, marge: f32, fond: [u8; 4]) -> image::RgbaImage {
+    let utile = ((cote as f32 * (1.0 - 2.0 * marge.clamp(0.0, 40.0) / 100.0)).round() as u32).max(1);
+    let motif = img.resize(utile, utile, FilterType::Lanczos3).to_rgba8();
+    let mut carre = image::RgbaImage::from_pixel(cote, cote, image::Rgba(fond));
+    image::imageops::overlay(&mut carre, &motif, ((cote - motif.width()) / 2) as i64, ((cote - motif.height()) / 2) as i64);
+    carre
+}
+
+fn encoder_png(img: &image::RgbaImage) -> Result<Vec<u8>, String> {
+    let mut png = Vec::new();
+    image::codecs::png::PngEncoder::new(&mut png)
+        .write_image(img.as_raw(), img.width(), img.height(), image::ExtendedColorType::Rgba8)
+        .map_err(|e| format!("Encodage PNG {}x{} : {}", img.width(), img.height(), e))?;
+    Ok(png)
+}
+
+/// Fichier ICNS macOS : en-tête "icns", puis une entrée PNG par type (OSType, longueur, données)
+pub fn encoder_icns(maitresse: &image::RgbaImage) -> Result<Vec<u8>, String> {
+    // Une même taille sert à plusieurs types (ic11 = icp5, ic13 = ic08…) : encodée une seule fois
+    let mut pngs: std::collections::BTreeMap<u32, Vec<u8>> = std::collections::BTreeMap::new();
+    let mut entrees = Vec::new();
+    for (type_icone, cote) in ENTREES_ICNS {
+        let png = match pngs.get(&cote) {
+            Some(png) => png,
+            None if cote == maitresse.width() => pngs.entry(cote).or_insert(encoder_png(maitresse)?),
+            None => pngs.entry(cote).or_insert(encoder_png(&image::imageops::resize(maitresse, cote, cote, FilterType::Lanczos3))?),
+        };
+        entrees.extend_from_slice(type_icone);
+        entrees.extend_from_slice(&(png.len() as u32 + 8).to_be_bytes());
+        entrees.extend_from_slice(png);
+    }
+    let mut icns = b"icns".to_vec();
+    icns.extend_from_slice(&(entrees.len() as u32 + 8).to_be_bytes());
+    icns.extend(entrees);
+    Ok(icns)
+}
+
+fn hex_couleur(c: [u8; 4]) -> String {
+    format!("#{:02x}{:02x}{:02x}", c[0], c[1], c[2])
+}
+
+/// Génère un jeu d'icônes complet dans `dossier` à partir d'une image ou d'un SVG :
+/// favicon.ico (16, 32, 48), favicons PNG, apple-touch-icon, tailles Android/PWA (dont une maskable),
+/// icon.icns, site.webmanifest et icons.html (balises à copier dans le `<head>`).
+/// Renvoie les fichiers écrits.
+pub fn generer_jeu_icones(input: &Path, dossier: &Path, options: &OptionsIcones, orientation_auto: bool) -> Result<Vec<PathBuf>, String> {
+    crate::log_info(&format!("pic::generer_jeu_icones | {:?} | {:?} -> {:?}", options, input, dossier));
+    let est_svg = input.extension().is_some_and(|e| e.eq_ignore_ascii_case("svg"));
+    let source = if est_svg { rasteriser_svg(input, COTE_ICONE_MAITRESSE)? } else { charger_image(input, orientation_auto)? };
+    std::fs::create_dir_all(dossier).map_err(|e| format!("Création {:?} : {}", dossier, e))?;
+
+    let maitresse = icone_carree(&source, COTE_ICONE_MAITRESSE, options.marge, options.fond);
+    // Fonds opaques : iOS remplit la transparence en noir, les masques Android découpent le fond
+    let opaque = if options.fond[3] == 0 { [255, 255, 255, 255] } else { [options.fond[0], options.fond[1], options.fond[2], 255] };
+    let maitresse_opaque = icone_carree(&source, COTE_ICONE_MAITRESSE, options.marge, opaque);
+    // Icône maskable : motif dans la zone sûre (cercle de 80 % du côté)
+    let maskable = icone_carree(&source, COTE_ICONE_MAITRESSE, options.marge.max(20.0), opaque);
+
+    let ico = dossier.join("favicon.ico");
+    if !convertir_ico_sizes(&image::DynamicImage::ImageRgba8(maitresse.clone()), &ico.to_string_lossy(), &[16, 32, 48]) {
+        return Err("Écriture de favicon.ico impossible".into());
+    }
+    let mut ecrits = vec![ico];
+    let mut ecrire = |nom: &str, octets: &[u8]| -> Result<(), String> {
+        let chemin = dossier.join(nom);
+        std::fs::write(&chemin, octets).map_err(|e| format!("Écriture {:?} : {}", chemin, e))?;
+        ecrits.push(chemin);
+        Ok(())
+    };
+    let png = |img: &image::RgbaImage, cote: u32| encoder_png(&image::imageops::resize(img, cote, cote, FilterType::Lanczos3));
+    for cote in [16, 32, 48] {
+        ecrire(&format!("favicon-{0}x{0}.png", cote), &png(&maitresse, cote)?)?;
+    }
+    ecrire("apple-touch-icon.png", &png(&maitresse_opaque, 180)?)?;
+    for cote in [192, 512] {
+        ecrire(&format!("android-chrome-{0}x{0}.png", cote), &png(&maitresse, cote)?)?;
+    }
+    ecrire("maskable-icon-512x512.png", &png(&maskable, 512)?)?;
+    ecrire("icon.icns", &encoder_icns(&maitresse)?)?;
+    if est_svg {
+        ecrire("favicon.svg", &std::fs::read(input).map_err(|e| e.to_string())?)?;
+    }
+
+    let prefixe = if options.prefixe_url.ends_with('/') { options.prefixe_url.clone() } else { format!("{}/", options.prefixe_url) };
+    let nom = if options.nom.trim().is_empty() {
+        input.file_stem().unwrap_or_default().to_string_lossy().to_string()
+    } else {
+        options.nom.trim().to_string()
+    };
+    let nom_court = if options.nom_court.trim().is_empty() { nom.clone() } else { options.nom_court.trim().to_string() };
+    let theme = hex_couleur(options.couleur_theme);
+    let manifeste = serde_json::json!({
+        "name": nom,
+        "short_name": nom_court,
+        "icons": [
+            { "src": format!("{}android-chrome-192x192.png", prefixe), "sizes": "192x192", "type": "image/png" },
+            { "src": format!("{}android-chrome-512x512.png", prefixe), "sizes": "512x512", "type": "image/png" },
+            { "src": format!("{}maskable-icon-512x512.png", prefixe), "sizes": "512x512", "type": "image/png", "purpose": "maskable" },
+        ],
+        "theme_color": theme,
+        "background_color": hex_couleur(opaque),
+        "display": "standalone",
+    });
+    let manifeste = serde_json::to_string_pretty(&manifeste).map_err(|e| e.to_string())?;
+    ecrire("site.webmanifest", format!("{}\n", manifeste).as_bytes())?;
+
+    let mut html = vec![format!(r#"<link rel="icon" href="{}favicon.ico" sizes="48x48">"#, prefixe)];
+    if est_svg {
+        html.push(format!(r#"<link rel="icon" href="{}favicon.svg" type="image/svg+xml">"#, prefixe));
+    }
+    html.extend([
+        format!(r#"<link rel="icon" type="image/png" sizes="32x32" href="{}favicon-32x32.png">"#, prefixe),
+        format!(r#"<link rel="icon" type="image/png" sizes="16x16" href="{}favicon-16x16.png">"#, prefixe),
+        format!(r#"<link rel="apple-touch-icon" sizes="180x180" href="{}apple-touch-icon.png">"#, prefixe),
+        format!(r#"<link rel="manifest" href="{}site.webmanifest">"#, prefixe),
+        format!(r#"<meta name="theme-color" content="{}">"#, theme),
+    ]);
+    ecrire("icons.html", format!("{}\n", html.join("\n")).as_bytes())?;
+
+    crate::log_info(&format!("pic::generer_jeu_icones OK | {} fichiers", ecrits.len()));
+    Ok(ecrits)
+}
+
 /// Conversion de format (ex: PNG -> JPG, WEBP -> PNG)
-pub fn convertir(input: &Path, output: &str) -> bool {
+pub fn convertir(input: &Path, output: &str, orientation_auto: bool, profil: &ProfilCible, tons: &OptionsTonales) -> bool {
     // Détection du format d'entrée
     if let Some(ext) = input.extension().and_then(|e| e.to_str()) {
         match ext.to_lowercase().as_str() {
             "svg" => return convertir_svg(input, output),
-            "psd" => return convertir_psd(input, output),
+            "psd" => return convertir_psd(input, output, profil),
             "jxl" => return convertir_jxl(input, output),
             "dng" | "cr2" | "nef" | "arw" | "orf" | "rw2" => {
                 crate::log_warn(&format!("pic::convertir format RAW non supporté pour {:?}", input));
@@ -278,10 +481,14 @@ pub fn convertir(input: &Path, output: &str) -> bool {
             _ => {}
         }
     }
+    if let Some(ok) = traiter_animation(input, output, Ok) {
+        return ok;
+    }
 
     // Format standard
-    match image::open(input) {
+    match ouvrir_image_profil(input, orientation_auto, profil) {
         Ok(img) => {
+            let img = adapter_sortie(&img, output, tons);
             // Si la sortie est JXL, encoder via zune-jpegxl
             if output.to_lowercase().ends_with(".jxl") {
                 return encoder_jxl(&img, output);
@@ -290,7 +497,9 @@ pub fn convertir(input: &Path, output: &str) -> bool {
             if output.to_lowercase().ends_with(".ico") {
                 return convertir_ico_sizes(&img, output, &[256]);
             }
-            img.save(output).is_ok()
+            let ok = img.save(output).is_ok();
+            if ok { reporter_metadonnees(input, output, true, orientation_auto, profil); }
+            ok
         },
         Err(_) => false,
     }
@@ -317,7 +526,8 @@ pub fn lire_exif(input: &Path) -> Vec<String> {
 
 /// Supprime l'EXIF en réenregistrant les pixels nus
 pub fn supprimer_exif(input: &Path, output: &str) -> bool {
-    if let Ok(img) = image::open(input) {
+    // Le tag Orientation disparaît avec l'EXIF : le sens d'affichage passe dans les pixels
+    if let Ok(img) = ouvrir_image(input, true) {
         // Enregistre uniquement les données de pixels, ignore les métadonnées sources
         img.save(output).is_ok()
     } else {
@@ -325,317 +535,3431 @@ pub fn supprimer_exif(input: &Path, output: &str) -> bool {
     }
 }
 
-/// Rotation simple (90, 180, 270)
-pub fn pivoter(input: &Path, output: &str, angle: u32) -> bool {
-    crate::log_info(&format!("pic::pivoter | angle={} | {:?} -> {}", angle, input, output));
-    match image::open(input) {
-        Ok(img) => {
-            let rotated = match angle {
-                90 => img.rotate90(),
-                180 => img.rotate180(),
-                270 => img.rotate270(),
-                _ => {
-                    crate::log_warn(&format!("pic::pivoter angle invalide {} pour {:?}, image non modifiée", angle, input));
-                    img
-                }
-            };
-            let ok = rotated.save(output).is_ok();
-            if !ok { crate::log_error(&format!("pic::pivoter échec save {:?}", output)); }
-            ok
-        },
-        Err(e) => {
-            crate::log_error(&format!("pic::pivoter impossible d'ouvrir {:?} : {}", input, e));
-            false
-        }
+// ════════════════════════════════════════════════════════════════════════
+//  MÉTADONNÉES — EXIF, XMP, ICC, IPTC (JPEG, PNG, WebP)
+// ════════════════════════════════════════════════════════════════════════
+
+const JPEG_EXIF: &[u8] = b"Exif\0\0";
+const JPEG_XMP: &[u8] = b"http://ns.adobe.com/xap/1.0/\0";
+const JPEG_ICC: &[u8] = b"ICC_PROFILE\0";
+const JPEG_IPTC: &[u8] = b"Photoshop 3.0\0";
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+const PNG_MOT_CLE_XMP: &[u8] = b"XML:com.adobe.xmp";
+/// Charge utile maximale d'un segment JPEG (longueur sur 16 bits, marqueur exclu)
+const JPEG_SEGMENT_MAX: usize = 65533;
+
+/// Métadonnées reportables d'un fichier image à l'autre
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Metadonnees {
+    /// Bloc EXIF brut (en-tête TIFF inclus)
+    pub exif: Option<Vec<u8>>,
+    /// Paquet XMP (XML)
+    pub xmp: Option<Vec<u8>>,
+    /// Profil ICC
+    pub icc: Option<Vec<u8>>,
+    /// Ressource Photoshop IPTC (JPEG uniquement)
+    pub iptc: Option<Vec<u8>>,
+}
+
+impl Metadonnees {
+    pub fn est_vide(&self) -> bool {
+        self.exif.is_none() && self.xmp.is_none() && self.icc.is_none() && self.iptc.is_none()
+    }
+
+    /// Poids approximatif une fois écrites (en-têtes de segments compris)
+    pub fn taille(&self) -> usize {
+        [&self.exif, &self.xmp, &self.icc, &self.iptc]
+            .iter()
+            .filter_map(|b| b.as_ref())
+            .map(|b| b.len() + 64)
+            .sum()
     }
 }
 
-/// Recadrage d'image (crop) - coordonnées en pourcentage de l'image
-/// x, y = coin supérieur gauche (0-100)
-/// width, height = dimensions du crop (0-100)
-pub fn recadrer(input: &Path, output: &str, x_pct: u32, y_pct: u32, width_pct: u32, height_pct: u32) -> bool {
-    crate::log_info(&format!("pic::recadrer | x={}% y={}% w={}% h={}% | {:?} -> {}", x_pct, y_pct, width_pct, height_pct, input, output));
-    match image::open(input) {
-        Ok(img) => {
-            let (img_w, img_h) = (img.width(), img.height());
-            
-            // Convertir pourcentages en pixels
-            let x = (img_w * x_pct) / 100;
-            let y = (img_h * y_pct) / 100;
-            let width = (img_w * width_pct) / 100;
-            let height = (img_h * height_pct) / 100;
-            
-            // Vérifier les limites
-            if x + width > img_w || y + height > img_h {
-                crate::log_error(&format!(
-                    "pic::recadrer crop hors limites | image={}x{} | crop x={} y={} w={} h={} | {:?}",
-                    img_w, img_h, x, y, width, height, input
-                ));
-                return false;
-            }
-            
-            let cropped = img.crop_imm(x, y, width, height);
-            let ok = cropped.save(output).is_ok();
-            if !ok { crate::log_error(&format!("pic::recadrer échec save {:?}", output)); }
-            ok
-        },
-        Err(e) => {
-            crate::log_error(&format!("pic::recadrer impossible d'ouvrir {:?} : {}", input, e));
-            false
-        }
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Conteneur {
+    Jpeg,
+    Png,
+    Webp,
+}
+
+fn detecter_conteneur(data: &[u8]) -> Option<Conteneur> {
+    if data.starts_with(&[0xFF, 0xD8]) {
+        Some(Conteneur::Jpeg)
+    } else if data.starts_with(PNG_SIGNATURE) {
+        Some(Conteneur::Png)
+    } else if data.len() >= 12 && &data[..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+        Some(Conteneur::Webp)
+    } else {
+        None
     }
 }
 
-/// Redimensionne à une largeur/hauteur spécifique en pixels
-pub fn redimensionner_pixels(input: &Path, output: &str, target_width: u32, target_height: u32) -> bool {
-    crate::log_info(&format!("pic::redimensionner_pixels | {}x{} | {:?} -> {}", target_width, target_height, input, output));
-    match image::open(input) {
-        Ok(img) => {
-            let resized = img.resize_exact(target_width, target_height, FilterType::Lanczos3);
-            let ok = resized.save(output).is_ok();
-            if !ok { crate::log_error(&format!("pic::redimensionner_pixels échec save {:?}", output)); }
-            ok
-        },
-        Err(e) => {
-            crate::log_error(&format!("pic::redimensionner_pixels impossible d'ouvrir {:?} : {}", input, e));
-            false
+/// Segments d'en-tête JPEG (marqueur, plage complète du segment) jusqu'au SOS exclu
+fn segments_jpeg(data: &[u8]) -> Option<Vec<(u8, std::ops::Range<usize>)>> {
+    let mut segments = Vec::new();
+    let mut i = 2;
+    loop {
+        if i + 4 > data.len() || data[i] != 0xFF { return None; }
+        let marqueur = data[i + 1];
+        if marqueur == 0xFF { i += 1; continue; }
+        if marqueur == 0xDA || marqueur == 0xD9 { return Some(segments); }
+        if marqueur == 0x01 || (0xD0..=0xD7).contains(&marqueur) { i += 2; continue; }
+        let fin = i + 2 + u16::from_be_bytes([data[i + 2], data[i + 3]]) as usize;
+        if fin > data.len() { return None; }
+        segments.push((marqueur, i..fin));
+        i = fin;
+    }
+}
+
+/// Nature d'un segment APPn porteur de métadonnées
+fn segment_metadonnee_jpeg(marqueur: u8, charge: &[u8]) -> Option<&'static [u8]> {
+    [(0xE1, JPEG_EXIF), (0xE1, JPEG_XMP), (0xE2, JPEG_ICC), (0xED, JPEG_IPTC)]
+        .into_iter()
+        .find(|(m, sig)| *m == marqueur && charge.starts_with(sig))
+        .map(|(_, sig)| sig)
+}
+
+fn lire_metadonnees_jpeg(data: &[u8]) -> Metadonnees {
+    let mut meta = Metadonnees::default();
+    let mut icc: Vec<(u8, &[u8])> = Vec::new();
+    for (marqueur, plage) in segments_jpeg(data).unwrap_or_default() {
+        let charge = &data[plage.start + 4..plage.end];
+        match segment_metadonnee_jpeg(marqueur, charge) {
+            Some(JPEG_EXIF) if meta.exif.is_none() => meta.exif = Some(charge[JPEG_EXIF.len()..].to_vec()),
+            Some(JPEG_XMP) if meta.xmp.is_none() => meta.xmp = Some(charge[JPEG_XMP.len()..].to_vec()),
+            Some(JPEG_ICC) if charge.len() > JPEG_ICC.len() + 2 => icc.push((charge[JPEG_ICC.len()], &charge[JPEG_ICC.len() + 2..])),
+            Some(JPEG_IPTC) if meta.iptc.is_none() => meta.iptc = Some(charge.to_vec()),
+            _ => {}
         }
     }
+    if !icc.is_empty() {
+        icc.sort_by_key(|(numero, _)| *numero);
+        meta.icc = Some(icc.into_iter().flat_map(|(_, morceau)| morceau.iter().copied()).collect());
+    }
+    meta
 }
 
-/// Redimensionne pour atteindre un poids maximum (en Ko)
-/// Réduit progressivement jusqu'à atteindre le poids cible
-pub fn redimensionner_poids(input: &Path, output: &str, max_size_kb: u32) -> bool {
-    crate::log_info(&format!("pic::redimensionner_poids | max={}Ko | {:?} -> {}", max_size_kb, input, output));
-    let img = match image::open(input) {
-        Ok(i) => i,
-        Err(e) => {
-            crate::log_error(&format!("pic::redimensionner_poids impossible d'ouvrir {:?} : {}", input, e));
-            return false;
+fn segment_jpeg(marqueur: u8, parties: &[&[u8]]) -> Vec<u8> {
+    let longueur: usize = parties.iter().map(|p| p.len()).sum::<usize>() + 2;
+    let mut seg = vec![0xFF, marqueur];
+    seg.extend_from_slice(&(longueur as u16).to_be_bytes());
+    for p in parties { seg.extend_from_slice(p); }
+    seg
+}
+
+fn ecrire_metadonnees_jpeg(data: &[u8], meta: &Metadonnees) -> Result<Vec<u8>, String> {
+    let segments = segments_jpeg(data).ok_or("JPEG illisible")?;
+    let mut nouveaux: Vec<Vec<u8>> = Vec::new();
+    for (sig, bloc) in [(JPEG_EXIF, &meta.exif), (JPEG_XMP, &meta.xmp)] {
+        if let Some(bloc) = bloc {
+            if bloc.len() + sig.len() > JPEG_SEGMENT_MAX {
+                crate::log_warn(&format!("pic::metadonnees bloc {} trop grand pour un segment JPEG ({} octets), ignoré", String::from_utf8_lossy(&sig[..4]), bloc.len()));
+                continue;
+            }
+            nouveaux.push(segment_jpeg(0xE1, &[sig, bloc]));
         }
-    };
-    
-    let (orig_w, orig_h) = (img.width(), img.height());
-    let max_size_bytes = max_size_kb as u64 * 1024;
-    
-    // Essayer différents ratios jusqu'à obtenir la taille voulue
-    for ratio in 1..=10 {
-        let new_w = orig_w / ratio;
-        let new_h = orig_h / ratio;
-        
-        if new_w < 10 || new_h < 10 {
-            crate::log_warn(&format!("pic::redimensionner_poids trop petit à ratio={} ({}x{}) pour {:?}", ratio, new_w, new_h, input));
-            break;
-        }
-        
-        let resized = img.resize(new_w, new_h, FilterType::Lanczos3);
-        
-        if resized.save(output).is_ok() {
-            if let Ok(metadata) = std::fs::metadata(output) {
-                crate::log_info(&format!("pic::redimensionner_poids ratio={} -> {}Ko (cible={}Ko)", ratio, metadata.len() / 1024, max_size_kb));
-                if metadata.len() <= max_size_bytes {
-                    return true;
-                }
+    }
+    if let Some(icc) = &meta.icc {
+        let morceaux: Vec<&[u8]> = icc.chunks(JPEG_SEGMENT_MAX - JPEG_ICC.len() - 2).collect();
+        if morceaux.len() <= 255 {
+            for (i, morceau) in morceaux.iter().enumerate() {
+                nouveaux.push(segment_jpeg(0xE2, &[JPEG_ICC, &[i as u8 + 1, morceaux.len() as u8], morceau]));
             }
         }
     }
-    
-    crate::log_error(&format!("pic::redimensionner_poids impossible d'atteindre {}Ko pour {:?}", max_size_kb, input));
-    false
+    if let Some(iptc) = meta.iptc.as_ref().filter(|b| b.len() <= JPEG_SEGMENT_MAX) {
+        nouveaux.push(segment_jpeg(0xED, &[iptc]));
+    }
+    // Insertion après SOI et l'éventuel APP0 (JFIF), anciens blocs retirés
+    let insertion = segments.iter().take_while(|(m, _)| *m == 0xE0).last().map_or(2, |(_, p)| p.end);
+    let mut sortie = Vec::with_capacity(data.len() + meta.taille());
+    sortie.extend_from_slice(&data[..insertion]);
+    for seg in &nouveaux { sortie.extend_from_slice(seg); }
+    let mut curseur = insertion;
+    for (marqueur, plage) in segments.iter().filter(|(_, p)| p.start >= insertion) {
+        if segment_metadonnee_jpeg(*marqueur, &data[plage.start + 4..plage.end]).is_some() {
+            sortie.extend_from_slice(&data[curseur..plage.start]);
+            curseur = plage.end;
+        }
+    }
+    sortie.extend_from_slice(&data[curseur..]);
+    Ok(sortie)
 }
 
-// === FONCTIONS POUR FORMATS SPÉCIAUX ===
+/// Chunk PNG : (type, plage des données, plage complète)
+type ChunkPng = ([u8; 4], std::ops::Range<usize>, std::ops::Range<usize>);
 
-/// Conversion SVG vers format raster
-fn convertir_svg(input: &Path, output: &str) -> bool {
-    let mut file = match File::open(input) {
-        Ok(f) => f,
-        Err(_) => return false,
-    };
-    
-    let mut svg_data = Vec::new();
-    if file.read_to_end(&mut svg_data).is_err() {
-        return false;
+fn chunks_png(data: &[u8]) -> Option<Vec<ChunkPng>> {
+    let mut chunks = Vec::new();
+    let mut i = PNG_SIGNATURE.len();
+    while i + 12 <= data.len() {
+        let longueur = u32::from_be_bytes(data[i..i + 4].try_into().ok()?) as usize;
+        let genre: [u8; 4] = data[i + 4..i + 8].try_into().ok()?;
+        let fin = i + 12 + longueur;
+        if fin > data.len() { return None; }
+        chunks.push((genre, i + 8..i + 8 + longueur, i..fin));
+        if &genre == b"IEND" { break; }
+        i = fin;
     }
+    Some(chunks)
+}
 
-    let opt = resvg::usvg::Options::default();
-    let tree = match resvg::usvg::Tree::from_data(&svg_data, &opt) {
-        Ok(t) => t,
-        Err(_) => return false,
-    };
+fn decompresser_zlib(data: &[u8]) -> Option<Vec<u8>> {
+    let mut sortie = Vec::new();
+    flate2::read::ZlibDecoder::new(data).read_to_end(&mut sortie).ok()?;
+    Some(sortie)
+}
 
-    let size = tree.size();
-    let mut pixmap = match resvg::tiny_skia::Pixmap::new(size.width() as u32, size.height() as u32) {
-        Some(p) => p,
-        None => return false,
-    };
-    
-    resvg::render(&tree, resvg::tiny_skia::Transform::default(), &mut pixmap.as_mut());
+fn compresser_zlib(data: &[u8]) -> Vec<u8> {
+    use std::io::Write;
+    let mut enc = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+    let _ = enc.write_all(data);
+    enc.finish().unwrap_or_default()
+}
 
-    // Convertir en image::RgbaImage
-    let img = match image::RgbaImage::from_raw(
-        pixmap.width(),
-        pixmap.height(),
-        pixmap.data().to_vec(),
-    ) {
-        Some(i) => i,
-        None => return false,
-    };
+/// Texte XMP d'un chunk iTXt « XML:com.adobe.xmp »
+fn xmp_itxt(charge: &[u8]) -> Option<Vec<u8>> {
+    let reste = charge.strip_prefix(PNG_MOT_CLE_XMP)?.strip_prefix(b"\0")?;
+    let (compresse, reste) = (*reste.first()? == 1, reste.get(2..)?);
+    // Langue puis mot-clé traduit, tous deux terminés par NUL
+    let debut_langue = reste.iter().position(|&b| b == 0)? + 1;
+    let debut_texte = debut_langue + reste[debut_langue..].iter().position(|&b| b == 0)? + 1;
+    let texte = &reste[debut_texte..];
+    if compresse { decompresser_zlib(texte) } else { Some(texte.to_vec()) }
+}
 
-    image::DynamicImage::ImageRgba8(img).save(output).is_ok()
+fn lire_metadonnees_png(data: &[u8]) -> Metadonnees {
+    let mut meta = Metadonnees::default();
+    for (genre, plage, _) in chunks_png(data).unwrap_or_default() {
+        let charge = &data[plage];
+        match &genre {
+            b"eXIf" => meta.exif = Some(charge.to_vec()),
+            b"iCCP" => {
+                // Nom du profil, NUL, méthode de compression, flux zlib
+                meta.icc = charge.iter().position(|&b| b == 0).and_then(|n| charge.get(n + 2..)).and_then(decompresser_zlib);
+            }
+            b"iTXt" if meta.xmp.is_none() => meta.xmp = xmp_itxt(charge),
+            _ => {}
+        }
+    }
+    meta
 }
 
-/// Compression SVG (rasterise sans redimensionnement)
-fn compresser_svg(input: &Path, output: &str) -> bool {
-    convertir_svg(input, output)
+fn chunk_png(genre: &[u8; 4], donnees: &[u8]) -> Vec<u8> {
+    let mut crc = flate2::Crc::new();
+    crc.update(genre);
+    crc.update(donnees);
+    let mut chunk = Vec::with_capacity(donnees.len() + 12);
+    chunk.extend_from_slice(&(donnees.len() as u32).to_be_bytes());
+    chunk.extend_from_slice(genre);
+    chunk.extend_from_slice(donnees);
+    chunk.extend_from_slice(&crc.sum().to_be_bytes());
+    chunk
 }
 
-// === FONCTIONS POUR FORMAT JXL ===
+fn ecrire_metadonnees_png(data: &[u8], meta: &Metadonnees) -> Result<Vec<u8>, String> {
+    let chunks = chunks_png(data).ok_or("PNG illisible")?;
+    let mut nouveaux = Vec::new();
+    if let Some(icc) = &meta.icc {
+        nouveaux.extend(chunk_png(b"iCCP", &[b"ICC Profile\0\0".as_slice(), &compresser_zlib(icc)].concat()));
+    }
+    if let Some(exif) = &meta.exif {
+        nouveaux.extend(chunk_png(b"eXIf", exif));
+    }
+    if let Some(xmp) = &meta.xmp {
+        nouveaux.extend(chunk_png(b"iTXt", &[PNG_MOT_CLE_XMP, b"\0\0\0\0\0", xmp].concat()));
+    }
+    let mut sortie = Vec::with_capacity(data.len() + nouveaux.len());
+    sortie.extend_from_slice(PNG_SIGNATURE);
+    for (genre, plage, complet) in &chunks {
+        let remplace = match genre {
+            b"eXIf" | b"iCCP" => true,
+            // sRGB et iCCP sont exclusifs
+            b"sRGB" => meta.icc.is_some(),
+            b"iTXt" => data[plage.clone()].starts_with(PNG_MOT_CLE_XMP),
+            _ => false,
+        };
+        if !remplace { sortie.extend_from_slice(&data[complet.clone()]); }
+        if genre == b"IHDR" { sortie.extend_from_slice(&nouveaux); }
+    }
+    Ok(sortie)
+}
 
-/// Encode une DynamicImage en JXL via zune-jpegxl (lossless)
-fn encoder_jxl(img: &image::DynamicImage, output: &str) -> bool {
-    let rgba = img.to_rgba8();
-    let (w, h) = (rgba.width(), rgba.height());
-    let pixels = rgba.as_raw();
-    let opts = EncoderOptions::new(w as usize, h as usize, ColorSpace::RGBA, BitDepth::Eight);
-    let encoder = JxlSimpleEncoder::new(pixels, opts);
-    let mut buf: Vec<u8> = Vec::new();
-    match encoder.encode(&mut buf) {
-        Ok(_) => std::fs::write(output, &buf).is_ok(),
-        Err(e) => {
-            crate::log_error(&format!("pic::encoder_jxl échec encodage JXL : {:?}", e));
-            false
+/// Chunks RIFF d'un WebP : (fourcc, données)
+fn chunks_webp(data: &[u8]) -> Option<Vec<([u8; 4], &[u8])>> {
+    let mut chunks = Vec::new();
+    let mut i = 12;
+    while i + 8 <= data.len() {
+        let fourcc: [u8; 4] = data[i..i + 4].try_into().ok()?;
+        let taille = u32::from_le_bytes(data[i + 4..i + 8].try_into().ok()?) as usize;
+        let fin = i + 8 + taille;
+        if fin > data.len() { return None; }
+        chunks.push((fourcc, &data[i + 8..fin]));
+        i = fin + (taille & 1);
+    }
+    Some(chunks)
+}
+
+fn lire_metadonnees_webp(data: &[u8]) -> Metadonnees {
+    let mut meta = Metadonnees::default();
+    for (fourcc, charge) in chunks_webp(data).unwrap_or_default() {
+        match &fourcc {
+            // Certains encodeurs gardent le préfixe JPEG « Exif\0\0 »
+            b"EXIF" => meta.exif = Some(charge.strip_prefix(JPEG_EXIF).unwrap_or(charge).to_vec()),
+            b"XMP " => meta.xmp = Some(charge.to_vec()),
+            b"ICCP" => meta.icc = Some(charge.to_vec()),
+            _ => {}
         }
     }
+    meta
 }
 
-/// Décodage JXL vers DynamicImage via jxl-oxide
-fn decoder_jxl(input: &Path) -> Option<image::DynamicImage> {
-    let data = match std::fs::read(input) {
-        Ok(d) => d,
-        Err(e) => {
-            crate::log_error(&format!("pic::decoder_jxl impossible de lire {:?} : {}", input, e));
-            return None;
+/// Dimensions du canevas et présence d'alpha d'après le flux VP8/VP8L
+fn canevas_webp(chunks: &[([u8; 4], &[u8])]) -> Option<(u32, u32, bool)> {
+    let alpha = chunks.iter().any(|(f, _)| f == b"ALPH");
+    chunks.iter().find_map(|(fourcc, d)| match fourcc {
+        b"VP8X" if d.len() >= 10 => {
+            let lire24 = |o: usize| u32::from_le_bytes([d[o], d[o + 1], d[o + 2], 0]) + 1;
+            Some((lire24(4), lire24(7), d[0] & 0x10 != 0))
         }
-    };
-    let cursor = std::io::Cursor::new(data);
-    let decoder = match jxl_oxide::integration::JxlDecoder::new(cursor) {
-        Ok(d) => d,
-        Err(e) => {
-            crate::log_error(&format!("pic::decoder_jxl échec init décodeur {:?} : {}", input, e));
-            return None;
+        b"VP8L" if d.len() >= 5 && d[0] == 0x2F => {
+            let bits = u32::from_le_bytes([d[1], d[2], d[3], d[4]]);
+            Some(((bits & 0x3FFF) + 1, ((bits >> 14) & 0x3FFF) + 1, bits & (1 << 28) != 0))
         }
-    };
-    match image::DynamicImage::from_decoder(decoder) {
-        Ok(img) => Some(img),
-        Err(e) => {
-            crate::log_error(&format!("pic::decoder_jxl échec décodage {:?} : {}", input, e));
-            None
+        b"VP8 " if d.len() >= 10 => {
+            Some((u16::from_le_bytes([d[6], d[7]]) as u32 & 0x3FFF, u16::from_le_bytes([d[8], d[9]]) as u32 & 0x3FFF, alpha))
         }
+        _ => None,
+    })
+}
+
+/// Chunk RIFF (fourcc, taille, données, octet de bourrage si taille impaire)
+fn chunk_webp(corps: &mut Vec<u8>, fourcc: &[u8], charge: &[u8]) {
+    corps.extend_from_slice(fourcc);
+    corps.extend_from_slice(&(charge.len() as u32).to_le_bytes());
+    corps.extend_from_slice(charge);
+    if charge.len() % 2 == 1 { corps.push(0); }
+}
+
+/// Charge VP8X : drapeaux et dimensions du canevas
+fn vp8x_webp(drapeaux: u8, largeur: u32, hauteur: u32) -> Vec<u8> {
+    let mut vp8x = vec![drapeaux, 0, 0, 0];
+    vp8x.extend_from_slice(&(largeur - 1).to_le_bytes()[..3]);
+    vp8x.extend_from_slice(&(hauteur - 1).to_le_bytes()[..3]);
+    vp8x
+}
+
+/// Enveloppe RIFF/WEBP autour des chunks
+fn riff_webp(corps: &[u8]) -> Vec<u8> {
+    let mut sortie = Vec::with_capacity(corps.len() + 12);
+    sortie.extend_from_slice(b"RIFF");
+    sortie.extend_from_slice(&(corps.len() as u32 + 4).to_le_bytes());
+    sortie.extend_from_slice(b"WEBP");
+    sortie.extend_from_slice(corps);
+    sortie
+}
+
+fn ecrire_metadonnees_webp(data: &[u8], meta: &Metadonnees) -> Result<Vec<u8>, String> {
+    let chunks = chunks_webp(data).ok_or("WebP illisible")?;
+    let (largeur, hauteur, alpha) = canevas_webp(&chunks).ok_or("WebP : dimensions introuvables")?;
+    let anime = chunks.iter().any(|(f, _)| f == b"ANIM");
+    let mut corps: Vec<u8> = Vec::new();
+    let mut ajouter = |fourcc: &[u8], charge: &[u8]| chunk_webp(&mut corps, fourcc, charge);
+    let drapeaux = (meta.icc.is_some() as u8) << 5 | (alpha as u8) << 4 | (meta.exif.is_some() as u8) << 3
+        | (meta.xmp.is_some() as u8) << 2 | (anime as u8) << 1;
+    ajouter(b"VP8X", &vp8x_webp(drapeaux, largeur, hauteur));
+    if let Some(icc) = &meta.icc { ajouter(b"ICCP", icc); }
+    for (fourcc, charge) in chunks.iter().filter(|(f, _)| !matches!(f, b"VP8X" | b"ICCP" | b"EXIF" | b"XMP ")) {
+        ajouter(fourcc, charge);
     }
+    if let Some(exif) = &meta.exif { ajouter(b"EXIF", exif); }
+    if let Some(xmp) = &meta.xmp { ajouter(b"XMP ", xmp); }
+    Ok(riff_webp(&corps))
 }
 
-/// Conversion JXL vers format standard (PNG, JPG, etc.)
-fn convertir_jxl(input: &Path, output: &str) -> bool {
-    match decoder_jxl(input) {
-        Some(img) => {
-            if output.to_lowercase().ends_with(".jxl") {
-                return encoder_jxl(&img, output);
-            }
-            img.save(output).is_ok()
-        },
-        None => false,
+/// Lit les métadonnées EXIF/XMP/ICC/IPTC d'un JPEG, PNG ou WebP (vide pour les autres formats)
+pub fn lire_metadonnees(input: &Path) -> Metadonnees {
+    let Ok(data) = std::fs::read(input) else { return Metadonnees::default() };
+    match detecter_conteneur(&data) {
+        Some(Conteneur::Jpeg) => lire_metadonnees_jpeg(&data),
+        Some(Conteneur::Png) => lire_metadonnees_png(&data),
+        Some(Conteneur::Webp) => lire_metadonnees_webp(&data),
+        None => Metadonnees::default(),
     }
 }
 
-/// Compression JXL avec qualité (décode puis réencode sans resize)
+/// Remplace les métadonnées d'un fichier JPEG, PNG ou WebP existant, sans réencoder les pixels
+pub fn ecrire_metadonnees(output: &str, meta: &Metadonnees) -> Result<(), String> {
+    let data = std::fs::read(output).map_err(|e| format!("Lecture {} : {}", output, e))?;
+    let nouveau = match detecter_conteneur(&data) {
+        Some(Conteneur::Jpeg) => ecrire_metadonnees_jpeg(&data, meta)?,
+        Some(Conteneur::Png) => ecrire_metadonnees_png(&data, meta)?,
+        Some(Conteneur::Webp) => ecrire_metadonnees_webp(&data, meta)?,
+        None => return Err(format!("Métadonnées non prises en charge pour {}", output)),
+    };
+    std::fs::write(output, nouveau).map_err(|e| format!("Écriture {} : {}", output, e))
+}
+
+/// Réécrit un bloc EXIF en ne gardant que les champs acceptés.
+/// `dimensions` met à jour PixelXDimension/PixelYDimension ; la miniature n'est gardée que si demandé.
+fn filtrer_exif(
+    exif: &[u8],
+    garder: impl Fn(&exif::Field) -> bool,
+    miniature: bool,
+    dimensions: Option<(u32, u32)>,
+) -> Result<Option<Vec<u8>>, String> {
+    let donnees = exif::Reader::new().read_raw(exif.to_vec()).map_err(|e| format!("EXIF illisible : {}", e))?;
+    let mut champs: Vec<exif::Field> = donnees
+        .fields()
+        .filter(|f| !matches!(f.value, exif::Value::Unknown(..)))
+        .filter(|f| miniature || f.ifd_num != exif::In::THUMBNAIL)
+        .filter(|f| garder(f))
+        .cloned()
+        .collect();
+    if let Some((w, h)) = dimensions {
+        for champ in champs.iter_mut().filter(|f| f.ifd_num == exif::In::PRIMARY) {
+            match champ.tag {
+                exif::Tag::PixelXDimension => champ.value = exif::Value::Long(vec![w]),
+                exif::Tag::PixelYDimension => champ.value = exif::Value::Long(vec![h]),
+                _ => {}
+            }
+        }
+    }
+    // Miniature JPEG : octets désignés par JPEGInterchangeFormat(+Length) dans l'IFD1
+    let vignette = miniature.then(|| {
+        let entier = |tag| donnees.get_field(tag, exif::In::THUMBNAIL).and_then(|f| f.value.get_uint(0)).map(|v| v as usize);
+        let debut = entier(exif::Tag::JPEGInterchangeFormat)?;
+        let longueur = entier(exif::Tag::JPEGInterchangeFormatLength)?;
+        donnees.buf().get(debut..debut.checked_add(longueur)?)
+    }).flatten();
+    if !champs.iter().any(|f| f.ifd_num == exif::In::PRIMARY) {
+        return Ok(None);
+    }
+    let mut writer = exif::experimental::Writer::new();
+    for champ in &champs { writer.push_field(champ); }
+    if let Some(v) = vignette { writer.set_jpeg(v, exif::In::THUMBNAIL); }
+    let mut sortie = std::io::Cursor::new(Vec::new());
+    writer.write(&mut sortie, donnees.little_endian()).map_err(|e| format!("Écriture EXIF : {}", e))?;
+    Ok(Some(sortie.into_inner()))
+}
+
+/// Reporte les métadonnées de `input` sur `output` après un traitement des pixels.
+/// Les dimensions EXIF suivent la sortie ; la miniature n'est gardée que si elle reste fidèle.
+/// Si `redresse`, les pixels ont suivi l'EXIF Orientation : le tag est remis à 1
+/// et la miniature (restée dans l'ancien sens) est abandonnée.
+fn reporter_metadonnees(input: &Path, output: &str, garder_miniature: bool, redresse: bool, cible: &ProfilCible) {
+    let mut meta = lire_metadonnees(input);
+    if meta.est_vide() { return; }
+    let tournee = redresse && meta.exif.as_deref()
+        .and_then(Orientation::from_exif_chunk)
+        .is_some_and(|o| o != Orientation::NoTransforms);
+    let garder_miniature = garder_miniature && !tournee;
+    let ext = Path::new(output).extension().unwrap_or_default().to_string_lossy().to_lowercase();
+    if !matches!(ext.as_str(), "jpg" | "jpeg" | "png" | "webp") {
+        crate::log_warn(&format!("pic::reporter_metadonnees format {} : métadonnées de {:?} non reportées", ext, input));
+        return;
+    }
+    if ext != "jpg" && ext != "jpeg" { meta.iptc = None; }
+    meta.icc = meta.icc.take().and_then(|icc| icc_sortie(icc, cible));
+    if let Some(exif) = &meta.exif {
+        match filtrer_exif(exif, |_| true, garder_miniature, image::image_dimensions(output).ok()) {
+            Ok(filtre) => meta.exif = filtre,
+            Err(e) => crate::log_warn(&format!("pic::reporter_metadonnees {} : EXIF reporté tel quel", e)),
+        }
+    }
+    if tournee { reinitialiser_orientation(&mut meta); }
+    if let Err(e) = ecrire_metadonnees(output, &meta) {
+        crate::log_warn(&format!("pic::reporter_metadonnees {}", e));
+    }
+}
+
+/// Métadonnées à retirer lors d'un nettoyage sélectif
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OptionsNettoyageMeta {
+    /// Position GPS (EXIF et XMP)
+    pub gps: bool,
+    /// Numéros de série boîtier/objectif, propriétaire, MakerNote
+    pub numeros_serie: bool,
+    /// Miniature EXIF embarquée
+    pub miniature: bool,
+    /// Tout sauf copyright et orientation (le profil ICC est conservé)
+    pub sauf_essentiel: bool,
+}
+
+impl OptionsNettoyageMeta {
+    pub fn est_vide(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+fn est_numero_serie(tag: exif::Tag) -> bool {
+    use exif::Tag;
+    matches!(tag, Tag::BodySerialNumber | Tag::LensSerialNumber | Tag::CameraOwnerName | Tag::ImageUniqueID | Tag::MakerNote)
+}
+
+/// Retire les propriétés GPS d'un paquet XMP (attributs et éléments exif:GPS…)
+fn retirer_gps_xmp(xmp: &[u8]) -> Vec<u8> {
+    let texte = String::from_utf8_lossy(xmp);
+    let attributs = regex::Regex::new(r#"\s+exif:GPS\w+\s*=\s*"[^"]*""#).expect("regex GPS");
+    let elements = regex::Regex::new(r"(?s)<exif:(GPS\w+)\b[^>]*?(?:/>|>.*?</exif:GPS\w+>)").expect("regex GPS");
+    let sans_attributs = attributs.replace_all(&texte, "");
+    elements.replace_all(&sans_attributs, "").into_owned().into_bytes()
+}
+
+/// Applique un nettoyage sélectif à des métadonnées
+pub fn filtrer_metadonnees(meta: &Metadonnees, options: &OptionsNettoyageMeta) -> Metadonnees {
+    let mut resultat = meta.clone();
+    if options.sauf_essentiel {
+        resultat.xmp = None;
+        resultat.iptc = None;
+    } else if options.gps {
+        resultat.xmp = resultat.xmp.as_deref().map(retirer_gps_xmp);
+    }
+    if let Some(exif) = &meta.exif {
+        let garder = |f: &exif::Field| {
+            if options.sauf_essentiel {
+                return f.ifd_num == exif::In::PRIMARY && matches!(f.tag, exif::Tag::Copyright | exif::Tag::Orientation);
+            }
+            let retire = (options.gps && f.tag.context() == exif::Context::Gps)
+                || (options.numeros_serie && est_numero_serie(f.tag));
+            !retire
+        };
+        let miniature = !options.miniature && !options.sauf_essentiel;
+        resultat.exif = filtrer_exif(exif, garder, miniature, None).unwrap_or_else(|e| {
+            // Par prudence, un EXIF qu'on ne sait pas filtrer est retiré
+            crate::log_warn(&format!("pic::filtrer_metadonnees {} : EXIF retiré", e));
+            None
+        });
+    }
+    resultat
+}
+
+/// Nettoyage sélectif des métadonnées sans réencodage (JPEG, PNG, WebP).
+/// Sans option, retire toutes les métadonnées comme `supprimer_exif`.
+pub fn nettoyer_metadonnees(input: &Path, output: &str, options: &OptionsNettoyageMeta) -> bool {
+    crate::log_info(&format!("pic::nettoyer_metadonnees | {:?} | {:?} -> {}", options, input, output));
+    if options.est_vide() {
+        return supprimer_exif(input, output);
+    }
+    let data = match std::fs::read(input) {
+        Ok(d) => d,
+        Err(e) => {
+            crate::log_error(&format!("pic::nettoyer_metadonnees impossible de lire {:?} : {}", input, e));
+            return false;
+        }
+    };
+    let meme_format = Path::new(output).extension().map(|e| e.to_ascii_lowercase()) == input.extension().map(|e| e.to_ascii_lowercase());
+    if detecter_conteneur(&data).is_none() || !meme_format {
+        crate::log_error(&format!("pic::nettoyer_metadonnees format non pris en charge (JPEG, PNG, WebP, même extension) : {:?} -> {}", input, output));
+        return false;
+    }
+    let meta = filtrer_metadonnees(&lire_metadonnees(input), options);
+    if let Err(e) = std::fs::write(output, &data).map_err(|e| e.to_string()).and_then(|_| ecrire_metadonnees(output, &meta)) {
+        crate::log_error(&format!("pic::nettoyer_metadonnees {}", e));
+        return false;
+    }
+    true
+}
+
+// ════════════════════════════════════════════════════════════════════════
+//  MÉTADONNÉES — édition (date, auteur, copyright, description, mots-clés, GPS)
+// ════════════════════════════════════════════════════════════════════════
+
+const TAG_DESCRIPTION: u16 = 0x010E;
+const TAG_DATE_MODIF: u16 = 0x0132;
+const TAG_ARTISTE: u16 = 0x013B;
+const TAG_XMP_TIFF: u16 = 0x02BC;
+const TAG_COPYRIGHT: u16 = 0x8298;
+const TAG_IFD_EXIF: u16 = 0x8769;
+const TAG_IFD_GPS: u16 = 0x8825;
+const TAG_MOTS_CLES_XP: u16 = 0x9C9E;
+const TAG_DATE_ORIGINALE: u16 = 0x9003;
+const TAG_DATE_NUMERISATION: u16 = 0x9004;
+const FORMAT_DATE_EXIF: &str = "%Y:%m:%d %H:%M:%S";
+/// Bloc EXIF minimal (big-endian, IFD0 vide) servant de base quand la source n'en a pas
+const EXIF_VIDE: &[u8] = b"MM\0\x2a\0\0\0\x08\0\0\0\0\0\0";
+
+/// Modifications de métadonnées. Pour les champs texte, `Some("")` supprime le champ.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EditionMeta {
+    /// Date de prise de vue (DateTimeOriginal et DateTimeDigitized)
+    pub date: Option<chrono::NaiveDateTime>,
+    /// Décalage appliqué à toutes les dates (horloge d'appareil déréglée)
+    pub decalage: Option<chrono::TimeDelta>,
+    pub artiste: Option<String>,
+    pub copyright: Option<String>,
+    pub description: Option<String>,
+    /// Mots-clés ; une liste vide les supprime
+    pub mots_cles: Option<Vec<String>>,
+    /// Latitude, longitude en degrés décimaux ; `Some(None)` supprime la position
+    pub gps: Option<Option<(f64, f64)>>,
+}
+
+impl EditionMeta {
+    pub const CLES: [&'static str; 6] = ["date", "artist", "copyright", "description", "keywords", "gps"];
+
+    pub fn est_vide(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Applique une affectation « clé=valeur » (clés : `CLES`)
+    pub fn affecter(&mut self, affectation: &str) -> Result<(), String> {
+        let (cle, valeur) = affectation.split_once('=').ok_or_else(|| format!("Affectation invalide '{}' (attendu clé=valeur)", affectation))?;
+        let valeur = valeur.trim();
+        match cle.trim().to_lowercase().as_str() {
+            "date" => self.date = Some(parser_date_meta(valeur)?),
+            "artist" => self.artiste = Some(valeur.to_string()),
+            "copyright" => self.copyright = Some(valeur.to_string()),
+            "description" => self.description = Some(valeur.to_string()),
+            "keywords" => {
+                self.mots_cles = Some(valeur.split([',', ';']).map(|m| m.trim().to_string()).filter(|m| !m.is_empty()).collect());
+            }
+            "gps" if valeur.is_empty() => self.gps = Some(None),
+            "gps" => {
+                let coords = valeur.split(',').map(|v| v.trim().parse::<f64>()).collect::<Result<Vec<_>, _>>();
+                match coords.as_deref() {
+                    Ok([lat, lon]) if lat.abs() <= 90.0 && lon.abs() <= 180.0 => self.gps = Some(Some((*lat, *lon))),
+                    _ => return Err(format!("Coordonnées GPS invalides '{}' (attendu lat,lon en degrés décimaux)", valeur)),
+                }
+            }
+            autre => return Err(format!("Clé inconnue '{}' (attendu {})", autre, Self::CLES.join(", "))),
+        }
+        Ok(())
+    }
+
+    fn touche_xmp(&self) -> bool {
+        self.date.is_some() || self.decalage.is_some() || self.artiste.is_some() || self.copyright.is_some()
+            || self.description.is_some() || self.mots_cles.is_some() || self.gps.is_some()
+    }
+
+    /// Nouvelle valeur d'une date existante (date imposée ou décalage)
+    fn nouvelle_date(&self, ancienne: Option<chrono::NaiveDateTime>, prise_de_vue: bool) -> Option<chrono::NaiveDateTime> {
+        let base = if prise_de_vue { self.date.or(ancienne) } else { ancienne };
+        base.map(|d| self.decalage.map_or(d, |delta| d + delta))
+    }
+}
+
+fn parser_date_meta(texte: &str) -> Result<chrono::NaiveDateTime, String> {
+    [FORMAT_DATE_EXIF, "%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M", "%Y:%m:%d %H:%M"]
+        .iter()
+        .find_map(|f| chrono::NaiveDateTime::parse_from_str(texte.trim(), f).ok())
+        .ok_or_else(|| format!("Date invalide '{}' (attendu AAAA:MM:JJ HH:MM:SS)", texte))
+}
+
+/// Décalage horaire : « +1h30m », « -2d », « +45s » ou « -01:30[:00] »
+pub fn parser_decalage(texte: &str) -> Result<chrono::TimeDelta, String> {
+    let erreur = || format!("Décalage invalide '{}' (ex. +1h30m, -2d, -01:30)", texte);
+    let t = texte.trim();
+    let (signe, corps) = match t.strip_prefix('-') {
+        Some(reste) => (-1, reste),
+        None => (1, t.strip_prefix('+').unwrap_or(t)),
+    };
+    let secondes: i64 = if corps.contains(':') {
+        let parties: Vec<i64> = corps.split(':').map(|p| p.parse().map_err(|_| erreur())).collect::<Result<_, _>>()?;
+        match parties.as_slice() {
+            [h, m] => h * 3600 + m * 60,
+            [h, m, s] => h * 3600 + m * 60 + s,
+            _ => return Err(erreur()),
+        }
+    } else {
+        let mut total = 0;
+        let mut nombre = String::new();
+        for c in corps.chars() {
+            if c.is_ascii_digit() {
+                nombre.push(c);
+                continue;
+            }
+            let n: i64 = nombre.parse().map_err(|_| erreur())?;
+            nombre.clear();
+            total += n * match c {
+                'd' | 'j' => 86400,
+                'h' => 3600,
+                'm' => 60,
+                's' => 1,
+                _ => return Err(erreur()),
+            };
+        }
+        if !nombre.is_empty() || corps.is_empty() { return Err(erreur()); }
+        total
+    };
+    Ok(chrono::TimeDelta::seconds(signe * secondes))
+}
+
+/// Entrée brute d'un IFD TIFF (valeur en ligne ou décalage)
+#[derive(Clone, Copy, Debug)]
+struct EntreeIfd {
+    tag: u16,
+    genre: u16,
+    nombre: u32,
+    valeur: [u8; 4],
+}
+
+/// Valeur à écrire dans un IFD
+enum ValeurTiff {
+    Ascii(String),
+    Octets(Vec<u8>),
+    Rationnels(Vec<(u32, u32)>),
+    Long(u32),
+}
+
+fn taille_type_tiff(genre: u16) -> usize {
+    match genre {
+        3 | 8 => 2,
+        4 | 9 | 11 | 13 => 4,
+        5 | 10 | 12 => 8,
+        _ => 1,
+    }
+}
+
+/// Lecture/écriture TIFF par ajout : les IFD modifiés sont réécrits en fin de fichier,
+/// les données existantes (bandes, MakerNote…) ne bougent pas.
+struct EditeurTiff {
+    data: Vec<u8>,
+    le: bool,
+}
+
+impl EditeurTiff {
+    fn nouveau(data: Vec<u8>) -> Result<Self, String> {
+        let le = match data.get(..4) {
+            Some(b"II\x2a\0") => true,
+            Some(b"MM\0\x2a") => false,
+            _ => return Err("En-tête TIFF invalide".into()),
+        };
+        Ok(Self { data, le })
+    }
+
+    fn u16_a(&self, o: usize) -> Option<u16> {
+        let b: [u8; 2] = self.data.get(o..o + 2)?.try_into().ok()?;
+        Some(if self.le { u16::from_le_bytes(b) } else { u16::from_be_bytes(b) })
+    }
+
+    fn u32_a(&self, o: usize) -> Option<u32> {
+        let b: [u8; 4] = self.data.get(o..o + 4)?.try_into().ok()?;
+        Some(if self.le { u32::from_le_bytes(b) } else { u32::from_be_bytes(b) })
+    }
+
+    fn u16_octets(&self, v: u16) -> [u8; 2] {
+        if self.le { v.to_le_bytes() } else { v.to_be_bytes() }
+    }
+
+    fn u32_octets(&self, v: u32) -> [u8; 4] {
+        if self.le { v.to_le_bytes() } else { v.to_be_bytes() }
+    }
+
+    /// Entrées et décalage de l'IFD suivant
+    fn lire_ifd(&self, offset: u32) -> Option<(Vec<EntreeIfd>, u32)> {
+        let o = offset as usize;
+        let n = self.u16_a(o)? as usize;
+        let entrees = (0..n)
+            .map(|i| {
+                let e = o + 2 + 12 * i;
+                Some(EntreeIfd {
+                    tag: self.u16_a(e)?,
+                    genre: self.u16_a(e + 2)?,
+                    nombre: self.u32_a(e + 4)?,
+                    valeur: self.data.get(e + 8..e + 12)?.try_into().ok()?,
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some((entrees, self.u32_a(o + 2 + 12 * n)?))
+    }
+
+    fn octets_entree<'a>(&'a self, e: &'a EntreeIfd) -> Option<&'a [u8]> {
+        let taille = taille_type_tiff(e.genre).checked_mul(e.nombre as usize)?;
+        if taille <= 4 { return Some(&e.valeur[..taille]); }
+        let o = self.u32_a_brut(e.valeur) as usize;
+        self.data.get(o..o.checked_add(taille)?)
+    }
+
+    fn u32_a_brut(&self, b: [u8; 4]) -> u32 {
+        if self.le { u32::from_le_bytes(b) } else { u32::from_be_bytes(b) }
+    }
+
+    fn texte_entree(&self, e: &EntreeIfd) -> Option<String> {
+        let octets = self.octets_entree(e)?;
+        Some(String::from_utf8_lossy(octets).trim_end_matches('\0').trim().to_string())
+    }
+
+    fn aligner(&mut self) {
+        if self.data.len() % 2 == 1 { self.data.push(0); }
+    }
+
+    /// Encode une valeur ; les données de plus de 4 octets sont ajoutées en fin de fichier
+    fn entree(&mut self, tag: u16, valeur: ValeurTiff) -> EntreeIfd {
+        let (genre, nombre, octets) = match valeur {
+            ValeurTiff::Ascii(t) => {
+                let mut o = t.into_bytes();
+                o.push(0);
+                (2, o.len() as u32, o)
+            }
+            ValeurTiff::Octets(o) => (1, o.len() as u32, o),
+            ValeurTiff::Rationnels(r) => {
+                let o = r.iter().flat_map(|&(n, d)| [self.u32_octets(n), self.u32_octets(d)]).flatten().collect();
+                (5, r.len() as u32, o)
+            }
+            ValeurTiff::Long(v) => (4, 1, self.u32_octets(v).to_vec()),
+        };
+        let mut brut = [0u8; 4];
+        if octets.len() <= 4 {
+            brut[..octets.len()].copy_from_slice(&octets);
+        } else {
+            self.aligner();
+            brut = self.u32_octets(self.data.len() as u32);
+            self.data.extend_from_slice(&octets);
+        }
+        EntreeIfd { tag, genre, nombre, valeur: brut }
+    }
+
+    /// Ajoute un IFD (entrées triées) en fin de fichier et renvoie son décalage
+    fn ecrire_ifd(&mut self, mut entrees: Vec<EntreeIfd>, suivant: u32) -> u32 {
+        entrees.sort_by_key(|e| e.tag);
+        self.aligner();
+        let offset = self.data.len() as u32;
+        let mut bloc = self.u16_octets(entrees.len() as u16).to_vec();
+        for e in &entrees {
+            bloc.extend_from_slice(&self.u16_octets(e.tag));
+            bloc.extend_from_slice(&self.u16_octets(e.genre));
+            bloc.extend_from_slice(&self.u32_octets(e.nombre));
+            bloc.extend_from_slice(&e.valeur);
+        }
+        bloc.extend_from_slice(&self.u32_octets(suivant));
+        self.data.extend_from_slice(&bloc);
+        offset
+    }
+}
+
+/// Remplace (ou retire si `None`) une entrée d'IFD
+fn remplacer_entree(entrees: &mut Vec<EntreeIfd>, tag: u16, nouvelle: Option<EntreeIfd>) {
+    entrees.retain(|e| e.tag != tag);
+    entrees.extend(nouvelle);
+}
+
+fn rationnels_gps(degres: f64) -> Vec<(u32, u32)> {
+    let d = degres.abs();
+    let minutes = (d.fract() * 60.0).floor();
+    let secondes = ((d.fract() * 60.0 - minutes) * 60.0 * 10_000.0).round() as u32;
+    vec![(d.trunc() as u32, 1), (minutes as u32, 1), (secondes, 10_000)]
+}
+
+/// Applique une édition à une structure TIFF (fichier TIFF ou bloc EXIF).
+/// `xmp` remplace le paquet XMP du tag 700 (fichiers TIFF uniquement).
+fn editer_tiff(data: Vec<u8>, edition: &EditionMeta, xmp: Option<Vec<u8>>) -> Result<Vec<u8>, String> {
+    let mut t = EditeurTiff::nouveau(data)?;
+    let offset0 = t.u32_a(4).ok_or("TIFF tronqué")?;
+    let (mut ifd0, suivant0) = t.lire_ifd(offset0).ok_or("IFD0 illisible")?;
+    let pointeur = |entrees: &[EntreeIfd], tag| entrees.iter().find(|e| e.tag == tag).map(|e| t.u32_a_brut(e.valeur));
+    let offset_exif = pointeur(&ifd0, TAG_IFD_EXIF);
+    let (mut ifd_exif, suivant_exif) = offset_exif.and_then(|o| t.lire_ifd(o)).unwrap_or_default();
+
+    // Dates : imposées (prise de vue) et/ou décalées
+    let date_de = |t: &EditeurTiff, entrees: &[EntreeIfd], tag| {
+        entrees.iter().find(|e| e.tag == tag).and_then(|e| t.texte_entree(e))
+            .and_then(|s| chrono::NaiveDateTime::parse_from_str(&s, FORMAT_DATE_EXIF).ok())
+    };
+    let dates = [
+        (false, TAG_DATE_MODIF, edition.nouvelle_date(date_de(&t, &ifd0, TAG_DATE_MODIF), false)),
+        (true, TAG_DATE_ORIGINALE, edition.nouvelle_date(date_de(&t, &ifd_exif, TAG_DATE_ORIGINALE), true)),
+        (true, TAG_DATE_NUMERISATION, edition.nouvelle_date(date_de(&t, &ifd_exif, TAG_DATE_NUMERISATION), true)),
+    ];
+    let modifie_exif = dates.iter().any(|(exif, _, d)| *exif && d.is_some());
+    for (dans_exif, tag, date) in dates {
+        if let Some(d) = date {
+            let e = t.entree(tag, ValeurTiff::Ascii(d.format(FORMAT_DATE_EXIF).to_string()));
+            remplacer_entree(if dans_exif { &mut ifd_exif } else { &mut ifd0 }, tag, Some(e));
+        }
+    }
+
+    // Champs texte de l'IFD0
+    for (tag, valeur) in [(TAG_ARTISTE, &edition.artiste), (TAG_COPYRIGHT, &edition.copyright), (TAG_DESCRIPTION, &edition.description)] {
+        if let Some(v) = valeur {
+            let e = (!v.is_empty()).then(|| t.entree(tag, ValeurTiff::Ascii(v.clone())));
+            remplacer_entree(&mut ifd0, tag, e);
+        }
+    }
+    if let Some(mots) = &edition.mots_cles {
+        // XPKeywords : UTF-16LE terminé par NUL, quel que soit l'ordre des octets du TIFF
+        let e = (!mots.is_empty()).then(|| {
+            let utf16: Vec<u8> = mots.join(";").encode_utf16().chain([0]).flat_map(|u| u.to_le_bytes()).collect();
+            t.entree(TAG_MOTS_CLES_XP, ValeurTiff::Octets(utf16))
+        });
+        remplacer_entree(&mut ifd0, TAG_MOTS_CLES_XP, e);
+    }
+    if let Some(xmp) = xmp {
+        let e = t.entree(TAG_XMP_TIFF, ValeurTiff::Octets(xmp));
+        remplacer_entree(&mut ifd0, TAG_XMP_TIFF, Some(e));
+    }
+
+    // GPS : nouvel IFD complet, ou retrait du pointeur
+    if let Some(gps) = edition.gps {
+        let pointeur_gps = gps.map(|(lat, lon)| {
+            let entrees = vec![
+                t.entree(0, ValeurTiff::Octets(vec![2, 3, 0, 0])),
+                t.entree(1, ValeurTiff::Ascii(if lat < 0.0 { "S" } else { "N" }.into())),
+                t.entree(2, ValeurTiff::Rationnels(rationnels_gps(lat))),
+                t.entree(3, ValeurTiff::Ascii(if lon < 0.0 { "W" } else { "E" }.into())),
+                t.entree(4, ValeurTiff::Rationnels(rationnels_gps(lon))),
+            ];
+            let offset = t.ecrire_ifd(entrees, 0);
+            t.entree(TAG_IFD_GPS, ValeurTiff::Long(offset))
+        });
+        remplacer_entree(&mut ifd0, TAG_IFD_GPS, pointeur_gps);
+    }
+
+    if modifie_exif {
+        let offset = t.ecrire_ifd(ifd_exif, suivant_exif);
+        let e = t.entree(TAG_IFD_EXIF, ValeurTiff::Long(offset));
+        remplacer_entree(&mut ifd0, TAG_IFD_EXIF, Some(e));
+    }
+    let nouvel_ifd0 = t.ecrire_ifd(ifd0, suivant0);
+    let octets = t.u32_octets(nouvel_ifd0);
+    t.data[4..8].copy_from_slice(&octets);
+    Ok(t.data)
+}
+
+fn echapper_xml(texte: &str) -> String {
+    texte.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Applique l'édition au paquet XMP (propriétés dc:*, dates, GPS), en le créant si besoin
+fn editer_xmp(xmp: Option<&[u8]>, edition: &EditionMeta) -> Option<Vec<u8>> {
+    if !edition.touche_xmp() { return xmp.map(|x| x.to_vec()); }
+    let cree = xmp.is_none();
+    let mut texte = match xmp {
+        Some(x) => String::from_utf8_lossy(x).into_owned(),
+        None => concat!(
+            "<?xpacket begin=\"\u{feff}\" id=\"W5M0MpCehiHzreSzNTczkc9d\"?>\n",
+            "<x:xmpmeta xmlns:x=\"adobe:ns:meta/\"><rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">",
+            "<rdf:Description rdf:about=\"\"></rdf:Description></rdf:RDF></x:xmpmeta>\n",
+            "<?xpacket end=\"w\"?>"
+        ).to_string(),
+    };
+
+    // Dates XMP existantes (ISO 8601) : imposées ou décalées, suffixe (fraction, fuseau) conservé
+    let re_dates = regex::Regex::new(
+        r#"((?:xmp:CreateDate|xmp:ModifyDate|exif:DateTimeOriginal|exif:DateTimeDigitized|photoshop:DateCreated)(?:\s*=\s*"|>))(\d{4}-\d{2}-\d{2}T\d{2}:\d{2}(?::\d{2})?)"#,
+    ).expect("regex dates XMP");
+    texte = re_dates.replace_all(&texte, |c: &regex::Captures| {
+        let prise_de_vue = !c[1].starts_with("xmp:ModifyDate");
+        let ancienne = parser_date_meta(&c[2].replace('T', " ")).ok();
+        match edition.nouvelle_date(ancienne, prise_de_vue) {
+            Some(d) => format!("{}{}", &c[1], d.format("%Y-%m-%dT%H:%M:%S")),
+            None => c[0].to_string(),
+        }
+    }).into_owned();
+    if edition.gps.is_some() {
+        texte = String::from_utf8_lossy(&retirer_gps_xmp(texte.as_bytes())).into_owned();
+    }
+
+    let alt = |v: &str| format!("<rdf:Alt><rdf:li xml:lang=\"x-default\">{}</rdf:li></rdf:Alt>", echapper_xml(v));
+    let liste = |genre: &str, v: &[String]| {
+        let items: String = v.iter().map(|m| format!("<rdf:li>{}</rdf:li>", echapper_xml(m))).collect();
+        format!("<rdf:{genre}>{items}</rdf:{genre}>")
+    };
+    let proprietes = [
+        ("creator", edition.artiste.as_ref().map(|v| (!v.is_empty()).then(|| liste("Seq", std::slice::from_ref(v))))),
+        ("rights", edition.copyright.as_ref().map(|v| (!v.is_empty()).then(|| alt(v)))),
+        ("description", edition.description.as_ref().map(|v| (!v.is_empty()).then(|| alt(v)))),
+        ("subject", edition.mots_cles.as_ref().map(|v| (!v.is_empty()).then(|| liste("Bag", v)))),
+    ];
+    let mut ajouts = String::new();
+    for (nom, valeur) in proprietes {
+        let Some(valeur) = valeur else { continue };
+        let element = regex::Regex::new(&format!(r"(?s)<dc:{nom}\b[^>]*?(?:/>|>.*?</dc:{nom}>)")).expect("regex dc");
+        let attribut = regex::Regex::new(&format!(r#"\s+dc:{nom}\s*=\s*"[^"]*""#)).expect("regex dc");
+        texte = attribut.replace_all(&element.replace_all(&texte, ""), "").into_owned();
+        if let Some(v) = valeur { ajouts.push_str(&format!("<dc:{nom}>{v}</dc:{nom}>")); }
+    }
+    if !ajouts.is_empty() {
+        // Insertion dans la première rdf:Description (ouverte si elle était auto-fermante)
+        let description = regex::Regex::new(r"(?s)<rdf:Description\b([^>]*?)(/?)>").expect("regex rdf");
+        let Some(c) = description.captures(&texte) else {
+            crate::log_warn("pic::editer_xmp rdf:Description introuvable, XMP inchangé");
+            return xmp.map(|x| x.to_vec());
+        };
+        let mut attributs = c[1].to_string();
+        if !texte.contains("xmlns:dc=") { attributs.push_str(" xmlns:dc=\"http://purl.org/dc/elements/1.1/\""); }
+        let fermeture = if &c[2] == "/" { "</rdf:Description>" } else { "" };
+        let remplacement = format!("<rdf:Description{attributs}>{ajouts}{fermeture}");
+        let plage = c.get(0).map(|m| m.range()).unwrap_or_default();
+        texte.replace_range(plage, &remplacement);
+    } else if cree {
+        return None;
+    }
+    Some(texte.into_bytes())
+}
+
+/// Édite les métadonnées sans réencoder les pixels (JPEG, PNG, WebP : EXIF + XMP ; TIFF : IFD0 + tag XMP)
+pub fn editer_metadonnees(input: &Path, output: &str, edition: &EditionMeta) -> bool {
+    crate::log_info(&format!("pic::editer_metadonnees | {:?} | {:?} -> {}", edition, input, output));
+    let resultat = std::fs::read(input).map_err(|e| format!("Lecture {:?} : {}", input, e)).and_then(|data| {
+        if detecter_conteneur(&data).is_some() {
+            let mut meta = lire_metadonnees(input);
+            let exif = meta.exif.take().unwrap_or_else(|| EXIF_VIDE.to_vec());
+            meta.exif = Some(editer_tiff(exif, edition, None)?);
+            meta.xmp = editer_xmp(meta.xmp.as_deref(), edition);
+            std::fs::write(output, &data).map_err(|e| format!("Écriture {} : {}", output, e))?;
+            ecrire_metadonnees(output, &meta)
+        } else {
+            let tiff = EditeurTiff::nouveau(data)?;
+            let xmp_actuel = tiff.u32_a(4).and_then(|o| tiff.lire_ifd(o)).and_then(|(ifd0, _)| {
+                ifd0.iter().find(|e| e.tag == TAG_XMP_TIFF).and_then(|e| tiff.octets_entree(e)).map(|x| x.to_vec())
+            });
+            let xmp = editer_xmp(xmp_actuel.as_deref(), edition).filter(|x| Some(x) != xmp_actuel.as_ref());
+            let nouveau = editer_tiff(tiff.data, edition, xmp)?;
+            std::fs::write(output, nouveau).map_err(|e| format!("Écriture {} : {}", output, e))
+        }
+    });
+    if let Err(e) = &resultat {
+        crate::log_error(&format!("pic::editer_metadonnees {:?} : {}", input, e));
+    }
+    resultat.is_ok()
+}
+
+// ════════════════════════════════════════════════════════════════════════
+//  GESTION DES COULEURS — profils ICC, CMYK, conversion vers sRGB
+// ════════════════════════════════════════════════════════════════════════
+
+/// Profil colorimétrique des sorties de `convertir` et `compresser`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum ProfilCible {
+    /// Pixels et profil d'origine conservés (les CMYK passent tout de même en sRGB)
+    Conserver,
+    /// sRGB, implicite : aucun profil n'est embarqué en sortie
+    #[default]
+    Srgb,
+    DisplayP3,
+    AdobeRgb,
+    /// Profil RGB lu depuis un fichier .icc/.icm
+    Fichier(Vec<u8>),
+}
+
+impl ProfilCible {
+    pub const TOUS: [&'static str; 4] = ["srgb", "display-p3", "adobe-rgb", "keep"];
+
+    /// Nom CLI (srgb, display-p3, adobe-rgb, keep) ou chemin d'un profil ICC RGB
+    pub fn depuis_nom(nom: &str) -> Result<Self, String> {
+        Ok(match nom.trim().to_lowercase().as_str() {
+            "srgb" => Self::Srgb,
+            "display-p3" | "p3" => Self::DisplayP3,
+            "adobe-rgb" | "adobergb" => Self::AdobeRgb,
+            "keep" => Self::Conserver,
+            _ => {
+                let icc = std::fs::read(nom.trim()).map_err(|e| format!("Profil '{}' : ni {}, ni fichier lisible ({})", nom, Self::TOUS.join(", "), e))?;
+                let profil = moxcms::ColorProfile::new_from_slice(&icc).map_err(|e| format!("Profil ICC {} illisible : {:?}", nom, e))?;
+                if profil.color_space != moxcms::DataColorSpace::Rgb {
+                    return Err(format!("Profil {} : un profil RGB est attendu ({:?})", nom, profil.color_space));
+                }
+                Self::Fichier(icc)
+            }
+        })
+    }
+
+    /// Profil de destination des pixels ; `None` pour `Conserver`
+    fn profil(&self) -> Option<moxcms::ColorProfile> {
+        match self {
+            Self::Conserver => None,
+            Self::Srgb => Some(moxcms::ColorProfile::new_srgb()),
+            Self::DisplayP3 => Some(moxcms::ColorProfile::new_display_p3()),
+            Self::AdobeRgb => Some(moxcms::ColorProfile::new_adobe_rgb()),
+            Self::Fichier(icc) => moxcms::ColorProfile::new_from_slice(icc).ok(),
+        }
+    }
+
+    /// Profil ICC à embarquer dans une sortie convertie (aucun pour sRGB)
+    fn icc(&self) -> Option<Vec<u8>> {
+        match self {
+            Self::Conserver | Self::Srgb => None,
+            Self::Fichier(icc) => Some(icc.clone()),
+            autre => autre.profil().and_then(|p| p.encode().ok()),
+        }
+    }
+}
+
+fn texte_profil(texte: &moxcms::ProfileText) -> Option<String> {
+    let brut = match texte {
+        moxcms::ProfileText::PlainString(s) => s.clone(),
+        moxcms::ProfileText::Localizable(l) => l.first()?.value.clone(),
+        moxcms::ProfileText::Description(d) => d.ascii_string.clone(),
+    };
+    let propre = brut.trim_matches(char::from(0)).trim().to_string();
+    (!propre.is_empty()).then_some(propre)
+}
+
+/// Description lisible d'un profil ICC : « Display P3 (Rgb) »
+pub fn decrire_profil(icc: &[u8]) -> String {
+    match moxcms::ColorProfile::new_from_slice(icc) {
+        Ok(p) => format!("{} ({:?})", p.description.as_ref().and_then(texte_profil).unwrap_or_else(|| "sans nom".into()), p.color_space),
+        Err(e) => format!("illisible ({:?})", e),
+    }
+}
+
+/// Profil ICC embarqué (JPEG, PNG, WebP, TIFF, PSD…)
+pub fn lire_profil_icc(input: &Path) -> Option<Vec<u8>> {
+    if input.extension().is_some_and(|e| e.eq_ignore_ascii_case("psd")) {
+        return std::fs::read(input).ok().and_then(|d| sections_psd(&d).ok()).and_then(|s| s.icc);
+    }
+    let mut decodeur = image::ImageReader::open(input).ok()?.into_decoder().ok()?;
+    decodeur.icc_profile().ok().flatten()
+}
+
+fn memes_primaires(a: &moxcms::ColorProfile, b: &moxcms::ColorProfile) -> bool {
+    let proche = |u: moxcms::Xyzd, v: moxcms::Xyzd| (u.x - v.x).abs() < 2e-3 && (u.y - v.y).abs() < 2e-3 && (u.z - v.z).abs() < 2e-3;
+    proche(a.red_colorant, b.red_colorant) && proche(a.green_colorant, b.green_colorant)
+        && proche(a.blue_colorant, b.blue_colorant) && proche(a.white_point, b.white_point)
+}
+
+/// Conversion (source, destination) à appliquer aux pixels décrits par `icc`, s'il y en a une.
+/// Les CMYK vont toujours vers un profil RGB ; les RGB déjà dans le profil cible sont laissés tels quels.
+fn conversion_profil(icc: &[u8], cible: &ProfilCible) -> Option<(moxcms::ColorProfile, moxcms::ColorProfile)> {
+    let source = match moxcms::ColorProfile::new_from_slice(icc) {
+        Ok(p) => p,
+        Err(e) => {
+            crate::log_warn(&format!("pic::conversion_profil profil ICC illisible ({:?}) : couleurs inchangées", e));
+            return None;
+        }
+    };
+    match source.color_space {
+        moxcms::DataColorSpace::Cmyk => Some((source, cible.profil().unwrap_or_else(moxcms::ColorProfile::new_srgb))),
+        moxcms::DataColorSpace::Rgb => {
+            let destination = cible.profil()?;
+            (!memes_primaires(&source, &destination)).then_some((source, destination))
+        }
+        _ => None,
+    }
+}
+
+/// Profil à écrire en sortie pour une source décrite par `icc` : celui de la cible si les
+/// pixels ont été convertis, l'original sinon
+fn icc_sortie(icc: Vec<u8>, cible: &ProfilCible) -> Option<Vec<u8>> {
+    if conversion_profil(&icc, cible).is_some() { cible.icc() } else { Some(icc) }
+}
+
+/// Écrit dans `output` (JPEG, PNG, WebP) le seul profil qui décrit ses pixels, pour les
+/// conversions qui ne reportent pas les autres métadonnées
+fn reporter_profil(icc_source: Option<Vec<u8>>, output: &str, cible: &ProfilCible) {
+    let Some(icc) = icc_source.and_then(|icc| icc_sortie(icc, cible)) else { return };
+    let ext = Path::new(output).extension().unwrap_or_default().to_string_lossy().to_lowercase();
+    if !matches!(ext.as_str(), "jpg" | "jpeg" | "png" | "webp") { return; }
+    let meta = Metadonnees { icc: Some(icc), ..Default::default() };
+    if let Err(e) = ecrire_metadonnees(output, &meta) {
+        crate::log_warn(&format!("pic::reporter_profil {}", e));
+    }
+}
+
+/// Ramène les pixels RGB(A) 8 ou 16 bits décrits par `icc` dans le profil `cible`.
+/// Renvoie vrai si les pixels ont changé.
+fn convertir_vers_cible(img: &mut image::DynamicImage, icc: &[u8], cible: &ProfilCible) -> bool {
+    use moxcms::{Layout, TransformOptions};
+    let Some((source, destination)) = conversion_profil(icc, cible) else { return false };
+    if source.color_space != moxcms::DataColorSpace::Rgb { return false; }
+    let options = TransformOptions::default();
+    let huit = |layout: Layout, pixels: &mut [u8]| -> Result<(), moxcms::CmsError> {
+        let entree = pixels.to_vec();
+        source.create_transform_8bit(layout, &destination, layout, options)?.transform(&entree, pixels)
+    };
+    let seize = |layout: Layout, pixels: &mut [u16]| -> Result<(), moxcms::CmsError> {
+        let entree = pixels.to_vec();
+        source.create_transform_16bit(layout, &destination, layout, options)?.transform(&entree, pixels)
+    };
+    let resultat = match img {
+        image::DynamicImage::ImageRgb8(b) => huit(Layout::Rgb, b),
+        image::DynamicImage::ImageRgba8(b) => huit(Layout::Rgba, b),
+        image::DynamicImage::ImageRgb16(b) => seize(Layout::Rgb, b),
+        image::DynamicImage::ImageRgba16(b) => seize(Layout::Rgba, b),
+        _ => return false,
+    };
+    match resultat {
+        Ok(()) => true,
+        Err(e) => {
+            crate::log_warn(&format!("pic::convertir_vers_cible transformation impossible ({:?}) : couleurs inchangées", e));
+            false
+        }
+    }
+}
+
+/// Convertit des pixels CMYK en encre (0 = pas d'encre) vers le profil `cible` (sRGB si `Conserver`).
+/// Sans profil CMYK exploitable, conversion naïve R = (1 - C)(1 - K).
+pub fn cmyk_vers_rgb(encre: &[u8], largeur: u32, hauteur: u32, icc: Option<&[u8]>, cible: &ProfilCible) -> image::RgbImage {
+    let mut rgb = vec![0u8; encre.len() / 4 * 3];
+    let transformation = icc.and_then(|icc| conversion_profil(icc, cible))
+        .filter(|(source, _)| source.color_space == moxcms::DataColorSpace::Cmyk)
+        .and_then(|(source, destination)| {
+            source.create_transform_8bit(moxcms::Layout::Rgba, &destination, moxcms::Layout::Rgb, moxcms::TransformOptions::default())
+                .map_err(|e| crate::log_warn(&format!("pic::cmyk_vers_rgb profil CMYK inutilisable ({:?}) : conversion naïve", e)))
+                .ok()
+        });
+    if transformation.is_none_or(|t| t.transform(encre, &mut rgb).is_err()) {
+        for (p, c) in rgb.chunks_exact_mut(3).zip(encre.chunks_exact(4)) {
+            let blanc = 255 - c[3] as u32;
+            for i in 0..3 { p[i] = ((255 - c[i] as u32) * blanc / 255) as u8; }
+        }
+    }
+    image::RgbImage::from_raw(largeur, hauteur, rgb).unwrap_or_default()
+}
+
+/// Décode un JPEG CMYK ou YCCK (Adobe, valeurs inversées) et le convertit via `cmyk_vers_rgb`.
+/// `None` pour un JPEG à 1 ou 3 composantes.
+fn decoder_jpeg_cmyk(data: &[u8], icc: Option<&[u8]>, cible: &ProfilCible) -> Option<Result<image::RgbImage, String>> {
+    let options = zune_core::options::DecoderOptions::default().set_strict_mode(false);
+    let mut decodeur = zune_jpeg::JpegDecoder::new_with_options(zune_core::bytestream::ZCursor::new(data), options);
+    decodeur.decode_headers().ok()?;
+    let espace = decodeur.input_colorspace()?;
+    if !matches!(espace, ColorSpace::CMYK | ColorSpace::YCCK) { return None; }
+    // Sortie dans l'espace d'entrée : composantes brutes, sans la conversion RGB naïve du décodeur
+    decodeur.set_options(decodeur.options().jpeg_set_out_colorspace(espace));
+    let (largeur, hauteur) = decodeur.dimensions()?;
+    Some(decodeur.decode().map_err(|e| format!("Décodage JPEG {:?} : {:?}", espace, e)).map(|brut| {
+        let mut encre = brut;
+        for p in encre.chunks_exact_mut(4) {
+            if espace == ColorSpace::YCCK {
+                // YCbCr → R'G'B' : R' est directement l'encre cyan une fois l'inversion Adobe retirée
+                let (y, cb, cr) = (p[0] as f32, p[1] as f32 - 128.0, p[2] as f32 - 128.0);
+                p[0] = (y + 1.402 * cr).round().clamp(0.0, 255.0) as u8;
+                p[1] = (y - 0.344_136 * cb - 0.714_136 * cr).round().clamp(0.0, 255.0) as u8;
+                p[2] = (y + 1.772 * cb).round().clamp(0.0, 255.0) as u8;
+            } else {
+                for c in &mut p[..3] { *c = 255 - *c; }
+            }
+            p[3] = 255 - p[3];
+        }
+        cmyk_vers_rgb(&encre, largeur as u32, hauteur as u32, icc, cible)
+    }))
+}
+
+// ─── PSD : en-tête, profil ICC et image composite ───────────────────
+
+const PSD_RESSOURCE_ICC: u16 = 1039;
+
+/// Sections utiles d'un PSD/PSB
+struct SectionsPsd {
+    psb: bool,
+    canaux: usize,
+    largeur: u32,
+    hauteur: u32,
+    profondeur: u16,
+    /// 1 niveaux de gris, 3 RGB, 4 CMYK, 9 Lab…
+    mode: u16,
+    icc: Option<Vec<u8>>,
+    /// Début de la section image composite (compression incluse)
+    donnees: usize,
+}
+
+fn sections_psd(data: &[u8]) -> Result<SectionsPsd, String> {
+    let u16_a = |i: usize| data.get(i..i + 2).map(|b| u16::from_be_bytes([b[0], b[1]])).ok_or("PSD tronqué");
+    let u32_a = |i: usize| data.get(i..i + 4).map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]])).ok_or("PSD tronqué");
+    if !data.starts_with(b"8BPS") { return Err("Signature PSD absente".into()); }
+    let psb = u16_a(4)? == 2;
+    let mut i = 26 + 4 + u32_a(26)? as usize;
+    let fin_ressources = i.saturating_add(4 + u32_a(i)? as usize).min(data.len());
+    i += 4;
+    let mut icc = None;
+    while i + 12 <= fin_ressources {
+        if data.get(i..i + 4) != Some(b"8BIM".as_slice()) { break; }
+        let id = u16_a(i + 4)?;
+        let nom = 1 + *data.get(i + 6).ok_or("PSD tronqué")? as usize;
+        let j = i + 6 + nom + nom % 2;
+        let taille = u32_a(j)? as usize;
+        let charge = data.get(j + 4..j + 4 + taille).ok_or("Ressource PSD tronquée")?;
+        if id == PSD_RESSOURCE_ICC { icc = Some(charge.to_vec()); }
+        i = j + 4 + taille + taille % 2;
+    }
+    let mut i = fin_ressources;
+    i += if psb {
+        8 + data.get(i..i + 8).map(|b| u64::from_be_bytes(b.try_into().unwrap_or_default())).ok_or("PSD tronqué")? as usize
+    } else {
+        4 + u32_a(i)? as usize
+    };
+    Ok(SectionsPsd {
+        psb,
+        canaux: u16_a(12)? as usize,
+        hauteur: u32_a(14)?,
+        largeur: u32_a(18)?,
+        profondeur: u16_a(22)?,
+        mode: u16_a(24)?,
+        icc,
+        donnees: i,
+    })
+}
+
+/// Décompression PackBits d'une ligne
+fn packbits(entree: &[u8], sortie: &mut Vec<u8>) {
+    let mut i = 0;
+    while i < entree.len() {
+        let n = entree[i] as i8;
+        i += 1;
+        if n >= 0 {
+            let fin = (i + n as usize + 1).min(entree.len());
+            sortie.extend_from_slice(&entree[i..fin]);
+            i = fin;
+        } else if n != -128 {
+            if let Some(&v) = entree.get(i) { sortie.extend(std::iter::repeat_n(v, (1 - n as isize) as usize)); }
+            i += 1;
+        }
+    }
+}
+
+/// Taille maximale de l'image composite décodée, alignée sur la limite d'allocation
+/// par défaut des décodeurs du crate image (512 Mio)
+const PSD_OCTETS_MAX: u64 = 512 * 1024 * 1024;
+
+/// Plans 8 bits de l'image composite (16 bits réduits à l'octet de poids fort)
+fn composite_psd(data: &[u8], s: &SectionsPsd) -> Result<Vec<Vec<u8>>, String> {
+    if !matches!(s.profondeur, 8 | 16) {
+        return Err(format!("Profondeur PSD {} bits non prise en charge", s.profondeur));
+    }
+    // Dimensions lues dans l'en-tête, non fiables : bornées avant toute allocation
+    let total = s.largeur as u64 * s.hauteur as u64 * s.canaux as u64 * (s.profondeur as u64 / 8);
+    if total == 0 || total > PSD_OCTETS_MAX {
+        return Err(format!("Image composite PSD hors limites ({}x{}, {} canaux)", s.largeur, s.hauteur, s.canaux));
+    }
+    let octets = s.profondeur as usize / 8;
+    let ligne = s.largeur as usize * octets;
+    let lignes = s.canaux * s.hauteur as usize;
+    let compression = data.get(s.donnees..s.donnees + 2).map(|b| u16::from_be_bytes([b[0], b[1]])).ok_or("Image composite absente")?;
+    let mut i = s.donnees + 2;
+    let brut = match compression {
+        0 => data.get(i..i + lignes * ligne).ok_or("Image composite tronquée")?.to_vec(),
+        1 => {
+            let largeur_compte = if s.psb { 4 } else { 2 };
+            let comptes: Vec<usize> = data.get(i..i + lignes * largeur_compte).ok_or("Image composite tronquée")?
+                .chunks_exact(largeur_compte)
+                .map(|c| c.iter().fold(0usize, |acc, &b| (acc << 8) | b as usize))
+                .collect();
+            i += lignes * largeur_compte;
+            // PackBits décompresse au plus 128 octets pour 2 lus
+            let mut brut = Vec::with_capacity((lignes * ligne).min(data.len().saturating_sub(i).saturating_mul(64)));
+            for n in comptes {
+                let debut = brut.len();
+                packbits(data.get(i..i + n).ok_or("Image composite tronquée")?, &mut brut);
+                brut.resize(debut + ligne, 0);
+                i += n;
+            }
+            brut
+        }
+        c => return Err(format!("Compression PSD {} non prise en charge", c)),
+    };
+    let plan = ligne * s.hauteur as usize;
+    Ok(brut.chunks_exact(plan).map(|p| p.iter().step_by(octets).copied().collect()).collect())
+}
+
+/// Image composite CMYK (valeurs PSD inversées : 255 = pas d'encre), alpha éventuel en 5e canal
+fn decoder_psd_cmyk(data: &[u8], s: &SectionsPsd, cible: &ProfilCible) -> Result<image::DynamicImage, String> {
+    let plans = composite_psd(data, s)?;
+    if plans.len() < 4 { return Err(format!("PSD CMYK à {} canaux", plans.len())); }
+    let pixels = s.largeur as usize * s.hauteur as usize;
+    let encre: Vec<u8> = (0..pixels).flat_map(|p| (0..4).map(move |c| (p, c))).map(|(p, c)| 255 - plans[c][p]).collect();
+    let rgb = cmyk_vers_rgb(&encre, s.largeur, s.hauteur, s.icc.as_deref(), cible);
+    Ok(match plans.get(4) {
+        Some(alpha) => {
+            let mut rgba = image::DynamicImage::ImageRgb8(rgb).to_rgba8();
+            for (p, a) in rgba.pixels_mut().zip(alpha) { p[3] = *a; }
+            image::DynamicImage::ImageRgba8(rgba)
+        }
+        None => image::DynamicImage::ImageRgb8(rgb),
+    })
+}
+
+// ════════════════════════════════════════════════════════════════════════
+//  HDR — mappage tonal (EXR, Radiance HDR vers formats 8 bits)
+// ════════════════════════════════════════════════════════════════════════
+
+/// Courbe de compression des hautes lumières
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum OperateurTonal {
+    /// ACES filmique (approximation de Narkowicz) : contraste doux, hautes lumières roulées
+    #[default]
+    Aces,
+    /// Reinhard sur la luminance, L / (1 + L) : teintes conservées
+    Reinhard,
+    /// Écrêtage à 1.0 (ancien comportement, gamma compris)
+    Ecretage,
+}
+
+impl OperateurTonal {
+    pub const TOUS: [&'static str; 3] = ["aces", "reinhard", "clip"];
+
+    pub fn depuis_nom(nom: &str) -> Option<Self> {
+        Some(match nom.trim().to_lowercase().as_str() {
+            "aces" | "filmic" => Self::Aces,
+            "reinhard" => Self::Reinhard,
+            "clip" | "none" => Self::Ecretage,
+            _ => return None,
+        })
+    }
+
+    pub fn nom(self) -> &'static str {
+        Self::TOUS[self as usize]
+    }
+}
+
+/// Réglages du passage d'une image flottante linéaire en 8 bits
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OptionsTonales {
+    pub operateur: OperateurTonal,
+    /// Exposition en EV ; `None` : automatique (moyenne logarithmique ramenée à 18 %)
+    pub exposition: Option<f32>,
+    /// Gamma d'encodage de la sortie
+    pub gamma: f32,
+}
+
+impl OptionsTonales {
+    pub const DEFAUT: Self = Self { operateur: OperateurTonal::Aces, exposition: None, gamma: 2.2 };
+}
+
+impl Default for OptionsTonales {
+    fn default() -> Self {
+        Self::DEFAUT
+    }
+}
+
+fn luminance(r: f32, g: f32, b: f32) -> f32 {
+    0.2126 * r + 0.7152 * g + 0.0722 * b
+}
+
+/// Exposition (EV) qui amène la moyenne logarithmique de la luminance au gris moyen (18 %)
+pub fn exposition_auto(img: &image::Rgba32FImage) -> f32 {
+    let (somme, n) = img.pixels()
+        .map(|p| luminance(p[0], p[1], p[2]))
+        .filter(|l| l.is_finite() && *l >= 0.0)
+        .fold((0.0f64, 0usize), |(s, n), l| (s + (1e-4 + l as f64).ln(), n + 1));
+    if n == 0 { return 0.0; }
+    let moyenne = (somme / n as f64).exp() as f32;
+    (0.18 / moyenne).log2().clamp(-12.0, 12.0)
+}
+
+fn aces(x: f32) -> f32 {
+    (x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14)
+}
+
+/// Ramène une image flottante linéaire (HDR, EXR) en 8 bits : exposition, courbe, gamma.
+/// L'alpha éventuel est conservé.
+pub fn mapper_tons(img: &image::DynamicImage, options: &OptionsTonales) -> image::DynamicImage {
+    let source = img.to_rgba32f();
+    let ev = options.exposition.unwrap_or_else(|| exposition_auto(&source));
+    let facteur = 2f32.powf(ev);
+    let inverse_gamma = 1.0 / options.gamma.max(0.1);
+    let encoder = |v: f32| (v.clamp(0.0, 1.0).powf(inverse_gamma) * 255.0).round() as u8;
+    let sortie = image::RgbaImage::from_fn(source.width(), source.height(), |x, y| {
+        let p = source.get_pixel(x, y);
+        let propre = |v: f32| if v.is_finite() { v.max(0.0) * facteur } else { 0.0 };
+        let rgb = [propre(p[0]), propre(p[1]), propre(p[2])];
+        let rgb = match options.operateur {
+            OperateurTonal::Aces => rgb.map(aces),
+            OperateurTonal::Reinhard => {
+                let l = luminance(rgb[0], rgb[1], rgb[2]);
+                let echelle = if l > 0.0 { 1.0 / (1.0 + l) } else { 0.0 };
+                rgb.map(|v| v * echelle)
+            }
+            OperateurTonal::Ecretage => rgb,
+        };
+        let alpha = if p[3].is_finite() { p[3] } else { 1.0 };
+        image::Rgba([encoder(rgb[0]), encoder(rgb[1]), encoder(rgb[2]), (alpha.clamp(0.0, 1.0) * 255.0).round() as u8])
+    });
+    if img.color().has_alpha() {
+        image::DynamicImage::ImageRgba8(sortie)
+    } else {
+        image::DynamicImage::ImageRgb8(image::DynamicImage::ImageRgba8(sortie).to_rgb8())
+    }
+}
+
+/// Vrai pour les formats de sortie qui gardent les valeurs flottantes (EXR, HDR, TIFF)
+fn sortie_flottante(output: &str) -> bool {
+    let ext = Path::new(output).extension().unwrap_or_default().to_string_lossy().to_lowercase();
+    matches!(ext.as_str(), "exr" | "hdr" | "tif" | "tiff")
+}
+
+/// Image prête à écrire dans `output` : une image flottante vers un format 8 bits passe par
+/// le mappage tonal `options` au lieu d'être écrêtée
+pub fn adapter_sortie<'a>(img: &'a image::DynamicImage, output: &str, options: &OptionsTonales) -> std::borrow::Cow<'a, image::DynamicImage> {
+    let flottante = matches!(img.color(), image::ColorType::Rgb32F | image::ColorType::Rgba32F);
+    if !flottante || sortie_flottante(output) {
+        return std::borrow::Cow::Borrowed(img);
+    }
+    crate::log_info(&format!("pic::adapter_sortie mappage tonal {} | exposition {:?} | gamma {} -> {}", options.operateur.nom(), options.exposition, options.gamma, output));
+    std::borrow::Cow::Owned(mapper_tons(img, options))
+}
+
+// ════════════════════════════════════════════════════════════════════════
+//  ORIENTATION EXIF
+// ════════════════════════════════════════════════════════════════════════
+
+/// Décode l'image sans la redresser et renvoie l'orientation EXIF déclarée par le conteneur.
+/// Les couleurs sont ramenées au profil `cible`, CMYK compris.
+fn decoder_avec_orientation(input: &Path, cible: &ProfilCible) -> image::ImageResult<(image::DynamicImage, Orientation)> {
+    let format = image::ImageFormat::from_path(input)?;
+    // Lu une seule fois : un JPEG CMYK repasse par ses octets bruts
+    let data = std::fs::read(input)?;
+    let mut decodeur = image::ImageReader::with_format(std::io::Cursor::new(data.as_slice()), format).into_decoder()?;
+    let orientation = decodeur.orientation().unwrap_or(Orientation::NoTransforms);
+    let icc = decodeur.icc_profile().ok().flatten();
+    if format == image::ImageFormat::Jpeg
+        && let Some(cmyk) = decoder_jpeg_cmyk(&data, icc.as_deref(), cible)
+    {
+        let rgb = cmyk.map_err(|e| image::ImageError::Decoding(
+            image::error::DecodingError::new(image::ImageFormat::Jpeg.into(), e)
+        ))?;
+        return Ok((image::DynamicImage::ImageRgb8(rgb), orientation));
+    }
+    let mut img = image::DynamicImage::from_decoder(decodeur)?;
+    if let Some(icc) = &icc { convertir_vers_cible(&mut img, icc, cible); }
+    Ok((img, orientation))
+}
+
+/// Ouvre une image ; si `orientation_auto`, applique l'EXIF Orientation aux pixels.
+/// Le profil ICC embarqué est conservé (seuls les CMYK passent en RGB).
+pub fn ouvrir_image(input: &Path, orientation_auto: bool) -> image::ImageResult<image::DynamicImage> {
+    ouvrir_image_profil(input, orientation_auto, &ProfilCible::Conserver)
+}
+
+/// Comme `ouvrir_image`, couleurs ramenées au profil `cible`
+fn ouvrir_image_profil(input: &Path, orientation_auto: bool, cible: &ProfilCible) -> image::ImageResult<image::DynamicImage> {
+    let (mut img, orientation) = decoder_avec_orientation(input, cible)?;
+    if orientation_auto { img.apply_orientation(orientation); }
+    Ok(img)
+}
+
+/// Orientation EXIF (1-8) déclarée par le fichier ; 1 si absente
+pub fn lire_orientation(input: &Path) -> u8 {
+    image::ImageReader::open(input)
+        .map_err(image::ImageError::from)
+        .and_then(|r| r.into_decoder())
+        .and_then(|mut d| d.orientation())
+        .map_or(1, Orientation::to_exif)
+}
+
+/// Remet l'orientation à 1 dans l'EXIF (IFD0) et le XMP une fois les pixels redressés.
+/// Renvoie vrai si une orientation autre que 1 était déclarée dans l'EXIF.
+fn reinitialiser_orientation(meta: &mut Metadonnees) -> bool {
+    let tournee = meta.exif.as_mut()
+        .and_then(|exif| Orientation::remove_from_exif_chunk(exif))
+        .is_some_and(|o| o != Orientation::NoTransforms);
+    if let Some(xmp) = meta.xmp.as_mut().filter(|_| tournee) {
+        let re = regex::Regex::new(r#"tiff:Orientation(="|>)[2-8]"#).expect("regex orientation");
+        *xmp = re.replace_all(&String::from_utf8_lossy(xmp), "tiff:Orientation${1}1").into_owned().into_bytes();
+    }
+    tournee
+}
+
+/// Redresse l'image selon son EXIF Orientation (les 8 cas) et remet le tag à 1.
+/// Sans rotation à appliquer et à format identique, la source est recopiée sans réencodage.
+pub fn orienter_auto(input: &Path, output: &str) -> bool {
+    crate::log_info(&format!("pic::orienter_auto | {:?} -> {}", input, output));
+    let (mut img, orientation) = match decoder_avec_orientation(input, &ProfilCible::Conserver) {
+        Ok(r) => r,
+        Err(e) => {
+            crate::log_error(&format!("pic::orienter_auto impossible d'ouvrir {:?} : {}", input, e));
+            return false;
+        }
+    };
+    let meme_format = Path::new(output).extension().map(|e| e.to_ascii_lowercase()) == input.extension().map(|e| e.to_ascii_lowercase());
+    if orientation == Orientation::NoTransforms && meme_format {
+        crate::log_info(&format!("pic::orienter_auto {:?} déjà droite, copie", input));
+        return std::fs::copy(input, output).is_ok();
+    }
+    img.apply_orientation(orientation);
+    // Qualité 9 (90 %) pour JPEG : le redressement ne doit pas dégrader visiblement l'image
+    let ok = sauvegarder_image(&img, output, 9);
+    if ok { reporter_metadonnees(input, output, true, true, &ProfilCible::Conserver); }
+    else { crate::log_error(&format!("pic::orienter_auto échec save {:?}", output)); }
+    ok
+}
+
+/// Rotation simple (90, 180, 270)
+pub fn pivoter(input: &Path, output: &str, angle: u32, orientation_auto: bool, tons: &OptionsTonales) -> bool {
+    crate::log_info(&format!("pic::pivoter | angle={} | {:?} -> {}", angle, input, output));
+    if matches!(angle, 90 | 180 | 270) {
+        let animation = traiter_animation(input, output, |img| Ok(match angle {
+            90 => img.rotate90(),
+            180 => img.rotate180(),
+            _ => img.rotate270(),
+        }));
+        if let Some(ok) = animation { return ok; }
+    }
+    match ouvrir_image(input, orientation_auto) {
+        Ok(img) => {
+            let rotated = match angle {
+                90 => img.rotate90(),
+                180 => img.rotate180(),
+                270 => img.rotate270(),
+                _ => {
+                    crate::log_warn(&format!("pic::pivoter angle invalide {} pour {:?}, image non modifiée", angle, input));
+                    img
+                }
+            };
+            let ok = adapter_sortie(&rotated, output, tons).save(output).is_ok();
+            if ok { reporter_metadonnees(input, output, false, orientation_auto, &ProfilCible::Conserver); }
+            else { crate::log_error(&format!("pic::pivoter échec save {:?}", output)); }
+            ok
+        },
+        Err(e) => {
+            crate::log_error(&format!("pic::pivoter impossible d'ouvrir {:?} : {}", input, e));
+            false
+        }
+    }
+}
+
+/// Recadrage d'image (crop) - coordonnées en pourcentage de l'image
+/// x, y = coin supérieur gauche (0-100)
+/// width, height = dimensions du crop (0-100)
+#[allow(clippy::too_many_arguments)]
+pub fn recadrer(input: &Path, output: &str, x_pct: u32, y_pct: u32, width_pct: u32, height_pct: u32, orientation_auto: bool, tons: &OptionsTonales) -> bool {
+    crate::log_info(&format!("pic::recadrer | x={}% y={}% w={}% h={}% | {:?} -> {}", x_pct, y_pct, width_pct, height_pct, input, output));
+    let animation = traiter_animation(input, output, |img| {
+        let (img_w, img_h) = (img.width(), img.height());
+        let (x, y) = ((img_w * x_pct) / 100, (img_h * y_pct) / 100);
+        let (width, height) = ((img_w * width_pct) / 100, (img_h * height_pct) / 100);
+        if x + width > img_w || y + height > img_h {
+            return Err(format!("crop hors limites | image={}x{} | crop x={} y={} w={} h={}", img_w, img_h, x, y, width, height));
+        }
+        Ok(img.crop_imm(x, y, width, height))
+    });
+    if let Some(ok) = animation { return ok; }
+    match ouvrir_image(input, orientation_auto) {
+        Ok(img) => {
+            let (img_w, img_h) = (img.width(), img.height());
+            
+            // Convertir pourcentages en pixels
+            let x = (img_w * x_pct) / 100;
+            let y = (img_h * y_pct) / 100;
+            let width = (img_w * width_pct) / 100;
+            let height = (img_h * height_pct) / 100;
+            
+            // Vérifier les limites
+            if x + width > img_w || y + height > img_h {
+                crate::log_error(&format!(
+                    "pic::recadrer crop hors limites | image={}x{} | crop x={} y={} w={} h={} | {:?}",
+                    img_w, img_h, x, y, width, height, input
+                ));
+                return false;
+            }
+            
+            let cropped = img.crop_imm(x, y, width, height);
+            let ok = adapter_sortie(&cropped, output, tons).save(output).is_ok();
+            if ok { reporter_metadonnees(input, output, false, orientation_auto, &ProfilCible::Conserver); }
+            else { crate::log_error(&format!("pic::recadrer échec save {:?}", output)); }
+            ok
+        },
+        Err(e) => {
+            crate::log_error(&format!("pic::recadrer impossible d'ouvrir {:?} : {}", input, e));
+            false
+        }
+    }
+}
+
+/// Redimensionne à une largeur/hauteur spécifique en pixels
+pub fn redimensionner_pixels(input: &Path, output: &str, target_width: u32, target_height: u32, orientation_auto: bool, tons: &OptionsTonales) -> bool {
+    crate::log_info(&format!("pic::redimensionner_pixels | {}x{} | {:?} -> {}", target_width, target_height, input, output));
+    if let Some(ok) = traiter_animation(input, output, |img| Ok(img.resize_exact(target_width, target_height, FilterType::Lanczos3))) {
+        return ok;
+    }
+    match ouvrir_image(input, orientation_auto) {
+        Ok(img) => {
+            let resized = img.resize_exact(target_width, target_height, FilterType::Lanczos3);
+            let ok = adapter_sortie(&resized, output, tons).save(output).is_ok();
+            if ok { reporter_metadonnees(input, output, true, orientation_auto, &ProfilCible::Conserver); }
+            else { crate::log_error(&format!("pic::redimensionner_pixels échec save {:?}", output)); }
+            ok
+        },
+        Err(e) => {
+            crate::log_error(&format!("pic::redimensionner_pixels impossible d'ouvrir {:?} : {}", input, e));
+            false
+        }
+    }
+}
+
+/// Mode de calcul de la taille cible
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum ModeRedimension {
+    /// Largeur × hauteur imposées (ratio libre ; une dimension à 0 suit le ratio)
+    #[default]
+    Exact,
+    /// Tient entièrement dans le cadre, ratio conservé
+    Contenir,
+    /// Couvre le cadre puis recadrage centré
+    Remplir,
+    /// Tient dans le cadre, complété par des marges de couleur
+    Marges,
+    /// Échelle en pourcentage
+    Pourcentage,
+    /// Le plus grand côté vaut `cote` pixels
+    GrandCote,
+    /// Le plus petit côté vaut `cote` pixels
+    PetitCote,
+}
+
+impl ModeRedimension {
+    pub const TOUS: [&'static str; 7] = ["exact", "fit", "fill", "pad", "percent", "long-edge", "short-edge"];
+
+    pub fn depuis_nom(nom: &str) -> Option<Self> {
+        Some(match nom.trim().to_lowercase().as_str() {
+            "exact" | "stretch" => Self::Exact,
+            "fit" => Self::Contenir,
+            "fill" | "cover" => Self::Remplir,
+            "pad" => Self::Marges,
+            "percent" | "%" => Self::Pourcentage,
+            "long-edge" | "long" => Self::GrandCote,
+            "short-edge" | "short" => Self::PetitCote,
+            _ => return None,
+        })
+    }
+
+    pub fn nom(self) -> &'static str {
+        Self::TOUS[self as usize]
+    }
+}
+
+/// Filtres de rééchantillonnage proposés (nom CLI, filtre)
+pub const FILTRES_REDIMENSION: [(&str, FilterType); 5] = [
+    ("nearest", FilterType::Nearest),
+    ("bilinear", FilterType::Triangle),
+    ("bicubic", FilterType::CatmullRom),
+    ("gaussian", FilterType::Gaussian),
+    ("lanczos3", FilterType::Lanczos3),
+];
+
+pub fn filtre_depuis_nom(nom: &str) -> Option<FilterType> {
+    let nom = nom.trim().to_lowercase();
+    FILTRES_REDIMENSION.iter().find(|(n, _)| *n == nom).map(|(_, f)| *f)
+}
+
+/// Paramètres de redimensionnement
+#[derive(Clone, Debug)]
+pub struct OptionsRedimension {
+    pub mode: ModeRedimension,
+    /// Cadre cible (Exact, Contenir, Remplir, Marges)
+    pub largeur: u32,
+    pub hauteur: u32,
+    /// Échelle en % (Pourcentage)
+    pub pourcentage: f32,
+    /// Longueur du côté (GrandCote, PetitCote)
+    pub cote: u32,
+    /// Couleur des marges (Marges), RGBA
+    pub fond: [u8; 4],
+    /// N'agrandit jamais l'image
+    pub reduire_seulement: bool,
+    pub filtre: FilterType,
+}
+
+impl Default for OptionsRedimension {
+    fn default() -> Self {
+        Self {
+            mode: ModeRedimension::Exact,
+            largeur: 0,
+            hauteur: 0,
+            pourcentage: 100.0,
+            cote: 0,
+            fond: [0, 0, 0, 0],
+            reduire_seulement: false,
+            filtre: FilterType::Lanczos3,
+        }
+    }
+}
+
+impl OptionsRedimension {
+    /// Vrai si le mode dispose des dimensions dont il a besoin
+    pub fn est_definie(&self) -> bool {
+        geometrie_redimension(1, 1, self).is_some()
+    }
+}
+
+/// Géométrie d'un redimensionnement : taille de l'image mise à l'échelle
+/// puis taille finale (recadrage pour Remplir, toile pour Marges)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GeometrieRedimension {
+    pub echelle: (u32, u32),
+    pub finale: (u32, u32),
+}
+
+/// Calcule la géométrie cible pour une image w×h, None si les options sont incomplètes
+pub fn geometrie_redimension(w: u32, h: u32, options: &OptionsRedimension) -> Option<GeometrieRedimension> {
+    if w == 0 || h == 0 { return None; }
+    let (wf, hf) = (w as f64, h as f64);
+    let (cl, ch) = (options.largeur as f64, options.hauteur as f64);
+    let arrondi = |v: f64| (v.round() as u32).max(1);
+    // Facteur d'échelle borné à 1 en mode « réduire seulement »
+    let borner = |f: f64| if options.reduire_seulement { f.min(1.0) } else { f };
+    let uniforme = |f: f64| {
+        let f = borner(f);
+        (arrondi(wf * f), arrondi(hf * f))
+    };
+    let echelle = match options.mode {
+        ModeRedimension::Exact => {
+            let (lw, lh) = match (options.largeur, options.hauteur) {
+                (0, 0) => return None,
+                (0, _) => (arrondi(wf * ch / hf), options.hauteur),
+                (_, 0) => (options.largeur, arrondi(hf * cl / wf)),
+                (lw, lh) => (lw, lh),
+            };
+            if options.reduire_seulement { (lw.min(w), lh.min(h)) } else { (lw, lh) }
+        }
+        ModeRedimension::Contenir | ModeRedimension::Marges => {
+            if options.largeur == 0 || options.hauteur == 0 { return None; }
+            uniforme((cl / wf).min(ch / hf))
+        }
+        ModeRedimension::Remplir => {
+            if options.largeur == 0 || options.hauteur == 0 { return None; }
+            uniforme((cl / wf).max(ch / hf))
+        }
+        ModeRedimension::Pourcentage => {
+            if options.pourcentage <= 0.0 { return None; }
+            uniforme(options.pourcentage as f64 / 100.0)
+        }
+        ModeRedimension::GrandCote => {
+            if options.cote == 0 { return None; }
+            uniforme(options.cote as f64 / wf.max(hf))
+        }
+        ModeRedimension::PetitCote => {
+            if options.cote == 0 { return None; }
+            uniforme(options.cote as f64 / wf.min(hf))
+        }
+    };
+    let finale = match options.mode {
+        ModeRedimension::Remplir => (options.largeur.min(echelle.0), options.hauteur.min(echelle.1)),
+        ModeRedimension::Marges => (options.largeur, options.hauteur),
+        _ => echelle,
+    };
+    Some(GeometrieRedimension { echelle, finale })
+}
+
+/// Redimensionne une image en mémoire selon les options
+pub fn redimensionner_image(img: &image::DynamicImage, options: &OptionsRedimension) -> Option<image::DynamicImage> {
+    let geo = geometrie_redimension(img.width(), img.height(), options)?;
+    let (ew, eh) = geo.echelle;
+    let redim = if (ew, eh) == (img.width(), img.height()) { img.clone() } else { img.resize_exact(ew, eh, options.filtre) };
+    let (fw, fh) = geo.finale;
+    Some(match options.mode {
+        ModeRedimension::Remplir if (fw, fh) != (ew, eh) => redim.crop_imm((ew - fw) / 2, (eh - fh) / 2, fw, fh),
+        ModeRedimension::Marges => {
+            let mut toile = image::RgbaImage::from_pixel(fw, fh, image::Rgba(options.fond));
+            image::imageops::overlay(&mut toile, &redim.to_rgba8(), ((fw - ew) / 2) as i64, ((fh - eh) / 2) as i64);
+            image::DynamicImage::ImageRgba8(toile)
+        }
+        _ => redim,
+    })
+}
+
+/// Redimensionne un fichier selon le mode choisi (contenir, remplir, marges, %, côté…)
+pub fn redimensionner(input: &Path, output: &str, options: &OptionsRedimension, orientation_auto: bool, tons: &OptionsTonales) -> bool {
+    crate::log_info(&format!(
+        "pic::redimensionner | mode={} {}x{} {}% cote={} reduire_seulement={} | {:?} -> {}",
+        options.mode.nom(), options.largeur, options.hauteur, options.pourcentage, options.cote, options.reduire_seulement, input, output
+    ));
+    let mut options = options.clone();
+    // Sans canal alpha, les marges transparentes sont composées sur du blanc
+    let ext = Path::new(output).extension().unwrap_or_default().to_string_lossy().to_lowercase();
+    if matches!(ext.as_str(), "jpg" | "jpeg" | "bmp") && options.fond[3] < 255 {
+        let a = options.fond[3] as u32;
+        for c in 0..3 {
+            options.fond[c] = ((options.fond[c] as u32 * a + 255 * (255 - a)) / 255) as u8;
+        }
+        options.fond[3] = 255;
+    }
+    let animation = traiter_animation(input, output, |img| {
+        redimensionner_image(&img, &options).ok_or_else(|| format!("options incomplètes pour le mode {}", options.mode.nom()))
+    });
+    if let Some(ok) = animation { return ok; }
+    let img = match ouvrir_image(input, orientation_auto) {
+        Ok(i) => i,
+        Err(e) => {
+            crate::log_error(&format!("pic::redimensionner impossible d'ouvrir {:?} : {}", input, e));
+            return false;
+        }
+    };
+    let Some(resultat) = redimensionner_image(&img, &options) else {
+        crate::log_error(&format!("pic::redimensionner options incomplètes pour le mode {} | {:?}", options.mode.nom(), input));
+        return false;
+    };
+    let ok = sauvegarder_image_tons(&resultat, output, 9, tons);
+    // Après recadrage (Remplir), la miniature EXIF montrerait des zones retirées
+    if ok { reporter_metadonnees(input, output, options.mode != ModeRedimension::Remplir, orientation_auto, &ProfilCible::Conserver); }
+    ok
+}
+
+/// Redimensionne pour atteindre un poids maximum (en Ko)
+/// Cherche d'abord la qualité à pleine résolution, puis réduit la résolution si besoin
+pub fn redimensionner_poids(input: &Path, output: &str, max_size_kb: u32, orientation_auto: bool) -> bool {
+    encoder_poids_cible(input, output, max_size_kb, QUALITE_MIN_POIDS, orientation_auto).is_ok()
+}
+
+/// Qualité minimale acceptée par défaut lors de la recherche de poids (1-100)
+pub const QUALITE_MIN_POIDS: u8 = 30;
+/// Qualité maximale essayée (au-delà le gain visuel est nul pour un poids bien plus élevé)
+const QUALITE_MAX_POIDS: u8 = 95;
+/// Pas de réduction de la résolution entre deux essais (facteur linéaire)
+const PAS_ECHELLE_MAX: f64 = 0.95;
+const PAS_ECHELLE_MIN: f64 = 0.75;
+const COTE_MIN_POIDS: u32 = 16;
+
+/// Encodeur utilisé pour la recherche de poids, déduit de l'extension de sortie
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum EncodeurPoids {
+    Jpeg,
+    Avif,
+    /// JXL sans perte (zune-jpegxl) : seule la résolution varie
+    Jxl,
+    /// Format sans réglage de qualité (PNG, TIFF, BMP, WebP…) : seule la résolution varie
+    SansPerte(image::ImageFormat),
+}
+
+impl EncodeurPoids {
+    fn depuis_sortie(output: &str) -> Result<Self, String> {
+        let ext = Path::new(output).extension().unwrap_or_default().to_string_lossy().to_lowercase();
+        if matches!(ext.as_str(), "webp" | "jxl") {
+            // Encodeurs disponibles sans perte uniquement : le poids ne s'obtient qu'en réduisant la résolution
+            crate::log_warn(&format!("pic::encoder_poids_cible | {} sans perte, qualité fixe : seule la résolution est réduite", ext.to_uppercase()));
+        }
+        Ok(match ext.as_str() {
+            "jpg" | "jpeg" => Self::Jpeg,
+            "avif" => Self::Avif,
+            "jxl" => Self::Jxl,
+            _ => Self::SansPerte(image::ImageFormat::from_extension(&ext)
+                .ok_or_else(|| format!("Format de sortie non pris en charge : {}", output))?),
+        })
+    }
+
+    fn avec_qualite(self) -> bool {
+        matches!(self, Self::Jpeg | Self::Avif)
+    }
+
+    fn encoder(self, img: &image::DynamicImage, qualite: u8) -> Result<Vec<u8>, String> {
+        let mut buf = Vec::new();
+        match self {
+            Self::Jpeg => {
+                let rgb = img.to_rgb8();
+                image::codecs::jpeg::JpegEncoder::new_with_quality(&mut buf, qualite)
+                    .write_image(rgb.as_raw(), rgb.width(), rgb.height(), image::ExtendedColorType::Rgb8)
+                    .map_err(|e| format!("JPEG : {}", e))?;
+            }
+            Self::Avif => {
+                let rgba = img.to_rgba8();
+                image::codecs::avif::AvifEncoder::new_with_speed_quality(&mut buf, 8, qualite)
+                    .write_image(rgba.as_raw(), rgba.width(), rgba.height(), image::ExtendedColorType::Rgba8)
+                    .map_err(|e| format!("AVIF : {}", e))?;
+            }
+            Self::Jxl => buf = encoder_jxl_octets(img)?,
+            Self::SansPerte(format) => {
+                img.write_to(&mut std::io::Cursor::new(&mut buf), format).map_err(|e| format!("{:?} : {}", format, e))?;
+            }
+        }
+        Ok(buf)
+    }
+}
+
+/// Résultat d'un encodage à poids cible
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RapportPoids {
+    /// Qualité retenue (JPEG / AVIF) ; None pour les formats sans perte (PNG, TIFF, WebP, JXL…),
+    /// où seule la résolution a été réduite.
+    pub qualite: Option<u8>,
+    pub largeur: u32,
+    pub hauteur: u32,
+    pub octets: u64,
+}
+
+impl RapportPoids {
+    pub fn resume(&self) -> String {
+        let qualite = self.qualite.map_or_else(|| "lossless, ".to_string(), |q| format!("quality {}, ", q));
+        format!("{}{}x{}, {} KB", qualite, self.largeur, self.hauteur, self.octets.div_ceil(1024))
+    }
+}
+
+/// Plus haute qualité de [min, max] dont l'encodage tient dans `cible` octets (recherche dichotomique).
+/// Si même la qualité minimale dépasse, renvoie son poids en erreur interne.
+fn chercher_qualite(
+    encodeur: EncodeurPoids,
+    img: &image::DynamicImage,
+    cible: u64,
+    min: u8,
+    max: u8,
+) -> Result<Result<(u8, Vec<u8>), u64>, String> {
+    let au_min = encodeur.encoder(img, min)?;
+    if au_min.len() as u64 > cible {
+        return Ok(Err(au_min.len() as u64));
+    }
+    let (mut bas, mut haut) = (min, max);
+    let mut meilleur = (min, au_min);
+    while bas < haut {
+        let milieu = bas + (haut - bas).div_ceil(2);
+        let octets = encodeur.encoder(img, milieu)?;
+        if octets.len() as u64 <= cible {
+            meilleur = (milieu, octets);
+            bas = milieu;
+        } else {
+            haut = milieu - 1;
+        }
+    }
+    Ok(Ok(meilleur))
+}
+
+/// Encode une image décodée pour qu'elle pèse au plus `max_kb` Ko.
+/// JPEG et AVIF : qualité cherchée par dichotomie à pleine résolution, puis résolution
+/// réduite par petits pas. Formats sans perte (PNG, TIFF, WebP, JXL…) : seule la résolution varie.
+pub fn encoder_poids_cible_image(
+    img: &image::DynamicImage,
+    output: &str,
+    max_kb: u32,
+    qualite_min: u8,
+) -> Result<RapportPoids, String> {
+    encoder_poids_cible_octets(img, output, max_kb as u64 * 1024, qualite_min)
+}
+
+fn encoder_poids_cible_octets(
+    img: &image::DynamicImage,
+    output: &str,
+    cible: u64,
+    qualite_min: u8,
+) -> Result<RapportPoids, String> {
+    let encodeur = EncodeurPoids::depuis_sortie(output)?;
+    let max_kb = cible.div_ceil(1024);
+    if cible == 0 {
+        return Err("Poids cible nul".into());
+    }
+    let qualite_min = qualite_min.clamp(1, QUALITE_MAX_POIDS);
+    let (w0, h0) = (img.width(), img.height());
+    let mut echelle = 1.0f64;
+    loop {
+        let (w, h) = (((w0 as f64 * echelle).round() as u32).max(1), ((h0 as f64 * echelle).round() as u32).max(1));
+        if w.min(h) < COTE_MIN_POIDS.min(w0.min(h0)) {
+            return Err(format!("Impossible d'atteindre {} Ko (image réduite sous {} px)", max_kb, COTE_MIN_POIDS));
+        }
+        let courante = if echelle < 1.0 { img.resize_exact(w, h, FilterType::Lanczos3) } else { img.clone() };
+        let essai = if encodeur.avec_qualite() {
+            chercher_qualite(encodeur, &courante, cible, qualite_min, QUALITE_MAX_POIDS)?.map(|(q, o)| (Some(q), o))
+        } else {
+            let octets = encodeur.encoder(&courante, 0)?;
+            if octets.len() as u64 <= cible { Ok((None, octets)) } else { Err(octets.len() as u64) }
+        };
+        let trop_lourd = match essai {
+            Ok((qualite, octets)) => {
+                std::fs::write(output, &octets).map_err(|e| format!("Écriture {} : {}", output, e))?;
+                return Ok(RapportPoids { qualite, largeur: w, hauteur: h, octets: octets.len() as u64 });
+            }
+            Err(octets) => octets,
+        };
+        // Le poids suit à peu près le nombre de pixels : pas déduit du dépassement, borné
+        let pas = ((cible as f64 / trop_lourd as f64).sqrt() * 0.98).clamp(PAS_ECHELLE_MIN, PAS_ECHELLE_MAX);
+        echelle *= pas;
+    }
+}
+
+/// Encode un fichier image à un poids maximum (voir `encoder_poids_cible_image`).
+/// Les métadonnées de la source sont reportées, leur poids est réservé sur la cible.
+pub fn encoder_poids_cible(input: &Path, output: &str, max_kb: u32, qualite_min: u8, orientation_auto: bool) -> Result<RapportPoids, String> {
+    encoder_poids_cible_geometrie(input, output, None, max_kb, qualite_min, orientation_auto)
+}
+
+/// Redimensionne en mémoire puis encode au poids maximum, sans fichier intermédiaire
+pub fn redimensionner_poids_cible(
+    input: &Path,
+    output: &str,
+    options: &OptionsRedimension,
+    max_kb: u32,
+    qualite_min: u8,
+    orientation_auto: bool,
+) -> Result<RapportPoids, String> {
+    encoder_poids_cible_geometrie(input, output, Some(options), max_kb, qualite_min, orientation_auto)
+}
+
+fn encoder_poids_cible_geometrie(
+    input: &Path,
+    output: &str,
+    geometrie: Option<&OptionsRedimension>,
+    max_kb: u32,
+    qualite_min: u8,
+    orientation_auto: bool,
+) -> Result<RapportPoids, String> {
+    crate::log_info(&format!("pic::encoder_poids_cible | max={}Ko qmin={} | {:?} -> {}", max_kb, qualite_min, input, output));
+    let reserve = lire_metadonnees(input).taille() as u64;
+    let resultat = ouvrir_image(input, orientation_auto)
+        .map_err(|e| format!("Impossible d'ouvrir {:?} : {}", input, e))
+        .and_then(|img| match geometrie {
+            Some(options) => redimensionner_image(&img, options)
+                .ok_or_else(|| format!("options incomplètes pour le mode {}", options.mode.nom())),
+            None => Ok(img),
+        })
+        .and_then(|img| encoder_poids_cible_octets(&img, output, (max_kb as u64 * 1024).saturating_sub(reserve), qualite_min))
+        .map(|mut rapport| {
+            reporter_metadonnees(input, output, false, orientation_auto, &ProfilCible::Conserver);
+            rapport.octets = std::fs::metadata(output).map_or(rapport.octets, |m| m.len());
+            rapport
+        });
+    match &resultat {
+        Ok(rapport) => crate::log_info(&format!("pic::encoder_poids_cible {} -> {}", output, rapport.resume())),
+        Err(e) => crate::log_error(&format!("pic::encoder_poids_cible {:?} : {}", input, e)),
+    }
+    resultat
+}
+
+// ════════════════════════════════════════════════════════════════════════
+//  RÉGLAGES PHOTO — luminosité, contraste, saturation, netteté, niveaux auto
+// ════════════════════════════════════════════════════════════════════════
+
+/// Virage appliqué après les réglages de couleur
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Virage {
+    #[default]
+    Aucun,
+    NiveauxGris,
+    Sepia,
+}
+
+impl Virage {
+    pub const TOUS: [&'static str; 3] = ["none", "grayscale", "sepia"];
+
+    pub fn depuis_nom(nom: &str) -> Option<Self> {
+        Some(match nom.trim().to_lowercase().as_str() {
+            "none" => Self::Aucun,
+            "grayscale" | "gray" | "bw" => Self::NiveauxGris,
+            "sepia" => Self::Sepia,
+            _ => return None,
+        })
+    }
+
+    pub fn nom(self) -> &'static str {
+        Self::TOUS[self as usize]
+    }
+}
+
+/// Réglages tonals d'un lot de photos (valeurs neutres par défaut)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OptionsReglages {
+    /// Luminosité, -100 à 100
+    pub luminosite: f32,
+    /// Contraste, -100 à 100
+    pub contraste: f32,
+    /// Gamma des tons moyens (> 1 éclaircit)
+    pub gamma: f32,
+    /// Saturation, -100 (niveaux de gris) à 100
+    pub saturation: f32,
+    /// Force du masque flou (accentuation), 0 = aucune
+    pub nettete: f32,
+    /// Rayon (sigma) du masque flou
+    pub rayon_nettete: f32,
+    /// Écart minimal (0-255) en dessous duquel l'accentuation est ignorée
+    pub seuil_nettete: i32,
+    /// Flou gaussien de débruitage (sigma), 0 = aucun
+    pub debruitage: f32,
+    pub virage: Virage,
+    /// Étire l'histogramme de luminance (0,5 % écrêté de chaque côté)
+    pub niveaux_auto: bool,
+    /// Balance des blancs « monde gris » : les moyennes R, V, B sont égalisées
+    pub balance_auto: bool,
+}
+
+impl Default for OptionsReglages {
+    fn default() -> Self {
+        Self {
+            luminosite: 0.0,
+            contraste: 0.0,
+            gamma: 1.0,
+            saturation: 0.0,
+            nettete: 0.0,
+            rayon_nettete: 1.0,
+            seuil_nettete: 0,
+            debruitage: 0.0,
+            virage: Virage::Aucun,
+            niveaux_auto: false,
+            balance_auto: false,
+        }
+    }
+}
+
+impl OptionsReglages {
+    pub fn est_neutre(&self) -> bool {
+        let neutre = Self::default();
+        Self { rayon_nettete: neutre.rayon_nettete, seuil_nettete: neutre.seuil_nettete, ..*self } == neutre
+    }
+}
+
+/// Part des pixels écrêtée à chaque extrémité par les niveaux automatiques
+const NIVEAUX_AUTO_ECRETAGE: f32 = 0.005;
+
+fn luma(p: &image::Rgba<u8>) -> f32 {
+    0.2126 * p[0] as f32 + 0.7152 * p[1] as f32 + 0.0722 * p[2] as f32
+}
+
+/// Gains R, V, B qui ramènent la moyenne de chaque canal à la moyenne grise
+fn gains_balance_auto(img: &image::RgbaImage) -> [f32; 3] {
+    let mut sommes = [0f64; 3];
+    let mut n = 0f64;
+    for p in img.pixels().filter(|p| p[3] > 0) {
+        for c in 0..3 { sommes[c] += p[c] as f64; }
+        n += 1.0;
+    }
+    if n == 0.0 { return [1.0; 3]; }
+    let gris = (sommes[0] + sommes[1] + sommes[2]) / 3.0;
+    sommes.map(|s| if s > 0.0 { (gris / s).clamp(0.5, 2.0) as f32 } else { 1.0 })
+}
+
+/// Bornes (noir, blanc) de la luminance après écrêtage des extrêmes
+fn bornes_niveaux_auto(img: &image::RgbaImage) -> (f32, f32) {
+    let mut histogramme = [0usize; 256];
+    for p in img.pixels().filter(|p| p[3] > 0) {
+        histogramme[luma(p).round().clamp(0.0, 255.0) as usize] += 1;
+    }
+    let total: usize = histogramme.iter().sum();
+    let ecrete = (total as f32 * NIVEAUX_AUTO_ECRETAGE) as usize;
+    let borne = |niveaux: Vec<usize>| {
+        let mut cumul = 0;
+        niveaux.into_iter().find(|&v| { cumul += histogramme[v]; cumul > ecrete })
+    };
+    let noir = borne((0..256).collect()).unwrap_or(0) as f32;
+    let blanc = borne((0..256).rev().collect()).unwrap_or(255) as f32;
+    if blanc - noir < 8.0 { (0.0, 255.0) } else { (noir, blanc) }
+}
+
+/// Applique les réglages dans l'ordre : balance des blancs, niveaux, débruitage,
+/// luminosité/contraste/gamma, saturation, virage, netteté. L'alpha est conservé.
+pub fn appliquer_reglages(img: &image::DynamicImage, options: &OptionsReglages) -> image::DynamicImage {
+    let mut rgba = img.to_rgba8();
+    let gains = if options.balance_auto { gains_balance_auto(&rgba) } else { [1.0; 3] };
+    let (noir, blanc) = if options.niveaux_auto {
+        let mut equilibre = rgba.clone();
+        for p in equilibre.pixels_mut() {
+            for c in 0..3 { p[c] = (p[c] as f32 * gains[c]).round().clamp(0.0, 255.0) as u8; }
+        }
+        bornes_niveaux_auto(&equilibre)
+    } else {
+        (0.0, 255.0)
+    };
+    if options.debruitage > 0.0 {
+        rgba = image::imageops::blur(&rgba, options.debruitage);
+    }
+    // Table de tons : niveaux, luminosité, contraste puis gamma (0-1)
+    let contraste = (1.0 + options.contraste.clamp(-100.0, 100.0) / 100.0).max(0.0);
+    let inverse_gamma = 1.0 / options.gamma.max(0.05);
+    let ton = |v: f32| {
+        let x = (v - noir) / (blanc - noir) + options.luminosite.clamp(-100.0, 100.0) / 200.0;
+        ((x - 0.5) * contraste + 0.5).clamp(0.0, 1.0).powf(inverse_gamma)
+    };
+    let saturation = 1.0 + options.saturation.clamp(-100.0, 100.0) / 100.0;
+    for p in rgba.pixels_mut() {
+        let rgb = [0, 1, 2].map(|c| ton(p[c] as f32 * gains[c]));
+        let l = 0.2126 * rgb[0] + 0.7152 * rgb[1] + 0.0722 * rgb[2];
+        let [r, g, b] = rgb.map(|v| l + (v - l) * saturation);
+        let rgb = match options.virage {
+            Virage::Aucun => [r, g, b],
+            Virage::NiveauxGris => [0.2126 * r + 0.7152 * g + 0.0722 * b; 3],
+            Virage::Sepia => [
+                0.393 * r + 0.769 * g + 0.189 * b,
+                0.349 * r + 0.686 * g + 0.168 * b,
+                0.272 * r + 0.534 * g + 0.131 * b,
+            ],
+        };
+        for c in 0..3 { p[c] = (rgb[c] * 255.0).round().clamp(0.0, 255.0) as u8; }
+    }
+    if options.nettete > 0.0 {
+        // Masque flou : original + force × (accentué - original)
+        let accentue = image::imageops::unsharpen(&rgba, options.rayon_nettete.max(0.1), options.seuil_nettete);
+        for (p, a) in rgba.pixels_mut().zip(accentue.pixels()) {
+            for c in 0..3 {
+                p[c] = (p[c] as f32 + options.nettete * (a[c] as f32 - p[c] as f32)).round().clamp(0.0, 255.0) as u8;
+            }
+        }
+    }
+    let rgba = image::DynamicImage::ImageRgba8(rgba);
+    // Une image en niveaux de gris le reste, sauf virage sépia
+    match (img.color().has_color() || options.virage == Virage::Sepia, img.color().has_alpha()) {
+        (true, true) => rgba,
+        (true, false) => image::DynamicImage::ImageRgb8(rgba.to_rgb8()),
+        (false, true) => image::DynamicImage::ImageLumaA8(rgba.to_luma_alpha8()),
+        (false, false) => image::DynamicImage::ImageLuma8(rgba.to_luma8()),
+    }
+}
+
+/// Réglages photo en lot (toutes les trames pour une animation).
+/// La résolution n'est pas modifiée ; la miniature EXIF, qui ne correspond plus, est retirée.
+pub fn ajuster(input: &Path, output: &str, options: &OptionsReglages, orientation_auto: bool) -> bool {
+    crate::log_info(&format!("pic::ajuster | {:?} | {:?} -> {}", options, input, output));
+    if let Some(ok) = traiter_animation(input, output, |img| Ok(appliquer_reglages(&img, options))) {
+        return ok;
+    }
+    match ouvrir_image(input, orientation_auto) {
+        Ok(img) => {
+            let ok = sauvegarder_image(&appliquer_reglages(&img, options), output, 9);
+            if ok { reporter_metadonnees(input, output, false, orientation_auto, &ProfilCible::Conserver); }
+            else { crate::log_error(&format!("pic::ajuster échec save {:?}", output)); }
+            ok
+        },
+        Err(e) => {
+            crate::log_error(&format!("pic::ajuster impossible d'ouvrir {:?} : {}", input, e));
+            false
+        }
+    }
+}
+
+// ════════════════════════════════════════════════════════════════════════
+//  ANIMATIONS — GIF, APNG, WebP animé
+// ════════════════════════════════════════════════════════════════════════
+
+/// Délai des trames assemblées sans durée précisée
+pub const DELAI_TRAME_DEFAUT_MS: u32 = 100;
+
+/// Animation décodée : trames pleine taille (déjà composées) et nombre de boucles (0 = infini)
+#[derive(Clone)]
+pub struct Animation {
+    pub trames: Vec<image::Frame>,
+    pub boucles: u32,
+}
+
+impl Animation {
+    pub fn dimensions(&self) -> (u32, u32) {
+        self.trames.first().map_or((0, 0), |t| t.buffer().dimensions())
+    }
+
+    /// Durée d'une boucle en millisecondes
+    pub fn duree_ms(&self) -> u32 {
+        self.trames.iter().map(delai_trame_ms).sum()
+    }
+}
+
+/// Délai d'une trame arrondi à la milliseconde
+pub fn delai_trame_ms(trame: &image::Frame) -> u32 {
+    let (num, den) = trame.delay().numer_denom_ms();
+    (num + den / 2).checked_div(den).unwrap_or(0)
+}
+
+fn boucles_depuis(compte: image::metadata::LoopCount) -> u32 {
+    match compte {
+        image::metadata::LoopCount::Infinite => 0,
+        image::metadata::LoopCount::Finite(n) => n.get(),
+    }
+}
+
+/// Formats de sortie capables de porter une animation
+pub fn sortie_animable(output: &str) -> bool {
+    let ext = Path::new(output).extension().unwrap_or_default().to_string_lossy().to_lowercase();
+    matches!(ext.as_str(), "gif" | "png" | "apng" | "webp")
+}
+
+/// Décode toutes les trames d'un GIF, APNG ou WebP animé ; `Ok(None)` pour une image fixe
+pub fn lire_animation(input: &Path) -> Result<Option<Animation>, String> {
+    use image::AnimationDecoder;
+    let ext = input.extension().unwrap_or_default().to_string_lossy().to_lowercase();
+    let lecteur = || File::open(input).map(BufReader::new).map_err(|e| format!("Lecture {:?} : {}", input, e));
+    let erreur = |e: image::ImageError| format!("Décodage {:?} : {}", input, e);
+    let (boucles, trames) = match ext.as_str() {
+        "gif" => {
+            let decodeur = image::codecs::gif::GifDecoder::new(lecteur()?).map_err(erreur)?;
+            (boucles_depuis(decodeur.loop_count()), decodeur.into_frames())
+        }
+        "png" | "apng" => {
+            let decodeur = image::codecs::png::PngDecoder::new(lecteur()?).map_err(erreur)?;
+            if !decodeur.is_apng().map_err(erreur)? { return Ok(None); }
+            let decodeur = decodeur.apng().map_err(erreur)?;
+            (boucles_depuis(decodeur.loop_count()), decodeur.into_frames())
+        }
+        "webp" => {
+            let decodeur = image::codecs::webp::WebPDecoder::new(lecteur()?).map_err(erreur)?;
+            if !decodeur.has_animation() { return Ok(None); }
+            (boucles_depuis(decodeur.loop_count()), decodeur.into_frames())
+        }
+        _ => return Ok(None),
+    };
+    let trames = trames.collect_frames().map_err(erreur)?;
+    Ok((trames.len() > 1).then_some(Animation { trames, boucles }))
+}
+
+/// Encode une animation en GIF, APNG (.png/.apng) ou WebP animé (trames sans perte)
+pub fn ecrire_animation(animation: &Animation, output: &str) -> Result<(), String> {
+    if animation.trames.is_empty() {
+        return Err("Animation sans trame".into());
+    }
+    let ext = Path::new(output).extension().unwrap_or_default().to_string_lossy().to_lowercase();
+    let donnees = match ext.as_str() {
+        "gif" => encoder_gif_anime(animation)?,
+        "png" | "apng" => encoder_apng(animation)?,
+        "webp" => encoder_webp_anime(animation)?,
+        _ => return Err(format!("Format {} sans animation (gif, png, apng, webp)", ext)),
+    };
+    std::fs::write(output, donnees).map_err(|e| format!("Écriture {} : {}", output, e))
+}
+
+fn encoder_gif_anime(animation: &Animation) -> Result<Vec<u8>, String> {
+    use image::codecs::gif::{GifEncoder, Repeat};
+    let mut sortie = Vec::new();
+    {
+        let mut encodeur = GifEncoder::new_with_speed(&mut sortie, 10);
+        let repetition = match animation.boucles {
+            0 => Repeat::Infinite,
+            n => Repeat::Finite(n.min(u16::MAX as u32) as u16),
+        };
+        encodeur.set_repeat(repetition).map_err(|e| format!("GIF : {}", e))?;
+        encodeur.encode_frames(animation.trames.iter().cloned()).map_err(|e| format!("GIF : {}", e))?;
+    }
+    Ok(sortie)
+}
+
+fn encoder_apng(animation: &Animation) -> Result<Vec<u8>, String> {
+    let (w, h) = animation.dimensions();
+    let erreur = |e: png::EncodingError| format!("APNG : {}", e);
+    let mut sortie = Vec::new();
+    let mut encodeur = png::Encoder::new(&mut sortie, w, h);
+    encodeur.set_color(png::ColorType::Rgba);
+    encodeur.set_depth(png::BitDepth::Eight);
+    encodeur.set_animated(animation.trames.len() as u32, animation.boucles).map_err(erreur)?;
+    let mut ecrivain = encodeur.write_header().map_err(erreur)?;
+    for trame in &animation.trames {
+        ecrivain.set_frame_delay(delai_trame_ms(trame).min(u16::MAX as u32) as u16, 1000).map_err(erreur)?;
+        ecrivain.write_image_data(trame.buffer().as_raw()).map_err(erreur)?;
+    }
+    ecrivain.finish().map_err(erreur)?;
+    Ok(sortie)
+}
+
+/// WebP animé : VP8X + ANIM, puis une trame ANMF pleine taille (VP8L sans perte) par image
+fn encoder_webp_anime(animation: &Animation) -> Result<Vec<u8>, String> {
+    let (w, h) = animation.dimensions();
+    let mut corps = Vec::new();
+    // Drapeaux VP8X : alpha (0x10) et animation (0x02)
+    chunk_webp(&mut corps, b"VP8X", &vp8x_webp(0x12, w, h));
+    let mut anim = vec![0u8; 4]; // couleur de fond BGRA
+    anim.extend_from_slice(&(animation.boucles.min(u16::MAX as u32) as u16).to_le_bytes());
+    chunk_webp(&mut corps, b"ANIM", &anim);
+    for trame in &animation.trames {
+        let mut fichier = Vec::new();
+        image::codecs::webp::WebPEncoder::new_lossless(&mut fichier)
+            .write_image(trame.buffer().as_raw(), w, h, image::ExtendedColorType::Rgba8)
+            .map_err(|e| format!("WebP : {}", e))?;
+        let chunks = chunks_webp(&fichier).ok_or("WebP : trame illisible")?;
+        let vp8l = chunks.iter().find(|(f, _)| f == b"VP8L").ok_or("WebP : flux VP8L absent")?.1;
+        // Position (0,0), taille, durée sur 24 bits, puis drapeaux : sans mélange (0x02), sans effacement
+        let mut anmf = vec![0u8; 6];
+        anmf.extend_from_slice(&(w - 1).to_le_bytes()[..3]);
+        anmf.extend_from_slice(&(h - 1).to_le_bytes()[..3]);
+        anmf.extend_from_slice(&delai_trame_ms(trame).min(0xFF_FFFF).to_le_bytes()[..3]);
+        anmf.push(0x02);
+        chunk_webp(&mut anmf, b"VP8L", vp8l);
+        chunk_webp(&mut corps, b"ANMF", &anmf);
+    }
+    Ok(riff_webp(&corps))
+}
+
+/// Applique `traitement` à chaque trame quand `input` est animé et que `output` accepte l'animation.
+/// `None` : image fixe ou sortie sans animation, le traitement habituel s'applique.
+fn traiter_animation(
+    input: &Path,
+    output: &str,
+    mut traitement: impl FnMut(image::DynamicImage) -> Result<image::DynamicImage, String>,
+) -> Option<bool> {
+    if !sortie_animable(output) { return None; }
+    let mut animation = match lire_animation(input) {
+        Ok(Some(a)) => a,
+        Ok(None) => return None,
+        Err(e) => {
+            crate::log_warn(&format!("pic::traiter_animation {} : première trame seulement", e));
+            return None;
+        }
+    };
+    crate::log_info(&format!("pic::traiter_animation | {} trames, {} ms | {:?} -> {}", animation.trames.len(), animation.duree_ms(), input, output));
+    let trames = animation.trames.iter().map(|trame| {
+        let img = traitement(image::DynamicImage::ImageRgba8(trame.buffer().clone()))?;
+        Ok(image::Frame::from_parts(img.to_rgba8(), 0, 0, trame.delay()))
+    }).collect::<Result<Vec<_>, String>>();
+    let resultat = trames.and_then(|t| {
+        animation.trames = t;
+        ecrire_animation(&animation, output)
+    });
+    if let Err(e) = &resultat {
+        crate::log_error(&format!("pic::traiter_animation {:?} -> {} : {}", input, output, e));
+    }
+    Some(resultat.is_ok())
+}
+
+/// Enregistre chaque trame en PNG dans `dossier` (`<nom>_0001_100ms.png`…), délai inclus dans le nom.
+/// Une image fixe donne une seule trame. Renvoie le nombre de fichiers écrits.
+pub fn extraire_trames(input: &Path, dossier: &Path, orientation_auto: bool) -> Result<usize, String> {
+    crate::log_info(&format!("pic::extraire_trames | {:?} -> {:?}", input, dossier));
+    let trames = match lire_animation(input)? {
+        Some(animation) => animation.trames,
+        None => {
+            let img = ouvrir_image(input, orientation_auto).map_err(|e| format!("Ouverture {:?} : {}", input, e))?;
+            vec![image::Frame::new(img.to_rgba8())]
+        }
+    };
+    std::fs::create_dir_all(dossier).map_err(|e| format!("Création {:?} : {}", dossier, e))?;
+    let nom = input.file_stem().unwrap_or_default().to_string_lossy();
+    for (i, trame) in trames.iter().enumerate() {
+        let chemin = dossier.join(format!("{}_{:04}_{}ms.png", nom, i + 1, delai_trame_ms(trame)));
+        trame.buffer().save(&chemin).map_err(|e| format!("Écriture {:?} : {}", chemin, e))?;
+    }
+    Ok(trames.len())
+}
+
+/// Images d'un dossier triées par nom, prêtes à être assemblées
+pub fn collecter_images_dossier(dossier: &Path) -> Result<Vec<PathBuf>, String> {
+    let mut images: Vec<PathBuf> = std::fs::read_dir(dossier)
+        .map_err(|e| format!("Lecture {:?} : {}", dossier, e))?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.is_file() && image::ImageFormat::from_path(p).is_ok())
+        .collect();
+    images.sort();
+    Ok(images)
+}
+
+/// Assemble des images en animation. Le canevas est celui de la première image ; les autres y sont
+/// centrées sans déformation. Le délai vient du suffixe `_<n>ms` laissé par `extraire_trames`,
+/// sinon de `delai_ms`. `boucles` : 0 = infini.
+pub fn assembler_animation(images: &[PathBuf], output: &str, delai_ms: u32, boucles: u32, orientation_auto: bool) -> Result<usize, String> {
+    crate::log_info(&format!("pic::assembler_animation | {} images delai={}ms boucles={} -> {}", images.len(), delai_ms, boucles, output));
+    let suffixe = regex::Regex::new(r"_(\d+)ms$").expect("regex délai");
+    let mut trames: Vec<image::Frame> = Vec::with_capacity(images.len());
+    for chemin in images {
+        let mut img = ouvrir_image(chemin, orientation_auto).map_err(|e| format!("Ouverture {:?} : {}", chemin, e))?;
+        if let Some(premiere) = trames.first() {
+            let (w, h) = premiere.buffer().dimensions();
+            if (img.width(), img.height()) != (w, h) {
+                let options = OptionsRedimension { mode: ModeRedimension::Marges, largeur: w, hauteur: h, fond: [0, 0, 0, 0], ..Default::default() };
+                img = redimensionner_image(&img, &options).ok_or("Redimensionnement de trame impossible")?;
+            }
+        }
+        let delai = chemin.file_stem()
+            .and_then(|s| suffixe.captures(&s.to_string_lossy()).and_then(|c| c[1].parse().ok()))
+            .unwrap_or(delai_ms);
+        let delai = image::Delay::from_numer_denom_ms(delai, 1);
+        trames.push(image::Frame::from_parts(img.to_rgba8(), 0, 0, delai));
+    }
+    if trames.is_empty() {
+        return Err("Aucune image à assembler".into());
+    }
+    let nombre = trames.len();
+    ecrire_animation(&Animation { trames, boucles }, output)?;
+    Ok(nombre)
+}
+
+// === FONCTIONS POUR FORMATS SPÉCIAUX ===
+
+/// Conversion SVG vers format raster
+fn convertir_svg(input: &Path, output: &str) -> bool {
+    let mut file = match File::open(input) {
+        Ok(f) => f,
+        Err(_) => return false,
+    };
+    
+    let mut svg_data = Vec::new();
+    if file.read_to_end(&mut svg_data).is_err() {
+        return false;
+    }
+
+    let opt = resvg::usvg::Options::default();
+    let tree = match resvg::usvg::Tree::from_data(&svg_data, &opt) {
+        Ok(t) => t,
+        Err(_) => return false,
+    };
+
+    let size = tree.size();
+    let mut pixmap = match resvg::tiny_skia::Pixmap::new(size.width() as u32, size.height() as u32) {
+        Some(p) => p,
+        None => return false,
+    };
+    
+    resvg::render(&tree, resvg::tiny_skia::Transform::default(), &mut pixmap.as_mut());
+
+    // Convertir en image::RgbaImage
+    let img = match image::RgbaImage::from_raw(
+        pixmap.width(),
+        pixmap.height(),
+        pixmap.data().to_vec(),
+    ) {
+        Some(i) => i,
+        None => return false,
+    };
+
+    image::DynamicImage::ImageRgba8(img).save(output).is_ok()
+}
+
+/// Compression SVG (rasterise sans redimensionnement)
+fn compresser_svg(input: &Path, output: &str) -> bool {
+    convertir_svg(input, output)
+}
+
+// === FONCTIONS POUR FORMAT JXL ===
+
+/// Encode une DynamicImage en JXL via zune-jpegxl (lossless)
+fn encoder_jxl(img: &image::DynamicImage, output: &str) -> bool {
+    match encoder_jxl_octets(img) {
+        Ok(buf) => std::fs::write(output, &buf).is_ok(),
+        Err(e) => {
+            crate::log_error(&format!("pic::encoder_jxl {}", e));
+            false
+        }
+    }
+}
+
+fn encoder_jxl_octets(img: &image::DynamicImage) -> Result<Vec<u8>, String> {
+    let rgba = img.to_rgba8();
+    let (w, h) = (rgba.width(), rgba.height());
+    let opts = EncoderOptions::new(w as usize, h as usize, ColorSpace::RGBA, BitDepth::Eight);
+    let mut buf: Vec<u8> = Vec::new();
+    JxlSimpleEncoder::new(rgba.as_raw(), opts)
+        .encode(&mut buf)
+        .map_err(|e| format!("échec encodage JXL : {:?}", e))?;
+    Ok(buf)
+}
+
+/// Décodage JXL vers DynamicImage via jxl-oxide
+fn decoder_jxl(input: &Path) -> Option<image::DynamicImage> {
+    let data = match std::fs::read(input) {
+        Ok(d) => d,
+        Err(e) => {
+            crate::log_error(&format!("pic::decoder_jxl impossible de lire {:?} : {}", input, e));
+            return None;
+        }
+    };
+    let cursor = std::io::Cursor::new(data);
+    let decoder = match jxl_oxide::integration::JxlDecoder::new(cursor) {
+        Ok(d) => d,
+        Err(e) => {
+            crate::log_error(&format!("pic::decoder_jxl échec init décodeur {:?} : {}", input, e));
+            return None;
+        }
+    };
+    match image::DynamicImage::from_decoder(decoder) {
+        Ok(img) => Some(img),
+        Err(e) => {
+            crate::log_error(&format!("pic::decoder_jxl échec décodage {:?} : {}", input, e));
+            None
+        }
+    }
+}
+
+/// Conversion JXL vers format standard (PNG, JPG, etc.)
+fn convertir_jxl(input: &Path, output: &str) -> bool {
+    match decoder_jxl(input) {
+        Some(img) => {
+            if output.to_lowercase().ends_with(".jxl") {
+                return encoder_jxl(&img, output);
+            }
+            img.save(output).is_ok()
+        },
+        None => false,
+    }
+}
+
+/// Compression JXL avec qualité (décode puis réencode sans resize)
 fn compresser_jxl_qualite(input: &Path, output: &str, _qualite: u32) -> bool {
     match decoder_jxl(input) {
         Some(img) => {
             if output.to_lowercase().ends_with(".jxl") {
                 return encoder_jxl(&img, output);
             }
-            img.save(output).is_ok()
-        },
-        None => false,
+            img.save(output).is_ok()
+        },
+        None => false,
+    }
+}
+/// Conversion RAW vers format standard
+fn convertir_raw(input: &Path, output: &str) -> bool {
+    let mut file = match File::open(input) {
+        Ok(f) => f,
+        Err(_) => return false,
+    };
+
+    let decoded = match rawloader::decode(&mut file) {
+        Ok(d) => d,
+        Err(_) => return false,
+    };
+
+    let width = decoded.width;
+    let height = decoded.height;
+    
+    // Extraire les données selon le type
+    let image_data: Vec<u8> = match decoded.data {
+        rawloader::RawImageData::Integer(ref data) => {
+            data.iter().map(|&val| (val >> 8) as u8).collect()
+        },
+        rawloader::RawImageData::Float(ref data) => {
+            data.iter().map(|&val| (val.clamp(0.0, 1.0) * 255.0) as u8).collect()
+        },
+    };
+
+    let img = match image::RgbImage::from_raw(width as u32, height as u32, image_data) {
+        Some(i) => i,
+        None => return false,
+    };
+
+    image::DynamicImage::ImageRgb8(img).save(output).is_ok()
+}
+
+/// Compression RAW (décode sans redimensionnement)
+fn compresser_raw(input: &Path, output: &str) -> bool {
+    convertir_raw(input, output)
+}
+
+/// Conversion PSD vers format standard.
+/// RGB et niveaux de gris : calques aplatis ; CMYK : image composite convertie via le profil ICC.
+fn convertir_psd(input: &Path, output: &str, cible: &ProfilCible) -> bool {
+    let mut file = match File::open(input) {
+        Ok(f) => f,
+        Err(_) => return false,
+    };
+
+    let mut psd_data = Vec::new();
+    if file.read_to_end(&mut psd_data).is_err() {
+        return false;
+    }
+
+    let (img, sections) = match decoder_psd(&psd_data, cible) {
+        Ok(r) => r,
+        Err(e) => {
+            crate::log_error(&format!("pic::convertir_psd {:?} : {}", input, e));
+            return false;
+        }
+    };
+
+    let ok = img.save(output).is_ok();
+    if ok { reporter_profil(sections.icc.filter(|_| sections.mode != 1), output, cible); }
+    ok
+}
+
+/// Décode un PSD selon son mode colorimétrique (niveaux de gris, RGB, CMYK)
+fn decoder_psd(data: &[u8], cible: &ProfilCible) -> Result<(image::DynamicImage, SectionsPsd), String> {
+    let sections = sections_psd(data)?;
+    let img = match sections.mode {
+        1 | 3 => decoder_psd_rgb(data, &sections, cible),
+        4 => decoder_psd_cmyk(data, &sections, cible),
+        mode => Err(format!("mode colorimétrique {} non pris en charge (niveaux de gris, RGB, CMYK)", mode)),
+    }?;
+    Ok((img, sections))
+}
+
+/// PSD RGB ou niveaux de gris : calques aplatis (image composite si le PSD n'est pas lisible
+/// par calques), couleurs ramenées au profil cible
+fn decoder_psd_rgb(data: &[u8], s: &SectionsPsd, cible: &ProfilCible) -> Result<image::DynamicImage, String> {
+    let rgba = match psd::Psd::from_bytes(data).map_err(|e| e.to_string()).and_then(|p| p.flatten_layers_rgba(&|_| true).map_err(|e| e.to_string())) {
+        Ok(rgba) => rgba,
+        Err(e) => {
+            crate::log_warn(&format!("pic::convertir_psd calques illisibles ({}) : image composite", e));
+            let plans = composite_psd(data, s)?;
+            let base = if s.mode == 1 { 1 } else { 3 };
+            if plans.len() < base { return Err(format!("PSD à {} canaux", plans.len())); }
+            (0..s.largeur as usize * s.hauteur as usize)
+                .flat_map(|p| (0..4).map(move |c| (p, c)))
+                .map(|(p, c)| match c {
+                    3 => plans.get(base).map_or(255, |a| a[p]),
+                    _ => plans[if base == 1 { 0 } else { c }][p],
+                })
+                .collect()
+        }
+    };
+    let mut img = image::DynamicImage::ImageRgba8(image::RgbaImage::from_raw(s.largeur, s.hauteur, rgba).ok_or("Dimensions PSD incohérentes")?);
+    if let Some(icc) = s.icc.as_deref().filter(|_| s.mode == 3) { convertir_vers_cible(&mut img, icc, cible); }
+    Ok(img)
+}
+
+/// Compression PSD (décode sans redimensionnement)
+fn compresser_psd(input: &Path, output: &str) -> bool {
+    convertir_psd(input, output, &ProfilCible::Conserver)
+}
+
+// ════════════════════════════════════════════════════════════════════════
+//  DOUBLONS — empreintes perceptuelles (aHash, dHash, pHash)
+// ════════════════════════════════════════════════════════════════════════
+
+/// Extensions RAW dont on lit l'aperçu JPEG embarqué
+const EXTENSIONS_RAW: [&str; 10] = ["dng", "cr2", "cr3", "nef", "arw", "orf", "rw2", "raf", "pef", "srw"];
+
+/// Longueur d'un flux JPEG commençant par SOI, en suivant ses segments jusqu'à EOI
+fn longueur_jpeg(data: &[u8]) -> Option<usize> {
+    if !data.starts_with(&[0xFF, 0xD8]) { return None; }
+    let mut i = 2;
+    loop {
+        if *data.get(i)? != 0xFF { return None; }
+        let marqueur = *data.get(i + 1)?;
+        match marqueur {
+            0xFF => { i += 1; continue; }
+            0xD9 => return Some(i + 2),
+            0x01 | 0xD0..=0xD7 => { i += 2; continue; }
+            _ => {}
+        }
+        i += 2 + u16::from_be_bytes([*data.get(i + 2)?, *data.get(i + 3)?]) as usize;
+        if marqueur == 0xDA {
+            // Données entropiques : jusqu'au prochain marqueur (ni octet de bourrage FF00, ni RSTn)
+            while *data.get(i)? != 0xFF || matches!(*data.get(i + 1)?, 0x00 | 0xD0..=0xD7) { i += 1; }
+        }
+    }
+}
+
+/// Plus grand aperçu JPEG décodable embarqué dans un fichier RAW (DNG, CR2, NEF, ARW…),
+/// redressé selon l'orientation déclarée par le RAW
+fn apercu_raw(input: &Path, orientation_auto: bool) -> Result<image::DynamicImage, String> {
+    let data = std::fs::read(input).map_err(|e| format!("Lecture {:?} : {}", input, e))?;
+    let mut candidats: Vec<&[u8]> = Vec::new();
+    let mut i = 0;
+    while let Some(p) = data[i..].windows(3).position(|w| w == [0xFF, 0xD8, 0xFF]) {
+        let debut = i + p;
+        match longueur_jpeg(&data[debut..]) {
+            Some(n) => { candidats.push(&data[debut..debut + n]); i = debut + n; }
+            None => i = debut + 3,
+        }
+    }
+    candidats.sort_by_key(|c| std::cmp::Reverse(c.len()));
+    // Les données RAW elles-mêmes peuvent être du JPEG sans perte (CR2, DNG) : illisibles ici, on passe au suivant
+    let mut img = candidats.iter()
+        .find_map(|c| image::load_from_memory_with_format(c, image::ImageFormat::Jpeg).ok())
+        .ok_or("aucun aperçu JPEG embarqué")?;
+    let orientation = exif::Reader::new().read_from_container(&mut std::io::Cursor::new(&data)).ok()
+        .and_then(|e| e.get_field(exif::Tag::Orientation, exif::In::PRIMARY).and_then(|f| f.value.get_uint(0)))
+        .and_then(|o| Orientation::from_exif(o as u8));
+    if let Some(o) = orientation.filter(|_| orientation_auto) { img.apply_orientation(o); }
+    Ok(img)
+}
+
+/// Ouvre une image quel que soit son format : formats courants (orientation EXIF appliquée si `orientation_auto`),
+/// JXL, PSD (aplati) et RAW (aperçu JPEG embarqué)
+pub fn charger_image(input: &Path, orientation_auto: bool) -> Result<image::DynamicImage, String> {
+    let ext = input.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+    match ext.as_str() {
+        "jxl" => decoder_jxl(input).ok_or_else(|| "JXL illisible".to_string()),
+        "psd" => std::fs::read(input).map_err(|e| e.to_string()).and_then(|d| decoder_psd(&d, &ProfilCible::Conserver)).map(|(img, _)| img),
+        e if EXTENSIONS_RAW.contains(&e) => apercu_raw(input, orientation_auto),
+        _ => ouvrir_image(input, orientation_auto).map_err(|e| e.to_string()),
+    }
+}
+
+/// Vrai si `charger_image` sait lire le fichier d'après son extension
+fn est_image_chargeable(p: &Path) -> bool {
+    let ext = p.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+    matches!(ext.as_str(), "jxl" | "psd") || EXTENSIONS_RAW.contains(&ext.as_str())
+        || image::ImageFormat::from_extension(&ext).is_some_and(|f| f.reading_enabled())
+}
+
+/// Images lisibles parmi des fichiers et des dossiers (parcourus récursivement), triées et sans répétition
+pub fn collecter_images(sources: &[PathBuf]) -> Vec<PathBuf> {
+    fn parcourir(dossier: &Path, images: &mut Vec<PathBuf>) {
+        let Ok(entrees) = std::fs::read_dir(dossier) else {
+            crate::log_warn(&format!("pic::collecter_images dossier illisible {:?}", dossier));
+            return;
+        };
+        for p in entrees.filter_map(|e| e.ok().map(|e| e.path())) {
+            if p.is_dir() { parcourir(&p, images); } else if est_image_chargeable(&p) { images.push(p); }
+        }
+    }
+    let mut images = Vec::new();
+    for s in sources {
+        if s.is_dir() { parcourir(s, &mut images); } else if est_image_chargeable(s) { images.push(s.clone()); }
+    }
+    images.sort();
+    images.dedup();
+    images
+}
+
+/// Algorithme d'empreinte perceptuelle (64 bits)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Empreinte {
+    /// aHash : pixels 8×8 comparés à la moyenne
+    Moyenne,
+    /// dHash : gradients horizontaux sur 9×8
+    Difference,
+    /// pHash : basses fréquences de la DCT 32×32 comparées à leur médiane
+    #[default]
+    Perceptuelle,
+}
+
+impl Empreinte {
+    pub const TOUS: [&'static str; 3] = ["ahash", "dhash", "phash"];
+
+    pub fn depuis_nom(nom: &str) -> Option<Self> {
+        Some(match nom.trim().to_lowercase().as_str() {
+            "ahash" | "average" => Self::Moyenne,
+            "dhash" | "difference" => Self::Difference,
+            "phash" | "perceptual" => Self::Perceptuelle,
+            _ => return None,
+        })
+    }
+
+    pub fn nom(self) -> &'static str {
+        Self::TOUS[self as usize]
+    }
+}
+
+/// Coefficients 8×8 de basse fréquence de la DCT-II d'un bloc 32×32
+fn dct_basse_frequence(bloc: &[f32]) -> Vec<f32> {
+    let base: Vec<f32> = (0..8)
+        .flat_map(|u| (0..32).map(move |x| ((2 * x + 1) as f32 * u as f32 * std::f32::consts::PI / 64.0).cos()))
+        .collect();
+    // Passe horizontale (32 lignes × 8 fréquences), puis verticale
+    let lignes: Vec<f32> = bloc.chunks(32)
+        .flat_map(|ligne| base.chunks(32).map(move |b| ligne.iter().zip(b).map(|(p, c)| p * c).sum::<f32>()))
+        .collect();
+    (0..64)
+        .map(|i| (0..32).map(|y| lignes[y * 8 + i % 8] * base[(i / 8) * 32 + y]).sum())
+        .collect()
+}
+
+/// Empreinte perceptuelle 64 bits d'une image (bit de poids fort = premier pixel ou coefficient)
+pub fn empreinte(img: &image::DynamicImage, algo: Empreinte) -> u64 {
+    let bits: Vec<bool> = match algo {
+        Empreinte::Moyenne => {
+            let g = img.thumbnail_exact(8, 8).to_luma32f().into_raw();
+            let moyenne = g.iter().sum::<f32>() / g.len() as f32;
+            g.iter().map(|&v| v > moyenne).collect()
+        }
+        Empreinte::Difference => {
+            let g = img.thumbnail_exact(9, 8).to_luma32f().into_raw();
+            g.chunks(9).flat_map(|l| l.windows(2).map(|w| w[0] > w[1])).collect()
+        }
+        Empreinte::Perceptuelle => {
+            let coef = dct_basse_frequence(&img.thumbnail_exact(32, 32).to_luma32f().into_raw());
+            // La composante continue (DC) est exclue du calcul de la médiane
+            let mut tri = coef[1..].to_vec();
+            tri.sort_by(f32::total_cmp);
+            let mediane = tri[tri.len() / 2];
+            coef.iter().map(|&c| c > mediane).collect()
+        }
+    };
+    bits.iter().fold(0, |h, &b| h << 1 | b as u64)
+}
+
+/// Nombre de bits différents entre deux empreintes
+pub fn distance_hamming(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+/// Image analysée par la recherche de doublons
+#[derive(Clone, Debug)]
+pub struct ImageEmpreinte {
+    pub chemin: PathBuf,
+    pub empreinte: u64,
+    pub largeur: u32,
+    pub hauteur: u32,
+    pub octets: u64,
+    pub modifie: std::time::SystemTime,
+}
+
+fn analyser_image(chemin: &Path, algo: Empreinte, orientation_auto: bool) -> Result<ImageEmpreinte, String> {
+    let meta = std::fs::metadata(chemin).map_err(|e| e.to_string())?;
+    let img = charger_image(chemin, orientation_auto)?;
+    Ok(ImageEmpreinte {
+        chemin: chemin.to_path_buf(),
+        empreinte: empreinte(&img, algo),
+        largeur: img.width(),
+        hauteur: img.height(),
+        octets: meta.len(),
+        modifie: meta.modified().unwrap_or(std::time::UNIX_EPOCH),
+    })
+}
+
+/// Empreintes d'une liste d'images, calculées en parallèle. Les fichiers illisibles sont journalisés et ignorés.
+pub fn calculer_empreintes(fichiers: &[PathBuf], algo: Empreinte, orientation_auto: bool) -> Vec<ImageEmpreinte> {
+    let suivant = std::sync::atomic::AtomicUsize::new(0);
+    let resultats = std::sync::Mutex::new(Vec::with_capacity(fichiers.len()));
+    let fils = std::thread::available_parallelism().map_or(4, |n| n.get()).min(fichiers.len().max(1));
+    std::thread::scope(|s| {
+        for _ in 0..fils {
+            s.spawn(|| while let Some(f) = fichiers.get(suivant.fetch_add(1, std::sync::atomic::Ordering::Relaxed)) {
+                match analyser_image(f, algo, orientation_auto) {
+                    Ok(im) => resultats.lock().unwrap_or_else(|e| e.into_inner()).push(im),
+                    Err(e) => crate::log_warn(&format!("pic::calculer_empreintes {:?} ignoré : {}", f, e)),
+                }
+            });
+        }
+    });
+    let mut images = resultats.into_inner().unwrap_or_else(|e| e.into_inner());
+    images.sort_by(|a, b| a.chemin.cmp(&b.chemin));
+    images
+}
+
+/// Règle désignant l'image conservée dans un groupe de doublons
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Conservation {
+    /// Plus grande définition, puis plus gros fichier
+    #[default]
+    PlusGrande,
+    /// Date de modification la plus ancienne
+    PlusAncienne,
+}
+
+impl Conservation {
+    pub const TOUS: [&'static str; 2] = ["largest", "oldest"];
+
+    pub fn depuis_nom(nom: &str) -> Option<Self> {
+        Some(match nom.trim().to_lowercase().as_str() {
+            "largest" | "keep-largest" => Self::PlusGrande,
+            "oldest" | "keep-oldest" => Self::PlusAncienne,
+            _ => return None,
+        })
+    }
+
+    pub fn nom(self) -> &'static str {
+        Self::TOUS[self as usize]
+    }
+
+    /// Ordre de préférence : l'image à conserver d'abord (à égalité, le chemin le plus court)
+    fn comparer(self, a: &ImageEmpreinte, b: &ImageEmpreinte) -> std::cmp::Ordering {
+        let ordre = match self {
+            Self::PlusGrande => (b.largeur as u64 * b.hauteur as u64, b.octets).cmp(&(a.largeur as u64 * a.hauteur as u64, a.octets)),
+            Self::PlusAncienne => a.modifie.cmp(&b.modifie),
+        };
+        ordre.then_with(|| a.chemin.as_os_str().len().cmp(&b.chemin.as_os_str().len())).then_with(|| a.chemin.cmp(&b.chemin))
+    }
+}
+
+/// Groupe d'images quasi identiques, l'image à conserver en tête
+#[derive(Clone, Debug)]
+pub struct GroupeDoublons {
+    pub images: Vec<ImageEmpreinte>,
+    /// Plus grande distance de Hamming entre la première image et les autres
+    pub distance: u32,
+}
+
+/// Regroupe les images autour de celle à conserver selon `regle` : chaque image rejoint la
+/// première image conservée (par ordre de préférence) dont elle est à `seuil` bits ou moins.
+/// Deux doublons d'un même groupe peuvent être plus éloignés, jamais un doublon et sa tête.
+pub fn grouper_doublons(images: &[ImageEmpreinte], seuil: u32, regle: Conservation) -> Vec<GroupeDoublons> {
+    let mut ordre: Vec<&ImageEmpreinte> = images.iter().collect();
+    ordre.sort_by(|a, b| regle.comparer(a, b));
+    let mut place = vec![false; ordre.len()];
+    let mut groupes = Vec::new();
+    for (i, tete) in ordre.iter().enumerate() {
+        if place[i] { continue; }
+        let mut membres = vec![(*tete).clone()];
+        let mut distance = 0;
+        for (j, im) in ordre.iter().enumerate().skip(i + 1) {
+            let d = distance_hamming(tete.empreinte, im.empreinte);
+            if !place[j] && d <= seuil {
+                place[j] = true;
+                membres.push((*im).clone());
+                distance = distance.max(d);
+            }
+        }
+        if membres.len() > 1 { groupes.push(GroupeDoublons { images: membres, distance }); }
     }
+    groupes.sort_by(|a, b| a.images[0].chemin.cmp(&b.images[0].chemin));
+    groupes
 }
-/// Conversion RAW vers format standard
-fn convertir_raw(input: &Path, output: &str) -> bool {
-    let mut file = match File::open(input) {
-        Ok(f) => f,
-        Err(_) => return false,
-    };
 
-    let decoded = match rawloader::decode(&mut file) {
-        Ok(d) => d,
-        Err(_) => return false,
-    };
+/// Recherche les quasi-doublons parmi des fichiers et dossiers (récursivement), groupés
+/// autour de l'image que `regle` conserve
+pub fn chercher_doublons(sources: &[PathBuf], algo: Empreinte, seuil: u32, regle: Conservation, orientation_auto: bool) -> Result<Vec<GroupeDoublons>, String> {
+    let fichiers = collecter_images(sources);
+    crate::log_info(&format!("pic::chercher_doublons | {} images algo={} seuil={} garder={}", fichiers.len(), algo.nom(), seuil, regle.nom()));
+    if fichiers.is_empty() { return Err("Aucune image trouvée".into()); }
+    let images = calculer_empreintes(&fichiers, algo, orientation_auto);
+    if images.is_empty() { return Err("Aucune image lisible".into()); }
+    Ok(grouper_doublons(&images, seuil, regle))
+}
 
-    let width = decoded.width;
-    let height = decoded.height;
-    
-    // Extraire les données selon le type
-    let image_data: Vec<u8> = match decoded.data {
-        rawloader::RawImageData::Integer(ref data) => {
-            data.iter().map(|&val| (val >> 8) as u8).collect()
-        },
-        rawloader::RawImageData::Float(ref data) => {
-            data.iter().map(|&val| (val.clamp(0.0, 1.0) * 255.0) as u8).collect()
-        },
-    };
+/// Bilan du traitement des doublons
+#[derive(Clone, Debug, Default)]
+pub struct BilanDoublons {
+    /// Rapport lisible : un bloc par groupe, image conservée en tête
+    pub rapport: String,
+    /// Doublons supprimés ou déplacés (ou qui le seraient en simulation)
+    pub retires: usize,
+    pub octets_liberes: u64,
+    pub echecs: usize,
+}
 
-    let img = match image::RgbImage::from_raw(width as u32, height as u32, image_data) {
-        Some(i) => i,
-        None => return false,
-    };
+/// Déplace un fichier dans `dossier` sans écraser un homonyme (copie puis suppression entre volumes)
+fn deplacer_vers(fichier: &Path, dossier: &Path) -> Result<PathBuf, String> {
+    std::fs::create_dir_all(dossier).map_err(|e| format!("Création {:?} : {}", dossier, e))?;
+    let nom = fichier.file_stem().and_then(|s| s.to_str()).unwrap_or("image");
+    let ext = fichier.extension().and_then(|e| e.to_str()).map(|e| format!(".{}", e)).unwrap_or_default();
+    let cible = (0..)
+        .map(|n| dossier.join(if n == 0 { format!("{}{}", nom, ext) } else { format!("{}_{}{}", nom, n, ext) }))
+        .find(|p| !p.exists())
+        .expect("nom libre");
+    if std::fs::rename(fichier, &cible).is_err() {
+        std::fs::copy(fichier, &cible).map_err(|e| e.to_string())?;
+        std::fs::remove_file(fichier).map_err(|e| e.to_string())?;
+    }
+    Ok(cible)
+}
 
-    image::DynamicImage::ImageRgb8(img).save(output).is_ok()
+/// Garde la première image de chaque groupe et retire les autres si `retirer` : déplacées dans
+/// `corbeille` si fourni, supprimées sinon. En `simulation`, rien n'est touché et le rapport
+/// décrit ce qui serait fait. Sans `retirer`, les groupes sont seulement listés.
+pub fn traiter_doublons(groupes: &[GroupeDoublons], retirer: bool, corbeille: Option<&Path>, simulation: bool) -> BilanDoublons {
+    let mut bilan = BilanDoublons::default();
+    let mut lignes = Vec::new();
+    for (n, groupe) in groupes.iter().enumerate() {
+        lignes.push(format!("Group {} — {} images, distance ≤ {}", n + 1, groupe.images.len(), groupe.distance));
+        for (i, im) in groupe.images.iter().enumerate() {
+            let date = chrono::DateTime::<chrono::Local>::from(im.modifie).format("%Y-%m-%d %H:%M");
+            let echecs = bilan.echecs;
+            let detail = format!("{} ({}x{}, {:.1} MB, {})", im.chemin.display(), im.largeur, im.hauteur, im.octets as f64 / 1_048_576.0, date);
+            let etat = match (retirer, i) {
+                (false, _) => "",
+                (true, 0) => "keep    ",
+                (true, _) if simulation => match corbeille { Some(_) => "would move   ", None => "would delete " },
+                (true, _) => {
+                    let resultat = match corbeille {
+                        Some(d) => deplacer_vers(&im.chemin, d).map(|_| "moved   "),
+                        None => std::fs::remove_file(&im.chemin).map(|_| "deleted ").map_err(|e| e.to_string()),
+                    };
+                    match resultat {
+                        Ok(etat) => etat,
+                        Err(e) => {
+                            crate::log_error(&format!("pic::traiter_doublons {:?} : {}", im.chemin, e));
+                            bilan.echecs += 1;
+                            "failed  "
+                        }
+                    }
+                }
+            };
+            if retirer && i > 0 && bilan.echecs == echecs {
+                bilan.retires += 1;
+                bilan.octets_liberes += im.octets;
+            }
+            lignes.push(format!("  {}{}", etat, detail));
+        }
+    }
+    if retirer {
+        lignes.push(format!(
+            "{} {} duplicate(s), {:.1} MB{}",
+            if simulation { "Dry run:" } else { "Removed" },
+            bilan.retires,
+            bilan.octets_liberes as f64 / 1_048_576.0,
+            if bilan.echecs > 0 { format!(", {} failure(s)", bilan.echecs) } else { String::new() },
+        ));
+    }
+    crate::log_info(&format!("pic::traiter_doublons | {} groupes retires={} echecs={} simulation={}", groupes.len(), bilan.retires, bilan.echecs, simulation));
+    bilan.rapport = lignes.join("\n");
+    bilan
 }
 
-/// Compression RAW (décode sans redimensionnement)
-fn compresser_raw(input: &Path, output: &str) -> bool {
-    convertir_raw(input, output)
+// ════════════════════════════════════════════════════════════════════════
+//  MONTAGE — planche contact et collage d'images en grille
+// ════════════════════════════════════════════════════════════════════════
+
+/// Légende imprimée sous chaque image du montage
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Legende {
+    Aucune,
+    #[default]
+    NomFichier,
+    /// Date de prise de vue EXIF (nom du fichier si absente)
+    DateExif,
 }
 
-/// Conversion PSD vers format standard
-fn convertir_psd(input: &Path, output: &str) -> bool {
-    let mut file = match File::open(input) {
-        Ok(f) => f,
-        Err(_) => return false,
-    };
+impl Legende {
+    pub const TOUS: [&'static str; 3] = ["none", "filename", "date"];
 
-    let mut psd_data = Vec::new();
-    if file.read_to_end(&mut psd_data).is_err() {
-        return false;
+    pub fn depuis_nom(nom: &str) -> Option<Self> {
+        Some(match nom.trim().to_lowercase().as_str() {
+            "none" => Self::Aucune,
+            "filename" | "name" => Self::NomFichier,
+            "date" | "exif-date" => Self::DateExif,
+            _ => return None,
+        })
     }
 
-    let psd_file = match psd::Psd::from_bytes(&psd_data) {
-        Ok(p) => p,
-        Err(_) => return false,
-    };
+    pub fn nom(self) -> &'static str {
+        Self::TOUS[self as usize]
+    }
+}
 
-    // Récupérer l'image composite (flatten)
-    let rgba = match psd_file.flatten_layers_rgba(&|_| true) {
-        Ok(r) => r,
-        Err(_) => return false,
+/// Format de page en millimètres (portrait) : a3, a4, a5, letter, legal ou "LxH"
+pub fn parser_format_page(texte: &str) -> Result<(f32, f32), String> {
+    let texte = texte.trim().to_lowercase();
+    Ok(match texte.as_str() {
+        "a3" => (297.0, 420.0),
+        "a4" => (210.0, 297.0),
+        "a5" => (148.0, 210.0),
+        "letter" => (215.9, 279.4),
+        "legal" => (215.9, 355.6),
+        _ => texte.split_once('x')
+            .and_then(|(l, h)| Some((l.trim().parse::<f32>().ok()?, h.trim().parse::<f32>().ok()?)))
+            .filter(|&(l, h)| l >= 10.0 && h >= 10.0)
+            .ok_or_else(|| format!("Format de page invalide '{}' (a3, a4, a5, letter, legal ou LxH en mm)", texte))?,
+    })
+}
+
+/// Mise en page d'un montage
+#[derive(Clone, Debug, PartialEq)]
+pub struct OptionsMontage {
+    /// Colonnes ; 0 = automatique
+    pub colonnes: u32,
+    /// Lignes par page ; 0 = automatique
+    pub lignes: u32,
+    /// Largeur d'une case en pixels, sans format de page
+    pub taille_case: u32,
+    /// Espace entre les cases et autour de la grille (px)
+    pub espacement: u32,
+    /// Couleur de fond RGBA
+    pub fond: [u8; 4],
+    pub legende: Legende,
+    /// Page (largeur, hauteur) en mm ; None = une seule planche à la taille de la grille
+    pub page_mm: Option<(f32, f32)>,
+    /// Résolution des pages, en points par pouce
+    pub dpi: u32,
+    /// Fichier TTF / OTF ; None = police embarquée
+    pub police: Option<PathBuf>,
+}
+
+impl Default for OptionsMontage {
+    fn default() -> Self {
+        OptionsMontage {
+            colonnes: 0,
+            lignes: 0,
+            taille_case: 300,
+            espacement: 16,
+            fond: [255, 255, 255, 255],
+            legende: Legende::NomFichier,
+            page_mm: None,
+            dpi: 150,
+            police: None,
+        }
+    }
+}
+
+/// Grille calculée : colonnes, lignes par page, case et page en pixels, taille du texte de légende,
+/// marge haute qui centre la grille verticalement sur la page
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct GrilleMontage {
+    colonnes: u32,
+    lignes: u32,
+    case: (u32, u32),
+    page: (u32, u32),
+    taille_legende: f32,
+    haut: u32,
+}
+
+impl GrilleMontage {
+    fn hauteur_legende(&self) -> u32 {
+        if self.taille_legende > 0.0 { (self.taille_legende * 1.6).ceil() as u32 } else { 0 }
+    }
+}
+
+/// `ratio` : largeur / hauteur médiane des images, qui donne la proportion des cases
+fn grille_montage(nombre: u32, ratio: f32, options: &OptionsMontage) -> GrilleMontage {
+    let ratio = ratio.clamp(0.5, 2.0);
+    let nombre = nombre.max(1);
+    let esp = options.espacement;
+    let taille_legende = |largeur_case: u32| match options.legende {
+        Legende::Aucune => 0.0,
+        _ => (largeur_case as f32 / 16.0).clamp(10.0, 32.0),
+    };
+    let Some((lmm, hmm)) = options.page_mm else {
+        let colonnes = match (options.colonnes, options.lignes) {
+            (0, 0) => (nombre as f32).sqrt().ceil() as u32,
+            (0, l) => nombre.div_ceil(l),
+            (c, _) => c,
+        }.max(1);
+        let lignes = if options.lignes > 0 { options.lignes } else { nombre.div_ceil(colonnes) };
+        let cw = options.taille_case.max(16);
+        let ch = (cw as f32 / ratio).round() as u32;
+        let mut g = GrilleMontage { colonnes, lignes, case: (cw, ch), page: (0, 0), taille_legende: taille_legende(cw), haut: esp };
+        g.page = (colonnes * cw + (colonnes + 1) * esp, lignes * (ch + g.hauteur_legende()) + (lignes + 1) * esp);
+        return g;
+    };
+    let page = ((lmm / 25.4 * options.dpi as f32).round() as u32, (hmm / 25.4 * options.dpi as f32).round() as u32);
+    let utile = (page.0.saturating_sub(2 * esp).max(16), page.1.saturating_sub(2 * esp).max(16));
+    // Par défaut, au plus une vingtaine d'images par page
+    let proportion = utile.0 as f32 / utile.1 as f32 / ratio;
+    let colonnes = match (options.colonnes, options.lignes) {
+        (0, 0) => ((nombre.min(20) as f32 * proportion).sqrt().ceil() as u32).max(1),
+        (0, l) => ((l as f32 * proportion).round() as u32).max(1),
+        (c, _) => c,
     };
+    let largeur_case = (utile.0.saturating_sub((colonnes - 1) * esp) / colonnes).max(8);
+    let legende = taille_legende(largeur_case);
+    let haut_legende = if legende > 0.0 { (legende * 1.6).ceil() as u32 } else { 0 };
+    let (lignes, hauteur_case) = match options.lignes {
+        0 => {
+            let hauteur_case = ((largeur_case as f32 / ratio).round() as u32).min(utile.1.saturating_sub(haut_legende).max(8));
+            let lignes = ((utile.1 + esp) / (hauteur_case + haut_legende + esp)).clamp(1, nombre.div_ceil(colonnes));
+            (lignes, hauteur_case)
+        }
+        l => (l, ((utile.1.saturating_sub((l - 1) * esp) / l).saturating_sub(haut_legende)).max(8)),
+    };
+    let hauteur_grille = lignes * (hauteur_case + haut_legende) + (lignes - 1) * esp;
+    let haut = esp + utile.1.saturating_sub(hauteur_grille) / 2;
+    GrilleMontage { colonnes, lignes, case: (largeur_case, hauteur_case), page, taille_legende: legende, haut }
+}
 
-    let img = match image::RgbaImage::from_raw(
-        psd_file.width(),
-        psd_file.height(),
-        rgba,
+/// Date de prise de vue EXIF (DateTimeOriginal, sinon DateTime) au format "AAAA-MM-JJ HH:MM"
+fn date_exif(input: &Path) -> Option<String> {
+    let fichier = File::open(input).ok()?;
+    let exif = exif::Reader::new().read_from_container(&mut BufReader::new(fichier)).ok()?;
+    let champ = exif.get_field(exif::Tag::DateTimeOriginal, exif::In::PRIMARY)
+        .or_else(|| exif.get_field(exif::Tag::DateTime, exif::In::PRIMARY))?;
+    let exif::Value::Ascii(valeurs) = &champ.value else { return None };
+    let d = exif::DateTime::from_ascii(valeurs.first()?).ok()?;
+    Some(format!("{:04}-{:02}-{:02} {:02}:{:02}", d.year, d.month, d.day, d.hour, d.minute))
+}
+
+/// Texte rendu, raccourci avec "..." pour tenir dans `largeur`
+fn rendre_texte_borne(police: &FontArc, texte: &str, style: &StyleTexte, largeur: u32) -> image::RgbaImage {
+    let mut rendu = rendre_texte_police(police, texte, style);
+    let mut car: Vec<char> = texte.chars().collect();
+    if rendu.width() > largeur {
+        car.truncate((car.len() as u64 * largeur as u64 / rendu.width() as u64) as usize + 1);
+    }
+    while rendu.width() > largeur && !car.is_empty() {
+        car.pop();
+        rendu = rendre_texte_police(police, &format!("{}...", car.iter().collect::<String>()), style);
+    }
+    rendu
+}
+
+/// Emplacement des légendes d'une grille de vignettes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlacementLegende {
+    /// Centrée dans la bande réservée sous la case, raccourcie à sa largeur
+    Dessous,
+    /// Incrustée dans le coin bas droit de la vignette
+    CoinBasDroit,
+}
+
+/// Grille de vignettes commune au montage et à la planche contact vidéo
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GrilleVignettes {
+    pub colonnes: u32,
+    /// Taille d'une case en pixels
+    pub case: (u32, u32),
+    /// Espace entre deux cases (px)
+    pub espacement: u32,
+    /// Bande réservée à la légende sous chaque case (px)
+    pub hauteur_legende: u32,
+    /// Coin haut gauche de la première case
+    pub origine: (u32, u32),
+}
+
+impl GrilleVignettes {
+    /// Coin haut gauche de la case `i`, les cases étant remplies ligne par ligne
+    pub fn position(&self, i: usize) -> (u32, u32) {
+        let (col, ligne) = (i as u32 % self.colonnes.max(1), i as u32 / self.colonnes.max(1));
+        (
+            self.origine.0 + col * (self.case.0 + self.espacement),
+            self.origine.1 + ligne * (self.case.1 + self.hauteur_legende + self.espacement),
+        )
+    }
+
+    /// Dessine sur `feuille` chaque vignette ajustée et centrée dans sa case, avec sa légende éventuelle
+    pub fn dessiner<'a>(
+        &self,
+        feuille: &mut image::RgbaImage,
+        cases: impl IntoIterator<Item = (&'a image::DynamicImage, Option<String>)>,
+        police: &FontArc,
+        style: &StyleTexte,
+        placement: PlacementLegende,
     ) {
-        Some(i) => i,
-        None => return false,
-    };
+        let (cw, ch) = self.case;
+        for (i, (img, legende)) in cases.into_iter().enumerate() {
+            let (x, y) = self.position(i);
+            let vignette = img.resize(cw, ch, FilterType::Triangle).to_rgba8();
+            let (vx, vy) = (x + cw.saturating_sub(vignette.width()) / 2, y + ch.saturating_sub(vignette.height()) / 2);
+            image::imageops::overlay(feuille, &vignette, vx as i64, vy as i64);
+            let Some(texte) = legende else { continue };
+            let (rendu, tx, ty) = match placement {
+                PlacementLegende::Dessous => {
+                    let rendu = rendre_texte_borne(police, &texte, style, cw);
+                    let tx = x as i64 + (cw as i64 - rendu.width() as i64) / 2;
+                    let ty = (y + ch) as i64 + (self.hauteur_legende as i64 - rendu.height() as i64) / 2;
+                    (rendu, tx, ty)
+                }
+                PlacementLegende::CoinBasDroit => {
+                    let rendu = rendre_texte_police(police, &texte, style);
+                    let tx = (vx + vignette.width()) as i64 - rendu.width() as i64 - 4;
+                    let ty = (vy + vignette.height()) as i64 - rendu.height() as i64 - 2;
+                    (rendu, tx, ty)
+                }
+            };
+            image::imageops::overlay(feuille, &rendu, tx, ty);
+        }
+    }
+}
 
-    image::DynamicImage::ImageRgba8(img).save(output).is_ok()
+/// Taille maximale qu'une case du montage peut atteindre, quelles que soient les proportions
+/// des images : les vignettes sont réduites à cette taille dès le chargement
+fn case_maximale(options: &OptionsMontage) -> (u32, u32) {
+    match options.page_mm {
+        // Les cases suivent le ratio médian borné à [0.5, 2] : jamais plus de deux fois plus hautes que larges
+        None => (options.taille_case.max(16), options.taille_case.max(16) * 2),
+        Some((lmm, hmm)) => (
+            ((lmm / 25.4 * options.dpi as f32).round() as u32).max(16),
+            ((hmm / 25.4 * options.dpi as f32).round() as u32).max(16),
+        ),
+    }
 }
 
-/// Compression PSD (décode sans redimensionnement)
-fn compresser_psd(input: &Path, output: &str) -> bool {
-    convertir_psd(input, output)
+/// Compose les pages du montage : images ajustées et centrées dans leur case, légende dessous.
+/// Les images illisibles sont journalisées et ignorées.
+pub fn composer_montage(images: &[PathBuf], options: &OptionsMontage, orientation_auto: bool) -> Result<Vec<image::RgbaImage>, String> {
+    let (mw, mh) = case_maximale(options);
+    // (chemin, proportions d'origine, vignette) : l'image pleine résolution est libérée aussitôt
+    let chargees: Vec<(&PathBuf, f32, image::DynamicImage)> = images.iter()
+        .filter_map(|p| match charger_image(p, orientation_auto) {
+            Ok(img) => {
+                let ratio = img.width() as f32 / img.height().max(1) as f32;
+                let vignette = if img.width() > mw || img.height() > mh { img.resize(mw, mh, FilterType::Triangle) } else { img };
+                Some((p, ratio, vignette))
+            }
+            Err(e) => {
+                crate::log_warn(&format!("pic::composer_montage {:?} ignoré : {}", p, e));
+                None
+            }
+        })
+        .collect();
+    if chargees.is_empty() { return Err("Aucune image lisible".into()); }
+    let mut ratios: Vec<f32> = chargees.iter().map(|(_, ratio, _)| *ratio).collect();
+    ratios.sort_by(f32::total_cmp);
+    let g = grille_montage(chargees.len() as u32, ratios[ratios.len() / 2], options);
+    crate::log_info(&format!("pic::composer_montage | {} images | {:?}", chargees.len(), g));
+    let grille = GrilleVignettes {
+        colonnes: g.colonnes,
+        case: g.case,
+        espacement: options.espacement,
+        hauteur_legende: g.hauteur_legende(),
+        origine: (options.espacement, g.haut),
+    };
+    let fond = options.fond;
+    // Texte noir sur fond clair, blanc sur fond sombre
+    let clair = 0.299 * fond[0] as f32 + 0.587 * fond[1] as f32 + 0.114 * fond[2] as f32 > 128.0;
+    let police = charger_police(options.police.as_deref())?;
+    let style = StyleTexte {
+        police: options.police.clone(),
+        taille: g.taille_legende,
+        couleur: if clair { [0, 0, 0, 255] } else { [255, 255, 255, 255] },
+        ..Default::default()
+    };
+    let legende = |chemin: &Path| {
+        let texte = match options.legende {
+            Legende::Aucune => return None,
+            Legende::DateExif => date_exif(chemin),
+            Legende::NomFichier => None,
+        };
+        Some(texte.unwrap_or_else(|| chemin.file_name().unwrap_or_default().to_string_lossy().to_string()))
+    };
+    let mut pages = Vec::new();
+    for page in chargees.chunks((g.colonnes * g.lignes) as usize) {
+        let mut feuille = image::RgbaImage::from_pixel(g.page.0, g.page.1, image::Rgba(fond));
+        let cases = page.iter().map(|(chemin, _, img)| (img, legende(chemin)));
+        grille.dessiner(&mut feuille, cases, &police, &style, PlacementLegende::Dessous);
+        pages.push(feuille);
+    }
+    Ok(pages)
+}
+
+/// Montage / planche contact. Sortie PDF : une page par planche via le module doc ;
+/// sinon PNG, JPEG… numérotés `<nom>_001.ext` s'il y a plusieurs pages. Renvoie le nombre de pages.
+pub fn montage(images: &[PathBuf], output: &str, options: &OptionsMontage, orientation_auto: bool) -> Result<usize, String> {
+    crate::log_info(&format!("pic::montage | {} images | {:?} -> {}", images.len(), options, output));
+    let pages: Vec<image::DynamicImage> = composer_montage(images, options, orientation_auto)?
+        .into_iter()
+        .map(image::DynamicImage::ImageRgba8)
+        .collect();
+    let sortie = Path::new(output);
+    let ext = sortie.extension().and_then(|e| e.to_str()).unwrap_or("png").to_lowercase();
+    if ext == "pdf" {
+        crate::modules::doc::images_vers_pdf(&pages, output, options.dpi as f32)?;
+        return Ok(pages.len());
+    }
+    for (i, page) in pages.iter().enumerate() {
+        let chemin = if pages.len() == 1 {
+            output.to_string()
+        } else {
+            let nom = sortie.file_stem().unwrap_or_default().to_string_lossy();
+            sortie.with_file_name(format!("{}_{:03}.{}", nom, i + 1, ext)).to_string_lossy().to_string()
+        };
+        if !sauvegarder_image(page, &chemin, 9) {
+            return Err(format!("Écriture du montage {} impossible", chemin));
+        }
+    }
+    Ok(pages.len())
 }
 
 // ════════════════════════════════════════════════════════════════════════
@@ -649,7 +3973,7 @@ fn est_source_jxl(ext: &str) -> bool {
 
 /// Convertit un fichier image en JXL lossless (en place, à côté du fichier source).
 /// Skip si le .jxl existe déjà. (oxyj.ps1)
-pub fn convertir_jxl_lossless(input: &Path) -> Result<(), String> {
+pub fn convertir_jxl_lossless(input: &Path, orientation_auto: bool) -> Result<(), String> {
     let out = input.with_extension("jxl");
     if out.exists() {
         return Ok(()); // déjà converti
@@ -657,7 +3981,7 @@ pub fn convertir_jxl_lossless(input: &Path) -> Result<(), String> {
 
     crate::log_info(&format!("pic::convertir_jxl_lossless | {:?} -> {:?}", input, out));
 
-    let img = image::open(input)
+    let img = ouvrir_image(input, orientation_auto)
         .map_err(|e| format!("Impossible d'ouvrir {:?} : {}", input, e))?;
 
     let out_str = out.to_string_lossy().to_string();
@@ -670,7 +3994,7 @@ pub fn convertir_jxl_lossless(input: &Path) -> Result<(), String> {
 
 /// Convertit en JXL dans un dossier séparé "{dossier} jxl".
 /// Tente d'abord un encodage direct, puis fallback pixel mode. (oxyjf.ps1)
-pub fn convertir_jxl_dossier(input: &Path) -> Result<(), String> {
+pub fn convertir_jxl_dossier(input: &Path, orientation_auto: bool) -> Result<(), String> {
     let parent = input.parent().ok_or("Pas de dossier parent")?;
     let dir_name = parent.file_name().unwrap_or_default().to_string_lossy().to_string();
 
@@ -697,7 +4021,7 @@ pub fn convertir_jxl_dossier(input: &Path) -> Result<(), String> {
 
     crate::log_info(&format!("pic::convertir_jxl_dossier | {:?} -> {:?}", input, out));
 
-    let img = image::open(input)
+    let img = ouvrir_image(input, orientation_auto)
         .map_err(|e| format!("Impossible d'ouvrir {:?} : {}", input, e))?;
 
     let out_str = out.to_string_lossy().to_string();
@@ -711,7 +4035,7 @@ pub fn convertir_jxl_dossier(input: &Path) -> Result<(), String> {
 /// Convertit en JXL via PNG pivot (pour les fichiers problématiques). (oxyjp.ps1)
 /// Décode l'image via la crate image (équivalent du System.Drawing du PS1),
 /// sauvegarde en PNG temporaire, puis encode en JXL.
-pub fn convertir_jxl_pivot(input: &Path) -> Result<(), String> {
+pub fn convertir_jxl_pivot(input: &Path, orientation_auto: bool) -> Result<(), String> {
     let stem = input.file_stem().unwrap_or_default().to_string_lossy().to_string();
     let parent = input.parent().ok_or("Pas de dossier parent")?;
     let out = parent.join(format!("{}_pivot.jxl", stem));
@@ -723,7 +4047,7 @@ pub fn convertir_jxl_pivot(input: &Path) -> Result<(), String> {
     crate::log_info(&format!("pic::convertir_jxl_pivot | {:?} -> {:?}", input, out));
 
     // Charger via image crate (équivalent du System.Drawing.Bitmap)
-    let img = image::open(input)
+    let img = ouvrir_image(input, orientation_auto)
         .map_err(|e| format!("Impossible d'ouvrir {:?} : {}", input, e))?;
 
     // Sauvegarder en PNG temporaire (format pivot propre)
@@ -774,129 +4098,361 @@ fn collecter_sources_jxl_inner(dir: &Path, out: &mut Vec<std::path::PathBuf>) {
 }
 
 // ════════════════════════════════════════════════════════════════════════
-//  IMAGE WATERMARK — texte en diagonal semi-transparent
+//  RENDU DE TEXTE — police TrueType / OpenType anti-crénelée
 // ════════════════════════════════════════════════════════════════════════
 
-/// Ajoute un watermark texte en diagonal au centre de l'image.
-/// taille = taille du texte en pixels, opacite = 0.0..1.0
-pub fn watermark(input: &Path, output: &str, texte: &str, taille: f32, opacite: f32) -> bool {
-    crate::log_info(&format!("pic::watermark | texte='{}' taille={} opacite={} | {:?} -> {}", texte, taille, opacite, input, output));
-    let img = match image::open(input) {
+/// Police embarquée par défaut (DejaVu Sans : latin étendu, grec, cyrillique)
+const POLICE_DEFAUT: &[u8] = include_bytes!("../../assets/fonts/DejaVuSans.ttf");
+
+/// Style d'un texte dessiné sur une image
+#[derive(Debug, Clone)]
+pub struct StyleTexte {
+    /// Fichier TTF / OTF ; None = police embarquée
+    pub police: Option<PathBuf>,
+    /// Hauteur du corps en pixels
+    pub taille: f32,
+    /// Couleur RGBA
+    pub couleur: [u8; 4],
+    /// Contour : couleur et épaisseur (px)
+    pub contour: Option<([u8; 4], f32)>,
+    /// Ombre portée : couleur et décalage (px, vers la droite / le bas)
+    pub ombre: Option<([u8; 4], (f32, f32))>,
+    /// Rotation en degrés, sens trigonométrique
+    pub rotation: f32,
+}
+
+impl Default for StyleTexte {
+    fn default() -> Self {
+        StyleTexte { police: None, taille: 48.0, couleur: [255, 255, 255, 255], contour: None, ombre: None, rotation: 0.0 }
+    }
+}
+
+/// Couleur "#RRGGBB" ou "#RRGGBBAA" (dièse facultatif)
+pub fn parser_couleur(texte: &str) -> Result<[u8; 4], String> {
+    let hex = texte.trim().trim_start_matches('#');
+    if !matches!(hex.len(), 6 | 8) || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("Couleur invalide '{}' (attendu #RRGGBB ou #RRGGBBAA)", texte));
+    }
+    let canal = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).unwrap_or(255);
+    Ok([canal(0), canal(2), canal(4), if hex.len() == 8 { canal(6) } else { 255 }])
+}
+
+/// Charge une police (None = police embarquée). À faire une fois par opération,
+/// puis passer la police à `rendre_texte_police` pour chaque texte.
+pub fn charger_police(chemin: Option<&Path>) -> Result<FontArc, String> {
+    match chemin {
+        None => FontArc::try_from_slice(POLICE_DEFAUT).map_err(|e| format!("Police embarquée invalide : {}", e)),
+        Some(p) => {
+            let octets = std::fs::read(p).map_err(|e| format!("Lecture police {:?} : {}", p, e))?;
+            FontArc::try_from_vec(octets).map_err(|e| format!("Police {:?} invalide : {}", p, e))
+        }
+    }
+}
+
+/// Avance totale d'une ligne (px), crénage compris
+fn largeur_ligne<F: Font, SF: ScaleFont<F>>(echelle: &SF, ligne: &str) -> f32 {
+    let mut largeur = 0.0;
+    let mut precedent = None;
+    for c in ligne.chars() {
+        let id = echelle.glyph_id(c);
+        if let Some(p) = precedent { largeur += echelle.kern(p, id); }
+        largeur += echelle.h_advance(id);
+        precedent = Some(id);
+    }
+    largeur
+}
+
+/// Couverture (0.0 – 1.0) d'un texte, lignes centrées, avec `marge` px de chaque côté
+struct Masque {
+    largeur: u32,
+    hauteur: u32,
+    valeurs: Vec<f32>,
+}
+
+impl Masque {
+    fn get(&self, x: i64, y: i64) -> f32 {
+        if x < 0 || y < 0 || x >= self.largeur as i64 || y >= self.hauteur as i64 { return 0.0; }
+        self.valeurs[y as usize * self.largeur as usize + x as usize]
+    }
+}
+
+fn rasteriser_texte(police: &FontArc, texte: &str, taille: f32, marge: u32) -> Masque {
+    let echelle = police.as_scaled(PxScale::from(taille));
+    let lignes: Vec<&str> = if texte.is_empty() { vec![""] } else { texte.lines().collect() };
+    let largeurs: Vec<f32> = lignes.iter().map(|l| largeur_ligne(&echelle, l)).collect();
+    let largeur_max = largeurs.iter().copied().fold(0.0, f32::max);
+    let pas_ligne = echelle.height() + echelle.line_gap();
+    let largeur = largeur_max.ceil() as u32 + 2 * marge;
+    let hauteur = (pas_ligne * lignes.len() as f32 - echelle.line_gap()).ceil() as u32 + 2 * marge;
+    let mut masque = Masque { largeur, hauteur, valeurs: vec![0.0; (largeur * hauteur) as usize] };
+
+    for (i, ligne) in lignes.iter().enumerate() {
+        let mut x = marge as f32 + (largeur_max - largeurs[i]) / 2.0;
+        let ligne_base = marge as f32 + echelle.ascent() + i as f32 * pas_ligne;
+        let mut precedent = None;
+        for c in ligne.chars() {
+            let mut glyphe = echelle.scaled_glyph(c);
+            if let Some(p) = precedent { x += echelle.kern(p, glyphe.id); }
+            glyphe.position = ab_glyph::point(x, ligne_base);
+            x += echelle.h_advance(glyphe.id);
+            precedent = Some(glyphe.id);
+            let Some(contour) = police.outline_glyph(glyphe) else { continue };
+            let bornes = contour.px_bounds();
+            contour.draw(|gx, gy, couverture| {
+                let (px, py) = (bornes.min.x as i64 + gx as i64, bornes.min.y as i64 + gy as i64);
+                if px >= 0 && py >= 0 && (px as u32) < largeur && (py as u32) < hauteur {
+                    let v = &mut masque.valeurs[py as usize * largeur as usize + px as usize];
+                    *v = (*v + couverture).min(1.0);
+                }
+            });
+        }
+    }
+    masque
+}
+
+/// Dilate un masque d'un disque de `rayon` px (bord adouci) : sert au contour
+fn dilater_masque(masque: &Masque, rayon: f32) -> Masque {
+    let r = rayon.ceil() as i64;
+    let decalages: Vec<(i64, i64, f32)> = (-r..=r)
+        .flat_map(|dy| (-r..=r).map(move |dx| (dx, dy)))
+        .filter_map(|(dx, dy)| {
+            let poids = (rayon + 0.5 - ((dx * dx + dy * dy) as f32).sqrt()).clamp(0.0, 1.0);
+            (poids > 0.0).then_some((dx, dy, poids))
+        })
+        .collect();
+    let (w, h) = (masque.largeur as i64, masque.hauteur as i64);
+    let mut valeurs = vec![0.0f32; masque.valeurs.len()];
+    for y in 0..h {
+        for x in 0..w {
+            let source = masque.valeurs[(y * w + x) as usize];
+            if source <= 0.0 { continue; }
+            for &(dx, dy, poids) in &decalages {
+                let (px, py) = (x + dx, y + dy);
+                if px < 0 || py < 0 || px >= w || py >= h { continue; }
+                let v = &mut valeurs[(py * w + px) as usize];
+                *v = v.max(source * poids);
+            }
+        }
+    }
+    Masque { largeur: masque.largeur, hauteur: masque.hauteur, valeurs }
+}
+
+/// Dépose `src` sur `dest` en (x, y) — opérateur « over », alpha non prémultiplié
+fn superposer(dest: &mut image::RgbaImage, src: &image::RgbaImage, x: i64, y: i64, opacite: f32) {
+    for (sx, sy, p) in src.enumerate_pixels() {
+        let (dx, dy) = (x + sx as i64, y + sy as i64);
+        if dx < 0 || dy < 0 || dx >= dest.width() as i64 || dy >= dest.height() as i64 { continue; }
+        let a_src = p[3] as f32 / 255.0 * opacite;
+        if a_src <= 0.0 { continue; }
+        let d = dest.get_pixel_mut(dx as u32, dy as u32);
+        let a_dest = d[3] as f32 / 255.0;
+        let a_out = a_src + a_dest * (1.0 - a_src);
+        for c in 0..3 {
+            d[c] = ((p[c] as f32 * a_src + d[c] as f32 * a_dest * (1.0 - a_src)) / a_out).round() as u8;
+        }
+        d[3] = (a_out * 255.0).round() as u8;
+    }
+}
+
+/// Rotation (degrés, sens trigonométrique) d'une image RGBA, interpolation bilinéaire ;
+/// le cadre est agrandi pour contenir l'image entière
+fn tourner_rgba(img: &image::RgbaImage, angle: f32) -> image::RgbaImage {
+    let (sin, cos) = angle.to_radians().sin_cos();
+    let (w, h) = (img.width() as f32, img.height() as f32);
+    let nw = (w * cos.abs() + h * sin.abs()).ceil() as u32;
+    let nh = (w * sin.abs() + h * cos.abs()).ceil() as u32;
+    let (cx, cy, ncx, ncy) = (w / 2.0, h / 2.0, nw as f32 / 2.0, nh as f32 / 2.0);
+    // Canaux prémultipliés pour ne pas assombrir les bords
+    let echantillon = |x: i64, y: i64| -> [f32; 4] {
+        if x < 0 || y < 0 || x >= img.width() as i64 || y >= img.height() as i64 { return [0.0; 4]; }
+        let p = img.get_pixel(x as u32, y as u32);
+        let a = p[3] as f32 / 255.0;
+        [p[0] as f32 * a, p[1] as f32 * a, p[2] as f32 * a, p[3] as f32]
+    };
+    image::RgbaImage::from_fn(nw, nh, |x, y| {
+        // Axe y vers le bas : la rotation trigonométrique à l'écran inverse le signe du sinus
+        let (dx, dy) = (x as f32 + 0.5 - ncx, y as f32 + 0.5 - ncy);
+        let sx = dx * cos - dy * sin + cx - 0.5;
+        let sy = dx * sin + dy * cos + cy - 0.5;
+        let (x0, y0) = (sx.floor(), sy.floor());
+        let (fx, fy) = (sx - x0, sy - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+        let mut acc = [0.0f32; 4];
+        for (px, py, poids) in [(x0, y0, (1.0 - fx) * (1.0 - fy)), (x0 + 1, y0, fx * (1.0 - fy)), (x0, y0 + 1, (1.0 - fx) * fy), (x0 + 1, y0 + 1, fx * fy)] {
+            let e = echantillon(px, py);
+            for c in 0..4 { acc[c] += e[c] * poids; }
+        }
+        if acc[3] <= 0.0 { return image::Rgba([0, 0, 0, 0]); }
+        let a = acc[3] / 255.0;
+        image::Rgba([(acc[0] / a).round() as u8, (acc[1] / a).round() as u8, (acc[2] / a).round() as u8, acc[3].round() as u8])
+    })
+}
+
+/// Rend un texte (couleur, contour, rotation, ombre) sur un fond transparent
+pub fn rendre_texte(texte: &str, style: &StyleTexte) -> Result<image::RgbaImage, String> {
+    let police = charger_police(style.police.as_deref())?;
+    Ok(rendre_texte_police(&police, texte, style))
+}
+
+/// Comme `rendre_texte`, avec une police déjà chargée (`style.police` est ignoré)
+pub fn rendre_texte_police(police: &FontArc, texte: &str, style: &StyleTexte) -> image::RgbaImage {
+    let epaisseur = style.contour.map(|(_, e)| e.max(0.0)).unwrap_or(0.0);
+    let masque = rasteriser_texte(police, texte, style.taille.max(1.0), epaisseur.ceil() as u32 + 1);
+    let contour = style.contour.filter(|_| epaisseur > 0.0).map(|(c, _)| (c, dilater_masque(&masque, epaisseur)));
+
+    let mut sprite = image::RgbaImage::new(masque.largeur, masque.hauteur);
+    for (x, y, p) in sprite.enumerate_pixels_mut() {
+        let (xi, yi) = (x as i64, y as i64);
+        let mut pixel = image::Rgba([0, 0, 0, 0]);
+        if let Some((couleur, dilate)) = &contour {
+            let a = dilate.get(xi, yi) * couleur[3] as f32;
+            pixel = image::Rgba([couleur[0], couleur[1], couleur[2], a.round() as u8]);
+        }
+        let a_texte = masque.get(xi, yi) * style.couleur[3] as f32 / 255.0;
+        if a_texte > 0.0 {
+            let a_fond = pixel[3] as f32 / 255.0;
+            let a_out = a_texte + a_fond * (1.0 - a_texte);
+            for c in 0..3 {
+                pixel[c] = ((style.couleur[c] as f32 * a_texte + pixel[c] as f32 * a_fond * (1.0 - a_texte)) / a_out).round() as u8;
+            }
+            pixel[3] = (a_out * 255.0).round() as u8;
+        }
+        *p = pixel;
+    }
+
+    if style.rotation.rem_euclid(360.0) != 0.0 {
+        sprite = tourner_rgba(&sprite, style.rotation);
+    }
+
+    if let Some((couleur, (ox, oy))) = style.ombre {
+        let (ox, oy) = (ox.round() as i64, oy.round() as i64);
+        let (w, h) = (sprite.width() + ox.unsigned_abs() as u32, sprite.height() + oy.unsigned_abs() as u32);
+        let (tx, ty) = ((-ox).max(0), (-oy).max(0));
+        let ombre = image::RgbaImage::from_fn(sprite.width(), sprite.height(), |x, y| {
+            let a = sprite.get_pixel(x, y)[3] as f32 * couleur[3] as f32 / 255.0;
+            image::Rgba([couleur[0], couleur[1], couleur[2], a.round() as u8])
+        });
+        let mut avec_ombre = image::RgbaImage::new(w, h);
+        superposer(&mut avec_ombre, &ombre, tx + ox, ty + oy, 1.0);
+        superposer(&mut avec_ombre, &sprite, tx, ty, 1.0);
+        sprite = avec_ombre;
+    }
+    sprite
+}
+
+/// Coin haut-gauche d'un élément ancré dans un cadre, à `marge` px des bords non centrés
+fn position_ancree(ancre: AncreTampon, cadre: (u32, u32), element: (u32, u32), marge: i64) -> (i64, i64) {
+    let (ax, ay) = ancre.alignement();
+    let libre_x = cadre.0 as i64 - element.0 as i64;
+    let libre_y = cadre.1 as i64 - element.1 as i64;
+    let x = match ax { -1 => marge, 0 => libre_x / 2, _ => libre_x - marge };
+    // alignement vertical : 1 = haut
+    let y = match ay { 1 => marge, 0 => libre_y / 2, _ => libre_y - marge };
+    (x, y)
+}
+
+// ════════════════════════════════════════════════════════════════════════
+//  IMAGE WATERMARK — texte semi-transparent (diagonale, ancre ou mosaïque)
+// ════════════════════════════════════════════════════════════════════════
+
+/// Options du watermark texte
+#[derive(Debug, Clone)]
+pub struct OptionsWatermark {
+    pub style: StyleTexte,
+    /// Opacité globale 0.0 – 1.0
+    pub opacite: f32,
+    pub ancre: AncreTampon,
+    /// Distance aux bords (px) pour les ancres non centrées
+    pub marge: u32,
+    /// Oriente le texte selon la diagonale de l'image (remplace `style.rotation`)
+    pub diagonale: bool,
+    /// Répète le texte sur toute l'image
+    pub mosaique: bool,
+}
+
+impl Default for OptionsWatermark {
+    fn default() -> Self {
+        OptionsWatermark {
+            style: StyleTexte::default(),
+            opacite: 0.5,
+            ancre: AncreTampon::Centre,
+            marge: 20,
+            diagonale: true,
+            mosaique: false,
+        }
+    }
+}
+
+/// Ajoute un watermark texte selon `options`
+pub fn watermark_avance(input: &Path, output: &str, texte: &str, options: &OptionsWatermark, orientation_auto: bool) -> bool {
+    crate::log_info(&format!("pic::watermark_avance | texte='{}' {:?} | {:?} -> {}", texte, options, input, output));
+    if let Some(ok) = traiter_animation(input, output, |img| {
+        appliquer_watermark(&img, texte, options).map(image::DynamicImage::ImageRgba8)
+    }) {
+        return ok;
+    }
+    let img = match ouvrir_image(input, orientation_auto) {
         Ok(i) => i,
         Err(e) => {
             crate::log_error(&format!("pic::watermark impossible d'ouvrir {:?} : {}", input, e));
             return false;
         }
     };
+    match appliquer_watermark(&img, texte, options) {
+        Ok(rgba) => sauvegarder_image(&image::DynamicImage::ImageRgba8(rgba), output, 9),
+        Err(e) => {
+            crate::log_error(&format!("pic::watermark {}", e));
+            false
+        }
+    }
+}
+
+/// Incruste le watermark dans une image décodée
+fn appliquer_watermark(img: &image::DynamicImage, texte: &str, options: &OptionsWatermark) -> Result<image::RgbaImage, String> {
     let mut rgba = img.to_rgba8();
-    let (w, h) = (rgba.width(), rgba.height());
+    let (w, h) = rgba.dimensions();
 
-    // Dessiner le texte caractère par caractère en mode très simple
-    // On utilise une approche bitmap basique sans dépendance de police
-    let alpha = (opacite.clamp(0.0, 1.0) * 255.0) as u8;
-    let char_w = (taille * 0.6) as u32;
-    let char_h = taille as u32;
-    let text_total_w = texte.len() as u32 * char_w;
-
-    // Position centrée, rotation simulée par décalage diagonal
-    let start_x = w.saturating_sub(text_total_w) / 2;
-    let start_y = h.saturating_sub(char_h) / 2;
-
-    // Dessiner un rectangle semi-transparent derrière le texte
-    for dy in 0..char_h.min(h) {
-        for dx in 0..text_total_w.min(w) {
-            let px = start_x + dx;
-            let py = start_y + dy;
-            if px < w && py < h {
-                let pixel = rgba.get_pixel_mut(px, py);
-                // Blend avec couleur grise semi-transparente
-                let bg_alpha = alpha / 3;
-                pixel[0] = blend_channel(pixel[0], 128, bg_alpha);
-                pixel[1] = blend_channel(pixel[1], 128, bg_alpha);
-                pixel[2] = blend_channel(pixel[2], 128, bg_alpha);
-            }
-        }
+    let mut style = options.style.clone();
+    if options.diagonale {
+        style.rotation = (h as f32).atan2(w as f32).to_degrees();
     }
+    let sprite = rendre_texte(texte, &style).map_err(|e| format!("rendu du texte : {}", e))?;
+    let opacite = options.opacite.clamp(0.0, 1.0);
 
-    // Dessiner le texte avec des pixels blancs (police bitmap 5x7 simplifiée)
-    let scale = (taille / 14.0).max(1.0) as u32;
-    for (ci, ch) in texte.chars().enumerate() {
-        let glyph = get_bitmap_glyph(ch);
-        for (row, bits) in glyph.iter().enumerate() {
-            for col in 0..5 {
-                if bits & (1 << (4 - col)) != 0 {
-                    for sy in 0..scale {
-                        for sx in 0..scale {
-                            let px = start_x + (ci as u32) * char_w + col * scale + sx;
-                            let py = start_y + (row as u32) * scale + sy;
-                            if px < w && py < h {
-                                let pixel = rgba.get_pixel_mut(px, py);
-                                pixel[0] = blend_channel(pixel[0], 255, alpha);
-                                pixel[1] = blend_channel(pixel[1], 255, alpha);
-                                pixel[2] = blend_channel(pixel[2], 255, alpha);
-                            }
-                        }
-                    }
-                }
+    if options.mosaique {
+        let ecart = style.taille.max(8.0) as i64;
+        let (pas_x, pas_y) = (sprite.width() as i64 + ecart, sprite.height() as i64 + ecart);
+        let mut rangee = 0;
+        let mut y = -pas_y / 2;
+        while y < h as i64 {
+            let mut x = if rangee % 2 == 0 { -pas_x / 2 } else { -pas_x };
+            while x < w as i64 {
+                superposer(&mut rgba, &sprite, x, y, opacite);
+                x += pas_x;
             }
+            y += pas_y;
+            rangee += 1;
         }
+    } else {
+        let (x, y) = position_ancree(options.ancre, (w, h), sprite.dimensions(), options.marge as i64);
+        superposer(&mut rgba, &sprite, x, y, opacite);
     }
+    Ok(rgba)
+}
 
-    image::DynamicImage::ImageRgba8(rgba).save(output).is_ok()
-}
-
-fn blend_channel(bg: u8, fg: u8, alpha: u8) -> u8 {
-    let a = alpha as u16;
-    let result = (fg as u16 * a + bg as u16 * (255 - a)) / 255;
-    result as u8
-}
-
-/// Retourne un glyphe bitmap 5x7 pour un caractère (simplifié)
-fn get_bitmap_glyph(ch: char) -> [u8; 7] {
-    match ch.to_ascii_uppercase() {
-        'A' => [0b01110, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001],
-        'B' => [0b11110, 0b10001, 0b11110, 0b10001, 0b10001, 0b10001, 0b11110],
-        'C' => [0b01110, 0b10001, 0b10000, 0b10000, 0b10000, 0b10001, 0b01110],
-        'D' => [0b11110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b11110],
-        'E' => [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b11111],
-        'F' => [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b10000],
-        'G' => [0b01110, 0b10001, 0b10000, 0b10111, 0b10001, 0b10001, 0b01110],
-        'H' => [0b10001, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001],
-        'I' => [0b01110, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110],
-        'J' => [0b00111, 0b00010, 0b00010, 0b00010, 0b00010, 0b10010, 0b01100],
-        'K' => [0b10001, 0b10010, 0b10100, 0b11000, 0b10100, 0b10010, 0b10001],
-        'L' => [0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b11111],
-        'M' => [0b10001, 0b11011, 0b10101, 0b10101, 0b10001, 0b10001, 0b10001],
-        'N' => [0b10001, 0b11001, 0b10101, 0b10011, 0b10001, 0b10001, 0b10001],
-        'O' => [0b01110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110],
-        'P' => [0b11110, 0b10001, 0b10001, 0b11110, 0b10000, 0b10000, 0b10000],
-        'Q' => [0b01110, 0b10001, 0b10001, 0b10001, 0b10101, 0b10010, 0b01101],
-        'R' => [0b11110, 0b10001, 0b10001, 0b11110, 0b10100, 0b10010, 0b10001],
-        'S' => [0b01110, 0b10001, 0b10000, 0b01110, 0b00001, 0b10001, 0b01110],
-        'T' => [0b11111, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100],
-        'U' => [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110],
-        'V' => [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01010, 0b00100],
-        'W' => [0b10001, 0b10001, 0b10001, 0b10101, 0b10101, 0b11011, 0b10001],
-        'X' => [0b10001, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001, 0b10001],
-        'Y' => [0b10001, 0b10001, 0b01010, 0b00100, 0b00100, 0b00100, 0b00100],
-        'Z' => [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b11111],
-        '0' => [0b01110, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b01110],
-        '1' => [0b00100, 0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110],
-        '2' => [0b01110, 0b10001, 0b00001, 0b00110, 0b01000, 0b10000, 0b11111],
-        '3' => [0b01110, 0b10001, 0b00001, 0b00110, 0b00001, 0b10001, 0b01110],
-        '4' => [0b00010, 0b00110, 0b01010, 0b10010, 0b11111, 0b00010, 0b00010],
-        '5' => [0b11111, 0b10000, 0b11110, 0b00001, 0b00001, 0b10001, 0b01110],
-        '6' => [0b01110, 0b10000, 0b11110, 0b10001, 0b10001, 0b10001, 0b01110],
-        '7' => [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b01000, 0b01000],
-        '8' => [0b01110, 0b10001, 0b10001, 0b01110, 0b10001, 0b10001, 0b01110],
-        '9' => [0b01110, 0b10001, 0b10001, 0b01111, 0b00001, 0b00001, 0b01110],
-        ' ' => [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000],
-        '-' => [0b00000, 0b00000, 0b00000, 0b11111, 0b00000, 0b00000, 0b00000],
-        '.' => [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b01100],
-        '!' => [0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00000, 0b00100],
-        '?' => [0b01110, 0b10001, 0b00010, 0b00100, 0b00100, 0b00000, 0b00100],
-        _   => [0b11111, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b11111],
-    }
+/// Ajoute un watermark texte blanc contouré, en diagonale au centre de l'image.
+/// taille = taille du texte en pixels, opacite = 0.0..1.0
+pub fn watermark(input: &Path, output: &str, texte: &str, taille: f32, opacite: f32, orientation_auto: bool) -> bool {
+    let options = OptionsWatermark {
+        style: StyleTexte {
+            taille,
+            contour: Some(([0, 0, 0, 255], (taille / 24.0).max(1.0))),
+            ..Default::default()
+        },
+        opacite,
+        ..Default::default()
+    };
+    watermark_avance(input, output, texte, &options, orientation_auto)
 }
 
 // ════════════════════════════════════════════════════════════════════════
@@ -905,9 +4461,17 @@ fn get_bitmap_glyph(ch: char) -> [u8; 7] {
 
 /// Ajoute du texte meme-style (haut + bas) sur l'image.
 /// Bande noire avec texte blanc, style classique.
-pub fn meme(input: &Path, output: &str, top_text: &str, bottom_text: &str) -> bool {
-    crate::log_info(&format!("pic::meme | top='{}' bottom='{}' | {:?} -> {}", top_text, bottom_text, input, output));
-    let img = match image::open(input) {
+pub fn meme(input: &Path, output: &str, top_text: &str, bottom_text: &str, orientation_auto: bool) -> bool {
+    let style = StyleTexte { taille: 0.0, ..Default::default() };
+    meme_style(input, output, top_text, bottom_text, &style, true, orientation_auto)
+}
+
+/// Meme avec style de texte libre. `bandes` : texte sur bandes noires ajoutées ;
+/// sinon texte posé sur l'image, avec un contour noir si le style n'en définit pas.
+/// `style.taille` ≤ 0 : taille automatique ; le texte est réduit pour tenir dans la largeur.
+pub fn meme_style(input: &Path, output: &str, top_text: &str, bottom_text: &str, style: &StyleTexte, bandes: bool, orientation_auto: bool) -> bool {
+    crate::log_info(&format!("pic::meme | top='{}' bottom='{}' bandes={} | {:?} -> {}", top_text, bottom_text, bandes, input, output));
+    let img = match ouvrir_image(input, orientation_auto) {
         Ok(i) => i,
         Err(e) => {
             crate::log_error(&format!("pic::meme impossible d'ouvrir {:?} : {}", input, e));
@@ -916,57 +4480,47 @@ pub fn meme(input: &Path, output: &str, top_text: &str, bottom_text: &str) -> bo
     };
 
     let (w, h) = (img.width(), img.height());
-    let bar_h = (h / 8).max(40);
-    let new_h = h + if !top_text.is_empty() { bar_h } else { 0 }
-                  + if !bottom_text.is_empty() { bar_h } else { 0 };
-
-    let mut canvas = image::RgbaImage::from_pixel(w, new_h, image::Rgba([0, 0, 0, 255]));
+    let bar_h = if bandes { (h / 8).max(40) } else { 0 };
     let top_offset = if !top_text.is_empty() { bar_h } else { 0 };
+    let new_h = h + top_offset + if !bottom_text.is_empty() { bar_h } else { 0 };
 
-    // Copier l'image originale au centre
+    let mut canvas = image::RgbaImage::from_pixel(w, new_h, image::Rgba([0, 0, 0, 255]));
     image::imageops::overlay(&mut canvas, &img.to_rgba8(), 0, top_offset as i64);
 
-    // Dessiner le texte en haut
-    if !top_text.is_empty() {
-        draw_meme_text(&mut canvas, top_text, w, 0, bar_h);
+    // Zones du texte : bandes ajoutées, ou haut / bas de l'image (bornées à sa hauteur)
+    let zone_h = if bandes { bar_h } else { (h / 5).max(20).min(new_h) };
+    let mut style = style.clone();
+    if style.taille <= 0.0 {
+        style.taille = zone_h as f32 * if bandes { 0.6 } else { 0.7 };
     }
-
-    // Dessiner le texte en bas
-    if !bottom_text.is_empty() {
-        let y_start = top_offset + h;
-        draw_meme_text(&mut canvas, bottom_text, w, y_start, bar_h);
+    if !bandes && style.contour.is_none() {
+        style.contour = Some(([0, 0, 0, 255], (style.taille / 16.0).max(2.0)));
     }
 
-    image::DynamicImage::ImageRgba8(canvas).save(output).is_ok()
-}
+    let police = match charger_police(style.police.as_deref()) {
+        Ok(p) => p,
+        Err(e) => {
+            crate::log_error(&format!("pic::meme rendu du texte : {}", e));
+            return false;
+        }
+    };
 
-/// Dessine du texte centré dans une bande de l'image (fond noir, texte blanc)
-fn draw_meme_text(canvas: &mut image::RgbaImage, text: &str, width: u32, y_start: u32, bar_height: u32) {
-    let scale = ((bar_height as f32) / 10.0).max(1.0) as u32;
-    let char_w = 6 * scale;
-    let char_h = 7 * scale;
-    let text_total_w = text.len() as u32 * char_w;
-    let x_start = width.saturating_sub(text_total_w) / 2;
-    let y_center = y_start + (bar_height.saturating_sub(char_h)) / 2;
-
-    for (ci, ch) in text.chars().enumerate() {
-        let glyph = get_bitmap_glyph(ch);
-        for (row, bits) in glyph.iter().enumerate() {
-            for col in 0..5u32 {
-                if bits & (1 << (4 - col)) != 0 {
-                    for sy in 0..scale {
-                        for sx in 0..scale {
-                            let px = x_start + (ci as u32) * char_w + col * scale + sx;
-                            let py = y_center + (row as u32) * scale + sy;
-                            if px < canvas.width() && py < canvas.height() {
-                                canvas.put_pixel(px, py, image::Rgba([255, 255, 255, 255]));
-                            }
-                        }
-                    }
-                }
-            }
+    for (texte, en_haut) in [(top_text, true), (bottom_text, false)] {
+        if texte.is_empty() { continue; }
+        let y_zone = if en_haut { 0 } else { new_h.saturating_sub(zone_h) };
+        let mut sprite = rendre_texte_police(&police, texte, &style);
+        // Réduction pour tenir dans 94 % de la largeur
+        let largeur_max = w as f32 * 0.94;
+        if sprite.width() as f32 > largeur_max {
+            let reduit = StyleTexte { taille: style.taille * largeur_max / sprite.width() as f32, ..style.clone() };
+            sprite = rendre_texte_police(&police, texte, &reduit);
         }
+        let x = (w as i64 - sprite.width() as i64) / 2;
+        let y = y_zone as i64 + (zone_h as i64 - sprite.height() as i64) / 2;
+        superposer(&mut canvas, &sprite, x, y, 1.0);
     }
+
+    sauvegarder_image(&image::DynamicImage::ImageRgba8(canvas), output, 9)
 }
 
 // ════════════════════════════════════════════════════════════════════════
@@ -975,15 +4529,15 @@ fn draw_meme_text(canvas: &mut image::RgbaImage, text: &str, width: u32, y_start
 
 /// Agrandit l'image par un facteur entier (2x, 3x, 4x).
 /// Utilise Lanczos3 pour une qualité correcte.
-pub fn upscale(input: &Path, output: &str, factor: u32) -> bool {
+pub fn upscale(input: &Path, output: &str, factor: u32, orientation_auto: bool, tons: &OptionsTonales) -> bool {
     let factor = factor.clamp(2, 8);
     crate::log_info(&format!("pic::upscale | factor={}x | {:?} -> {}", factor, input, output));
-    match image::open(input) {
+    match ouvrir_image(input, orientation_auto) {
         Ok(img) => {
             let new_w = img.width() * factor;
             let new_h = img.height() * factor;
             let upscaled = img.resize_exact(new_w, new_h, FilterType::Lanczos3);
-            let ok = upscaled.save(output).is_ok();
+            let ok = adapter_sortie(&upscaled, output, tons).save(output).is_ok();
             if !ok { crate::log_error(&format!("pic::upscale échec save {:?}", output)); }
             ok
         },
@@ -1013,58 +4567,44 @@ pub fn html_to_image(input: &Path, output: &str, width: u32) -> bool {
 
     // Extraire le texte brut du HTML
     let text = strip_html_tags(&html);
-    let lines: Vec<&str> = text.lines().filter(|l| !l.trim().is_empty()).collect();
+    let lines: Vec<&str> = text.lines().map(|l| l.trim()).filter(|l| !l.is_empty()).collect();
 
-    let scale = 2u32;
-    let char_w = 6 * scale;
-    let char_h = 7 * scale;
-    let line_h = char_h + 4 * scale;
+    let police = match charger_police(None) {
+        Ok(p) => p,
+        Err(e) => {
+            crate::log_error(&format!("pic::html_to_image {}", e));
+            return false;
+        }
+    };
+    let taille = 18.0;
+    let echelle = police.as_scaled(PxScale::from(taille));
+    let line_h = (echelle.height() + echelle.line_gap()).ceil() as u32 + 4;
     let margin = 20u32;
-    let max_chars = ((width - 2 * margin) / char_w) as usize;
+    let max_w = width.saturating_sub(2 * margin) as f32;
 
-    // Word-wrap les lignes
+    // Word-wrap des lignes selon la largeur réelle des glyphes
     let mut wrapped: Vec<String> = Vec::new();
     for line in &lines {
-        if line.len() <= max_chars {
-            wrapped.push(line.to_string());
-        } else {
-            let mut remaining = *line;
-            while !remaining.is_empty() {
-                if remaining.len() <= max_chars {
-                    wrapped.push(remaining.to_string());
-                    break;
-                }
-                let cut = remaining[..max_chars].rfind(' ').unwrap_or(max_chars);
-                wrapped.push(remaining[..cut].to_string());
-                remaining = remaining[cut..].trim_start();
+        let mut courante = String::new();
+        for mot in line.split_whitespace() {
+            let candidate = if courante.is_empty() { mot.to_string() } else { format!("{} {}", courante, mot) };
+            if !courante.is_empty() && largeur_ligne(&echelle, &candidate) > max_w {
+                wrapped.push(std::mem::replace(&mut courante, mot.to_string()));
+            } else {
+                courante = candidate;
             }
         }
+        wrapped.push(courante);
     }
 
     let img_h = (wrapped.len() as u32 * line_h + 2 * margin).max(100);
     let mut canvas = image::RgbaImage::from_pixel(width, img_h, image::Rgba([255, 255, 255, 255]));
+    let style = StyleTexte { taille, couleur: [0, 0, 0, 255], ..Default::default() };
 
-    // Dessiner chaque ligne
+    // Dessiner chaque ligne, alignée à gauche (le rendu ajoute 1 px de marge)
     for (li, line) in wrapped.iter().enumerate() {
-        let y = margin + li as u32 * line_h;
-        for (ci, ch) in line.chars().enumerate() {
-            let glyph = get_bitmap_glyph(ch);
-            for (row, bits) in glyph.iter().enumerate() {
-                for col in 0..5u32 {
-                    if bits & (1 << (4 - col)) != 0 {
-                        for sy in 0..scale {
-                            for sx in 0..scale {
-                                let px = margin + (ci as u32) * char_w + col * scale + sx;
-                                let py = y + (row as u32) * scale + sy;
-                                if px < width && py < img_h {
-                                    canvas.put_pixel(px, py, image::Rgba([0, 0, 0, 255]));
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
+        let sprite = rendre_texte_police(&police, line, &style);
+        superposer(&mut canvas, &sprite, margin as i64 - 1, (margin + li as u32 * line_h) as i64 - 1, 1.0);
     }
 
     image::DynamicImage::ImageRgba8(canvas).save(output).is_ok()
@@ -1,7 +1,7 @@
 #![allow(dead_code)]
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Child;
-use crate::modules::binaries;
+use crate::modules::{binaries, pic};
 
 /// Convertit ou change le conteneur d'une vidéo
 pub fn traiter_video(
@@ -93,4 +93,296 @@ pub fn extraire_nom_codec(input: &PathBuf) -> String {
     } else {
         String::new()
     }
-}
\ No newline at end of file
+}
+
+// ════════════════════════════════════════════════════════════════════════
+//  ANIMATION, VIGNETTE ET PLANCHE CONTACT
+// ════════════════════════════════════════════════════════════════════════
+
+/// Instant "SS", "MM:SS" ou "HH:MM:SS" (décimales acceptées) en secondes
+pub fn parser_temps(texte: &str) -> Result<f64, String> {
+    let erreur = || format!("Instant invalide '{}' (attendu SS, MM:SS ou HH:MM:SS)", texte);
+    let parties: Vec<&str> = texte.trim().split(':').collect();
+    if parties.len() > 3 { return Err(erreur()); }
+    let mut secondes = 0.0;
+    for (i, partie) in parties.iter().enumerate() {
+        let valeur: f64 = partie.parse().map_err(|_| erreur())?;
+        if valeur < 0.0 || (i > 0 && valeur >= 60.0) || (i + 1 < parties.len() && valeur.fract() != 0.0) {
+            return Err(erreur());
+        }
+        secondes = secondes * 60.0 + valeur;
+    }
+    Ok(secondes)
+}
+
+/// Timecode "HH:MM:SS" (ou "MM:SS" sous l'heure)
+pub fn formater_timecode(secondes: f64) -> String {
+    let total = secondes.max(0.0).floor() as u64;
+    let (h, m, s) = (total / 3600, total / 60 % 60, total % 60);
+    if h > 0 { format!("{:02}:{:02}:{:02}", h, m, s) } else { format!("{:02}:{:02}", m, s) }
+}
+
+fn secondes(valeur: f64) -> String {
+    format!("{:.3}", valeur.max(0.0))
+}
+
+/// Durée et dimensions d'une vidéo (ffprobe)
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct InfosVideo {
+    pub duree: f64,
+    pub largeur: u32,
+    pub hauteur: u32,
+}
+
+pub fn infos_video(input: &Path) -> Result<InfosVideo, String> {
+    let sortie = binaries::silent_cmd(binaries::get_ffprobe())
+        .args(["-v", "error", "-select_streams", "v:0", "-show_entries", "stream=width,height:format=duration", "-of", "default=noprint_wrappers=1"])
+        .arg(input)
+        .output()
+        .map_err(|e| format!("ffprobe impossible à lancer : {}", e))?;
+    if !sortie.status.success() {
+        return Err(format!("ffprobe {:?} : {}", input, String::from_utf8_lossy(&sortie.stderr).trim()));
+    }
+    let mut infos = InfosVideo::default();
+    for ligne in String::from_utf8_lossy(&sortie.stdout).lines() {
+        match ligne.split_once('=') {
+            Some(("width", v)) => infos.largeur = v.trim().parse().unwrap_or(0),
+            Some(("height", v)) => infos.hauteur = v.trim().parse().unwrap_or(0),
+            Some(("duration", v)) => infos.duree = v.trim().parse().unwrap_or(0.0),
+            _ => {}
+        }
+    }
+    if infos.duree <= 0.0 {
+        return Err(format!("Durée inconnue pour {:?}", input));
+    }
+    Ok(infos)
+}
+
+/// Extraction d'une animation GIF / WebP sur une plage de la vidéo
+#[derive(Clone, Debug, PartialEq)]
+pub struct OptionsAnimationVideo {
+    /// Début en secondes
+    pub debut: f64,
+    /// Durée en secondes ; None = jusqu'à la fin
+    pub duree: Option<f64>,
+    pub fps: u32,
+    /// Largeur en pixels (hauteur proportionnelle) ; 0 = taille source
+    pub largeur: u32,
+    /// Nombre de répétitions ; 0 = boucle infinie
+    pub boucles: u32,
+    /// Qualité WebP (0-100)
+    pub qualite: u32,
+}
+
+impl Default for OptionsAnimationVideo {
+    fn default() -> Self {
+        OptionsAnimationVideo { debut: 0.0, duree: None, fps: 12, largeur: 480, boucles: 0, qualite: 75 }
+    }
+}
+
+/// Arguments ffmpeg : GIF en deux passes dans un seul graphe (palette optimisée puis tramage),
+/// WebP animé via libwebp.
+pub fn arguments_animation(input: &Path, output: &str, options: &OptionsAnimationVideo) -> Result<Vec<String>, String> {
+    if options.fps == 0 {
+        return Err("fps doit être supérieur à 0".into());
+    }
+    let mut args = vec!["-ss".to_string(), secondes(options.debut)];
+    if let Some(duree) = options.duree {
+        args.extend(["-t".to_string(), secondes(duree)]);
+    }
+    args.extend(["-i".to_string(), input.to_string_lossy().to_string()]);
+    let mut filtre = format!("fps={}", options.fps);
+    if options.largeur > 0 {
+        filtre.push_str(&format!(",scale={}:-2:flags=lanczos", options.largeur));
+    }
+    let ext = Path::new(output).extension().unwrap_or_default().to_string_lossy().to_lowercase();
+    match ext.as_str() {
+        "gif" => {
+            args.push("-filter_complex".into());
+            args.push(format!(
+                "{},split[a][b];[a]palettegen=stats_mode=diff[p];[b][p]paletteuse=dither=bayer:bayer_scale=5:diff_mode=rectangle",
+                filtre
+            ));
+        }
+        "webp" => {
+            args.extend(["-vf".to_string(), filtre]);
+            args.extend(["-c:v", "libwebp", "-lossless", "0"].map(String::from));
+            args.extend(["-q:v".to_string(), options.qualite.min(100).to_string()]);
+        }
+        _ => return Err(format!("Animation vidéo : format {} non pris en charge (gif, webp)", ext)),
+    }
+    args.extend(["-loop".to_string(), options.boucles.to_string()]);
+    args.extend(["-an", "-y"].map(String::from));
+    args.push(output.to_string());
+    Ok(args)
+}
+
+/// Produit un GIF ou un WebP animé à partir d'une plage de la vidéo
+pub fn video_vers_animation(input: &Path, output: &str, options: &OptionsAnimationVideo) -> Result<Child, std::io::Error> {
+    crate::log_info(&format!("video::video_vers_animation | {:?} | {:?} -> {}", options, input, output));
+    let args = arguments_animation(input, output, options)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+    let child = binaries::silent_cmd(binaries::get_ffmpeg())
+        .args(&args)
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn();
+    if let Err(ref e) = child {
+        crate::log_error(&format!("video::video_vers_animation impossible de lancer ffmpeg : {}", e));
+    }
+    child
+}
+
+/// Image à extraire pour une vignette
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PositionVignette {
+    /// Image à l'instant donné (secondes)
+    Instant(f64),
+    /// Première image d'un changement de scène après `apres`, score de scène > `seuil` (0-1)
+    Scene { apres: f64, seuil: f64 },
+}
+
+pub fn arguments_vignette(input: &Path, output: &str, position: PositionVignette, largeur: u32) -> Vec<String> {
+    let debut = match position {
+        PositionVignette::Instant(t) => t,
+        PositionVignette::Scene { apres, .. } => apres,
+    };
+    let mut args = vec!["-ss".to_string(), secondes(debut), "-i".to_string(), input.to_string_lossy().to_string()];
+    let mut filtres = Vec::new();
+    if let PositionVignette::Scene { seuil, .. } = position {
+        filtres.push(format!("select=gt(scene\\,{:.3})", seuil.clamp(0.0, 1.0)));
+    }
+    if largeur > 0 {
+        filtres.push(format!("scale={}:-2:flags=lanczos", largeur));
+    }
+    if !filtres.is_empty() {
+        args.extend(["-vf".to_string(), filtres.join(",")]);
+    }
+    args.extend(["-frames:v", "1", "-update", "1"].map(String::from));
+    let ext = Path::new(output).extension().unwrap_or_default().to_string_lossy().to_lowercase();
+    if ext == "jpg" || ext == "jpeg" {
+        args.extend(["-q:v", "2"].map(String::from));
+    }
+    args.extend(["-an".to_string(), "-y".to_string(), output.to_string()]);
+    args
+}
+
+/// Extrait une image fixe (jpg, png, webp) à un instant ou au changement de scène suivant
+pub fn extraire_vignette(input: &Path, output: &str, position: PositionVignette, largeur: u32) -> Result<Child, std::io::Error> {
+    crate::log_info(&format!("video::extraire_vignette | {:?} largeur={} | {:?} -> {}", position, largeur, input, output));
+    let child = binaries::silent_cmd(binaries::get_ffmpeg())
+        .args(arguments_vignette(input, output, position, largeur))
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn();
+    if let Err(ref e) = child {
+        crate::log_error(&format!("video::extraire_vignette impossible de lancer ffmpeg : {}", e));
+    }
+    child
+}
+
+/// Grille de vignettes horodatées
+#[derive(Clone, Debug, PartialEq)]
+pub struct OptionsPlanche {
+    pub colonnes: u32,
+    pub lignes: u32,
+    /// Largeur d'une vignette en pixels
+    pub largeur_vignette: u32,
+    /// Espace entre vignettes et autour de la grille
+    pub marge: u32,
+    /// Timecode incrusté en bas à droite de chaque vignette
+    pub timecodes: bool,
+    /// Bandeau avec nom du fichier, durée et définition
+    pub entete: bool,
+    /// Fichier TTF / OTF ; None = police embarquée
+    pub police: Option<PathBuf>,
+}
+
+impl Default for OptionsPlanche {
+    fn default() -> Self {
+        OptionsPlanche { colonnes: 4, lignes: 4, largeur_vignette: 320, marge: 8, timecodes: true, entete: true, police: None }
+    }
+}
+
+/// Instants répartis régulièrement, au milieu de chaque segment (évite génériques et noir final)
+pub fn instants_planche(duree: f64, nombre: u32) -> Vec<f64> {
+    (0..nombre).map(|i| duree * (i as f64 + 0.5) / nombre as f64).collect()
+}
+
+fn capturer_image(input: &Path, instant: f64, largeur: u32) -> Result<image::DynamicImage, String> {
+    let mut args = vec!["-ss".to_string(), secondes(instant), "-i".to_string(), input.to_string_lossy().to_string()];
+    args.extend(["-frames:v".to_string(), "1".to_string(), "-vf".to_string(), format!("scale={}:-2:flags=lanczos", largeur.max(16))]);
+    args.extend(["-f", "image2pipe", "-c:v", "png", "-"].map(String::from));
+    let sortie = binaries::silent_cmd(binaries::get_ffmpeg())
+        .args(&args)
+        .output()
+        .map_err(|e| format!("ffmpeg impossible à lancer : {}", e))?;
+    if !sortie.status.success() || sortie.stdout.is_empty() {
+        return Err(format!("Capture à {} impossible : {}", formater_timecode(instant), String::from_utf8_lossy(&sortie.stderr).lines().last().unwrap_or("")));
+    }
+    image::load_from_memory(&sortie.stdout).map_err(|e| format!("Capture à {} illisible : {}", formater_timecode(instant), e))
+}
+
+/// Assemble les vignettes (instant, image) en grille, dans l'ordre, avec bandeau facultatif
+pub fn composer_planche(vignettes: &[(f64, image::DynamicImage)], titre: Option<&str>, options: &OptionsPlanche) -> Result<image::RgbaImage, String> {
+    let premiere = &vignettes.first().ok_or("Aucune vignette")?.1;
+    let colonnes = options.colonnes.max(1);
+    let lignes = (vignettes.len() as u32).div_ceil(colonnes);
+    let tw = options.largeur_vignette.max(16);
+    let th = ((premiere.height() as u64 * tw as u64) / premiere.width().max(1) as u64).max(1) as u32;
+    let marge = options.marge;
+    let taille_texte = (tw as f32 / 12.0).clamp(12.0, 28.0);
+    let police = pic::charger_police(options.police.as_deref())?;
+    let style = pic::StyleTexte {
+        police: options.police.clone(),
+        taille: taille_texte,
+        contour: Some(([0, 0, 0, 255], (taille_texte / 8.0).max(1.5))),
+        ..Default::default()
+    };
+    let bandeau = titre.filter(|_| options.entete)
+        .map(|t| pic::rendre_texte_police(&police, t, &pic::StyleTexte { taille: taille_texte * 1.2, contour: None, ..style.clone() }));
+    let haut_grille = bandeau.as_ref().map_or(0, |b| b.height() + marge);
+    let largeur = colonnes * tw + (colonnes + 1) * marge;
+    let hauteur = haut_grille + lignes * th + (lignes + 1) * marge;
+    let mut planche = image::RgbaImage::from_pixel(largeur, hauteur, image::Rgba([24, 24, 24, 255]));
+    if let Some(b) = &bandeau {
+        image::imageops::overlay(&mut planche, b, marge as i64, marge as i64);
+    }
+    let grille = pic::GrilleVignettes {
+        colonnes,
+        case: (tw, th),
+        espacement: marge,
+        hauteur_legende: 0,
+        origine: (marge, haut_grille + marge),
+    };
+    let cases = vignettes.iter().map(|(instant, img)| (img, options.timecodes.then(|| formater_timecode(*instant))));
+    grille.dessiner(&mut planche, cases, &police, &style, pic::PlacementLegende::CoinBasDroit);
+    Ok(planche)
+}
+
+/// Planche contact : colonnes × lignes vignettes réparties sur toute la durée.
+/// Renvoie le nombre de vignettes placées.
+pub fn planche_contact(input: &Path, output: &str, options: &OptionsPlanche) -> Result<usize, String> {
+    crate::log_info(&format!("video::planche_contact | {:?} | {:?} -> {}", options, input, output));
+    let infos = infos_video(input)?;
+    let nombre = options.colonnes.max(1) * options.lignes.max(1);
+    let mut vignettes = Vec::with_capacity(nombre as usize);
+    for instant in instants_planche(infos.duree, nombre) {
+        match capturer_image(input, instant, options.largeur_vignette) {
+            Ok(img) => vignettes.push((instant, img)),
+            Err(e) => crate::log_warn(&format!("video::planche_contact {}", e)),
+        }
+    }
+    let titre = format!(
+        "{}  |  {}  |  {}x{}",
+        input.file_name().unwrap_or_default().to_string_lossy(),
+        formater_timecode(infos.duree),
+        infos.largeur,
+        infos.hauteur
+    );
+    let planche = composer_planche(&vignettes, Some(&titre), options)?;
+    if !pic::sauvegarder_image(&image::DynamicImage::ImageRgba8(planche), output, 9) {
+        return Err(format!("Écriture de la planche {} impossible", output));
+    }
+    Ok(vignettes.len())
+}
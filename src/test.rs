@@ -179,7 +179,7 @@ fn test_pic_compresser_jpg() {
     setup();
     let output = format!("{OUT}/pic_jpg_c.jpg");
     cleanup(&output);
-    assert!(crate::modules::pic::compresser(Path::new(&format!("{TEST_PIC}/JPG.jpg")), &output, 2));
+    assert!(crate::modules::pic::compresser(Path::new(&format!("{TEST_PIC}/JPG.jpg")), &output, 2, true, &Default::default(), &Default::default()));
     assert_output(&output, "compresser JPG");
     cleanup(&output);
 }
@@ -189,7 +189,7 @@ fn test_pic_compresser_png() {
     setup();
     let output = format!("{OUT}/pic_png_c.png");
     cleanup(&output);
-    assert!(crate::modules::pic::compresser(Path::new(&format!("{TEST_PIC}/PNG.png")), &output, 2));
+    assert!(crate::modules::pic::compresser(Path::new(&format!("{TEST_PIC}/PNG.png")), &output, 2, true, &Default::default(), &Default::default()));
     assert_output(&output, "compresser PNG");
     cleanup(&output);
 }
@@ -199,7 +199,7 @@ fn test_pic_compresser_webp() {
     setup();
     let output = format!("{OUT}/pic_webp_c.png");
     cleanup(&output);
-    assert!(crate::modules::pic::compresser(Path::new(&format!("{TEST_PIC}/WEBP.webp")), &output, 2));
+    assert!(crate::modules::pic::compresser(Path::new(&format!("{TEST_PIC}/WEBP.webp")), &output, 2, true, &Default::default(), &Default::default()));
     assert_output(&output, "compresser WEBP");
     cleanup(&output);
 }
@@ -209,7 +209,7 @@ fn test_pic_compresser_gif() {
     setup();
     let output = format!("{OUT}/pic_gif_c.png");
     cleanup(&output);
-    assert!(crate::modules::pic::compresser(Path::new(&format!("{TEST_PIC}/GIF.gif")), &output, 2));
+    assert!(crate::modules::pic::compresser(Path::new(&format!("{TEST_PIC}/GIF.gif")), &output, 2, true, &Default::default(), &Default::default()));
     assert_output(&output, "compresser GIF");
     cleanup(&output);
 }
@@ -219,7 +219,7 @@ fn test_pic_compresser_svg() {
     setup();
     let output = format!("{OUT}/pic_svg_c.png");
     cleanup(&output);
-    assert!(crate::modules::pic::compresser(Path::new(&format!("{TEST_PIC}/SVG.svg")), &output, 1));
+    assert!(crate::modules::pic::compresser(Path::new(&format!("{TEST_PIC}/SVG.svg")), &output, 1, true, &Default::default(), &Default::default()));
     assert_output(&output, "compresser SVG");
     cleanup(&output);
 }
@@ -229,7 +229,7 @@ fn test_pic_compresser_psd() {
     setup();
     let output = format!("{OUT}/pic_psd_c.png");
     cleanup(&output);
-    assert!(crate::modules::pic::compresser(Path::new(&format!("{TEST_PIC}/PSD.psd")), &output, 1));
+    assert!(crate::modules::pic::compresser(Path::new(&format!("{TEST_PIC}/PSD.psd")), &output, 1, true, &Default::default(), &Default::default()));
     assert_output(&output, "compresser PSD");
     cleanup(&output);
 }
@@ -239,7 +239,7 @@ fn test_pic_compresser_ico() {
     setup();
     let output = format!("{OUT}/pic_ico_c.png");
     cleanup(&output);
-    assert!(crate::modules::pic::compresser(Path::new(&format!("{TEST_PIC}/ICO.ico")), &output, 2));
+    assert!(crate::modules::pic::compresser(Path::new(&format!("{TEST_PIC}/ICO.ico")), &output, 2, true, &Default::default(), &Default::default()));
     assert_output(&output, "compresser ICO");
     cleanup(&output);
 }
@@ -249,7 +249,7 @@ fn test_pic_compresser_tiff() {
     setup();
     let output = format!("{OUT}/pic_tiff_c.png");
     cleanup(&output);
-    assert!(crate::modules::pic::compresser(Path::new(&format!("{TEST_PIC}/Tiff.tiff")), &output, 2));
+    assert!(crate::modules::pic::compresser(Path::new(&format!("{TEST_PIC}/Tiff.tiff")), &output, 2, true, &Default::default(), &Default::default()));
     assert_output(&output, "compresser TIFF");
     cleanup(&output);
 }
@@ -262,7 +262,7 @@ fn test_pic_pivoter_90() {
     setup();
     let output = format!("{OUT}/pic_rot90.jpg");
     cleanup(&output);
-    assert!(crate::modules::pic::pivoter(Path::new(&format!("{TEST_PIC}/JPG.jpg")), &output, 90));
+    assert!(crate::modules::pic::pivoter(Path::new(&format!("{TEST_PIC}/JPG.jpg")), &output, 90, true, &Default::default()));
     assert_output(&output, "pivoter 90°");
     cleanup(&output);
 }
@@ -272,7 +272,7 @@ fn test_pic_pivoter_180() {
     setup();
     let output = format!("{OUT}/pic_rot180.jpg");
     cleanup(&output);
-    assert!(crate::modules::pic::pivoter(Path::new(&format!("{TEST_PIC}/JPG.jpg")), &output, 180));
+    assert!(crate::modules::pic::pivoter(Path::new(&format!("{TEST_PIC}/JPG.jpg")), &output, 180, true, &Default::default()));
     assert_output(&output, "pivoter 180°");
     cleanup(&output);
 }
@@ -282,7 +282,7 @@ fn test_pic_pivoter_270() {
     setup();
     let output = format!("{OUT}/pic_rot270.jpg");
     cleanup(&output);
-    assert!(crate::modules::pic::pivoter(Path::new(&format!("{TEST_PIC}/JPG.jpg")), &output, 270));
+    assert!(crate::modules::pic::pivoter(Path::new(&format!("{TEST_PIC}/JPG.jpg")), &output, 270, true, &Default::default()));
     assert_output(&output, "pivoter 270°");
     cleanup(&output);
 }
@@ -295,7 +295,7 @@ fn test_pic_recadrer() {
     setup();
     let output = format!("{OUT}/pic_crop.jpg");
     cleanup(&output);
-    assert!(crate::modules::pic::recadrer(Path::new(&format!("{TEST_PIC}/JPG.jpg")), &output, 10, 10, 50, 50));
+    assert!(crate::modules::pic::recadrer(Path::new(&format!("{TEST_PIC}/JPG.jpg")), &output, 10, 10, 50, 50, true, &Default::default()));
     assert_output(&output, "recadrer");
     cleanup(&output);
 }
@@ -305,7 +305,7 @@ fn test_pic_redimensionner_pixels() {
     setup();
     let output = format!("{OUT}/pic_resize_px.jpg");
     cleanup(&output);
-    assert!(crate::modules::pic::redimensionner_pixels(Path::new(&format!("{TEST_PIC}/JPG.jpg")), &output, 200, 150));
+    assert!(crate::modules::pic::redimensionner_pixels(Path::new(&format!("{TEST_PIC}/JPG.jpg")), &output, 200, 150, true, &Default::default()));
     assert_output(&output, "resize 200x150");
     cleanup(&output);
 }
@@ -315,11 +315,111 @@ fn test_pic_redimensionner_poids() {
     setup();
     let output = format!("{OUT}/pic_resize_kb.jpg");
     cleanup(&output);
-    assert!(crate::modules::pic::redimensionner_poids(Path::new(&format!("{TEST_PIC}/JPG.jpg")), &output, 50));
+    assert!(crate::modules::pic::redimensionner_poids(Path::new(&format!("{TEST_PIC}/JPG.jpg")), &output, 50, true));
     assert_output(&output, "resize max 50Ko");
     cleanup(&output);
 }
 
+#[test]
+fn test_pic_encoder_poids_cible() {
+    use crate::modules::pic::encoder_poids_cible_image;
+    setup();
+    let img = image::open(format!("{TEST_PIC}/JPG.jpg")).expect("ouverture JPG.jpg");
+    let (w, h) = (img.width(), img.height());
+    // JPEG : la qualité baisse d'abord, la résolution reste entière
+    let output = format!("{OUT}/pic_poids_cible.jpg");
+    cleanup(&output);
+    let rapport = encoder_poids_cible_image(&img, &output, 600, 30).expect("poids cible JPEG");
+    assert!(rapport.qualite.is_some_and(|q| (30..=95).contains(&q)), "{:?}", rapport);
+    assert_eq!((rapport.largeur, rapport.hauteur), (w, h));
+    assert_eq!(std::fs::metadata(&output).map(|m| m.len()).ok(), Some(rapport.octets));
+    assert!(rapport.octets <= 600 * 1024);
+    cleanup(&output);
+    // AVIF : même recherche de qualité
+    let petite = img.resize(240, 240, image::imageops::FilterType::Triangle);
+    let output = format!("{OUT}/pic_poids_cible.avif");
+    cleanup(&output);
+    let rapport = encoder_poids_cible_image(&petite, &output, 4, 30).expect("poids cible AVIF");
+    assert!(rapport.qualite.is_some() && rapport.octets <= 4 * 1024, "{:?}", rapport);
+    cleanup(&output);
+    // PNG sans perte : seule la résolution diminue
+    let output = format!("{OUT}/pic_poids_cible.png");
+    cleanup(&output);
+    let moyenne = img.resize(600, 600, image::imageops::FilterType::Triangle);
+    let rapport = encoder_poids_cible_image(&moyenne, &output, 120, 30).expect("poids cible PNG");
+    assert_eq!(rapport.qualite, None);
+    assert!(rapport.largeur < moyenne.width() && rapport.octets <= 120 * 1024, "{:?}", rapport);
+    assert_eq!(image::image_dimensions(&output).ok(), Some((rapport.largeur, rapport.hauteur)));
+    cleanup(&output);
+    // WebP / JXL : encodeurs sans perte, la cible est atteinte en réduisant la résolution
+    let output = format!("{OUT}/pic_poids_cible.webp");
+    cleanup(&output);
+    let rapport = encoder_poids_cible_image(&moyenne, &output, 120, 30).expect("poids cible WebP");
+    assert_eq!(rapport.qualite, None);
+    assert!(rapport.largeur < moyenne.width() && rapport.octets <= 120 * 1024, "{:?}", rapport);
+    assert_eq!(image::image_dimensions(&output).ok(), Some((rapport.largeur, rapport.hauteur)));
+    cleanup(&output);
+    let output = format!("{OUT}/pic_poids_cible.jxl");
+    cleanup(&output);
+    let rapport = encoder_poids_cible_image(&moyenne, &output, 120, 30).expect("poids cible JXL");
+    assert!(rapport.qualite.is_none() && rapport.octets <= 120 * 1024, "{:?}", rapport);
+    assert_eq!(std::fs::metadata(&output).map(|m| m.len()).ok(), Some(rapport.octets));
+    cleanup(&output);
+    // Géométrie + poids : redimensionné en mémoire, encodé une seule fois
+    let output = format!("{OUT}/pic_poids_cible_geo.jpg");
+    cleanup(&output);
+    let options = crate::modules::pic::OptionsRedimension { largeur: 320, ..Default::default() };
+    let source = Path::new(&format!("{TEST_PIC}/JPG.jpg")).to_path_buf();
+    let rapport = crate::modules::pic::redimensionner_poids_cible(&source, &output, &options, 40, 30, true).expect("géométrie + poids");
+    assert_eq!(rapport.largeur, 320);
+    assert!(rapport.octets <= 40 * 1024, "{:?}", rapport);
+    cleanup(&output);
+}
+
+#[test]
+fn test_pic_geometrie_redimension() {
+    use crate::modules::pic::{geometrie_redimension, GeometrieRedimension, ModeRedimension, OptionsRedimension};
+    let opts = |mode, largeur, hauteur| OptionsRedimension { mode, largeur, hauteur, ..Default::default() };
+    let geo = |w, h, o: &OptionsRedimension| geometrie_redimension(w, h, o).map(|g: GeometrieRedimension| (g.echelle, g.finale));
+    assert_eq!(geo(400, 200, &opts(ModeRedimension::Exact, 100, 0)), Some(((100, 50), (100, 50))));
+    assert_eq!(geo(400, 200, &opts(ModeRedimension::Contenir, 100, 100)), Some(((100, 50), (100, 50))));
+    assert_eq!(geo(400, 200, &opts(ModeRedimension::Remplir, 100, 100)), Some(((200, 100), (100, 100))));
+    assert_eq!(geo(400, 200, &opts(ModeRedimension::Marges, 100, 100)), Some(((100, 50), (100, 100))));
+    assert_eq!(geo(400, 200, &OptionsRedimension { mode: ModeRedimension::Pourcentage, pourcentage: 25.0, ..Default::default() }), Some(((100, 50), (100, 50))));
+    assert_eq!(geo(400, 200, &OptionsRedimension { mode: ModeRedimension::GrandCote, cote: 800, ..Default::default() }), Some(((800, 400), (800, 400))));
+    assert_eq!(geo(400, 200, &OptionsRedimension { mode: ModeRedimension::PetitCote, cote: 100, ..Default::default() }), Some(((200, 100), (200, 100))));
+    // Réduire seulement : pas d'agrandissement, la toile des marges reste au format demandé
+    let mut o = opts(ModeRedimension::Marges, 800, 800);
+    o.reduire_seulement = true;
+    assert_eq!(geo(400, 200, &o), Some(((400, 200), (800, 800))));
+    assert_eq!(geo(400, 200, &opts(ModeRedimension::Contenir, 100, 0)), None);
+}
+
+#[test]
+fn test_pic_redimensionner_modes() {
+    use crate::modules::pic::{redimensionner, ModeRedimension, OptionsRedimension};
+    setup();
+    let input = Path::new(&format!("{TEST_PIC}/JPG.jpg")).to_path_buf();
+    let (w, h) = image::image_dimensions(&input).expect("dimensions JPG.jpg");
+    let cas = [
+        ("fill", OptionsRedimension { mode: ModeRedimension::Remplir, largeur: 120, hauteur: 120, ..Default::default() }, (120, 120)),
+        ("pad", OptionsRedimension { mode: ModeRedimension::Marges, largeur: 120, hauteur: 120, fond: [255, 0, 0, 255], ..Default::default() }, (120, 120)),
+        ("long", OptionsRedimension { mode: ModeRedimension::GrandCote, cote: 150, ..Default::default() }, if w >= h { (150, (h as f64 * 150.0 / w as f64).round() as u32) } else { ((w as f64 * 150.0 / h as f64).round() as u32, 150) }),
+    ];
+    for (nom, options, attendu) in cas {
+        let output = format!("{OUT}/pic_resize_{nom}.png");
+        cleanup(&output);
+        assert!(redimensionner(&input, &output, &options, true, &Default::default()), "redimensionner {nom}");
+        assert_eq!(image::image_dimensions(&output).ok(), Some(attendu), "dimensions {nom}");
+        if nom == "pad" && w != h {
+            // Un coin de la toile est une marge : couleur de fond
+            let img = image::open(&output).expect("relecture pad").to_rgba8();
+            assert_eq!(img.get_pixel(0, 0).0, [255, 0, 0, 255]);
+        }
+        cleanup(&output);
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════
 //  IMAGE — conversions entre formats
 // ═══════════════════════════════════════════════════════════════
@@ -328,7 +428,7 @@ fn test_pic_convertir_jpg_vers_png() {
     setup();
     let output = format!("{OUT}/pic_jpg2png.png");
     cleanup(&output);
-    assert!(crate::modules::pic::convertir(Path::new(&format!("{TEST_PIC}/JPG.jpg")), &output));
+    assert!(crate::modules::pic::convertir(Path::new(&format!("{TEST_PIC}/JPG.jpg")), &output, true, &Default::default(), &Default::default()));
     assert_output(&output, "JPG→PNG");
     cleanup(&output);
 }
@@ -338,7 +438,7 @@ fn test_pic_convertir_png_vers_jpg() {
     setup();
     let output = format!("{OUT}/pic_png2jpg.jpg");
     cleanup(&output);
-    assert!(crate::modules::pic::convertir(Path::new(&format!("{TEST_PIC}/PNG.png")), &output));
+    assert!(crate::modules::pic::convertir(Path::new(&format!("{TEST_PIC}/PNG.png")), &output, true, &Default::default(), &Default::default()));
     assert_output(&output, "PNG→JPG");
     cleanup(&output);
 }
@@ -348,7 +448,7 @@ fn test_pic_convertir_svg_vers_png() {
     setup();
     let output = format!("{OUT}/pic_svg2png.png");
     cleanup(&output);
-    assert!(crate::modules::pic::convertir(Path::new(&format!("{TEST_PIC}/SVG.svg")), &output));
+    assert!(crate::modules::pic::convertir(Path::new(&format!("{TEST_PIC}/SVG.svg")), &output, true, &Default::default(), &Default::default()));
     assert_output(&output, "SVG→PNG");
     cleanup(&output);
 }
@@ -358,7 +458,7 @@ fn test_pic_convertir_jpg_vers_webp() {
     setup();
     let output = format!("{OUT}/pic_jpg2webp.webp");
     cleanup(&output);
-    assert!(crate::modules::pic::convertir(Path::new(&format!("{TEST_PIC}/JPG.jpg")), &output));
+    assert!(crate::modules::pic::convertir(Path::new(&format!("{TEST_PIC}/JPG.jpg")), &output, true, &Default::default(), &Default::default()));
     assert_output(&output, "JPG→WEBP");
     cleanup(&output);
 }
@@ -368,7 +468,7 @@ fn test_pic_convertir_png_vers_webp() {
     setup();
     let output = format!("{OUT}/pic_png2webp.webp");
     cleanup(&output);
-    assert!(crate::modules::pic::convertir(Path::new(&format!("{TEST_PIC}/PNG.png")), &output));
+    assert!(crate::modules::pic::convertir(Path::new(&format!("{TEST_PIC}/PNG.png")), &output, true, &Default::default(), &Default::default()));
     assert_output(&output, "PNG→WEBP");
     cleanup(&output);
 }
@@ -378,7 +478,7 @@ fn test_pic_convertir_webp_vers_png() {
     setup();
     let output = format!("{OUT}/pic_webp2png.png");
     cleanup(&output);
-    assert!(crate::modules::pic::convertir(Path::new(&format!("{TEST_PIC}/WEBP.webp")), &output));
+    assert!(crate::modules::pic::convertir(Path::new(&format!("{TEST_PIC}/WEBP.webp")), &output, true, &Default::default(), &Default::default()));
     assert_output(&output, "WEBP→PNG");
     cleanup(&output);
 }
@@ -388,7 +488,7 @@ fn test_pic_convertir_gif_vers_png() {
     setup();
     let output = format!("{OUT}/pic_gif2png.png");
     cleanup(&output);
-    assert!(crate::modules::pic::convertir(Path::new(&format!("{TEST_PIC}/GIF.gif")), &output));
+    assert!(crate::modules::pic::convertir(Path::new(&format!("{TEST_PIC}/GIF.gif")), &output, true, &Default::default(), &Default::default()));
     assert_output(&output, "GIF→PNG");
     cleanup(&output);
 }
@@ -398,7 +498,7 @@ fn test_pic_convertir_tiff_vers_jpg() {
     setup();
     let output = format!("{OUT}/pic_tiff2jpg.jpg");
     cleanup(&output);
-    assert!(crate::modules::pic::convertir(Path::new(&format!("{TEST_PIC}/Tiff.tiff")), &output));
+    assert!(crate::modules::pic::convertir(Path::new(&format!("{TEST_PIC}/Tiff.tiff")), &output, true, &Default::default(), &Default::default()));
     assert_output(&output, "TIFF→JPG");
     cleanup(&output);
 }
@@ -409,7 +509,7 @@ fn test_pic_convertir_jpg_vers_jxl() {
     setup();
     let output = format!("{OUT}/pic_jpg2jxl.jxl");
     cleanup(&output);
-    assert!(crate::modules::pic::convertir(Path::new(&format!("{TEST_PIC}/JPG.jpg")), &output));
+    assert!(crate::modules::pic::convertir(Path::new(&format!("{TEST_PIC}/JPG.jpg")), &output, true, &Default::default(), &Default::default()));
     assert_output(&output, "JPG→JXL");
     cleanup(&output);
 }
@@ -419,7 +519,7 @@ fn test_pic_convertir_png_vers_jxl() {
     setup();
     let output = format!("{OUT}/pic_png2jxl.jxl");
     cleanup(&output);
-    assert!(crate::modules::pic::convertir(Path::new(&format!("{TEST_PIC}/PNG.png")), &output));
+    assert!(crate::modules::pic::convertir(Path::new(&format!("{TEST_PIC}/PNG.png")), &output, true, &Default::default(), &Default::default()));
     assert_output(&output, "PNG→JXL");
     cleanup(&output);
 }
@@ -429,7 +529,7 @@ fn test_pic_convertir_jxl_vers_jpg() {
     setup();
     let output = format!("{OUT}/pic_jxl2jpg.jpg");
     cleanup(&output);
-    assert!(crate::modules::pic::convertir(Path::new(&format!("{TEST_PIC}/JXL.jxl")), &output));
+    assert!(crate::modules::pic::convertir(Path::new(&format!("{TEST_PIC}/JXL.jxl")), &output, true, &Default::default(), &Default::default()));
     assert_output(&output, "JXL→JPG");
     cleanup(&output);
 }
@@ -439,7 +539,7 @@ fn test_pic_convertir_jxl_vers_png() {
     setup();
     let output = format!("{OUT}/pic_jxl2png.png");
     cleanup(&output);
-    assert!(crate::modules::pic::convertir(Path::new(&format!("{TEST_PIC}/JXL.jxl")), &output));
+    assert!(crate::modules::pic::convertir(Path::new(&format!("{TEST_PIC}/JXL.jxl")), &output, true, &Default::default(), &Default::default()));
     assert_output(&output, "JXL→PNG");
     cleanup(&output);
 }
@@ -464,6 +564,768 @@ fn test_pic_supprimer_exif() {
     cleanup(&output);
 }
 
+/// JPEG 64×48 avec EXIF (GPS, série, copyright, orientation, miniature), XMP GPS et ICC factice
+fn jpeg_avec_metadonnees(chemin: &str) {
+    use exif::{Field, In, Tag, Value};
+    let champ = |tag, ifd_num, value| Field { tag, ifd_num, value };
+    let champs = [
+        champ(Tag::Orientation, In::PRIMARY, Value::Short(vec![6])),
+        champ(Tag::Copyright, In::PRIMARY, Value::Ascii(vec![b"(c) Oxyon".to_vec()])),
+        champ(Tag::Make, In::PRIMARY, Value::Ascii(vec![b"Boitier".to_vec()])),
+        champ(Tag::PixelXDimension, In::PRIMARY, Value::Long(vec![64])),
+        champ(Tag::PixelYDimension, In::PRIMARY, Value::Long(vec![48])),
+        champ(Tag::BodySerialNumber, In::PRIMARY, Value::Ascii(vec![b"SN-123456".to_vec()])),
+        champ(Tag::GPSLatitudeRef, In::PRIMARY, Value::Ascii(vec![b"N".to_vec()])),
+        champ(Tag::GPSLatitude, In::PRIMARY, Value::Rational(vec![(48, 1).into(), (51, 1).into(), (0, 1).into()])),
+        champ(Tag::Compression, In::THUMBNAIL, Value::Short(vec![6])),
+    ];
+    let mut vignette = Vec::new();
+    image::DynamicImage::new_rgb8(8, 6).write_to(&mut std::io::Cursor::new(&mut vignette), image::ImageFormat::Jpeg).unwrap();
+    let mut writer = exif::experimental::Writer::new();
+    for c in &champs { writer.push_field(c); }
+    writer.set_jpeg(&vignette, In::THUMBNAIL);
+    let mut exif = std::io::Cursor::new(Vec::new());
+    writer.write(&mut exif, false).unwrap();
+    let img = image::RgbImage::from_fn(64, 48, |x, y| image::Rgb([(x * 4) as u8, (y * 5) as u8, 128]));
+    img.save(chemin).unwrap();
+    let meta = crate::modules::pic::Metadonnees {
+        exif: Some(exif.into_inner()),
+        xmp: Some(br#"<x:xmpmeta><rdf:Description exif:GPSLatitude="48,51N" dc:format="image/jpeg"/></x:xmpmeta>"#.to_vec()),
+        icc: Some((0..70_000u32).map(|i| (i % 251) as u8).collect()),
+        iptc: None,
+    };
+    crate::modules::pic::ecrire_metadonnees(chemin, &meta).unwrap();
+}
+
+fn champs_exif(meta: &crate::modules::pic::Metadonnees) -> Vec<(exif::Tag, exif::In, String)> {
+    let Some(brut) = &meta.exif else { return Vec::new() };
+    let exif = exif::Reader::new().read_raw(brut.clone()).expect("EXIF relisible");
+    exif.fields().map(|f| (f.tag, f.ifd_num, f.display_value().to_string())).collect()
+}
+
+#[test]
+fn test_pic_metadonnees_report_et_nettoyage() {
+    use crate::modules::pic::{lire_metadonnees, nettoyer_metadonnees, OptionsNettoyageMeta};
+    use exif::{In, Tag};
+    setup();
+    let source = format!("{OUT}/pic_meta_source.jpg");
+    jpeg_avec_metadonnees(&source);
+    let origine = lire_metadonnees(Path::new(&source));
+    assert_eq!(origine.icc.as_ref().map(|i| i.len()), Some(70_000), "ICC sur deux segments APP2");
+    let a = |champs: &[(Tag, In, String)], tag, ifd| champs.iter().any(|(t, i, _)| *t == tag && *i == ifd);
+    assert!(a(&champs_exif(&origine), Tag::GPSLatitude, In::PRIMARY));
+
+    // Redimensionnement vers PNG : EXIF/XMP/ICC reportés, dimensions mises à jour.
+    // La source (orientation 6) est redressée : la miniature, restée couchée, est retirée.
+    let png = format!("{OUT}/pic_meta_resize.png");
+    assert!(crate::modules::pic::redimensionner_pixels(Path::new(&source), &png, 32, 24, true, &Default::default()));
+    let meta = lire_metadonnees(Path::new(&png));
+    assert_eq!(meta.icc, origine.icc);
+    assert_eq!(meta.xmp, origine.xmp);
+    let champs = champs_exif(&meta);
+    assert!(champs.contains(&(Tag::PixelXDimension, In::PRIMARY, "32".into())), "{:?}", champs);
+    assert!(!a(&champs, Tag::Compression, In::THUMBNAIL), "miniature retirée après redressement");
+    assert!(champs.contains(&(Tag::Orientation, In::PRIMARY, "row 0 at top and column 0 at left".into())), "{:?}", champs);
+    assert_eq!(image::image_dimensions(&png).ok(), Some((32, 24)));
+
+    // Rotation vers WebP : miniature retirée, VP8X valide
+    let webp = format!("{OUT}/pic_meta_rotate.webp");
+    assert!(crate::modules::pic::pivoter(Path::new(&source), &webp, 90, true, &Default::default()));
+    let meta = lire_metadonnees(Path::new(&webp));
+    assert!(meta.icc.is_some() && meta.xmp.is_some());
+    assert!(!a(&champs_exif(&meta), Tag::Compression, In::THUMBNAIL));
+    // 64x48 stocké, redressé en 48x64 puis pivoté de 90°
+    assert_eq!(image::open(&webp).map(|i| (i.width(), i.height())).ok(), Some((64, 48)));
+
+    // Conversion JPEG → PNG : EXIF/XMP/ICC reportés, orientation remise à 1 après redressement
+    let converti = format!("{OUT}/pic_meta_convert.png");
+    assert!(crate::modules::pic::convertir(Path::new(&source), &converti, true, &Default::default(), &Default::default()));
+    let meta = lire_metadonnees(Path::new(&converti));
+    assert_eq!(meta.icc, origine.icc);
+    assert_eq!(meta.xmp, origine.xmp);
+    let champs = champs_exif(&meta);
+    assert!(a(&champs, Tag::GPSLatitude, In::PRIMARY) && a(&champs, Tag::Copyright, In::PRIMARY), "{:?}", champs);
+    assert!(champs.contains(&(Tag::Orientation, In::PRIMARY, "row 0 at top and column 0 at left".into())), "{:?}", champs);
+    assert_eq!(image::image_dimensions(&converti).ok(), Some((48, 64)));
+
+    // Nettoyage GPS + numéros de série, sans réencodage
+    let nettoye = format!("{OUT}/pic_meta_gps.jpg");
+    assert!(nettoyer_metadonnees(Path::new(&source), &nettoye, &OptionsNettoyageMeta { gps: true, numeros_serie: true, ..Default::default() }));
+    let meta = lire_metadonnees(Path::new(&nettoye));
+    let champs = champs_exif(&meta);
+    assert!(!champs.iter().any(|(t, _, _)| t.context() == exif::Context::Gps), "{:?}", champs);
+    assert!(!a(&champs, Tag::BodySerialNumber, In::PRIMARY));
+    assert!(a(&champs, Tag::Copyright, In::PRIMARY) && a(&champs, Tag::Make, In::PRIMARY));
+    assert!(a(&champs, Tag::Compression, In::THUMBNAIL));
+    let xmp = String::from_utf8(meta.xmp.clone().unwrap()).unwrap();
+    assert!(!xmp.contains("GPS") && xmp.contains("dc:format"), "{}", xmp);
+    assert_eq!(image::open(&nettoye).unwrap().to_rgb8(), image::open(&source).unwrap().to_rgb8(), "pixels intacts");
+
+    // Tout sauf copyright/orientation : ICC conservé
+    let essentiel = format!("{OUT}/pic_meta_essentiel.jpg");
+    assert!(nettoyer_metadonnees(Path::new(&source), &essentiel, &OptionsNettoyageMeta { sauf_essentiel: true, ..Default::default() }));
+    let meta = lire_metadonnees(Path::new(&essentiel));
+    let mut tags: Vec<Tag> = champs_exif(&meta).into_iter().map(|(t, _, _)| t).collect();
+    tags.sort_by_key(|t| t.number());
+    assert_eq!(tags, vec![Tag::Orientation, Tag::Copyright]);
+    assert!(meta.xmp.is_none() && meta.icc.is_some());
+
+    for f in [&source, &png, &webp, &converti, &nettoye, &essentiel] { cleanup(f); }
+}
+
+#[test]
+fn test_pic_orientation_exif() {
+    use crate::modules::pic::{ecrire_metadonnees, lire_metadonnees, lire_orientation, orienter_auto, Metadonnees};
+    setup();
+    // Image 3x2 aux pixels tous distincts : chaque cas EXIF a une position attendue connue
+    let (w, h) = (3u32, 2u32);
+    let stockee = image::RgbImage::from_fn(w, h, |x, y| image::Rgb([x as u8 * 80, y as u8 * 120, 40]));
+    let attendu = |n: u8, x: u32, y: u32| match n {
+        1 => (x, y),
+        2 => (w - 1 - x, y),
+        3 => (w - 1 - x, h - 1 - y),
+        4 => (x, h - 1 - y),
+        5 => (y, x),
+        6 => (h - 1 - y, x),
+        7 => (h - 1 - y, w - 1 - x),
+        _ => (y, w - 1 - x),
+    };
+    for n in 1..=8u8 {
+        let source = format!("{OUT}/pic_orient_{n}.png");
+        let sortie = format!("{OUT}/pic_orient_{n}_droite.png");
+        stockee.save(&source).unwrap();
+        let mut writer = exif::experimental::Writer::new();
+        let champ = exif::Field { tag: exif::Tag::Orientation, ifd_num: exif::In::PRIMARY, value: exif::Value::Short(vec![n as u16]) };
+        writer.push_field(&champ);
+        let mut exif = std::io::Cursor::new(Vec::new());
+        writer.write(&mut exif, n % 2 == 0).unwrap();
+        ecrire_metadonnees(&source, &Metadonnees { exif: Some(exif.into_inner()), ..Default::default() }).unwrap();
+        assert_eq!(lire_orientation(Path::new(&source)), n);
+
+        assert!(orienter_auto(Path::new(&source), &sortie), "orientation {n}");
+        let droite = image::open(&sortie).unwrap().to_rgb8();
+        let (dw, dh) = if n >= 5 { (h, w) } else { (w, h) };
+        assert_eq!(droite.dimensions(), (dw, dh), "orientation {n}");
+        for (x, y, px) in stockee.enumerate_pixels() {
+            let (ax, ay) = attendu(n, x, y);
+            assert_eq!(droite.get_pixel(ax, ay), px, "orientation {n} pixel ({x},{y})");
+        }
+        assert_eq!(lire_orientation(Path::new(&sortie)), 1, "tag remis à 1");
+        assert!(lire_metadonnees(Path::new(&sortie)).exif.is_some());
+        if n == 1 {
+            assert_eq!(std::fs::read(&sortie).unwrap(), std::fs::read(&source).unwrap(), "image droite recopiée");
+        }
+        cleanup(&source);
+        cleanup(&sortie);
+    }
+
+    // Pipeline standard : la compression redresse aussi et abandonne la miniature
+    let source = format!("{OUT}/pic_orient_source.jpg");
+    let sortie = format!("{OUT}/pic_orient_compresse.jpg");
+    jpeg_avec_metadonnees(&source);
+    assert!(crate::modules::pic::compresser(Path::new(&source), &sortie, 8, true, &Default::default(), &Default::default()));
+    assert_eq!(image::image_dimensions(&sortie).ok(), Some((48, 64)));
+    assert_eq!(lire_orientation(Path::new(&sortie)), 1);
+    let champs = champs_exif(&lire_metadonnees(Path::new(&sortie)));
+    assert!(!champs.iter().any(|(_, i, _)| *i == exif::In::THUMBNAIL), "{:?}", champs);
+    assert!(champs.iter().any(|(t, _, _)| *t == exif::Tag::Copyright));
+
+    // Redressement désactivé : ordre stocké et tag d'origine conservés
+    let brute = format!("{OUT}/pic_orient_compresse_brute.jpg");
+    assert!(crate::modules::pic::compresser(Path::new(&source), &brute, 8, false, &Default::default(), &Default::default()));
+    assert_eq!(image::image_dimensions(&brute).ok(), image::image_dimensions(&source).ok());
+    assert_eq!(lire_orientation(Path::new(&brute)), lire_orientation(Path::new(&source)));
+    cleanup(&source);
+    cleanup(&sortie);
+    cleanup(&brute);
+}
+
+#[test]
+fn test_pic_animations() {
+    use crate::modules::pic::{assembler_animation, collecter_images_dossier, delai_trame_ms, ecrire_animation, extraire_trames, lire_animation, Animation};
+    setup();
+    let couleurs = [[220u8, 30, 30, 255], [30, 200, 40, 255], [40, 60, 210, 255]];
+    let trames = couleurs.iter().zip([100, 200, 300]).map(|(c, ms)| {
+        let img = image::RgbaImage::from_fn(20, 10, |x, _| if x < 2 { image::Rgba([255, 255, 255, 255]) } else { image::Rgba(*c) });
+        image::Frame::from_parts(img, 0, 0, image::Delay::from_numer_denom_ms(ms, 1))
+    }).collect();
+    let gif = format!("{OUT}/pic_anim.gif");
+    ecrire_animation(&Animation { trames, boucles: 0 }, &gif).unwrap();
+
+    let verifier = |chemin: &str, dims: (u32, u32)| {
+        let anim = lire_animation(Path::new(chemin)).unwrap().unwrap_or_else(|| panic!("{} non animé", chemin));
+        assert_eq!(anim.trames.iter().map(delai_trame_ms).collect::<Vec<_>>(), vec![100, 200, 300], "{}", chemin);
+        assert_eq!(anim.dimensions(), dims, "{}", chemin);
+        for (trame, c) in anim.trames.iter().zip(&couleurs) {
+            let (w, h) = dims;
+            let px = trame.buffer().get_pixel(w / 2, h / 2).0;
+            assert!(px.iter().zip(c).all(|(a, b)| a.abs_diff(*b) <= 12), "{} : {:?} vs {:?}", chemin, px, c);
+        }
+        anim
+    };
+    assert_eq!(verifier(&gif, (20, 10)).boucles, 0);
+    assert!(lire_animation(Path::new(&format!("{TEST_PIC}/WEBP.webp"))).unwrap().is_none(), "image fixe");
+
+    // Conversions GIF -> WebP animé / APNG, délais conservés
+    let webp = format!("{OUT}/pic_anim.webp");
+    let apng = format!("{OUT}/pic_anim.png");
+    assert!(crate::modules::pic::convertir(Path::new(&gif), &webp, true, &Default::default(), &Default::default()));
+    assert!(crate::modules::pic::compresser(Path::new(&webp), &apng, 9, true, &Default::default(), &Default::default()));
+    verifier(&webp, (20, 10));
+    verifier(&apng, (20, 10));
+
+    // Traitements trame par trame
+    let tourne = format!("{OUT}/pic_anim_rot.gif");
+    assert!(crate::modules::pic::pivoter(Path::new(&apng), &tourne, 90, true, &Default::default()));
+    verifier(&tourne, (10, 20));
+    let reduit = format!("{OUT}/pic_anim_fit.webp");
+    let options = crate::modules::pic::OptionsRedimension { mode: crate::modules::pic::ModeRedimension::Contenir, largeur: 10, hauteur: 10, ..Default::default() };
+    assert!(crate::modules::pic::redimensionner(Path::new(&gif), &reduit, &options, true, &Default::default()));
+    verifier(&reduit, (10, 5));
+    let recadre = format!("{OUT}/pic_anim_crop.png");
+    assert!(crate::modules::pic::recadrer(Path::new(&gif), &recadre, 50, 0, 50, 100, true, &Default::default()));
+    verifier(&recadre, (10, 10));
+    // Sortie fixe : première trame
+    let fixe = format!("{OUT}/pic_anim_fixe.jpg");
+    assert!(crate::modules::pic::convertir(Path::new(&gif), &fixe, true, &Default::default(), &Default::default()));
+    assert_eq!(image::image_dimensions(&fixe).ok(), Some((20, 10)));
+
+    // Extraction puis réassemblage : délais lus dans les noms de fichiers
+    let dossier = std::path::PathBuf::from(format!("{OUT}/pic_anim_frames"));
+    let _ = fs::remove_dir_all(&dossier);
+    assert_eq!(extraire_trames(Path::new(&webp), &dossier, true), Ok(3));
+    let images = collecter_images_dossier(&dossier).unwrap();
+    assert!(images[1].to_string_lossy().ends_with("pic_anim_0002_200ms.png"), "{:?}", images);
+    let assemble = format!("{OUT}/pic_anim_assemble.png");
+    assert_eq!(assembler_animation(&images, &assemble, 50, 2, true), Ok(3));
+    assert_eq!(verifier(&assemble, (20, 10)).boucles, 2);
+    let _ = fs::remove_dir_all(&dossier);
+
+    for f in [&gif, &webp, &apng, &tourne, &reduit, &recadre, &fixe, &assemble] { cleanup(f); }
+}
+
+/// PSD CMYK 8 bits compressé en PackBits (valeurs stockées inversées : 255 = pas d'encre)
+fn psd_cmyk(chemin: &str, pixels: &[[u8; 4]]) {
+    let mut psd = b"8BPS\x00\x01\x00\x00\x00\x00\x00\x00".to_vec();
+    psd.extend_from_slice(&4u16.to_be_bytes());
+    psd.extend_from_slice(&1u32.to_be_bytes());
+    psd.extend_from_slice(&(pixels.len() as u32).to_be_bytes());
+    psd.extend_from_slice(&8u16.to_be_bytes());
+    psd.extend_from_slice(&4u16.to_be_bytes());
+    psd.extend_from_slice(&[0; 12]); // mode couleur, ressources, calques : vides
+    psd.extend_from_slice(&1u16.to_be_bytes());
+    // Une ligne par canal, en littéraux PackBits
+    let lignes: Vec<Vec<u8>> = (0..4).map(|c| {
+        let mut l = vec![pixels.len() as u8 - 1];
+        l.extend(pixels.iter().map(|p| 255 - p[c]));
+        l
+    }).collect();
+    for l in &lignes { psd.extend_from_slice(&(l.len() as u16).to_be_bytes()); }
+    for l in &lignes { psd.extend_from_slice(l); }
+    fs::write(chemin, psd).unwrap();
+}
+
+#[test]
+fn test_pic_profils_couleur() {
+    use crate::modules::pic::{cmyk_vers_rgb, compresser, convertir, lire_metadonnees, pivoter, ProfilCible};
+    setup();
+    // Naïf sans profil : encre nulle = blanc, cyan pur, noir
+    let rgb = cmyk_vers_rgb(&[0, 0, 0, 0, 255, 0, 0, 0, 0, 0, 0, 255], 3, 1, None, &ProfilCible::Srgb);
+    assert_eq!(rgb.pixels().map(|p| p.0).collect::<Vec<_>>(), vec![[255, 255, 255], [0, 255, 255], [0, 0, 0]]);
+
+    // PSD CMYK : image composite décodée, encre convertie
+    let psd = format!("{OUT}/pic_cmyk.psd");
+    let png_psd = format!("{OUT}/pic_cmyk_psd.png");
+    psd_cmyk(&psd, &[[255, 0, 0, 0], [0, 0, 0, 0], [0, 255, 255, 0]]);
+    assert!(convertir(Path::new(&psd), &png_psd, true, &ProfilCible::Srgb, &Default::default()));
+    let img = image::open(&png_psd).unwrap().to_rgb8();
+    assert_eq!(img.pixels().map(|p| p.0).collect::<Vec<_>>(), vec![[0, 255, 255], [255, 255, 255], [255, 0, 0]]);
+
+    // Section ressources annoncée plus longue que le fichier : refusée sans paniquer
+    let tronque = format!("{OUT}/pic_tronque.psd");
+    let mut octets = fs::read(&psd).unwrap()[..30].to_vec();
+    octets.extend_from_slice(&u32::MAX.to_be_bytes());
+    octets.extend_from_slice(b"8B");
+    fs::write(&tronque, octets).unwrap();
+    assert!(crate::modules::pic::lire_profil_icc(Path::new(&tronque)).is_none());
+    assert!(!convertir(Path::new(&tronque), &png_psd, true, &ProfilCible::Srgb, &Default::default()));
+    // En-tête annonçant une composite démesurée (4 × 200 Mo) : refusée avant allocation
+    let mut octets = fs::read(&psd).unwrap();
+    octets[18..22].copy_from_slice(&200_000_000u32.to_be_bytes());
+    fs::write(&tronque, octets).unwrap();
+    assert!(!convertir(Path::new(&tronque), &png_psd, true, &ProfilCible::Srgb, &Default::default()));
+
+    // Photo Display P3 : convertie en sRGB (profil retiré), gardée en P3, ou laissée telle quelle
+    let source = format!("{OUT}/pic_p3.png");
+    let p3 = moxcms::ColorProfile::new_display_p3().encode().unwrap();
+    image::RgbImage::from_pixel(8, 8, image::Rgb([200, 100, 50])).save(&source).unwrap();
+    crate::modules::pic::ecrire_metadonnees(&source, &crate::modules::pic::Metadonnees { icc: Some(p3.clone()), ..Default::default() }).unwrap();
+    let pixel = |f: &str| image::open(f).unwrap().to_rgb8().get_pixel(0, 0).0;
+
+    let srgb = format!("{OUT}/pic_p3_srgb.png");
+    assert!(compresser(Path::new(&source), &srgb, 9, true, &ProfilCible::Srgb, &Default::default()));
+    let [r, g, b] = pixel(&srgb);
+    assert!(r > 200 && g < 100 && b < 50, "orange P3 plus saturé en sRGB : {:?}", [r, g, b]);
+    assert!(lire_metadonnees(Path::new(&srgb)).icc.is_none());
+    let jpg = format!("{OUT}/pic_p3_srgb.jpg");
+    assert!(convertir(Path::new(&source), &jpg, true, &ProfilCible::Srgb, &Default::default()));
+    assert!(lire_metadonnees(Path::new(&jpg)).icc.is_none());
+
+    let garde = format!("{OUT}/pic_p3_garde.png");
+    let conserve = format!("{OUT}/pic_p3_conserve.png");
+    assert!(compresser(Path::new(&source), &garde, 9, true, &ProfilCible::DisplayP3, &Default::default()));
+    assert!(convertir(Path::new(&source), &conserve, true, &ProfilCible::Conserver, &Default::default()));
+    assert_eq!(pixel(&garde), [200, 100, 50]);
+    assert!(lire_metadonnees(Path::new(&garde)).icc.is_some());
+    assert_eq!(pixel(&conserve), [200, 100, 50]);
+    assert_eq!(lire_metadonnees(Path::new(&conserve)).icc, Some(p3.clone()));
+
+    // Hors conversion, le profil embarqué est conservé tel quel
+    let tourne = format!("{OUT}/pic_p3_tourne.png");
+    assert!(pivoter(Path::new(&source), &tourne, 180, true, &Default::default()));
+    assert_eq!(pixel(&tourne), [200, 100, 50]);
+    assert_eq!(lire_metadonnees(Path::new(&tourne)).icc, Some(p3));
+
+    assert_eq!(ProfilCible::depuis_nom("adobe-rgb"), Ok(ProfilCible::AdobeRgb));
+    assert!(ProfilCible::depuis_nom("inexistant.icc").is_err());
+    for f in [&psd, &tronque, &png_psd, &source, &srgb, &jpg, &garde, &conserve, &tourne] { cleanup(f); }
+}
+
+#[test]
+fn test_pic_mappage_tonal() {
+    use crate::modules::pic::{convertir, mapper_tons, pivoter, OperateurTonal, OptionsTonales};
+    setup();
+    let valeurs = [0.0f32, 0.18, 1.0, 4.0, 16.0];
+    let hdr = image::DynamicImage::ImageRgb32F(image::Rgb32FImage::from_fn(valeurs.len() as u32, 1, |x, _| {
+        image::Rgb([valeurs[x as usize]; 3])
+    }));
+    let niveaux = |img: &image::DynamicImage| img.to_rgb8().pixels().map(|p| p[0]).collect::<Vec<u8>>();
+
+    // Reinhard à exposition nulle : 255 · (L / (1 + L))^(1/2.2)
+    let reinhard = mapper_tons(&hdr, &OptionsTonales { operateur: OperateurTonal::Reinhard, exposition: Some(0.0), gamma: 2.2 });
+    let attendus: Vec<u8> = valeurs.iter().map(|l| ((l / (1.0 + l)).powf(1.0 / 2.2) * 255.0).round() as u8).collect();
+    assert_eq!(niveaux(&reinhard), attendus);
+    assert_eq!(reinhard.color(), image::ColorType::Rgb8);
+
+    // Écrêtage : tout ce qui dépasse 1.0 sature
+    let ecrete = niveaux(&mapper_tons(&hdr, &OptionsTonales { operateur: OperateurTonal::Ecretage, exposition: Some(0.0), gamma: 1.0 }));
+    assert_eq!(ecrete, vec![0, 46, 255, 255, 255]);
+
+    // EXR vers PNG : ACES automatique, hautes lumières encore distinctes, EXR vers EXR intact
+    let exr = format!("{OUT}/pic_hdr.exr");
+    let png = format!("{OUT}/pic_hdr.png");
+    let copie = format!("{OUT}/pic_hdr_copie.exr");
+    hdr.save(&exr).unwrap();
+    assert!(convertir(Path::new(&exr), &png, true, &Default::default(), &Default::default()));
+    let aces = niveaux(&image::open(&png).unwrap());
+    assert!(aces.windows(2).all(|w| w[0] < w[1]), "{:?}", aces);
+    assert!(convertir(Path::new(&exr), &copie, true, &Default::default(), &Default::default()));
+    let relu = image::open(&copie).unwrap().to_rgb32f();
+    assert_eq!(relu.get_pixel(4, 0)[0], 16.0);
+
+    // Réglages transmis par l'appelant : rotation avec écrêtage
+    let tourne = format!("{OUT}/pic_hdr_tourne.png");
+    assert!(pivoter(Path::new(&exr), &tourne, 180, true, &OptionsTonales { operateur: OperateurTonal::Ecretage, exposition: Some(0.0), gamma: 1.0 }));
+    assert_eq!(niveaux(&image::open(&tourne).unwrap()), vec![255, 255, 255, 46, 0]);
+
+    assert_eq!(OperateurTonal::depuis_nom("filmic"), Some(OperateurTonal::Aces));
+    for f in [&exr, &png, &copie, &tourne] { cleanup(f); }
+}
+
+#[test]
+fn test_pic_reglages_photo() {
+    use crate::modules::pic::{ajuster, appliquer_reglages, OptionsReglages, Virage};
+    setup();
+    let degrade = image::DynamicImage::ImageRgb8(image::RgbImage::from_fn(64, 8, |x, _| {
+        let v = 64 + x as u8 * 2;
+        image::Rgb([v, v / 2 + 40, 255 - v])
+    }));
+    let regle = |options: OptionsReglages| appliquer_reglages(&degrade, &options).to_rgb8();
+
+    // Réglages neutres : pixels intacts
+    assert!(OptionsReglages::default().est_neutre());
+    assert_eq!(regle(OptionsReglages::default()), degrade.to_rgb8());
+
+    let clair = regle(OptionsReglages { luminosite: 50.0, ..Default::default() });
+    assert!(clair.pixels().zip(degrade.to_rgb8().pixels()).all(|(a, b)| a[0] >= b[0] && a[1] > b[1]));
+    let gris = regle(OptionsReglages { saturation: -100.0, ..Default::default() });
+    assert!(gris.pixels().all(|p| p[0].abs_diff(p[1]) <= 1 && p[1].abs_diff(p[2]) <= 1));
+    let sepia = regle(OptionsReglages { virage: Virage::Sepia, ..Default::default() });
+    assert!(sepia.pixels().all(|p| p[0] >= p[1] && p[1] >= p[2]), "sépia : rouge ≥ vert ≥ bleu");
+
+    // Niveaux auto : dégradé gris 64-190 étiré sur toute la plage
+    let terne = image::DynamicImage::ImageLuma8(image::GrayImage::from_fn(64, 8, |x, _| image::Luma([64 + x as u8 * 2])));
+    let niveaux = appliquer_reglages(&terne, &OptionsReglages { niveaux_auto: true, ..Default::default() }).to_luma8();
+    let (min, max) = niveaux.pixels().fold((255, 0), |(a, b), p| (p[0].min(a), p[0].max(b)));
+    assert!(min <= 2 && max >= 253, "{} - {}", min, max);
+
+    // Balance des blancs : une dominante uniforme devient un gris neutre
+    let dominante = image::DynamicImage::ImageRgb8(image::RgbImage::from_pixel(4, 4, image::Rgb([200, 150, 100])));
+    let neutre = appliquer_reglages(&dominante, &OptionsReglages { balance_auto: true, ..Default::default() }).to_rgb8();
+    assert_eq!(neutre.get_pixel(0, 0).0, [150, 150, 150]);
+
+    // Netteté : le bord d'une marche est accentué (dépassement de part et d'autre)
+    let marche = image::DynamicImage::ImageLuma8(image::GrayImage::from_fn(16, 4, |x, _| image::Luma([if x < 8 { 80 } else { 160 }])));
+    let net = appliquer_reglages(&marche, &OptionsReglages { nettete: 1.5, rayon_nettete: 1.0, ..Default::default() }).to_luma8();
+    assert!(net.get_pixel(7, 1)[0] < 80 && net.get_pixel(8, 1)[0] > 160);
+
+    // Lot sur fichier : dimensions conservées
+    let source = format!("{OUT}/pic_reglages.png");
+    let sortie = format!("{OUT}/pic_reglages_ajuste.jpg");
+    degrade.save(&source).unwrap();
+    assert!(ajuster(Path::new(&source), &sortie, &OptionsReglages { contraste: 30.0, debruitage: 1.0, ..Default::default() }, true));
+    assert_eq!(image::image_dimensions(&sortie).ok(), Some((64, 8)));
+    for f in [&source, &sortie] { cleanup(f); }
+}
+
+#[test]
+fn test_pic_doublons() {
+    use crate::modules::pic::{charger_image, chercher_doublons, distance_hamming, empreinte, grouper_doublons, traiter_doublons, Conservation, Empreinte, ImageEmpreinte};
+    setup();
+    let dossier = std::path::PathBuf::from(format!("{OUT}/pic_doublons"));
+    let corbeille = std::path::PathBuf::from(format!("{OUT}/pic_doublons_corbeille"));
+    let _ = fs::remove_dir_all(&dossier);
+    let _ = fs::remove_dir_all(&corbeille);
+    fs::create_dir_all(dossier.join("exports")).unwrap();
+    let motif = |inverse: bool| image::DynamicImage::ImageRgb8(image::RgbImage::from_fn(128, 96, move |x, y| {
+        let v = 128.0 + 100.0 * (x as f32 / 15.0).sin() * (y as f32 / 11.0).cos();
+        let v = if inverse { 255.0 - v } else { v } as u8;
+        image::Rgb([v, v / 2 + 60, 255 - v])
+    }));
+    let original = motif(false);
+    let jpeg = |img: &image::DynamicImage| {
+        let mut octets = Vec::new();
+        img.write_to(&mut std::io::Cursor::new(&mut octets), image::ImageFormat::Jpeg).unwrap();
+        octets
+    };
+
+    // Empreintes : stables au redimensionnement, éloignées pour une image différente
+    for algo in [Empreinte::Moyenne, Empreinte::Difference, Empreinte::Perceptuelle] {
+        let h = empreinte(&original, algo);
+        assert!(distance_hamming(h, empreinte(&original.thumbnail(64, 48), algo)) <= 6, "{}", algo.nom());
+        assert!(distance_hamming(h, empreinte(&motif(true), algo)) > 20, "{}", algo.nom());
+    }
+
+    original.save(dossier.join("a.png")).unwrap();
+    fs::write(dossier.join("exports/a_web.jpg"), jpeg(&original.thumbnail(64, 48))).unwrap();
+    motif(true).save(dossier.join("c.png")).unwrap();
+    // Faux RAW : en-tête TIFF, flux JPEG sans perte (SOF3) illisible, miniature et aperçu JPEG
+    let mut raw = b"II*\0\x08\0\0\0".to_vec();
+    raw.extend_from_slice(&[0xFF, 0xD8, 0xFF, 0xC3, 0, 11, 8, 0, 16, 0, 16, 1, 1, 0x11, 0]);
+    raw.extend_from_slice(&[0xFF, 0xDA, 0, 8, 1, 1, 0, 0, 0x3F, 0]);
+    raw.extend(std::iter::repeat_n(0x11, 20_000));
+    raw.extend_from_slice(&[0xFF, 0xD9, 0, 0]);
+    raw.extend(jpeg(&original.thumbnail(16, 12)));
+    raw.extend(jpeg(&original.thumbnail(96, 72)));
+    fs::write(dossier.join("exports/a.nef"), raw).unwrap();
+    assert_eq!(charger_image(&dossier.join("exports/a.nef"), true).map(|i| (i.width(), i.height())), Ok((96, 72)));
+
+    let groupes = chercher_doublons(std::slice::from_ref(&dossier), Empreinte::Perceptuelle, 8, Conservation::PlusGrande, true).unwrap();
+    assert_eq!(groupes.len(), 1);
+    assert_eq!(groupes[0].images.len(), 3);
+    assert!(!groupes[0].images.iter().any(|i| i.chemin.ends_with("c.png")));
+
+    // Simulation : rien n'est touché, la plus grande image est conservée
+    let bilan = traiter_doublons(&groupes, true, Some(&corbeille), true);
+    assert_eq!((bilan.retires, bilan.echecs), (2, 0));
+    assert!(groupes[0].images[0].chemin.ends_with("a.png"));
+    assert!(bilan.rapport.contains("keep") && bilan.rapport.contains("would move"), "{}", bilan.rapport);
+    assert!(dossier.join("exports/a.nef").exists() && !corbeille.exists());
+
+    let bilan = traiter_doublons(&groupes, true, Some(&corbeille), false);
+    assert_eq!((bilan.retires, bilan.echecs), (2, 0));
+    assert!(dossier.join("a.png").exists() && dossier.join("c.png").exists());
+    assert!(!dossier.join("exports/a_web.jpg").exists() && corbeille.join("a_web.jpg").exists() && corbeille.join("a.nef").exists());
+    assert!(chercher_doublons(std::slice::from_ref(&dossier), Empreinte::Perceptuelle, 8, Conservation::PlusGrande, true).unwrap().is_empty());
+
+    // Chaîne a ~ b ~ c : c est à 4 bits de b mais à 8 de a, l'image conservée ; il reste hors du groupe
+    let image = |nom: &str, empreinte: u64, largeur: u32| ImageEmpreinte {
+        chemin: dossier.join(nom), empreinte, largeur, hauteur: 10, octets: 0, modifie: std::time::SystemTime::UNIX_EPOCH,
+    };
+    let chaine = [image("a.png", 0, 300), image("b.png", 0x0F, 200), image("c.png", 0xFF, 100)];
+    let groupes = grouper_doublons(&chaine, 4, Conservation::PlusGrande);
+    assert_eq!(groupes.len(), 1);
+    assert_eq!(groupes[0].images.iter().map(|i| i.chemin.file_name().unwrap().to_str().unwrap()).collect::<Vec<_>>(), ["a.png", "b.png"]);
+    assert_eq!(groupes[0].distance, 4);
+
+    let _ = fs::remove_dir_all(&dossier);
+    let _ = fs::remove_dir_all(&corbeille);
+}
+
+#[test]
+fn test_pic_montage() {
+    use crate::modules::pic::{montage, parser_format_page, Legende, OptionsMontage};
+    setup();
+    let dossier = std::path::PathBuf::from(format!("{OUT}/pic_montage"));
+    let _ = fs::remove_dir_all(&dossier);
+    fs::create_dir_all(&dossier).unwrap();
+    let images: Vec<std::path::PathBuf> = (0..5u8).map(|i| {
+        let p = dossier.join(format!("img_{}.png", i));
+        let (w, h) = if i % 2 == 0 { (80, 40) } else { (30, 60) };
+        image::RgbImage::from_pixel(w, h, image::Rgb([i * 50, 0, 255 - i * 50])).save(&p).unwrap();
+        p
+    }).collect();
+
+    assert_eq!(parser_format_page("A4"), Ok((210.0, 297.0)));
+    assert_eq!(parser_format_page("100x150"), Ok((100.0, 150.0)));
+    assert!(parser_format_page("a9").is_err());
+
+    // Sans page : grille automatique 3 × 2, cases de 100 px à la proportion médiane (2:1), sans légende
+    let planche = format!("{OUT}/pic_montage.png");
+    let options = OptionsMontage { taille_case: 100, espacement: 10, legende: Legende::Aucune, ..Default::default() };
+    assert_eq!(montage(&images, &planche, &options, true), Ok(1));
+    let img = image::open(&planche).unwrap().to_rgba8();
+    assert_eq!(img.dimensions(), (3 * 100 + 4 * 10, 2 * 50 + 3 * 10));
+    assert_eq!(img.get_pixel(5, 5).0, [255, 255, 255, 255], "fond");
+    assert_eq!(img.get_pixel(15, 15).0, [0, 0, 255, 255], "image 2:1 : remplit sa case");
+    assert_eq!(img.get_pixel(125, 15).0, [255, 255, 255, 255], "image 1:2 : centrée, bandes sur les côtés");
+    assert_eq!(img.get_pixel(170, 35).0, [50, 0, 205, 255]);
+
+    // Lignes imposées : plusieurs planches numérotées, légendes sous les cases
+    let numerotee = format!("{OUT}/pic_montage_pages.jpg");
+    let options = OptionsMontage { colonnes: 2, lignes: 1, taille_case: 64, fond: [32, 32, 32, 255], ..Default::default() };
+    assert_eq!(montage(&images, &numerotee, &options, true), Ok(3));
+    let page3 = format!("{OUT}/pic_montage_pages_003.jpg");
+    let (w, h) = image::image_dimensions(&page3).unwrap();
+    assert!(w == 2 * 64 + 3 * 16 && h > 32 + 2 * 16, "{}x{}", w, h);
+
+    // PDF A5 : une page par planche, à la taille du format
+    let pdf = format!("{OUT}/pic_montage.pdf");
+    let options = OptionsMontage { page_mm: Some((148.0, 210.0)), colonnes: 2, lignes: 2, dpi: 72, ..Default::default() };
+    assert_eq!(montage(&images, &pdf, &options, true), Ok(2));
+    let doc = lopdf::Document::load(&pdf).unwrap();
+    assert_eq!(doc.get_pages().len(), 2);
+    let page = doc.get_pages().values().next().copied().unwrap();
+    let boite = doc.get_dictionary(page).unwrap().get(b"MediaBox").unwrap().as_array().unwrap().clone();
+    assert!((boite[2].as_float().unwrap() - 420.0).abs() < 1.0 && (boite[3].as_float().unwrap() - 595.0).abs() < 1.0, "{:?}", boite);
+
+    for f in [&planche, &pdf, &page3] { cleanup(f); }
+    for i in 1..3 { cleanup(&format!("{OUT}/pic_montage_pages_{:03}.jpg", i)); }
+    let _ = fs::remove_dir_all(&dossier);
+}
+
+#[test]
+fn test_pic_jeu_icones() {
+    use crate::modules::pic::{generer_jeu_icones, OptionsIcones};
+    setup();
+    let dossier = std::path::PathBuf::from(format!("{OUT}/pic_icones"));
+    let _ = fs::remove_dir_all(&dossier);
+    let svg = format!("{OUT}/pic_icones.svg");
+    fs::write(&svg, r##"<svg xmlns="http://www.w3.org/2000/svg" width="200" height="100"><rect width="200" height="100" fill="#ff0000"/></svg>"##).unwrap();
+
+    let options = OptionsIcones { marge: 10.0, nom: "Oxyon Test".into(), prefixe_url: "/static".into(), ..Default::default() };
+    let fichiers = generer_jeu_icones(Path::new(&svg), &dossier, &options, true).unwrap();
+    for nom in ["favicon.ico", "favicon-16x16.png", "favicon-32x32.png", "apple-touch-icon.png", "android-chrome-192x192.png",
+                "android-chrome-512x512.png", "maskable-icon-512x512.png", "icon.icns", "favicon.svg", "site.webmanifest", "icons.html"] {
+        assert!(fichiers.contains(&dossier.join(nom)), "{} absent de {:?}", nom, fichiers);
+    }
+    assert_eq!(image::image_dimensions(dossier.join("favicon.ico")).ok(), Some((48, 48)));
+
+    // Source 2:1 avec marge : bandes transparentes, sauf sur l'icône Apple (fond blanc opaque)
+    let android = image::open(dossier.join("android-chrome-512x512.png")).unwrap().to_rgba8();
+    assert_eq!(android.dimensions(), (512, 512));
+    assert_eq!(android.get_pixel(256, 20)[3], 0);
+    assert_eq!(android.get_pixel(256, 256).0, [255, 0, 0, 255]);
+    assert_eq!(android.get_pixel(30, 256)[3], 0, "marge de 10 %");
+    let apple = image::open(dossier.join("apple-touch-icon.png")).unwrap().to_rgba8();
+    assert_eq!((apple.width(), apple.get_pixel(2, 2).0), (180, [255, 255, 255, 255]));
+
+    // ICNS : longueur totale, puis entrées PNG (OSType, longueur incluant l'en-tête)
+    let icns = fs::read(dossier.join("icon.icns")).unwrap();
+    assert_eq!(&icns[..4], b"icns");
+    assert_eq!(u32::from_be_bytes(icns[4..8].try_into().unwrap()) as usize, icns.len());
+    let mut entrees = std::collections::HashMap::new();
+    let mut i = 8;
+    while i < icns.len() {
+        let longueur = u32::from_be_bytes(icns[i + 4..i + 8].try_into().unwrap()) as usize;
+        let png = image::load_from_memory_with_format(&icns[i + 8..i + longueur], image::ImageFormat::Png).unwrap();
+        entrees.insert(String::from_utf8_lossy(&icns[i..i + 4]).to_string(), png.width());
+        i += longueur;
+    }
+    assert_eq!(i, icns.len());
+    assert_eq!((entrees["icp4"], entrees["ic07"], entrees["ic10"], entrees["ic14"]), (16, 128, 1024, 512));
+
+    let manifeste: serde_json::Value = serde_json::from_str(&fs::read_to_string(dossier.join("site.webmanifest")).unwrap()).unwrap();
+    assert_eq!(manifeste["name"], "Oxyon Test");
+    assert_eq!(manifeste["short_name"], "Oxyon Test");
+    assert_eq!(manifeste["icons"][0]["src"], "/static/android-chrome-192x192.png");
+    assert_eq!(manifeste["icons"][2]["purpose"], "maskable");
+    let html = fs::read_to_string(dossier.join("icons.html")).unwrap();
+    assert!(html.contains(r#"href="/static/apple-touch-icon.png""#) && html.contains("image/svg+xml"), "{}", html);
+
+    // Source raster, fond opaque : pas de favicon.svg
+    let png = format!("{OUT}/pic_icones.png");
+    image::RgbaImage::from_pixel(64, 64, image::Rgba([0, 128, 0, 255])).save(&png).unwrap();
+    let _ = fs::remove_dir_all(&dossier);
+    let options = OptionsIcones { fond: [0, 0, 255, 255], marge: 25.0, ..Default::default() };
+    let fichiers = generer_jeu_icones(Path::new(&png), &dossier, &options, true).unwrap();
+    assert!(!fichiers.contains(&dossier.join("favicon.svg")));
+    let icone = image::open(dossier.join("favicon-32x32.png")).unwrap().to_rgba8();
+    assert_eq!((icone.get_pixel(1, 1).0, icone.get_pixel(16, 16).0), ([0, 0, 255, 255], [0, 128, 0, 255]));
+
+    for f in [&svg, &png] { cleanup(f); }
+    let _ = fs::remove_dir_all(&dossier);
+}
+
+#[test]
+fn test_pic_editer_metadonnees() {
+    use crate::modules::pic::{editer_metadonnees, lire_metadonnees, parser_decalage, EditionMeta};
+    use exif::{In, Tag};
+    setup();
+    assert_eq!(parser_decalage("+1h30m"), Ok(chrono::TimeDelta::seconds(5400)));
+    assert_eq!(parser_decalage("-2d"), Ok(chrono::TimeDelta::seconds(-172_800)));
+    assert_eq!(parser_decalage("-01:30"), Ok(chrono::TimeDelta::seconds(-5400)));
+    assert!(parser_decalage("+1x").is_err());
+
+    let source = format!("{OUT}/pic_edit_source.jpg");
+    jpeg_avec_metadonnees(&source);
+    let mut edition = EditionMeta::default();
+    for a in ["date=2024:07:14 21:30:00", "artist=Jeanne & Co", "copyright=", "keywords=mer, plage", "gps=-33.8568,151.2153"] {
+        edition.affecter(a).unwrap();
+    }
+    edition.decalage = Some(parser_decalage("+1h30m").unwrap());
+    assert!(edition.affecter("couleur=bleu").is_err());
+
+    let sortie = format!("{OUT}/pic_edit.jpg");
+    assert!(editer_metadonnees(Path::new(&source), &sortie, &edition));
+    let meta = lire_metadonnees(Path::new(&sortie));
+    let champs = champs_exif(&meta);
+    let valeur = |tag| champs.iter().find(|(t, i, _)| *t == tag && *i == In::PRIMARY).map(|(_, _, v)| v.clone());
+    assert_eq!(valeur(Tag::DateTimeOriginal).as_deref(), Some("2024-07-14 23:00:00"));
+    assert_eq!(valeur(Tag::Artist).as_deref(), Some("\"Jeanne & Co\""));
+    assert_eq!(valeur(Tag::Copyright), None);
+    assert_eq!(valeur(Tag::GPSLatitudeRef).as_deref(), Some("S"));
+    assert!(valeur(Tag::GPSLatitude).is_some_and(|v| v.starts_with("33 deg 51 min")), "{:?}", valeur(Tag::GPSLatitude));
+    assert_eq!(valeur(Tag::Orientation).as_deref(), Some("row 0 at right and column 0 at top"));
+    assert!(champs.iter().any(|(t, i, _)| *t == Tag::Compression && *i == In::THUMBNAIL), "miniature conservée");
+    let xmp = String::from_utf8(meta.xmp.unwrap()).unwrap();
+    assert!(xmp.contains("<dc:subject><rdf:Bag><rdf:li>mer</rdf:li><rdf:li>plage</rdf:li></rdf:Bag></dc:subject>"), "{}", xmp);
+    assert!(xmp.contains("Jeanne &amp; Co") && !xmp.contains("GPS"), "{}", xmp);
+    assert_eq!(image::open(&sortie).unwrap().to_rgb8(), image::open(&source).unwrap().to_rgb8());
+
+    // TIFF : IFD0 réécrit en fin de fichier, pixels inchangés
+    let tiff = format!("{OUT}/pic_edit_source.tif");
+    image::RgbImage::from_fn(20, 10, |x, y| image::Rgb([x as u8 * 10, y as u8 * 20, 7])).save(&tiff).unwrap();
+    let sortie_tiff = format!("{OUT}/pic_edit.tif");
+    let mut edition = EditionMeta::default();
+    edition.affecter("description=Test TIFF").unwrap();
+    edition.affecter("keywords=a,b").unwrap();
+    assert!(editer_metadonnees(Path::new(&tiff), &sortie_tiff, &edition));
+    let mut lecteur = std::io::BufReader::new(std::fs::File::open(&sortie_tiff).unwrap());
+    let exif = exif::Reader::new().read_from_container(&mut lecteur).expect("EXIF TIFF");
+    let description = exif.get_field(Tag::ImageDescription, In::PRIMARY).map(|f| f.display_value().to_string());
+    assert_eq!(description.as_deref(), Some("\"Test TIFF\""));
+    assert!(exif.get_field(Tag(exif::Context::Tiff, 700), In::PRIMARY).is_some(), "paquet XMP");
+    assert_eq!(image::open(&sortie_tiff).unwrap().to_rgb8(), image::open(&tiff).unwrap().to_rgb8());
+
+    for f in [&source, &sortie, &tiff, &sortie_tiff] { cleanup(f); }
+}
+
+// ═══════════════════════════════════════════════════════════════
+//  IMAGE — texte TrueType : watermark, meme, HTML
+// ═══════════════════════════════════════════════════════════════
+#[test]
+fn test_pic_watermark_ttf() {
+    use crate::modules::doc::AncreTampon;
+    use crate::modules::pic::{parser_couleur, watermark, watermark_avance, OptionsWatermark, StyleTexte};
+    setup();
+    let input = format!("{OUT}/pic_wm_source.png");
+    let centre = format!("{OUT}/pic_wm_centre.png");
+    let mosaique = format!("{OUT}/pic_wm_mosaique.jpg");
+    for f in [&input, &centre, &mosaique] { cleanup(f); }
+    image::RgbImage::from_pixel(400, 200, image::Rgb([128, 128, 128])).save(&input).unwrap();
+
+    assert_eq!(parser_couleur("#FF000080").unwrap(), [255, 0, 0, 128]);
+    assert_eq!(parser_couleur("00ff00").unwrap(), [0, 255, 0, 255]);
+    assert!(parser_couleur("rouge").is_err());
+
+    // Texte non ASCII, rouge contouré de noir, centré sans rotation
+    let options = OptionsWatermark {
+        style: StyleTexte {
+            police: Some("assets/fonts/DejaVuSans.ttf".into()),
+            taille: 40.0,
+            couleur: [255, 0, 0, 255],
+            contour: Some(([0, 0, 0, 255], 2.0)),
+            ..Default::default()
+        },
+        opacite: 1.0,
+        diagonale: false,
+        ..Default::default()
+    };
+    assert!(watermark_avance(Path::new(&input), &centre, "Épreuve ü", &options, true));
+    let img = image::open(&centre).unwrap().to_rgb8();
+    assert_eq!(img.get_pixel(5, 5), &image::Rgb([128, 128, 128]), "coin modifié");
+    let pixels: Vec<&image::Rgb<u8>> = img.pixels().collect();
+    assert!(pixels.iter().any(|p| p[0] > 220 && p[1] < 40), "texte rouge absent");
+    assert!(pixels.iter().any(|p| p[0] < 30 && p[1] < 30), "contour absent");
+    // Niveaux intermédiaires : anti-crénelage
+    assert!(pixels.iter().any(|p| p[0] > 60 && p[0] < 200 && p[1] < 60));
+
+    let mauvaise_police = OptionsWatermark { style: StyleTexte { police: Some("absente.ttf".into()), ..Default::default() }, ..Default::default() };
+    assert!(!watermark_avance(Path::new(&input), &centre, "x", &mauvaise_police, true));
+
+    // Mosaïque en diagonale : les quatre quarts de l'image sont couverts
+    let options = OptionsWatermark { style: StyleTexte { taille: 24.0, ..Default::default() }, opacite: 0.8, mosaique: true, ..Default::default() };
+    assert!(watermark_avance(Path::new(&input), &mosaique, "DRAFT", &options, true));
+    let img = image::open(&mosaique).unwrap().to_rgb8();
+    for (x0, y0) in [(0, 0), (200, 0), (0, 100), (200, 100)] {
+        let clairs = (x0..x0 + 200).flat_map(|x| (y0..y0 + 100).map(move |y| (x, y)))
+            .filter(|&(x, y)| img.get_pixel(x, y)[0] > 200)
+            .count();
+        assert!(clairs > 50, "quart ({x0},{y0}) sans texte");
+    }
+
+    // Ancrage en bas à droite
+    let options = OptionsWatermark { ancre: AncreTampon::BasDroite, diagonale: false, opacite: 1.0, ..Default::default() };
+    assert!(watermark_avance(Path::new(&input), &centre, "©", &options, true));
+    let img = image::open(&centre).unwrap().to_rgb8();
+    assert!((300..380).any(|x| (100..180).any(|y| img.get_pixel(x, y)[0] > 200)));
+    assert!((0..100).all(|x| (0..100).all(|y| img.get_pixel(x, y)[0] == 128)));
+
+    assert!(watermark(Path::new(&input), &mosaique, "TEST", 30.0, 0.4, true));
+    assert_output(&mosaique, "watermark simple");
+    for f in [&input, &centre, &mosaique] { cleanup(f); }
+}
+
+#[test]
+fn test_pic_meme_html_ttf() {
+    use crate::modules::pic::{html_to_image, meme, meme_style, StyleTexte};
+    setup();
+    let input = format!("{OUT}/pic_meme_source.png");
+    let bandes = format!("{OUT}/pic_meme_bandes.png");
+    let classique = format!("{OUT}/pic_meme_classique.png");
+    let html = format!("{OUT}/pic_html_source.html");
+    let rendu = format!("{OUT}/pic_html_rendu.png");
+    for f in [&input, &bandes, &classique, &html, &rendu] { cleanup(f); }
+    image::RgbImage::from_pixel(320, 240, image::Rgb([40, 90, 160])).save(&input).unwrap();
+
+    assert!(meme(Path::new(&input), &bandes, "Quand ça compile", "du premier coup", true));
+    let img = image::open(&bandes).unwrap();
+    assert_eq!((img.width(), img.height()), (320, 240 + 2 * 40));
+
+    // Sans bandes : texte contouré sur l'image, réduit pour tenir dans la largeur
+    let style = StyleTexte { taille: 0.0, ..Default::default() };
+    assert!(meme_style(Path::new(&input), &classique, "UN TEXTE BEAUCOUP TROP LONG POUR LA LARGEUR", "", &style, false, true));
+    let img = image::open(&classique).unwrap().to_rgb8();
+    assert_eq!(img.dimensions(), (320, 240));
+    assert!(img.pixels().any(|p| p[0] > 240 && p[2] > 240), "texte blanc absent");
+    assert_eq!(img.get_pixel(2, 30), &image::Rgb([40, 90, 160]), "texte hors largeur");
+
+    // Image de moins de 20 px de haut : la zone de texte est bornée à l'image
+    image::RgbImage::from_pixel(120, 12, image::Rgb([40, 90, 160])).save(&input).unwrap();
+    assert!(meme_style(Path::new(&input), &classique, "", "bas", &style, false, true));
+    assert_eq!(image::open(&classique).unwrap().height(), 12);
+
+    fs::write(&html, "<h1>Café crème</h1><p>Ünïcødé — texte rendu avec une vraie police, coupé selon la largeur disponible pour tenir dans l'image.</p>").unwrap();
+    assert!(html_to_image(Path::new(&html), &rendu, 300));
+    let img = image::open(&rendu).unwrap().to_rgb8();
+    assert_eq!(img.width(), 300);
+    assert!(img.height() > 100, "retour à la ligne absent");
+    assert!(img.pixels().any(|p| p[0] < 50));
+
+    for f in [&input, &bandes, &classique, &html, &rendu] { cleanup(f); }
+}
+
 // ═══════════════════════════════════════════════════════════════
 //  DOC — conversion pandoc
 // ═══════════════════════════════════════════════════════════════
@@ -580,6 +1442,424 @@ fn test_doc_convertir_odt_vers_html() {
 // ═══════════════════════════════════════════════════════════════
 //  DOC — opérations PDF
 // ═══════════════════════════════════════════════════════════════
+#[test]
+fn test_doc_parser_selection_pages() {
+    use crate::modules::doc::parser_selection_pages as sel;
+    assert_eq!(sel("1,3,5", 10).unwrap(), vec![1, 3, 5]);
+    assert_eq!(sel("2-4,8-", 10).unwrap(), vec![2, 3, 4, 8, 9, 10]);
+    assert_eq!(sel("4-2", 10).unwrap(), vec![4, 3, 2]);
+    assert_eq!(sel("last,-2", 10).unwrap(), vec![10, 9]);
+    assert_eq!(sel("-3--1", 10).unwrap(), vec![8, 9, 10]);
+    assert_eq!(sel("odd", 5).unwrap(), vec![1, 3, 5]);
+    assert_eq!(sel("1-6:even", 10).unwrap(), vec![2, 4, 6]);
+    assert_eq!(sel("reverse", 3).unwrap(), vec![3, 2, 1]);
+    assert_eq!(sel("1-10,!3,!5-9", 10).unwrap(), vec![1, 2, 4, 10]);
+    assert_eq!(sel("!1", 3).unwrap(), vec![2, 3], "exclusion seule = tout sauf");
+    assert!(sel("11", 10).is_err(), "page hors document acceptée");
+    assert!(sel("-11", 10).is_err(), "index négatif hors document accepté");
+    assert!(sel("abc", 10).is_err(), "page invalide acceptée");
+    assert!(sel("1-3,!1-3", 10).is_err(), "sélection vide acceptée");
+}
+
+#[test]
+fn test_doc_pdf_selection_pages() {
+    setup();
+    let input = format!("{TEST_DOC}/PDF.pdf");
+    let p = Path::new(&input);
+    assert_eq!(crate::modules::doc::pdf_nombre_pages(p).unwrap(), 3);
+    assert_eq!(crate::modules::doc::pdf_selection_pages(p, "").unwrap(), None);
+    assert_eq!(crate::modules::doc::pdf_selection_pages(p, "2-").unwrap(), Some(vec![2, 3]));
+
+    // Non-PDF : une seule conversion, partagée entre comptage et opération, supprimée à la libération
+    let source = crate::modules::doc::pdf_preparer(Path::new(&format!("{TEST_DOC}/MD.md"))).unwrap();
+    let temp = source.chemin().to_path_buf();
+    assert!(temp.exists(), "PDF temporaire absent");
+    assert_eq!(temp.file_stem().unwrap(), "MD", "nom de base non conservé");
+    assert!(source.nb_pages().unwrap() >= 1);
+    assert_eq!(source.selection("all").unwrap(), None);
+    drop(source);
+    assert!(!temp.exists(), "PDF temporaire non supprimé");
+
+    let output = format!("{OUT}/doc_pdf_organize_reverse.pdf");
+    cleanup(&output);
+    let ordre = crate::modules::doc::parser_selection_pages("reverse", 3).unwrap();
+    crate::modules::doc::pdf_organiser(p, &output, &ordre).unwrap();
+    assert_output(&output, "pdf organize reverse");
+    cleanup(&output);
+}
+
+#[test]
+fn test_doc_pdf_imposer() {
+    use crate::modules::doc::{pdf_imposer, ModeImposition};
+    setup();
+    let input = format!("{TEST_DOC}/PDF.pdf");
+    let output = format!("{OUT}/doc_pdf_impose.pdf");
+    let nb_pages = |path: &str| lopdf::Document::load(path).unwrap().get_pages().len();
+
+    cleanup(&output);
+    pdf_imposer(Path::new(&input), &output, &ModeImposition::NUp(2)).unwrap();
+    assert_eq!(nb_pages(&output), 2, "3 pages en 2-up → 2 feuilles");
+
+    pdf_imposer(Path::new(&input), &output, &ModeImposition::NUp(4)).unwrap();
+    assert_eq!(nb_pages(&output), 1, "3 pages en 4-up → 1 feuille");
+
+    // 3 pages complétées à 4 → 1 feuille recto-verso = 2 faces
+    pdf_imposer(Path::new(&input), &output, &ModeImposition::Livret).unwrap();
+    assert_eq!(nb_pages(&output), 2);
+
+    pdf_imposer(Path::new(&input), &output, &ModeImposition::Mosaique { echelle: 2.0, recouvrement_mm: 10.0 }).unwrap();
+    let tuiles = nb_pages(&output);
+    assert!(tuiles >= 3 * 4 && tuiles % 3 == 0, "mosaïque ×2 : {} tuiles", tuiles);
+
+    assert!(pdf_imposer(Path::new(&input), &output, &ModeImposition::NUp(0)).is_err());
+    cleanup(&output);
+}
+
+#[test]
+fn test_doc_pdf_tamponner() {
+    use crate::modules::doc::{pdf_tamponner, AncreTampon, PlacementTampon, Tampon};
+    setup();
+    let input = format!("{TEST_DOC}/PDF.pdf");
+    let logo = format!("{OUT}/doc_tampon_logo.png");
+    let output = format!("{OUT}/doc_pdf_tampon.pdf");
+    cleanup(&output);
+    image::RgbaImage::from_fn(40, 20, |x, _| image::Rgba([255, 0, 0, if x < 20 { 255 } else { 0 }]))
+        .save(&logo)
+        .unwrap();
+
+    let tampons = vec![
+        Tampon::Image {
+            chemin: logo.clone().into(),
+            echelle: 20.0,
+            placement: PlacementTampon { ancre: AncreTampon::HautDroite, rotation: 30.0, opacite: 0.5, ..Default::default() },
+        },
+        Tampon::Texte {
+            modele: "Page {page} / {pages} — {filename}".into(),
+            taille_police: 9.0,
+            placement: PlacementTampon { ancre: AncreTampon::BasCentre, ..Default::default() },
+        },
+    ];
+    let result = pdf_tamponner(Path::new(&input), &output, &tampons, Some(&[2, 3]));
+    assert!(result.is_ok(), "pdf_tamponner échoué : {:?}", result);
+    assert_output(&output, "pdf stamp");
+
+    let doc = lopdf::Document::load(&output).unwrap();
+    let pages: Vec<_> = doc.get_pages().into_values().collect();
+    assert_eq!(pages.len(), 3);
+    let texte = |id| String::from_utf8_lossy(&doc.get_page_content(id).unwrap()).to_string();
+    assert!(!texte(pages[0]).contains("Page 1 / 3"), "page 1 hors sélection tamponnée");
+    assert!(texte(pages[1]).contains("Page 2 / 3"), "pied de page absent");
+    assert!(texte(pages[2]).contains("/Imtp0 Do"), "image absente page 3");
+    let image = doc.objects.values()
+        .filter_map(|o| o.as_stream().ok())
+        .find(|s| s.dict.get(b"Width").and_then(|w| w.as_i64()).ok() == Some(40) && s.dict.has(b"SMask"))
+        .expect("XObject image avec SMask absent (transparence PNG perdue)");
+    assert_eq!(image.dict.get(b"ColorSpace").and_then(|c| c.as_name()).unwrap(), b"DeviceRGB");
+
+    // Page tournée de 90° : le tampon suit le repère affiché
+    let tournee = format!("{OUT}/doc_pdf_tampon_rotate.pdf");
+    crate::modules::doc::pdf_rotate(Path::new(&input), &tournee, 90, Some(&[1])).unwrap();
+    pdf_tamponner(Path::new(&tournee), &output, &tampons[1..], Some(&[1])).unwrap();
+    let doc = lopdf::Document::load(&output).unwrap();
+    let page = doc.get_pages()[&1];
+    let contenu = lopdf::content::Content::decode(&doc.get_page_content(page).unwrap()).unwrap();
+    let reperes: Vec<Vec<f32>> = contenu.operations.iter()
+        .filter(|op| op.operator == "cm")
+        .map(|op| op.operands.iter().map(|o| o.as_float().unwrap()).collect())
+        .collect();
+    assert!(reperes.iter().any(|m| m[..4] == [0.0, 1.0, -1.0, 0.0]), "repère /Rotate 90 absent : {:?}", reperes);
+
+    cleanup(&tournee);
+    cleanup(&output);
+    cleanup(&logo);
+}
+
+#[test]
+fn test_doc_pdf_annotations() {
+    use crate::modules::doc::{
+        pdf_ajouter_annotations, pdf_aplatir_annotations, pdf_lister_annotations,
+        pdf_supprimer_annotations, NouvelleAnnotation, TypeAnnotation,
+    };
+    setup();
+    let input = format!("{TEST_DOC}/PDF.pdf");
+    let annote = format!("{OUT}/doc_pdf_annote.pdf");
+    let nettoye = format!("{OUT}/doc_pdf_annote_sans_surlignage.pdf");
+    let aplati = format!("{OUT}/doc_pdf_annote_aplati.pdf");
+    for f in [&annote, &nettoye, &aplati] { cleanup(f); }
+
+    let mut note = NouvelleAnnotation::new(TypeAnnotation::Note, "À vérifier", [80.0, 90.0, 0.0, 0.0]);
+    note.auteur = "Relecteur".into();
+    let annotations = vec![
+        note,
+        NouvelleAnnotation::new(TypeAnnotation::TexteLibre, "Texte libre sur plusieurs mots pour forcer le retour à la ligne", [10.0, 70.0, 30.0, 10.0]),
+        NouvelleAnnotation::new(TypeAnnotation::Surlignage, "", [10.0, 50.0, 60.0, 2.0]),
+        NouvelleAnnotation::new(TypeAnnotation::Lien("https://example.com".into()), "", [10.0, 5.0, 20.0, 3.0]),
+    ];
+    let result = pdf_ajouter_annotations(Path::new(&input), &annote, &annotations, Some(&[1]));
+    assert!(result.is_ok(), "pdf_ajouter_annotations échoué : {:?}", result);
+    assert_output(&annote, "pdf annotate");
+
+    let liste = pdf_lister_annotations(Path::new(&annote)).unwrap();
+    assert_eq!(liste.len(), 4, "annotations : {:?}", liste);
+    assert!(liste.iter().all(|a| a.page == 1));
+    let note = liste.iter().find(|a| a.sous_type == "Text").expect("note absente");
+    assert_eq!(note.contenu, "À vérifier");
+    assert_eq!(note.auteur, "Relecteur");
+    assert!(liste.iter().filter(|a| a.sous_type != "Link").all(|a| a.apparence), "flux d'apparence manquant");
+
+    let retirees = pdf_supprimer_annotations(Path::new(&annote), &nettoye, &["highlight".into()], None).unwrap();
+    assert_eq!(retirees, 1);
+    let restantes = pdf_lister_annotations(Path::new(&nettoye)).unwrap();
+    assert!(restantes.iter().all(|a| a.sous_type != "Highlight"));
+    assert_eq!(restantes.len(), 3);
+
+    let aplaties = pdf_aplatir_annotations(Path::new(&annote), &aplati).unwrap();
+    assert_eq!(aplaties, 3);
+    let apres = pdf_lister_annotations(Path::new(&aplati)).unwrap();
+    assert_eq!(apres.len(), 1, "seul le lien doit rester : {:?}", apres);
+    assert_eq!(apres[0].sous_type, "Link");
+    let doc = lopdf::Document::load(&aplati).unwrap();
+    let page = *doc.get_pages().get(&1).unwrap();
+    let contenu = String::from_utf8_lossy(&doc.get_page_content(page).unwrap()).to_string();
+    assert!(contenu.contains("/Aplat0 Do") && contenu.contains("/Aplat2 Do"), "apparences non incrustées");
+
+    for f in [&annote, &nettoye, &aplati] { cleanup(f); }
+}
+
+#[test]
+fn test_doc_pdf_pieces_jointes() {
+    use crate::modules::doc::{
+        pdf_ajouter_pieces_jointes, pdf_extraire_pieces_jointes, pdf_lister_pieces_jointes,
+        pdf_supprimer_pieces_jointes,
+    };
+    setup();
+    let input = format!("{TEST_DOC}/PDF.pdf");
+    let xml = format!("{OUT}/factur-x.xml");
+    let csv = format!("{OUT}/données.csv");
+    let joint = format!("{OUT}/doc_pdf_pj.pdf");
+    let detache = format!("{OUT}/doc_pdf_pj_detache.pdf");
+    let dossier = format!("{OUT}/doc_pdf_pj_extraits");
+    for f in [&joint, &detache] { cleanup(f); }
+    let _ = fs::remove_dir_all(&dossier);
+    fs::write(&xml, "<rsm:CrossIndustryInvoice/>").unwrap();
+    fs::write(&csv, "a;b\n1;2\n".repeat(50)).unwrap();
+
+    let fichiers = vec![
+        (std::path::PathBuf::from(&xml), "Factur-X".to_string()),
+        (std::path::PathBuf::from(&csv), String::new()),
+    ];
+    let result = pdf_ajouter_pieces_jointes(Path::new(&input), &joint, &fichiers);
+    assert!(result.is_ok(), "pdf_ajouter_pieces_jointes échoué : {:?}", result);
+    assert_output(&joint, "pdf attach");
+
+    let pieces = pdf_lister_pieces_jointes(Path::new(&joint)).unwrap();
+    assert_eq!(pieces.len(), 2, "pièces jointes : {:?}", pieces);
+    let facture = pieces.iter().find(|p| p.nom == "factur-x.xml").expect("factur-x.xml absent");
+    assert_eq!(facture.mime, "text/xml");
+    assert_eq!(facture.description, "Factur-X");
+    assert!(facture.page.is_none());
+    assert!(pieces.iter().any(|p| p.nom == "données.csv" && p.taille == 400));
+
+    // Ré-ajout du même nom : remplacement, pas de doublon
+    pdf_ajouter_pieces_jointes(Path::new(&joint), &detache, &fichiers[..1]).unwrap();
+    assert_eq!(pdf_lister_pieces_jointes(Path::new(&detache)).unwrap().len(), 2);
+
+    let ecrits = pdf_extraire_pieces_jointes(Path::new(&joint), &dossier).unwrap();
+    assert_eq!(ecrits.len(), 2);
+    assert_eq!(fs::read(format!("{dossier}/données.csv")).unwrap(), fs::read(&csv).unwrap());
+
+    let retirees = pdf_supprimer_pieces_jointes(Path::new(&joint), &detache, &["données.csv".into()]).unwrap();
+    assert_eq!(retirees, 1);
+    let restantes = pdf_lister_pieces_jointes(Path::new(&detache)).unwrap();
+    assert_eq!(restantes.len(), 1);
+    assert_eq!(restantes[0].nom, "factur-x.xml");
+    assert_eq!(pdf_supprimer_pieces_jointes(Path::new(&detache), &detache, &[]).unwrap(), 1);
+    assert!(pdf_lister_pieces_jointes(Path::new(&detache)).unwrap().is_empty());
+
+    for f in [&joint, &detache, &xml, &csv] { cleanup(f); }
+    let _ = fs::remove_dir_all(&dossier);
+}
+
+#[test]
+fn test_doc_pdf_extraire_images() {
+    use crate::modules::doc::{pdf_extraire_images, pdf_tamponner, PlacementTampon, Tampon};
+    use lopdf::{dictionary, Object, Stream};
+    setup();
+    let input = format!("{TEST_DOC}/PDF.pdf");
+    let png = format!("{OUT}/doc_images_logo.png");
+    let jpg = format!("{OUT}/doc_images_photo.jpg");
+    let tampon = format!("{OUT}/doc_images_source.pdf");
+    let dossier = format!("{OUT}/doc_images_extraites");
+    cleanup(&tampon);
+    let _ = fs::remove_dir_all(&dossier);
+    image::RgbaImage::from_fn(64, 48, |x, _| image::Rgba([0, 128, 255, if x < 32 { 255 } else { 0 }])).save(&png).unwrap();
+    image::RgbImage::from_fn(80, 60, |x, y| image::Rgb([x as u8 * 3, y as u8 * 4, 90])).save(&jpg).unwrap();
+
+    let placement = PlacementTampon::default();
+    let tampons = vec![
+        Tampon::Image { chemin: png.clone().into(), echelle: 20.0, placement: placement.clone() },
+        Tampon::Image { chemin: jpg.clone().into(), echelle: 20.0, placement },
+    ];
+    pdf_tamponner(Path::new(&input), &tampon, &tampons, Some(&[1])).unwrap();
+
+    // Ajout d'images CMJN 8 bits et indexée 1 bit directement dans les ressources de la page 2
+    let mut doc = lopdf::Document::load(&tampon).unwrap();
+    let page2 = *doc.get_pages().get(&2).unwrap();
+    let mut cmjn = Stream::new(dictionary! {
+        "Type" => "XObject", "Subtype" => "Image", "Width" => 40, "Height" => 40,
+        "ColorSpace" => "DeviceCMYK", "BitsPerComponent" => 8,
+    }, [0u8, 255, 255, 0].repeat(1600));
+    cmjn.compress().unwrap();
+    let cmjn_id = doc.add_object(cmjn);
+    let indexe_id = doc.add_object(Stream::new(dictionary! {
+        "Type" => "XObject", "Subtype" => "Image", "Width" => 40, "Height" => 40,
+        "ColorSpace" => vec![Object::Name(b"Indexed".to_vec()), Object::Name(b"DeviceRGB".to_vec()), 1.into(),
+            Object::String(vec![0, 0, 0, 0, 255, 0], lopdf::StringFormat::Hexadecimal)],
+        "BitsPerComponent" => 1,
+    }, [0b1010_1010u8, 0xAA, 0xAA, 0xAA, 0xAA].repeat(40)));
+    let minuscule_id = doc.add_object(Stream::new(dictionary! {
+        "Type" => "XObject", "Subtype" => "Image", "Width" => 4, "Height" => 4,
+        "ColorSpace" => "DeviceGray", "BitsPerComponent" => 8,
+    }, vec![0u8; 16]));
+    // JPEG gris inversé par /Decode : à réencoder, pas à copier tel quel
+    let mut noir = std::io::Cursor::new(Vec::new());
+    image::DynamicImage::ImageLuma8(image::GrayImage::new(40, 40))
+        .write_to(&mut noir, image::ImageFormat::Jpeg)
+        .unwrap();
+    let inverse_id = doc.add_object(Stream::new(dictionary! {
+        "Type" => "XObject", "Subtype" => "Image", "Width" => 40, "Height" => 40,
+        "ColorSpace" => "DeviceGray", "BitsPerComponent" => 8, "Filter" => "DCTDecode",
+        "Decode" => vec![1.into(), 0.into()],
+    }, noir.into_inner()));
+    let encres_id = doc.add_object(Stream::new(dictionary! {
+        "Type" => "XObject", "Subtype" => "Image", "Width" => 40, "Height" => 40,
+        "ColorSpace" => vec![Object::Name(b"DeviceN".to_vec()),
+            vec![Object::Name(b"Cyan".to_vec()), Object::Name(b"Spot".to_vec())].into(),
+            Object::Name(b"DeviceCMYK".to_vec()), Object::Null],
+        "BitsPerComponent" => 8,
+    }, [0u8, 255].repeat(1600)));
+    // Dimensions corrompues : nulle, négative, démesurée → ignorées sans panique
+    let corrompue = |doc: &mut lopdf::Document, largeur: i64| doc.add_object(Stream::new(dictionary! {
+        "Type" => "XObject", "Subtype" => "Image", "Width" => largeur, "Height" => 4,
+        "ColorSpace" => "DeviceGray", "BitsPerComponent" => 8,
+    }, vec![0u8; 16]));
+    let (nulle_id, negative_id, geante_id) = (corrompue(&mut doc, 0), corrompue(&mut doc, -5), corrompue(&mut doc, 1 << 40));
+    doc.get_object_mut(page2).unwrap().as_dict_mut().unwrap().set("Resources", dictionary! {
+        "XObject" => dictionary! {
+            "Cm" => cmjn_id, "Ix" => indexe_id, "Mini" => minuscule_id, "Inv" => inverse_id, "Dn" => encres_id,
+            "Nul" => nulle_id, "Neg" => negative_id, "Geant" => geante_id,
+        },
+    });
+    doc.save(&tampon).unwrap();
+
+    let ecrits = pdf_extraire_images(Path::new(&tampon), &dossier, "auto", 16, None).unwrap();
+    assert_eq!(ecrits.len(), 6, "images extraites : {:?}", ecrits);
+    assert_eq!(ecrits.iter().filter(|e| e.ends_with(".jpg")).count(), 1, "seul le JPEG RGB est copié tel quel");
+    let jpeg = ecrits.iter().find(|e| e.ends_with(".jpg")).expect("JPEG non conservé");
+    assert!(jpeg.contains("doc_images_source_p001_"));
+    assert_eq!(image::open(jpeg).unwrap().width(), 80);
+    let logo = ecrits.iter()
+        .map(|e| image::open(e).unwrap())
+        .find(|i| i.width() == 64)
+        .expect("PNG source absent");
+    let logo = logo.to_rgba8();
+    assert_eq!(logo.get_pixel(0, 0)[3], 255);
+    assert_eq!(logo.get_pixel(63, 0)[3], 0, "masque doux perdu");
+
+    let rouge = image::open(format!("{dossier}/doc_images_source_p002_1.png")).unwrap().to_rgb8();
+    assert_eq!(rouge.get_pixel(5, 5), &image::Rgb([255, 0, 0]), "conversion CMJN");
+    let indexe = image::open(format!("{dossier}/doc_images_source_p002_2.png")).unwrap().to_rgb8();
+    assert_eq!(indexe.get_pixel(0, 0), &image::Rgb([0, 255, 0]));
+    assert_eq!(indexe.get_pixel(1, 0), &image::Rgb([0, 0, 0]));
+    let inverse = image::open(format!("{dossier}/doc_images_source_p002_4.png")).unwrap().to_luma8();
+    assert!(inverse.get_pixel(20, 20)[0] > 240, "/Decode [1 0] ignoré sur un JPEG");
+    let encres = image::open(format!("{dossier}/doc_images_source_p002_5.png")).unwrap().to_rgb8();
+    assert_eq!(encres.dimensions(), (40, 40));
+    assert_eq!(encres.get_pixel(0, 0), &image::Rgb([0, 0, 0]), "DeviceN à 2 encres mal lu");
+
+    // Filtre de taille et conversion de format
+    let _ = fs::remove_dir_all(&dossier);
+    let webp = pdf_extraire_images(Path::new(&tampon), &dossier, "webp", 40, Some(&[1])).unwrap();
+    assert_eq!(webp.len(), 2);
+    assert!(webp.iter().all(|e| e.ends_with(".webp")));
+    let toutes = pdf_extraire_images(Path::new(&tampon), &dossier, "png", 0, Some(&[2])).unwrap();
+    assert_eq!(toutes.len(), 5, "images corrompues extraites : {:?}", toutes);
+
+    for f in [&png, &jpg, &tampon] { cleanup(f); }
+    let _ = fs::remove_dir_all(&dossier);
+}
+
+#[test]
+fn test_doc_pdf_nettoyer_scan() {
+    use crate::modules::doc::{pdf_nettoyer_scan, OptionsScan};
+    use lopdf::{dictionary, Document, Object, Stream};
+    setup();
+    let scan = format!("{OUT}/doc_scan_lot.pdf");
+    let propre = format!("{OUT}/doc_scan_propre.pdf");
+    let texte = format!("{OUT}/doc_scan_texte.pdf");
+    for f in [&scan, &propre, &texte] { cleanup(f); }
+
+    // Pages numérisées 850×1100 : lignes inclinées de 3°, page blanche avec poussières, bloc droit en bas à gauche
+    let (w, h) = (850u32, 1100u32);
+    let lignes = |angle: f64, x0: f64, x1: f64, y0: f64, y1: f64| {
+        let (sin, cos) = angle.to_radians().sin_cos();
+        image::GrayImage::from_fn(w, h, move |x, y| {
+            let (dx, dy) = (x as f64 - w as f64 / 2.0, y as f64 - h as f64 / 2.0);
+            let (u, v) = (dx * cos + dy * sin + w as f64 / 2.0, dy * cos - dx * sin + h as f64 / 2.0);
+            let encre = (x0..x1).contains(&u) && (y0..y1).contains(&v) && (v - y0) % 30.0 < 6.0;
+            image::Luma([if encre { 0 } else { 255 }])
+        })
+    };
+    let poussieres = image::GrayImage::from_fn(w, h, |x, y| image::Luma([if x % 200 == 7 && y % 300 == 11 { 0 } else { 255 }]));
+    let images = [lignes(3.0, 150.0, 700.0, 200.0, 800.0), poussieres, lignes(0.0, 60.0, 400.0, 700.0, 1000.0)];
+
+    let mut doc = Document::with_version("1.5");
+    let pages_id = doc.new_object_id();
+    let kids: Vec<Object> = images.iter().map(|img| {
+        let mut flux = Stream::new(dictionary! {
+            "Type" => "XObject", "Subtype" => "Image", "Width" => w as i64, "Height" => h as i64,
+            "ColorSpace" => "DeviceGray", "BitsPerComponent" => 8,
+        }, img.as_raw().clone());
+        flux.compress().unwrap();
+        let image_id = doc.add_object(flux);
+        let contenu_id = doc.add_object(Stream::new(dictionary! {}, b"q 595 0 0 842 0 0 cm /Scan Do Q".to_vec()));
+        doc.add_object(dictionary! {
+            "Type" => "Page", "Parent" => pages_id,
+            "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+            "Resources" => dictionary! { "XObject" => dictionary! { "Scan" => image_id } },
+            "Contents" => contenu_id,
+        }).into()
+    }).collect();
+    doc.objects.insert(pages_id, Object::Dictionary(dictionary! { "Type" => "Pages", "Count" => 3, "Kids" => kids }));
+    let catalogue = doc.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id });
+    doc.trailer.set("Root", catalogue);
+    doc.save(&scan).unwrap();
+
+    let rapport = pdf_nettoyer_scan(Path::new(&scan), &propre, &OptionsScan::default(), None).unwrap();
+    assert_eq!(rapport.pages_supprimees, vec![2]);
+    assert_eq!(rapport.pages_redressees.len(), 1, "{:?}", rapport);
+    let (page, angle) = rapport.pages_redressees[0];
+    assert_eq!(page, 1);
+    assert!((angle - 3.0).abs() < 0.2, "angle estimé {angle}");
+    assert_eq!(rapport.pages_recadrees, vec![1, 3]);
+
+    let doc = Document::load(&propre).unwrap();
+    let pages = doc.get_pages();
+    assert_eq!(pages.len(), 2);
+    let crop: Vec<f32> = doc.get_dictionary(pages[&2]).unwrap().get(b"CropBox").unwrap().as_array().unwrap()
+        .iter().map(|v| v.as_float().unwrap()).collect();
+    assert!(crop[0] > 20.0 && crop[2] < 300.0, "recadrage horizontal : {:?}", crop);
+    assert!(crop[1] > 80.0 && crop[3] < 330.0, "recadrage vertical : {:?}", crop);
+
+    // PDF texte : recadrage seul, aucune page blanche ni redressée
+    let options = OptionsScan { redresser: false, ..Default::default() };
+    let rapport = pdf_nettoyer_scan(Path::new(&format!("{TEST_DOC}/PDF.pdf")), &texte, &options, None).unwrap();
+    assert_eq!(rapport.pages_recadrees, vec![1, 2, 3]);
+    assert!(rapport.pages_supprimees.is_empty());
+
+    for f in [&scan, &propre, &texte] { cleanup(f); }
+}
+
 #[test]
 fn test_doc_pdf_split() {
     setup();
@@ -590,6 +1870,32 @@ fn test_doc_pdf_split() {
     let _ = fs::remove_dir_all(&output_dir);
 }
 
+#[test]
+fn test_doc_pdf_split_modes() {
+    use crate::modules::doc::{pdf_split_mode, ModeSplit};
+    setup();
+    let input = format!("{TEST_DOC}/PDF.pdf");
+    let output_dir = format!("{OUT}/pdf_split_modes");
+    let _ = fs::remove_dir_all(&output_dir);
+
+    let fichiers = pdf_split_mode(Path::new(&input), &output_dir, &ModeSplit::Plages("1-2,3-".into()), "{name}_{start}-{end}").unwrap();
+    assert_eq!(fichiers, vec![format!("{output_dir}/PDF_1-2.pdf"), format!("{output_dir}/PDF_3-3.pdf")]);
+    assert_eq!(lopdf::Document::load(&fichiers[0]).unwrap().get_pages().len(), 2);
+    assert_eq!(lopdf::Document::load(&fichiers[1]).unwrap().get_pages().len(), 1);
+
+    let fichiers = pdf_split_mode(Path::new(&input), &output_dir, &ModeSplit::ToutesLesN(2), "").unwrap();
+    assert_eq!(fichiers.len(), 2, "3 pages / 2 → 2 fichiers");
+    assert!(fichiers[0].ends_with("PDF_part_0001.pdf"), "modèle par défaut : {:?}", fichiers);
+
+    let fichiers = pdf_split_mode(Path::new(&input), &output_dir, &ModeSplit::TailleMax(0.0001), "").unwrap();
+    assert_eq!(fichiers.len(), 3, "taille minuscule → une page par fichier");
+    let fichiers = pdf_split_mode(Path::new(&input), &output_dir, &ModeSplit::TailleMax(100.0), "").unwrap();
+    assert_eq!(fichiers.len(), 1, "limite large → un seul fichier");
+
+    assert!(pdf_split_mode(Path::new(&input), &output_dir, &ModeSplit::Plages("4-5".into()), "").is_err(), "plage hors document acceptée");
+    let _ = fs::remove_dir_all(&output_dir);
+}
+
 #[test]
 fn test_doc_pdf_merge() {
     setup();
@@ -745,6 +2051,193 @@ fn test_doc_pdf_protect_unlock() {
     cleanup(&unlocked);
 }
 
+#[test]
+fn test_doc_pdf_aes256_rapport() {
+    use crate::modules::doc::{pdf_proteger_avance, pdf_rapport, ChiffrementPdf, PermissionsPdf};
+    setup();
+    let input = format!("{TEST_DOC}/PDF.pdf");
+    let ouvert = format!("{OUT}/doc_pdf_aes256_ouvert.pdf");
+    let verrouille = format!("{OUT}/doc_pdf_aes256_verrou.pdf");
+    let deverrouille = format!("{OUT}/doc_pdf_aes256_unlk.pdf");
+    for f in [&ouvert, &verrouille, &deverrouille] { cleanup(f); }
+
+    let rapport = pdf_rapport(Path::new(&input), "").unwrap();
+    assert!(rapport.chiffrement.is_none());
+    assert_eq!(rapport.pages, 3);
+    assert!(!rapport.polices.is_empty(), "polices non listées");
+    assert!(rapport.en_texte().contains("Chiffrement : aucun"));
+
+    // Sans mot de passe d'ouverture : contenu lisible, droits restreints
+    let permissions = PermissionsPdf { copie: false, modification: false, assemblage: false, ..Default::default() };
+    pdf_proteger_avance(Path::new(&input), &ouvert, "owner123", "", &permissions, ChiffrementPdf::Aes256).unwrap();
+    let rapport = pdf_rapport(Path::new(&ouvert), "").unwrap();
+    assert_eq!(rapport.chiffrement.as_deref(), Some("AES-256 (V5)"));
+    assert_eq!(rapport.version, "2.0", "AES-256 impose PDF 2.0");
+    assert!(rapport.contenu_lisible && !rapport.mot_de_passe_ouverture);
+    assert_eq!(rapport.pages, 3);
+    let droits = rapport.permissions.unwrap();
+    assert!(droits.impression && droits.annotation && droits.formulaires);
+    assert!(!droits.copie && !droits.modification && !droits.assemblage);
+
+    // Avec mot de passe d'ouverture : seul le chiffrement est lisible sans le mot de passe
+    pdf_proteger_avance(Path::new(&input), &verrouille, "owner123", "user123", &PermissionsPdf::default(), ChiffrementPdf::Aes256).unwrap();
+    let rapport = pdf_rapport(Path::new(&verrouille), "").unwrap();
+    assert!(rapport.mot_de_passe_ouverture && !rapport.contenu_lisible);
+    assert_eq!(rapport.chiffrement.as_deref(), Some("AES-256 (V5)"));
+    assert_eq!(pdf_rapport(Path::new(&verrouille), "user123").unwrap().pages, 3);
+
+    crate::modules::doc::pdf_dechiffrer(Path::new(&verrouille), &deverrouille, "owner123").unwrap();
+    assert!(pdf_rapport(Path::new(&deverrouille), "").unwrap().chiffrement.is_none());
+
+    for f in [&ouvert, &verrouille, &deverrouille] { cleanup(f); }
+}
+
+#[test]
+fn test_doc_pdf_comparer() {
+    use crate::modules::doc::{pdf_comparer, pdf_lister_annotations, pdf_tamponner, PlacementTampon, AncreTampon, Tampon, TypeDifference};
+    setup();
+    let ancien = format!("{TEST_DOC}/PDF.pdf");
+    let nouveau = format!("{OUT}/doc_pdf_compare_v2.pdf");
+    let surligne = format!("{OUT}/doc_pdf_compare_ajouts.pdf");
+    let inverse = format!("{OUT}/doc_pdf_compare_suppr.pdf");
+    for f in [&nouveau, &surligne, &inverse] { cleanup(f); }
+
+    let rapport = pdf_comparer(Path::new(&ancien), Path::new(&ancien), None).unwrap();
+    assert!(rapport.est_identique(), "document identique : {}", rapport.en_texte());
+    assert_eq!(rapport.pages_ancien, 3);
+
+    // Une ligne de pied de page ajoutée sur chaque page
+    let pied = Tampon::Texte {
+        modele: "Revision clause {page}".into(),
+        taille_police: 10.0,
+        placement: PlacementTampon { ancre: AncreTampon::BasCentre, ..Default::default() },
+    };
+    pdf_tamponner(Path::new(&ancien), &nouveau, &[pied], None).unwrap();
+
+    let rapport = pdf_comparer(Path::new(&ancien), Path::new(&nouveau), Some(&surligne)).unwrap();
+    assert!(!rapport.est_identique());
+    assert_eq!(rapport.differences.len(), 3, "{}", rapport.en_texte());
+    for (i, d) in rapport.differences.iter().enumerate() {
+        assert_eq!(d.type_difference, TypeDifference::Ajout);
+        assert_eq!(d.page, i as u32 + 1);
+        assert_eq!(d.texte, format!("Revision clause {}", i + 1));
+    }
+    assert!(rapport.en_texte().contains("+ l."));
+    assert!(Path::new(&surligne).exists());
+
+    // Sens inverse : suppressions, signalées par une note sur chaque page
+    let rapport = pdf_comparer(Path::new(&nouveau), Path::new(&ancien), Some(&inverse)).unwrap();
+    assert!(rapport.differences.iter().all(|d| d.type_difference == TypeDifference::Suppression));
+    assert_eq!(rapport.differences.len(), 3);
+    let notes = pdf_lister_annotations(Path::new(&inverse)).unwrap();
+    assert_eq!(notes.iter().filter(|a| a.sous_type == "Text").count(), 3);
+    assert!(notes[0].contenu.contains("Revision clause 1"));
+
+    // Page retirée : les pages restantes sont appariées par ObjectId, pas par position
+    crate::modules::doc::pdf_supprimer_pages(Path::new(&ancien), &nouveau, &[1]).unwrap();
+    let rapport = pdf_comparer(Path::new(&ancien), Path::new(&nouveau), None).unwrap();
+    assert!(!rapport.differences.is_empty());
+    assert!(
+        rapport.differences.iter().all(|d| d.type_difference == TypeDifference::Suppression && d.page == 1),
+        "{}", rapport.en_texte()
+    );
+
+    for f in [&nouveau, &surligne, &inverse] { cleanup(f); }
+}
+
+#[test]
+fn test_doc_pdf_pdfa() {
+    setup();
+    let output = format!("{OUT}/doc_pdf_pdfa.pdf");
+    cleanup(&output);
+    let result = crate::modules::doc::pdf_vers_pdfa(Path::new(&format!("{TEST_DOC}/PDF.pdf")), &output);
+    assert!(result.is_ok(), "pdf_vers_pdfa échoué : {:?}", result);
+    assert_output(&output, "pdf/a");
+
+    let doc = lopdf::Document::load(&output).unwrap();
+    let catalog = doc.catalog().unwrap();
+    assert!(catalog.has(b"OutputIntents"), "OutputIntents absent");
+    assert!(catalog.has(b"Metadata"), "Metadata XMP absent");
+    assert_eq!(doc.version, "1.7");
+
+    let rapport = crate::modules::doc::pdfa_valider(Path::new(&output)).unwrap();
+    assert!(rapport.est_conforme(), "violations PDF/A restantes : {:?}", rapport.violations);
+    cleanup(&output);
+}
+
+#[test]
+fn test_doc_pdfa_polices_incorporees() {
+    setup();
+    // texte_vers_pdf utilise Helvetica non incorporée → substitution obligatoire
+    let output = format!("{OUT}/doc_txt_pdfa.pdf");
+    cleanup(&output);
+    let result = crate::modules::doc::pdf_vers_pdfa(Path::new(&format!("{TEST_DOC}/TXT.txt")), &output);
+    assert!(result.is_ok(), "pdf_vers_pdfa (txt) échoué : {:?}", result);
+    let rapport = result.unwrap();
+    assert!(rapport.corrections.iter().any(|c| c.contains("DejaVu")), "aucune police substituée : {:?}", rapport.corrections);
+    assert!(rapport.est_conforme(), "violations PDF/A restantes : {:?}", rapport.violations);
+
+    let original = crate::modules::doc::pdfa_valider(Path::new(&format!("{TEST_DOC}/PDF.pdf"))).unwrap();
+    assert!(!original.est_conforme(), "le PDF source ne devrait pas être PDF/A");
+    cleanup(&output);
+}
+
+#[test]
+fn test_doc_pdfa_date_creation_et_codages() {
+    use lopdf::{dictionary, Document, Object, Stream};
+    setup();
+    let source = format!("{OUT}/doc_pdfa_codages.pdf");
+    let output = format!("{OUT}/doc_pdfa_codages_a.pdf");
+    for f in [&source, &output] { cleanup(f); }
+
+    // Helvetica WinAnsi avec chasses propres, police à /Differences, date de création datée
+    let mut doc = Document::with_version("1.4");
+    let pages_id = doc.new_object_id();
+    let latine = doc.add_object(dictionary! {
+        "Type" => "Font", "Subtype" => "Type1", "BaseFont" => "Helvetica", "Encoding" => "WinAnsiEncoding",
+        "FirstChar" => 65, "LastChar" => 66, "Widths" => vec![611.into(), 667.into()],
+    });
+    let differences = doc.add_object(dictionary! {
+        "Type" => "Font", "Subtype" => "Type1", "BaseFont" => "Wingdings",
+        "Encoding" => dictionary! { "Type" => "Encoding", "Differences" => vec![65.into(), Object::Name(b"a71".to_vec())] },
+    });
+    let contenu = doc.add_object(Stream::new(dictionary! {}, b"BT /F1 12 Tf 72 720 Td (AB) Tj /F2 12 Tf (A) Tj ET".to_vec()));
+    let page = doc.add_object(dictionary! {
+        "Type" => "Page", "Parent" => pages_id,
+        "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+        "Resources" => dictionary! { "Font" => dictionary! { "F1" => latine, "F2" => differences } },
+        "Contents" => contenu,
+    });
+    doc.objects.insert(pages_id, Object::Dictionary(dictionary! { "Type" => "Pages", "Count" => 1, "Kids" => vec![page.into()] }));
+    let catalogue = doc.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id });
+    let info = doc.add_object(dictionary! { "CreationDate" => Object::string_literal("D:20200102030405+02'00'") });
+    doc.trailer.set("Root", catalogue);
+    doc.trailer.set("Info", info);
+    doc.save(&source).unwrap();
+
+    let rapport = crate::modules::doc::pdf_vers_pdfa(Path::new(&source), &output).unwrap();
+    assert!(rapport.violations.iter().any(|v| v.contains("Wingdings")), "{:?}", rapport.violations);
+    assert!(!rapport.violations.iter().any(|v| v.contains("Helvetica")), "{:?}", rapport.violations);
+
+    let doc = Document::load(&output).unwrap();
+    let info = doc.get_dictionary(doc.trailer.get(b"Info").unwrap().as_reference().unwrap()).unwrap();
+    assert_eq!(info.get(b"CreationDate").unwrap().as_str().unwrap(), b"D:20200102030405+02'00'");
+    assert_ne!(info.get(b"ModDate").unwrap().as_str().unwrap(), b"D:20200102030405+02'00'");
+    let xmp = doc.get_object(doc.catalog().unwrap().get(b"Metadata").unwrap().as_reference().unwrap()).unwrap()
+        .as_stream().unwrap().content.clone();
+    assert!(String::from_utf8_lossy(&xmp).contains("<xmp:CreateDate>2020-01-02T03:04:05+02:00</xmp:CreateDate>"));
+
+    // Police latine substituée en gardant ses chasses ; la police à /Differences reste intacte
+    let f1 = doc.get_dictionary(latine).unwrap();
+    assert!(f1.has(b"FontDescriptor"));
+    assert_eq!(f1.get(b"FirstChar").unwrap().as_i64().unwrap(), 65);
+    assert_eq!(f1.get(b"Widths").unwrap().as_array().unwrap().len(), 2);
+    let f2 = doc.get_dictionary(differences).unwrap();
+    assert_eq!(f2.get(b"BaseFont").unwrap().as_name().unwrap(), b"Wingdings");
+    assert!(f2.get(b"Encoding").unwrap().as_dict().unwrap().has(b"Differences"));
+    for f in [&source, &output] { cleanup(f); }
+}
+
 // ═══════════════════════════════════════════════════════════════
 //  ARCHIVE — zip, 7z, tar + extraction
 // ═══════════════════════════════════════════════════════════════
@@ -866,6 +2359,58 @@ fn test_video_copie_flux() {
     assert_output(&output, "copie flux");
     cleanup(&output);
 }
+
+#[test]
+fn test_video_temps_et_arguments() {
+    use crate::modules::video::*;
+    assert_eq!(parser_temps("90"), Ok(90.0));
+    assert_eq!(parser_temps("1:05.5"), Ok(65.5));
+    assert_eq!(parser_temps("01:02:03"), Ok(3723.0));
+    assert!(parser_temps("1:75").is_err() && parser_temps("a").is_err() && parser_temps("1:2:3:4").is_err());
+    assert_eq!(formater_timecode(65.9), "01:05");
+    assert_eq!(formater_timecode(3723.0), "01:02:03");
+    assert_eq!(instants_planche(80.0, 4), vec![10.0, 30.0, 50.0, 70.0]);
+
+    let source = Path::new("clip.mp4");
+    let options = OptionsAnimationVideo { debut: 65.0, duree: Some(4.0), ..Default::default() };
+    let gif = arguments_animation(source, "clip.gif", &options).unwrap();
+    assert_eq!(&gif[..6], ["-ss", "65.000", "-t", "4.000", "-i", "clip.mp4"]);
+    let graphe = &gif[gif.iter().position(|a| a == "-filter_complex").unwrap() + 1];
+    assert!(graphe.starts_with("fps=12,scale=480:-2:flags=lanczos,split[a][b];[a]palettegen"), "{}", graphe);
+    assert!(graphe.contains("paletteuse"), "{}", graphe);
+    let webp = arguments_animation(source, "clip.webp", &OptionsAnimationVideo { largeur: 0, ..Default::default() }).unwrap();
+    assert!(webp.windows(2).any(|w| w == ["-vf", "fps=12"]) && webp.contains(&"libwebp".to_string()), "{:?}", webp);
+    assert!(!webp.contains(&"-t".to_string()));
+    assert!(arguments_animation(source, "clip.png", &options).is_err());
+
+    let scene = arguments_vignette(source, "clip.jpg", PositionVignette::Scene { apres: 60.0, seuil: 0.4 }, 640);
+    assert!(scene.windows(2).any(|w| w == ["-vf", "select=gt(scene\\,0.400),scale=640:-2:flags=lanczos"]), "{:?}", scene);
+    assert!(scene.windows(2).any(|w| w == ["-frames:v", "1"]) && scene.windows(2).any(|w| w == ["-q:v", "2"]));
+    let instant = arguments_vignette(source, "clip.png", PositionVignette::Instant(12.5), 0);
+    assert!(!instant.contains(&"-vf".to_string()) && instant[1] == "12.500");
+}
+
+#[test]
+fn test_video_composer_planche() {
+    use crate::modules::video::{composer_planche, OptionsPlanche};
+    let vignettes: Vec<(f64, image::DynamicImage)> = (0..5u8)
+        .map(|i| (i as f64 * 600.0, image::DynamicImage::ImageRgb8(image::RgbImage::from_pixel(64, 36, image::Rgb([i * 50, 90, 160])))))
+        .collect();
+    let options = OptionsPlanche { colonnes: 3, largeur_vignette: 128, marge: 4, ..Default::default() };
+    let sans_titre = composer_planche(&vignettes, None, &OptionsPlanche { entete: false, ..options.clone() }).unwrap();
+    // 3 colonnes x 2 lignes de 128x72
+    assert_eq!(sans_titre.dimensions(), (3 * 128 + 4 * 4, 2 * 72 + 3 * 4));
+    // Coin haut gauche de la 5e vignette (ligne 2, colonne 2), hors timecode
+    assert_eq!(sans_titre.get_pixel(4 + 132 + 2, 4 + 76 + 2).0, [200, 90, 160, 255]);
+    assert_eq!(sans_titre.get_pixel(4 + 264 + 2, 4 + 76 + 2).0, [24, 24, 24, 255], "case vide");
+    // Timecode blanc en bas à droite de la première vignette
+    let texte = (4 + 64..4 + 128).flat_map(|x| (4 + 50..4 + 72).map(move |y| (x, y)))
+        .any(|(x, y)| sans_titre.get_pixel(x, y).0[..3].iter().all(|&c| c > 230));
+    assert!(texte, "timecode incrusté");
+    let avec_titre = composer_planche(&vignettes, Some("film.mkv  |  50:00  |  1920x1080"), &options).unwrap();
+    assert!(avec_titre.height() > sans_titre.height());
+    assert!(composer_planche(&[], None, &options).is_err());
+}
 // ═══════════════════════════════════════════════════════════════
 //  AUDIO — extraction depuis vidéo
 // ═══════════════════════════════════════════════════════════════